@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
+
+use bravie::core::basis::PlaneWaveBasis;
+use bravie::core::fft::FftGrid;
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::density::calculate_initial_density;
+use bravie::dft::solver::{apply_hamiltonian_local, apply_hamiltonian_local_into};
+use bravie::Pseudopotential;
+
+const SI_PSEUDO: &str = "pp/Si.pbe-n-rrkjus_psl.1.0.0.UPF";
+const ECUT: f64 = 30.0; // Ry
+
+/// Si diamante (2 átomos), o mesmo sistema dos binários de demonstração.
+fn silicon() -> Structure {
+    let a: f64 = 10.26;
+    Structure::builder()
+        .lattice([0.0, a / 2.0, a / 2.0], [a / 2.0, 0.0, a / 2.0], [a / 2.0, a / 2.0, 0.0])
+        .add_species(Species {
+            id: 0,
+            element: "Si".to_string(),
+            atomic_number: 14,
+            mass: 28.085,
+            pseudo_path: SI_PSEUDO.to_string(),
+        })
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([a / 4.0, a / 4.0, a / 4.0], 0)
+        .build()
+        .expect("estrutura de Si válida")
+}
+
+fn test_coefficients(n: usize) -> Array1<Complex64> {
+    Array1::from_shape_fn(n, |i| Complex64::new(1.0 / (i as f64 + 1.0), 0.5 / (i as f64 + 2.0)))
+}
+
+fn bench_basis(c: &mut Criterion) {
+    let structure = silicon();
+    c.bench_function("basis/g_vectors", |b| {
+        b.iter(|| PlaneWaveBasis::new(black_box(&structure), ECUT, Some([0.25, 0.25, 0.25])))
+    });
+}
+
+fn bench_fft(c: &mut Criterion) {
+    let structure = silicon();
+    let basis = PlaneWaveBasis::new(&structure, ECUT, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let coeffs = test_coefficients(basis.g_vectors.len());
+    let mut out = Array1::<Complex64>::zeros(basis.g_vectors.len());
+
+    c.bench_function("fft/to_real_space", |b| {
+        b.iter(|| fft.to_real_space(&basis, black_box(&coeffs)).unwrap())
+    });
+    c.bench_function("fft/to_recip_space", |b| {
+        b.iter(|| fft.to_recip_space(&basis, black_box(&mut out)).unwrap())
+    });
+}
+
+fn bench_hamiltonian(c: &mut Criterion) {
+    let structure = silicon();
+    let basis = PlaneWaveBasis::new(&structure, ECUT, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let [nx, ny, nz] = fft.size;
+    let v_eff = Array3::from_shape_fn((nx, ny, nz), |(i, j, k)| -((i + j + k) as f64 / (nx + ny + nz) as f64));
+    let psi = test_coefficients(basis.g_vectors.len());
+
+    c.bench_function("hamiltonian/apply_local", |b| {
+        b.iter(|| apply_hamiltonian_local(&basis, &mut fft, black_box(&v_eff), black_box(&psi)).unwrap())
+    });
+    let mut out = Array1::<Complex64>::zeros(psi.len());
+    let mut work = Array1::<Complex64>::zeros(psi.len());
+    c.bench_function("hamiltonian/apply_local_into", |b| {
+        b.iter(|| apply_hamiltonian_local_into(&basis, &mut fft, black_box(&v_eff), black_box(&psi), &mut out, &mut work).unwrap())
+    });
+}
+
+fn bench_sad_density(c: &mut Criterion) {
+    let structure = silicon();
+    let basis = PlaneWaveBasis::new(&structure, ECUT, None);
+    let fft = FftGrid::new(&basis).unwrap();
+    let pseudos = HashMap::from([(0, Pseudopotential::mock("Si", 4.0))]);
+
+    c.bench_function("density/sad", |b| {
+        b.iter(|| calculate_initial_density(black_box(&structure), &fft, &pseudos).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_basis, bench_fft, bench_hamiltonian, bench_sad_density);
+criterion_main!(benches);
@@ -6,7 +6,9 @@ use num_complex::{Complex, Complex64};
 use bravie::core::structure::{Structure, Species};
 use bravie::core::kpoints::KGrid;
 use bravie::utils::welcome::print_welcome;
+use bravie::utils::logger::{self, Verbosity};
 use bravie::Simulation;
+use bravie::utils::timer;
 
 fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
     print_welcome();
@@ -46,10 +48,10 @@ fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
         .k_grid(KGrid::gamma()) // Usando apenas ponto Gamma para o exemplo
         .build()?;
 
-    sim.initialize_density();
+    sim.initialize_density()?;
     // Verifique se o rho não é tudo zero
     let center = sim.fft_grid.size.map(|x| x / 2);
-    println!("Rho no centro: {:.4}", sim.rho[[center[0], center[1], center[2]]]);
+    println!("Rho no centro: {:.4}", sim.rho.data[[center[0], center[1], center[2]]]);
 
     // 3. Inspecionar os Motores Numéricos
     // Acessamos a primeira base (k=0) e o grid FFT
@@ -60,7 +62,7 @@ fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
     println!("  -> K-Point: {:?}", basis.k_point);
     println!("  -> Número de Ondas Planas (NPW): {}", basis.g_vectors.len());
     println!("  -> Cutoff Função de Onda: {:.1} Ry", basis.ecut);
-    println!("  -> Cutoff Densidade (4x): {:.1} Ry", basis.ecut_rho);
+    println!("  -> Cutoff Densidade: {:.1} Ry", basis.ecut_rho);
 
     println!("\n[Análise do Grid FFT]");
     println!("  -> Dimensões: {} x {} x {}", fft.size[0], fft.size[1], fft.size[2]);
@@ -80,7 +82,7 @@ fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
 
     // A. Recíproco -> Real (Transformada Inversa)
     // Isso popula o buffer interno 'sim.fft_grid.buffer'
-    fft.to_real_space(&c_in);
+    fft.to_real_space(basis, &c_in)?;
     
     // Vamos espiar o valor em um ponto do espaço real
     let val_real = fft.buffer[[0, 0, 0]];
@@ -88,7 +90,7 @@ fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
 
     // B. Real -> Recíproco (Transformada Direta)
     let mut c_out = Array1::<Complex64>::zeros(n_pw);
-    fft.to_recip_space(&mut c_out);
+    fft.to_recip_space(basis, &mut c_out)?;
 
     // C. Comparar Entrada vs Saída
     let mut max_diff = 0.0;
@@ -107,10 +109,13 @@ fn run_basis_demo() -> Result<(), Box<dyn std::error::Error>> {
         println!("\nALERTA: Diferença numérica detectada.");
     }
 
+    println!("\n{}", timer::report());
+
     Ok(())
 }
 
 fn main() {
+    logger::init(Verbosity::Verbose);
     if let Err(e) = run_basis_demo() {
         eprintln!("Erro: {}", e);
         process::exit(1);
@@ -1,6 +1,7 @@
 use std::process;
 use bravie::core::structure::{Structure, Species};
 use bravie::utils::welcome::{print_welcome};
+use bravie::utils::logger::{self, Verbosity};
 use bravie::Simulation;
 
 fn run_structure_test() -> Result<(), Box<dyn std::error::Error>> {
@@ -41,6 +42,7 @@ fn run_structure_test() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn main() {
+    logger::init(Verbosity::Normal);
     // Captura o resultado da execução
     if let Err(e) = run_structure_test() {
         eprintln!("Erro Fatal: {}", e);
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+use std::process;
+
+// Imports do Bravie
+use bravie::core::structure::{Structure, Species};
+use bravie::core::kpoints::KGrid;
+use bravie::tools::energy_check::{compare, EnergyReference, EnergyTerms};
+use bravie::utils::logger::{self, Verbosity};
+use bravie::Simulation;
+
+/// Si diamante em geometria fixa. A referência do Quantum ESPRESSO deve ser gerada com
+/// os mesmos parâmetros (a = 10.26 Bohr, ecutwfc = 30 Ry, malha 4x4x4 sem deslocamento).
+fn silicon() -> Result<Structure, Box<dyn std::error::Error>> {
+    let a: f64 = 10.26;
+    let si = Species {
+        id: 0,
+        element: "Si".to_string(),
+        atomic_number: 14,
+        mass: 28.085,
+        pseudo_path: "pp/Si.pbe-n-rrkjus_psl.1.0.0.UPF".to_string(),
+    };
+    Ok(Structure::builder()
+        .lattice(
+            [0.0, a/2.0, a/2.0],
+            [a/2.0, 0.0, a/2.0],
+            [a/2.0, a/2.0, 0.0]
+        )
+        .add_species(si)
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([a/4.0, a/4.0, a/4.0], 0)
+        .build()?)
+}
+
+/// Roda o Si e compara cada termo da energia com a referência. Retorna `true` se algum
+/// termo foi calculado e nenhum ficou fora da tolerância.
+fn run_verification(reference_path: &PathBuf) -> Result<bool, Box<dyn std::error::Error>> {
+    let reference = EnergyReference::from_file(reference_path)?;
+
+    let mut sim = Simulation::builder()
+        .structure(silicon()?)
+        .ecut(30.0)
+        .k_grid(KGrid::monkhorst_pack([4, 4, 4], [0.0, 0.0, 0.0]))
+        .build()?;
+    sim.initialize_density()?;
+
+    // Ewald só depende da geometria e das cargas de valência. Os termos eletrônicos exigem
+    // a densidade convergida e ficam pendentes até existir um SCF completo (com XC)
+    let computed = EnergyTerms {
+        ewald: Some(sim.ion_ion_energy()?),
+        ..Default::default()
+    };
+
+    let checks = compare(&computed, &reference);
+    println!("{:<10} {:>16} {:>16} {:>12}", "Termo", "Bravie (Ry)", "Referência (Ry)", "Diferença");
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let failed = checks.iter().filter(|c| c.passed() == Some(false)).count();
+    let pending = checks.iter().filter(|c| c.passed().is_none()).count();
+    println!("\n{} termo(s) fora da tolerância ({:.1e} Ry), {} pendente(s)", failed, reference.tolerance, pending);
+    // Sem nenhum termo calculado a comparação não verifica nada
+    Ok(failed == 0 && pending < checks.len())
+}
+
+fn main() {
+    logger::init(Verbosity::Normal);
+    let Some(reference_path) = std::env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("Uso: verify_energy <referencia.toml>");
+        process::exit(2);
+    };
+    match run_verification(&reference_path) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("Erro: {}", e);
+            process::exit(1);
+        }
+    }
+}
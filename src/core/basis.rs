@@ -1,5 +1,9 @@
+use std::collections::HashMap;
 use nalgebra::Vector3;
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
 use crate::core::structure::Structure;
+use crate::utils::{logger, timer};
 
 /// Representa a base de ondas planas para um ponto K específico.
 /// Responsável por determinar a geometria do grid e listar os vetores G ativos.
@@ -7,56 +11,263 @@ pub struct PlaneWaveBasis {
     /// Energia de corte para as funções de onda (Ry)
     pub ecut: f64,
     
-    /// Energia de corte para a densidade de carga (geralmente 4 * ecut)
+    /// Energia de corte para a densidade de carga (padrão 4 * ecut; 8-12x para US/PAW)
     pub ecut_rho: f64,
     
     /// Dimensões do grid FFT (nx, ny, nz)
     pub fft_grid: [usize; 3],
     
-    /// Lista de índices (i, j, k) dos vetores G onde |k + G|^2 <= Ecut
+    /// Lista de índices (i, j, k) dos vetores G onde |k + G|^2 <= Ecut, ordenados por
+    /// |k + G|^2 (em passos de `SHELL_TOLERANCE`) e depois por (i, j, k). A ordem só
+    /// depende da rede, de Ecut e de k, então coeficientes salvos são portáveis entre execuções.
     pub g_vectors: Vec<(i32, i32, i32)>,
+
+    /// |k + G|^2 (Ry) de cada vetor em `g_vectors`, na mesma ordem.
+    /// É a energia cinética da onda plana, usada pelo pré-condicionador e pelo Hamiltoniano.
+    pub g_norm_sq: Vec<f64>,
     
     /// Ponto K associado a esta base (coordenadas fracionárias)
     pub k_point: Vector3<f64>,
+
+    /// Índice linear (C-order) de cada vetor em `g_vectors` no grid FFT, com wrap periódico.
+    /// Cada ponto K tem o seu; o grid FFT em si é compartilhado.
+    pub fft_map: Vec<usize>,
+
+    /// Grid "suave" das transformadas de ψ (H·ψ) e o mapa G -> posição nele. Igual ao
+    /// grid denso, a menos que `with_smooth_grid` escolha um menor.
+    pub smooth_grid: [usize; 3],
+    pub smooth_map: Vec<usize>,
+
+    /// Tabelas de e^{ik·r} nos grids denso e suave, para montar ψ_k(r) completo.
+    pub phases: KPhases,
+    pub smooth_phases: KPhases,
+
+    /// Agrupamento dos vetores em cascas de |k + G| igual.
+    pub shells: GShells,
+}
+
+/// Razão padrão Ecut_rho / Ecut (mínimo para representar |ψ|² exatamente).
+pub const DEFAULT_DUAL: f64 = 4.0;
+
+/// Tolerância (Ry) para considerar dois |G|^2 na mesma casca.
+pub const SHELL_TOLERANCE: f64 = 1e-8;
+
+/// Cascas de vetores G com a mesma norma. Grandezas que só dependem de |k + G|
+/// (projetores β e orbitais atômicos interpolados das tabelas radiais) são calculadas
+/// uma vez por casca e depois espalhadas para os vetores.
+#[derive(Debug, Clone, Default)]
+pub struct GShells {
+    /// |G|^2 de cada casca, em ordem crescente (Ry)
+    pub norm_sq: Vec<f64>,
+    /// Casca de cada vetor, na ordem original
+    pub shell_of: Vec<usize>,
+}
+
+impl GShells {
+    /// Agrupa uma lista de |G|^2 (qualquer ordem) em cascas.
+    pub fn from_norm_sq(g_norm_sq: &[f64], tol: f64) -> Self {
+        let mut order: Vec<usize> = (0..g_norm_sq.len()).collect();
+        order.sort_by(|&a, &b| g_norm_sq[a].total_cmp(&g_norm_sq[b]));
+
+        let mut norm_sq: Vec<f64> = Vec::new();
+        let mut shell_of = vec![0; g_norm_sq.len()];
+        for idx in order {
+            let g2 = g_norm_sq[idx];
+            match norm_sq.last() {
+                Some(&last) if g2 - last <= tol => {}
+                _ => norm_sq.push(g2),
+            }
+            shell_of[idx] = norm_sq.len() - 1;
+        }
+        Self { norm_sq, shell_of }
+    }
+
+    pub fn len(&self) -> usize {
+        self.norm_sq.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.norm_sq.is_empty()
+    }
+
+    /// Avalia `f(|G|)` uma vez por casca.
+    pub fn map<T, F: Fn(f64) -> T>(&self, f: F) -> Vec<T> {
+        self.norm_sq.iter().map(|g2| f(g2.sqrt())).collect()
+    }
+
+    /// Espalha valores por casca para cada vetor G (ordem original).
+    pub fn expand<T: Clone>(&self, per_shell: &[T]) -> Vec<T> {
+        self.shell_of.iter().map(|&s| per_shell[s].clone()).collect()
+    }
+}
+
+/// Fatores de fase e^{ik·r} nos pontos de um grid FFT. Com r = Σ_a (n_a/N_a)·a_a e k
+/// fracionário, k·r = 2π Σ_a k_a·n_a/N_a: a tabela é separável e guarda um vetor por
+/// eixo (N_x + N_y + N_z valores em vez de N_x·N_y·N_z).
+///
+/// Os coeficientes c(G) de uma base descrevem a parte periódica u_k, e as transformadas
+/// de `FftGrid` produzem u_k(r). Isso basta para |ψ|² e V·ψ (a fase cancela e a cinética
+/// usa |k + G|²); a fase só é necessária para o ψ_k(r) = e^{ik·r}·u_k(r) completo, ex:
+/// produtos entre pontos K diferentes ou exportação de ψ.
+#[derive(Debug, Clone)]
+pub struct KPhases {
+    pub size: [usize; 3],
+    /// e^{2πi k_a n / N_a}, n = 0..N_a, para cada eixo a
+    pub axes: [Vec<Complex64>; 3],
+    gamma: bool,
+}
+
+impl KPhases {
+    pub fn new(k_point: Vector3<f64>, size: [usize; 3]) -> Self {
+        let axes = [0, 1, 2].map(|a| {
+            (0..size[a])
+                .map(|n| Complex64::from_polar(1.0, 2.0 * std::f64::consts::PI * k_point[a] * n as f64 / size[a] as f64))
+                .collect()
+        });
+        Self { size, axes, gamma: k_point.norm() < 1e-12 }
+    }
+
+    /// k = Γ: todas as fases valem 1.
+    pub fn is_gamma(&self) -> bool {
+        self.gamma
+    }
+
+    /// e^{ik·r} no ponto (i, j, k) do grid.
+    pub fn at(&self, i: usize, j: usize, k: usize) -> Complex64 {
+        self.axes[0][i] * self.axes[1][j] * self.axes[2][k]
+    }
+
+    /// Multiplica `field` por e^{ik·r} (ou por e^{-ik·r} com `conjugate`).
+    pub fn apply(&self, field: &mut Array3<Complex64>, conjugate: bool) {
+        if self.gamma {
+            return;
+        }
+        let conj = |c: Complex64| if conjugate { c.conj() } else { c };
+        for ((i, j, k), value) in field.indexed_iter_mut() {
+            *value *= conj(self.at(i, j, k));
+        }
+    }
 }
 
 impl PlaneWaveBasis {
-    /// Cria uma nova base para um dado Structure e Ecut.
+    /// Cria uma nova base para um dado Structure e Ecut, com Ecut_rho = 4 * Ecut.
     /// Se k_point for None, assume Gamma (0, 0, 0).
     pub fn new(structure: &Structure, ecut: f64, k_point: Option<[f64; 3]>) -> Self {
-        // 1. Definição do Dual Grid (Densidade requer 4x a energia)
-        // Isso garante que a convolução |psi|^2 seja exata no grid.
-        let ecut_rho = 4.0 * ecut;
-        
+        // Dual Grid padrão (Densidade requer 4x a energia):
+        // garante que a convolução |psi|^2 seja exata no grid.
+        Self::with_ecut_rho(structure, ecut, DEFAULT_DUAL * ecut, k_point)
+    }
+
+    /// Cria a base com corte da densidade independente (`ecut_rho` >= 4 * `ecut`).
+    /// Pseudos ultrasoft/PAW precisam de um grid denso maior para as cargas de aumento.
+    pub fn with_ecut_rho(structure: &Structure, ecut: f64, ecut_rho: f64, k_point: Option<[f64; 3]>) -> Self {
+        let fft_grid = Self::calculate_optimal_fft_grid(&structure.lattice.reciprocal(), ecut_rho);
+        Self::with_grid(structure, ecut, ecut_rho, fft_grid, k_point)
+    }
+
+    /// Cria a base num grid FFT escolhido pelo chamador (override ou padding).
+    /// O grid deve conter a esfera de Ecut (ver `SimulationBuilder::fft_grid`).
+    pub fn with_grid(
+        structure: &Structure,
+        ecut: f64,
+        ecut_rho: f64,
+        fft_grid: [usize; 3],
+        k_point: Option<[f64; 3]>,
+    ) -> Self {
+        let _t = timer::scope("basis");
         let k_vec = if let Some(k) = k_point {
             Vector3::from(k)
         } else {
             Vector3::zeros()
         };
 
-        // 2. Calcula as dimensões ótimas do grid FFT (baseado em Ecut_rho)
-        let fft_grid = Self::calculate_optimal_fft_grid(&structure.lattice.reciprocal(), ecut_rho);
-
-        // 3. Gera os vetores G ativos para este k-point (baseado em Ecut)
+        // Gera os vetores G ativos para este k-point (baseado em Ecut)
         let g_vectors = Self::generate_g_vectors(structure, fft_grid, ecut, k_vec);
+        let g_norm_sq = Self::compute_g_norm_sq(structure, &g_vectors, k_vec);
+        let shells = GShells::from_norm_sq(&g_norm_sq, SHELL_TOLERANCE);
+        let fft_map = Self::compute_fft_map(&g_vectors, fft_grid);
 
-        println!(
-            "    Basis Init: Ecut={:.1} Ry | Grid=[{}, {}, {}] | NG={} (k={:?})",
-            ecut, fft_grid[0], fft_grid[1], fft_grid[2], g_vectors.len(), k_vec.as_slice()
+        log::debug!(
+            "    Basis Init: Ecut={:.1} Ry | Ecut_rho={:.1} Ry | Grid=[{}, {}, {}] | NG={} | Cascas={} (k={:?})",
+            ecut, ecut_rho, fft_grid[0], fft_grid[1], fft_grid[2], g_vectors.len(), shells.len(), k_vec.as_slice()
         );
+        logger::record("basis_init", &[
+            ("ecut", format!("{}", ecut)),
+            ("ecut_rho", format!("{}", ecut_rho)),
+            ("grid", format!("{}x{}x{}", fft_grid[0], fft_grid[1], fft_grid[2])),
+            ("npw", format!("{}", g_vectors.len())),
+            ("k", format!("{},{},{}", k_vec.x, k_vec.y, k_vec.z)),
+        ]);
+
+        let phases = KPhases::new(k_vec, fft_grid);
 
         Self {
             ecut,
             ecut_rho,
             fft_grid,
             g_vectors,
+            g_norm_sq,
             k_point: k_vec,
+            smooth_map: fft_map.clone(),
+            fft_map,
+            smooth_grid: fft_grid,
+            smooth_phases: phases.clone(),
+            phases,
+            shells,
+        }
+    }
+
+    /// Usa `smooth` como grid das transformadas de ψ. Deve conter a esfera de Ecut.
+    pub fn with_smooth_grid(mut self, smooth: [usize; 3]) -> Self {
+        self.smooth_map = Self::compute_fft_map(&self.g_vectors, smooth);
+        self.smooth_phases = KPhases::new(self.k_point, smooth);
+        self.smooth_grid = smooth;
+        self
+    }
+
+    /// Mapa G -> grid para um grid FFT de tamanho `size` (denso ou suave).
+    pub fn map_for(&self, size: [usize; 3]) -> Option<&[usize]> {
+        if size == self.fft_grid {
+            Some(&self.fft_map)
+        } else if size == self.smooth_grid {
+            Some(&self.smooth_map)
+        } else {
+            None
         }
     }
 
+    /// Tabela de fases e^{ik·r} para um grid de tamanho `size` (denso ou suave).
+    pub fn phases_for(&self, size: [usize; 3]) -> Option<&KPhases> {
+        if size == self.fft_grid {
+            Some(&self.phases)
+        } else if size == self.smooth_grid {
+            Some(&self.smooth_phases)
+        } else {
+            None
+        }
+    }
+
+    /// Menor grid em que H·ψ é exato: V(G - G') só entra com |G - G'| <= 2√Ecut e
+    /// o produto V·ψ (até 3√Ecut) não rebate para dentro da esfera de Ecut, ou seja,
+    /// o grid de 4·Ecut. Só difere do denso com Ecut_rho > 4·Ecut (ultrasoft, PAW) ou padding.
+    pub fn smooth_fft_grid(recip_lattice: &nalgebra::Matrix3<f64>, ecut: f64, dense: [usize; 3]) -> [usize; 3] {
+        let smooth = Self::calculate_optimal_fft_grid(recip_lattice, DEFAULT_DUAL * ecut);
+        [0, 1, 2].map(|i| smooth[i].min(dense[i]))
+    }
+
+    /// Posição de cada G no buffer (nx, ny, nz): idx = u*ny*nz + v*nz + w,
+    /// com frequências negativas dobradas para o fim de cada eixo.
+    fn compute_fft_map(g_vectors: &[(i32, i32, i32)], fft_grid: [usize; 3]) -> Vec<usize> {
+        let [nx, ny, nz] = fft_grid;
+        let wrap = |g: i32, n: usize| g.rem_euclid(n as i32) as usize;
+        g_vectors.iter()
+            .map(|&(ig, jg, kg)| wrap(ig, nx) * ny * nz + wrap(jg, ny) * nz + wrap(kg, nz))
+            .collect()
+    }
+
     /// Calcula tamanho do grid para evitar aliasing (Shannon-Nyquist).
     /// Grid deve cobrir 2 * G_max_rho.
-    fn calculate_optimal_fft_grid(recip_lattice: &nalgebra::Matrix3<f64>, ecut_rho: f64) -> [usize; 3] {
+    pub fn calculate_optimal_fft_grid(recip_lattice: &nalgebra::Matrix3<f64>, ecut_rho: f64) -> [usize; 3] {
         // G_max é o raio da esfera de densidade no espaço recíproco
         let g_max = ecut_rho.sqrt();
 
@@ -80,18 +291,33 @@ impl PlaneWaveBasis {
         ]
     }
 
-    /// Gera a lista de vetores G inteiros (i, j, k) dentro da esfera de energia cinética.
+    /// Mapa (i, j, k) -> posição em `g_vectors`.
+    pub fn index_map(&self) -> HashMap<(i32, i32, i32), usize> {
+        self.g_vectors.iter().enumerate().map(|(ig, &g)| (g, ig)).collect()
+    }
+
+    /// Reordena coeficientes da base `from` para esta (mesmo k, ex: ψ lida de arquivo
+    /// ou de outro Ecut). Vetores ausentes em `from` ficam zerados; os que sobram são descartados.
+    pub fn transfer_coefficients(&self, from: &PlaneWaveBasis, coeffs: &Array1<Complex64>) -> Array1<Complex64> {
+        let index = from.index_map();
+        self.g_vectors.iter()
+            .map(|g| index.get(g).map_or(Complex64::new(0.0, 0.0), |&ig| coeffs[ig]))
+            .collect()
+    }
+
+    /// Gera a lista de vetores G inteiros (i, j, k) dentro da esfera de energia cinética,
+    /// em ordem determinística (ver `g_vectors`).
     fn generate_g_vectors(
         structure: &Structure, 
         grid_dim: [usize; 3], 
         ecut: f64, 
         k_point: Vector3<f64>
     ) -> Vec<(i32, i32, i32)> {
-        let mut g_vecs = Vec::new();
+        let mut g_vecs: Vec<(i64, (i32, i32, i32))> = Vec::new();
         let recip = structure.lattice.reciprocal(); // Matriz onde colunas são b1, b2, b3
 
         // Define a caixa de busca baseada no grid FFT.
-        // O grid FFT é calculado para 4*Ecut, então cobrir metade dele (frequência de Nyquist)
+        // O grid FFT é calculado para Ecut_rho >= 4*Ecut, então cobrir metade dele (frequência de Nyquist)
         // é mais que suficiente para encontrar todos os vetores de Ecut.
         // Usamos índices com sinal (i32).
         let nx_search = (grid_dim[0] / 2) as i32;
@@ -110,22 +336,38 @@ impl PlaneWaveBasis {
                     let q_cart = recip * kg_frac;
 
                     // Energia cinética (unidades atômicas/Ry) = |q|^2
-                    if q_cart.norm_squared() <= ecut {
-                        g_vecs.push((i, j, k));
+                    let g2 = q_cart.norm_squared();
+                    if g2 <= ecut {
+                        // |G|^2 quantizado: vetores da mesma casca empatam exatamente
+                        // e o desempate lexicográfico não depende de ruído de arredondamento
+                        g_vecs.push(((g2 / SHELL_TOLERANCE).round() as i64, (i, j, k)));
                     }
                 }
             }
         }
         
-        // Otimização opcional: Ordenar vetores por energia (ajuda no pré-condicionamento depois)
-        // g_vecs.sort_by(|a, b| ...);
-        
-        g_vecs
+        g_vecs.sort_unstable();
+        g_vecs.into_iter().map(|(_, g)| g).collect()
+    }
+
+    /// Calcula |k + G|^2 para cada vetor G da lista (coordenadas cartesianas via rede recíproca).
+    fn compute_g_norm_sq(
+        structure: &Structure,
+        g_vectors: &[(i32, i32, i32)],
+        k_point: Vector3<f64>
+    ) -> Vec<f64> {
+        let recip = structure.lattice.reciprocal();
+        g_vectors.iter()
+            .map(|&(i, j, k)| {
+                let kg_frac = k_point + Vector3::new(i as f64, j as f64, k as f64);
+                (recip * kg_frac).norm_squared()
+            })
+            .collect()
     }
 
     /// Encontra o próximo tamanho de grid que é produto de primos pequenos (2, 3, 5, 7).
     /// Isso é crítico para performance O(N log N) da FFT.
-    fn next_fft_size(n: usize) -> usize {
+    pub fn next_fft_size(n: usize) -> usize {
         let mut size = n;
         while !Self::is_smooth_number(size) {
             size += 1;
@@ -1,9 +1,20 @@
 use ndarray::{Array1, Array3};
-use ndrustfft::{FftHandler, ndfft_par, ndifft_par};
+#[cfg(not(feature = "fftw"))]
+use ndrustfft::FftHandler;
+#[cfg(all(feature = "parallel", not(feature = "fftw")))]
+use ndrustfft::{ndfft_par as ndfft, ndifft_par as ndifft};
+#[cfg(all(not(feature = "parallel"), not(feature = "fftw")))]
+use ndrustfft::{ndfft, ndifft};
 use num_complex::Complex64;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*; // Importante para o gather paralelo
 use crate::core::basis::PlaneWaveBasis;
+use crate::dft::error::DftError;
+use crate::utils::{logger, timer};
 
+/// Grid da FFT e seus buffers, compartilhado por todos os pontos K.
+/// O mapa G -> posição no grid é de cada base (`PlaneWaveBasis::fft_map` para o grid
+/// denso, `smooth_map` para o grid suave das funções de onda).
 pub struct FftGrid {
     pub size: [usize; 3],
     
@@ -11,117 +22,317 @@ pub struct FftGrid {
     pub buffer: Array3<Complex64>, 
     scratch: Array3<Complex64>, 
 
+    backend: Backend,
+}
+
+/// Transformadas 3D sobre `buffer` (resultado em `buffer`, `scratch` de rascunho).
+/// Padrão: ndrustfft, eixo a eixo. Com a feature `fftw`: planos 3D do FFTW.
+#[cfg(not(feature = "fftw"))]
+struct Backend {
     handler_x: FftHandler<f64>,
     handler_y: FftHandler<f64>,
     handler_z: FftHandler<f64>,
+}
+
+#[cfg(not(feature = "fftw"))]
+impl Backend {
+    fn new([nx, ny, nz]: [usize; 3]) -> Result<Self, DftError> {
+        Ok(Self {
+            handler_x: FftHandler::new(nx),
+            handler_y: FftHandler::new(ny),
+            handler_z: FftHandler::new(nz),
+        })
+    }
+
+    /// Sem normalização.
+    fn forward(&mut self, buffer: &mut Array3<Complex64>, scratch: &mut Array3<Complex64>) -> Result<(), DftError> {
+        ndfft(buffer, scratch, &self.handler_x, 0);
+        ndfft(scratch, buffer, &self.handler_y, 1);
+        ndfft(buffer, scratch, &self.handler_z, 2);
+        buffer.assign(scratch);
+        Ok(())
+    }
 
-    // Mapeamento Linear (Flat Index)
-    // Em vez de (u, v, w), guardamos o índice direto na memória linear do buffer.
-    // Isso evita calcular (u * ny * nz + v * nz + w) milhões de vezes.
-    map_g_to_flat_index: Vec<usize>,
+    /// Normaliza por 1/N.
+    fn inverse(&mut self, buffer: &mut Array3<Complex64>, scratch: &mut Array3<Complex64>) -> Result<(), DftError> {
+        ndifft(buffer, scratch, &self.handler_x, 0);
+        ndifft(scratch, buffer, &self.handler_y, 1);
+        ndifft(buffer, scratch, &self.handler_z, 2);
+        buffer.assign(scratch);
+        Ok(())
+    }
 }
 
+#[cfg(feature = "fftw")]
+pub use fftw_backend::{export_wisdom, WISDOM_ENV};
+#[cfg(feature = "fftw")]
+use fftw_backend::Backend;
+
 impl FftGrid {
-    pub fn new(basis: &PlaneWaveBasis) -> Self {
-        let (nx, ny, nz) = (basis.fft_grid[0], basis.fft_grid[1], basis.fft_grid[2]);
+    /// Grid no tamanho da base (`basis.fft_grid`).
+    pub fn new(basis: &PlaneWaveBasis) -> Result<Self, DftError> {
+        Self::with_size(basis.fft_grid)
+    }
+
+    /// Falha só se o backend não conseguir planejar as transformadas (FFTW).
+    pub fn with_size(size: [usize; 3]) -> Result<Self, DftError> {
+        let [nx, ny, nz] = size;
                 
-        println!("    FFT Grid init: {}x{}x{}", nx, ny, nz);
+        log::debug!("    FFT Grid init: {}x{}x{}", nx, ny, nz);
+        logger::record("fft_init", &[("grid", format!("{}x{}x{}", nx, ny, nz))]);
 
         let buffer = Array3::zeros((nx, ny, nz));
         let scratch = Array3::zeros((nx, ny, nz));
 
-        let handler_x = FftHandler::new(nx);
-        let handler_y = FftHandler::new(ny);
-        let handler_z = FftHandler::new(nz);
-
-        // Pré-cálculo dos strides para indexação linear
-        // O layout padrão do ndarray (C-order) é: idx = x*stride_x + y*stride_y + z*stride_z
-        // Para Array3::zeros, strides são (ny*nz, nz, 1)
-        let stride_x = ny * nz;
-        let stride_y = nz;
-        let stride_z = 1;
-
-        let inx = nx as i32;
-        let iny = ny as i32;
-        let inz = nz as i32;
-
-        let mut map_g_to_flat_index = Vec::with_capacity(basis.g_vectors.len());
-
-        for &(ig, jg, kg) in &basis.g_vectors {
-            // Wrap around (Periodic Boundary Conditions)
-            let u = ((ig % inx) + inx) as usize % nx;
-            let v = ((jg % iny) + iny) as usize % ny;
-            let w = ((kg % inz) + inz) as usize % nz;
-            
-            // Cálculo do índice linear (flat) uma única vez na vida
-            let flat_idx = u * stride_x + v * stride_y + w * stride_z;
-            map_g_to_flat_index.push(flat_idx);
-        }
-
-        Self {
-            size: [nx, ny, nz],
+        Ok(Self {
+            size,
             buffer,
             scratch,
-            handler_x, handler_y, handler_z,
-            map_g_to_flat_index,
-        }
+            backend: Backend::new(size)?,
+        })
+    }
+
+    /// Mapa G -> grid da base para este grid (denso ou suave).
+    fn map_for<'a>(&self, basis: &'a PlaneWaveBasis) -> Result<&'a [usize], DftError> {
+        basis.map_for(self.size).ok_or(DftError::GridMismatch(basis.fft_grid, self.size))
     }
 
-    /// IFFT: Coeficientes -> Grid -> FFT Inversa -> Buffer Real
-    pub fn to_real_space(&mut self, coeffs_recip: &Array1<Complex64>) {
+    /// IFFT: Coeficientes -> Grid -> FFT Inversa -> Buffer Real.
+    /// O buffer fica com a parte periódica u_k(r); ver `bloch_to_real_space`.
+    pub fn to_real_space(&mut self, basis: &PlaneWaveBasis, coeffs_recip: &Array1<Complex64>) -> Result<(), DftError> {
+        let _t = timer::scope("fft");
+        let map = self.map_for(basis)?;
         // Passo 1: Limpar buffer
         self.buffer.fill(Complex64::new(0.0, 0.0));
         
         // CORREÇÃO AQUI:
         // Convertemos ambos para "slices" brutos do Rust (&[T]).
         // Slices têm o método 'get_unchecked' e são mais leves que o ArrayView do ndarray.
-        let raw_buffer = self.buffer.as_slice_mut().expect("Buffer deve ser contíguo na memória");
-        let raw_coeffs = coeffs_recip.as_slice().expect("Coeffs deve ser contíguo");
+        let raw_buffer = self.buffer.as_slice_mut().ok_or(DftError::NonContiguous("buffer da FFT"))?;
+        let raw_coeffs = coeffs_recip.as_slice().ok_or(DftError::NonContiguous("coeficientes de entrada"))?;
 
+        // Um coeficiente por vetor G da base: sem preenchimento implícito com zeros
         let n_coeffs = coeffs_recip.len();
+        if n_coeffs != map.len() {
+            return Err(DftError::SizeMismatch("coeficientes de entrada", n_coeffs, map.len()));
+        }
         
         // Passo 2: Scatter (Loop Unsafe Otimizado)
-        for (g_idx, &flat_pos) in self.map_g_to_flat_index.iter().enumerate() {
-            if g_idx < n_coeffs {
-                unsafe {
-                    // Agora estamos chamando get_unchecked em primitivos slices do Rust
-                    *raw_buffer.get_unchecked_mut(flat_pos) = *raw_coeffs.get_unchecked(g_idx);
-                }
+        for (g_idx, &flat_pos) in map.iter().enumerate() {
+            unsafe {
+                // Agora estamos chamando get_unchecked em primitivos slices do Rust
+                *raw_buffer.get_unchecked_mut(flat_pos) = *raw_coeffs.get_unchecked(g_idx);
             }
         }
         
-        // Passo 3: FFT 3D (Ping-Pong buffers)
-        ndifft_par(&self.buffer, &mut self.scratch, &self.handler_x, 0);
-        ndifft_par(&self.scratch, &mut self.buffer, &self.handler_y, 1);
-        ndifft_par(&self.buffer, &mut self.scratch, &self.handler_z, 2);
-        
-        // Resultado em scratch -> buffer
-        self.buffer.assign(&self.scratch);
+        // Passo 3: FFT 3D inversa
+        self.backend.inverse(&mut self.buffer, &mut self.scratch)
+    }
+
+    /// Como `to_real_space`, mas deixa no buffer a função de Bloch completa
+    /// ψ_k(r) = e^{ik·r}·u_k(r), usando as fases pré-calculadas da base.
+    pub fn bloch_to_real_space(&mut self, basis: &PlaneWaveBasis, coeffs_recip: &Array1<Complex64>) -> Result<(), DftError> {
+        self.to_real_space(basis, coeffs_recip)?;
+        let phases = basis.phases_for(self.size).ok_or(DftError::GridMismatch(basis.fft_grid, self.size))?;
+        phases.apply(&mut self.buffer, false);
+        Ok(())
+    }
+
+    /// Inversa de `bloch_to_real_space`: remove e^{ik·r} do buffer e extrai os
+    /// coeficientes das ondas planas e^{i(k+G)·r} da base.
+    pub fn bloch_to_recip_space(&mut self, basis: &PlaneWaveBasis, coeffs_out: &mut Array1<Complex64>) -> Result<(), DftError> {
+        let phases = basis.phases_for(self.size).ok_or(DftError::GridMismatch(basis.fft_grid, self.size))?;
+        phases.apply(&mut self.buffer, true);
+        self.to_recip_space(basis, coeffs_out)
+    }
+
+    /// FFT Forward do buffer inteiro, in-place (sem gather).
+    /// Usado quando precisamos de todos os componentes de Fourier do grid denso,
+    /// ex: V(G - G') na montagem explícita do Hamiltoniano. Não normaliza.
+    pub fn forward_in_place(&mut self) -> Result<(), DftError> {
+        let _t = timer::scope("fft");
+        self.backend.forward(&mut self.buffer, &mut self.scratch)
+    }
+
+    /// FFT Inversa do buffer inteiro, in-place (sem scatter). Normaliza por 1/N,
+    /// como `to_real_space`: f(r) = N · buffer para f(r) = Σ_G f(G) e^{iG·r}.
+    pub fn inverse_in_place(&mut self) -> Result<(), DftError> {
+        let _t = timer::scope("fft");
+        self.backend.inverse(&mut self.buffer, &mut self.scratch)
     }
 
     /// FFT: Grid Real -> FFT Forward -> Extrair Coeficientes
-    pub fn to_recip_space(&mut self, coeffs_out: &mut Array1<Complex64>) {
+    pub fn to_recip_space(&mut self, basis: &PlaneWaveBasis, coeffs_out: &mut Array1<Complex64>) -> Result<(), DftError> {
+        let _t = timer::scope("fft");
+        let map = self.map_for(basis)?;
         // Passo 1: FFT 3D
-        ndfft_par(&self.buffer, &mut self.scratch, &self.handler_x, 0);
-        ndfft_par(&self.scratch, &mut self.buffer, &self.handler_y, 1);
-        ndfft_par(&self.buffer, &mut self.scratch, &self.handler_z, 2);
-
-        // Agora o resultado está em 'scratch'.
-        let raw_scratch = self.scratch.as_slice().expect("Buffer deve ser contíguo");
+        self.backend.forward(&mut self.buffer, &mut self.scratch)?;
+        let raw_buffer = self.buffer.as_slice().ok_or(DftError::NonContiguous("buffer da FFT"))?;
 
         // OTIMIZAÇÃO 3: Gather Paralelo
         // Diferente da escrita, a leitura pode ser feita em paralelo trivialmente!
         // Usamos Rayon para preencher 'coeffs_out' em paralelo.
         
-        // Se coeffs_out e map tiverem o mesmo tamanho (deveriam):
-        coeffs_out.as_slice_mut().expect("Coeffs contíguo")
-            .par_iter_mut()
-            .zip(&self.map_g_to_flat_index) // Zipa com o índice de onde ler
+        // coeffs_out e map precisam ter o mesmo tamanho
+        if coeffs_out.len() != map.len() {
+            return Err(DftError::SizeMismatch("coeficientes de saída", coeffs_out.len(), map.len()));
+        }
+        let out = coeffs_out.as_slice_mut().ok_or(DftError::NonContiguous("coeficientes de saída"))?;
+        #[cfg(feature = "parallel")]
+        let out = out.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let out = out.iter_mut();
+        out
+            .zip(map) // Zipa com o índice de onde ler
             .for_each(|(out_val, &flat_idx)| {
                 // Leitura unsafe também é válida e rápida, mas aqui o ganho maior é o paralelismo
                 unsafe {
-                    *out_val = *raw_scratch.get_unchecked(flat_idx);
+                    *out_val = *raw_buffer.get_unchecked(flat_idx);
                 }
             });
+        Ok(())
+    }
+}
+
+/// Interpolação de Fourier de um campo real entre grids (ex: V_eff do grid denso para o
+/// suave): os componentes f(G) que cabem nos dois grids são copiados e os demais
+/// descartados (ou zerados, ao ampliar). Frequências de Nyquist são omitidas para manter
+/// o campo real. Usa os buffers de `from` e `to`.
+pub fn fourier_resample(field: &Array3<f64>, from: &mut FftGrid, to: &mut FftGrid) -> Result<Array3<f64>, DftError> {
+    let [nx, ny, nz] = from.size;
+    let dim = field.dim();
+    if [dim.0, dim.1, dim.2] != from.size {
+        return Err(DftError::GridMismatch([dim.0, dim.1, dim.2], from.size));
+    }
+    if from.size == to.size {
+        return Ok(field.clone());
+    }
+    from.buffer.zip_mut_with(field, |c, &v| *c = Complex64::new(v, 0.0));
+    from.forward_in_place()?;
+
+    // buffer destino = N_to · f(G) = (N_to / N_from) · FFT_from
+    let scale = (to.size.iter().product::<usize>() as f64) / ((nx * ny * nz) as f64);
+    let freq = |i: usize, n: usize| if i > n / 2 { i as i64 - n as i64 } else { i as i64 };
+    // |m| < n/2 nos dois grids (exclui Nyquist)
+    let fits = |m: i64, n: usize| 2 * m.unsigned_abs() < n as u64;
+    let wrap = |m: i64, n: usize| m.rem_euclid(n as i64) as usize;
+    to.buffer.fill(Complex64::new(0.0, 0.0));
+    for ((i, j, k), &c) in from.buffer.indexed_iter() {
+        let m = [freq(i, nx), freq(j, ny), freq(k, nz)];
+        if (0..3).all(|d| fits(m[d], from.size[d]) && fits(m[d], to.size[d])) {
+            to.buffer[[wrap(m[0], to.size[0]), wrap(m[1], to.size[1]), wrap(m[2], to.size[2])]] = c * scale;
+        }
+    }
+    to.inverse_in_place()?;
+    Ok(to.buffer.mapv(|c| c.re))
+}
+
+/// Backend FFTW (feature `fftw`): planos 3D `FFTW_MEASURE`, que cronometram alternativas
+/// na criação e escolhem a mais rápida para o grid. Se `BRAVIE_FFTW_WISDOM` apontar para
+/// um arquivo, a wisdom é lida antes do primeiro plano e regravada por `export_wisdom` ao
+/// fim da execução (uma vez, não a cada `FftGrid`), então execuções seguintes com os
+/// mesmos grids não repetem as medições.
+#[cfg(feature = "fftw")]
+mod fftw_backend {
+    use std::ffi::CString;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, Once};
+    use fftw::array::AlignedVec;
+    use fftw::plan::{C2CPlan, C2CPlan64};
+    use fftw::types::{Flag, Sign};
+    use ndarray::Array3;
+    use num_complex::Complex64;
+
+    use crate::dft::error::DftError;
+
+    /// Variável de ambiente com o caminho do arquivo de wisdom.
+    pub const WISDOM_ENV: &str = "BRAVIE_FFTW_WISDOM";
+
+    // O planejador e a wisdom do FFTW não são thread-safe
+    static PLANNER: Mutex<()> = Mutex::new(());
+    static IMPORT: Once = Once::new();
+    // Há planos novos desde a última gravação?
+    static NEW_PLANS: AtomicBool = AtomicBool::new(false);
+
+    fn wisdom_path() -> Option<CString> {
+        let path = std::env::var_os(WISDOM_ENV)?;
+        CString::new(path.to_string_lossy().into_owned()).ok()
     }
-}
\ No newline at end of file
+
+    /// Grava a wisdom acumulada em `BRAVIE_FFTW_WISDOM` (sem efeito se não definida ou
+    /// se nenhum plano foi criado desde a última gravação). Chamar ao fim da execução.
+    pub fn export_wisdom() {
+        let Some(path) = wisdom_path() else { return };
+        if !NEW_PLANS.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        let _lock = PLANNER.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: caminho terminado em NUL; acesso ao planejador serializado pelo lock
+        if unsafe { fftw::ffi::fftw_export_wisdom_to_filename(path.as_ptr()) } == 0 {
+            log::warn!("Não foi possível gravar a wisdom do FFTW em {:?}", path);
+        }
+    }
+
+    pub(super) struct Backend {
+        forward: C2CPlan64,
+        backward: C2CPlan64,
+        input: AlignedVec<Complex64>,
+        output: AlignedVec<Complex64>,
+        norm: f64,
+    }
+
+    impl Backend {
+        pub(super) fn new(size: [usize; 3]) -> Result<Self, DftError> {
+            let n = size.iter().product();
+            let path = wisdom_path();
+            let (forward, backward) = {
+                let _lock = PLANNER.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(path) = &path {
+                    IMPORT.call_once(|| {
+                        // SAFETY: idem a export_wisdom; arquivo ausente só retorna 0
+                        if unsafe { fftw::ffi::fftw_import_wisdom_from_filename(path.as_ptr()) } != 0 {
+                            log::debug!("    Wisdom do FFTW lida de {:?}", path);
+                        }
+                    });
+                }
+                let plan = |sign| C2CPlan64::aligned(&size, sign, Flag::MEASURE)
+                    .map_err(|e| DftError::FftBackend(format!("plano FFTW {:?}: {}", size, e)));
+                let plans = (plan(Sign::Forward)?, plan(Sign::Backward)?);
+                NEW_PLANS.store(true, Ordering::Release);
+                plans
+            };
+
+            Ok(Self {
+                forward,
+                backward,
+                input: AlignedVec::new(n),
+                output: AlignedVec::new(n),
+                norm: 1.0 / n as f64,
+            })
+        }
+
+        /// Sem normalização.
+        pub(super) fn forward(&mut self, buffer: &mut Array3<Complex64>, _scratch: &mut Array3<Complex64>) -> Result<(), DftError> {
+            self.run(buffer, true)
+        }
+
+        /// Normaliza por 1/N, como o ndrustfft.
+        pub(super) fn inverse(&mut self, buffer: &mut Array3<Complex64>, _scratch: &mut Array3<Complex64>) -> Result<(), DftError> {
+            self.run(buffer, false)?;
+            let norm = self.norm;
+            buffer.mapv_inplace(|c| c * norm);
+            Ok(())
+        }
+
+        // Ordem row-major do ndarray = ordem do plano (nx, ny, nz)
+        fn run(&mut self, buffer: &mut Array3<Complex64>, forward: bool) -> Result<(), DftError> {
+            let data = buffer.as_slice_mut().ok_or(DftError::NonContiguous("buffer da FFT"))?;
+            self.input.copy_from_slice(data);
+            let plan = if forward { &mut self.forward } else { &mut self.backward };
+            plan.c2c(&mut self.input, &mut self.output)
+                .map_err(|e| DftError::FftBackend(format!("execução do plano FFTW: {}", e)))?;
+            data.copy_from_slice(&self.output);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::ops::{Deref, DerefMut};
+
+use ndarray::Array3;
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::gridops;
+use crate::core::structure::Lattice;
+use crate::dft::error::DftError;
+use crate::utils::parallel;
+
+/// Campo escalar real no grid denso da FFT, junto com a rede da célula.
+/// Concentra a contabilidade de volume (dV = Ω/N) usada nas integrais.
+#[derive(Debug, Clone)]
+pub struct GridField {
+    pub lattice: Lattice,
+    pub data: Array3<f64>,
+}
+
+impl GridField {
+    pub fn new(lattice: Lattice, data: Array3<f64>) -> Self {
+        Self { lattice, data }
+    }
+
+    pub fn zeros(lattice: Lattice, dims: [usize; 3]) -> Self {
+        Self::new(lattice, Array3::zeros((dims[0], dims[1], dims[2])))
+    }
+
+    pub fn dims(&self) -> [usize; 3] {
+        let (nx, ny, nz) = self.data.dim();
+        [nx, ny, nz]
+    }
+
+    /// Volume por ponto do grid (Bohr^3).
+    pub fn dvol(&self) -> f64 {
+        self.lattice.dvol(self.data.len())
+    }
+
+    /// ∫f dr = Ω/N Σ f(r).
+    pub fn integrate(&self) -> f64 {
+        self.data.as_slice().map_or_else(|| self.data.sum(), parallel::sum) * self.dvol()
+    }
+
+    /// ∫f g dr, ex: ∫ρ V_eff dr.
+    pub fn dot(&self, other: &GridField) -> Result<f64, DftError> {
+        if self.dims() != other.dims() {
+            return Err(DftError::SizeMismatch("campo no grid", other.data.len(), self.data.len()));
+        }
+        let sum = match (self.data.as_slice(), other.data.as_slice()) {
+            (Some(a), Some(b)) => parallel::dot(a, b)?,
+            _ => self.data.iter().zip(&other.data).map(|(a, b)| a * b).sum(),
+        };
+        Ok(sum * self.dvol())
+    }
+
+    /// f += a·g ponto a ponto, ex: V_eff = V_loc + V_H + V_xc.
+    pub fn add_scaled(&mut self, a: f64, other: &GridField) -> Result<(), DftError> {
+        if self.dims() != other.dims() {
+            return Err(DftError::SizeMismatch("campo no grid", other.data.len(), self.data.len()));
+        }
+        match (other.data.as_slice(), self.data.as_slice_mut()) {
+            (Some(x), Some(y)) => gridops::axpy(a, x, y)?,
+            _ => self.data.scaled_add(a, &other.data),
+        }
+        Ok(())
+    }
+
+    /// Coeficientes de Fourier f(G) = 1/N Σ_r f(r) e^{-iG·r}, tais que
+    /// f(r) = Σ_G f(G) e^{iG·r}. Usa o buffer de `fft` como área de trabalho.
+    pub fn to_gspace(&self, fft: &mut FftGrid) -> Result<Array3<Complex64>, DftError> {
+        if self.dims() != fft.size {
+            return Err(DftError::SizeMismatch("campo no grid", self.data.len(), fft.size.iter().product()));
+        }
+        fft.buffer.zip_mut_with(&self.data, |b, &f| *b = Complex64::new(f, 0.0));
+        fft.forward_in_place()?;
+        let inv_n = 1.0 / self.data.len() as f64;
+        Ok(fft.buffer.mapv(|c| c * inv_n))
+    }
+}
+
+/// Densidade eletrônica ρ(r) (e/Bohr^3).
+#[derive(Debug, Clone)]
+pub struct DensityField(pub GridField);
+
+/// Potencial no espaço real V(r) (Ry).
+#[derive(Debug, Clone)]
+pub struct PotentialField(pub GridField);
+
+impl DensityField {
+    pub fn new(lattice: Lattice, data: Array3<f64>) -> Self {
+        Self(GridField::new(lattice, data))
+    }
+
+    pub fn zeros(lattice: Lattice, dims: [usize; 3]) -> Self {
+        Self(GridField::zeros(lattice, dims))
+    }
+
+    /// Número de elétrons ∫ρ dr.
+    pub fn total_charge(&self) -> f64 {
+        self.integrate()
+    }
+}
+
+impl PotentialField {
+    pub fn new(lattice: Lattice, data: Array3<f64>) -> Self {
+        Self(GridField::new(lattice, data))
+    }
+
+    pub fn zeros(lattice: Lattice, dims: [usize; 3]) -> Self {
+        Self(GridField::zeros(lattice, dims))
+    }
+}
+
+impl Deref for DensityField {
+    type Target = GridField;
+    fn deref(&self) -> &GridField {
+        &self.0
+    }
+}
+
+impl DerefMut for DensityField {
+    fn deref_mut(&mut self) -> &mut GridField {
+        &mut self.0
+    }
+}
+
+impl Deref for PotentialField {
+    type Target = GridField;
+    fn deref(&self) -> &GridField {
+        &self.0
+    }
+}
+
+impl DerefMut for PotentialField {
+    fn deref_mut(&mut self) -> &mut GridField {
+        &mut self.0
+    }
+}
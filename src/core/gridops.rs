@@ -0,0 +1,76 @@
+use num_complex::Complex64;
+
+use crate::dft::error::DftError;
+
+/// Kernels ponto a ponto sobre o grid real, em slices contíguos.
+/// Escritos em blocos de `LANES` com acumuladores independentes para que o
+/// compilador vetorize (sem dependência serial entre iterações).
+const LANES: usize = 8;
+
+/// buf[i] *= v[i]  (ex: V_eff(r) · ψ(r)).
+pub fn mul_real(buf: &mut [Complex64], v: &[f64]) -> Result<(), DftError> {
+    check_len("gridops::mul_real", v.len(), buf.len())?;
+    for (b, &x) in buf.iter_mut().zip(v) {
+        b.re *= x;
+        b.im *= x;
+    }
+    Ok(())
+}
+
+/// y[i] += a · x[i]
+pub fn axpy(a: f64, x: &[f64], y: &mut [f64]) -> Result<(), DftError> {
+    check_len("gridops::axpy", x.len(), y.len())?;
+    for (yi, &xi) in y.iter_mut().zip(x) {
+        *yi += a * xi;
+    }
+    Ok(())
+}
+
+/// y[i] += a · |x[i]|²  (ex: acumular ρ(r) a partir de ψ(r)).
+pub fn axpy_norm_sqr(a: f64, x: &[Complex64], y: &mut [f64]) -> Result<(), DftError> {
+    check_len("gridops::axpy_norm_sqr", x.len(), y.len())?;
+    for (yi, xi) in y.iter_mut().zip(x) {
+        *yi += a * (xi.re * xi.re + xi.im * xi.im);
+    }
+    Ok(())
+}
+
+/// x[i] *= a
+pub fn scale(a: f64, x: &mut [f64]) {
+    for xi in x.iter_mut() {
+        *xi *= a;
+    }
+}
+
+/// Σ x[i]
+pub fn sum(x: &[f64]) -> f64 {
+    let mut acc = [0.0; LANES];
+    let chunks = x.chunks_exact(LANES);
+    let tail: f64 = chunks.remainder().iter().sum();
+    for c in chunks {
+        for l in 0..LANES {
+            acc[l] += c[l];
+        }
+    }
+    acc.iter().sum::<f64>() + tail
+}
+
+/// Σ x[i] y[i]
+pub fn dot(x: &[f64], y: &[f64]) -> Result<f64, DftError> {
+    check_len("gridops::dot", y.len(), x.len())?;
+    let mut acc = [0.0; LANES];
+    let cx = x.chunks_exact(LANES);
+    let cy = y.chunks_exact(LANES);
+    let tail: f64 = cx.remainder().iter().zip(cy.remainder()).map(|(a, b)| a * b).sum();
+    for (a, b) in cx.zip(cy) {
+        for l in 0..LANES {
+            acc[l] += a[l] * b[l];
+        }
+    }
+    Ok(acc.iter().sum::<f64>() + tail)
+}
+
+/// `DftError::SizeMismatch` se `len` ≠ `expected`.
+fn check_len(what: &'static str, len: usize, expected: usize) -> Result<(), DftError> {
+    if len == expected { Ok(()) } else { Err(DftError::SizeMismatch(what, len, expected)) }
+}
@@ -1,4 +1,8 @@
+use std::collections::HashMap;
 use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use crate::core::structure::Structure;
+use crate::utils::constants::ANGSTROM_TO_BOHR;
 
 #[derive(Debug, Clone)]
 pub struct KPoint {
@@ -6,9 +10,47 @@ pub struct KPoint {
     pub weight: f64,     // Peso para integração na Zona de Brillouin
 }
 
+/// Centragem da malha Monkhorst-Pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeshCentering {
+    /// Inclui Γ (shift 0).
+    #[default]
+    Gamma,
+    /// Deslocada de meio passo em cada direção (equivale a `1 1 1` no pw.x).
+    Shifted,
+}
+
+impl MeshCentering {
+    pub fn shift(&self) -> [f64; 3] {
+        match self {
+            MeshCentering::Gamma => [0.0; 3],
+            MeshCentering::Shifted => [0.5; 3],
+        }
+    }
+}
+
+/// Nó rotulado de um caminho de bandas.
+#[derive(Debug, Clone, Serialize)]
+pub struct PathLabel {
+    pub index: usize, // Índice do ponto K no caminho
+    pub label: String,
+}
+
+/// Metadados de um caminho de bandas: rótulos e distância acumulada por ponto.
+#[derive(Debug, Clone)]
+pub struct BandPath {
+    pub labels: Vec<PathLabel>,
+    pub distances: Vec<f64>, // Bohr⁻¹
+}
+
+/// Resolução da chave de coordenadas fracionárias na redução por simetria.
+const K_KEY_RESOLUTION: f64 = 1.0e6;
+
 #[derive(Debug, Clone)]
 pub struct KGrid {
     pub k_points: Vec<KPoint>,
+    pub path: Option<BandPath>, // Só para caminhos de bandas
     // Futuro: Simetrias para reduzir o número de pontos
 }
 
@@ -20,6 +62,7 @@ impl KGrid {
                 coord: [0.0, 0.0, 0.0],
                 weight: 1.0,
             }],
+            path: None,
         }
     }
 
@@ -51,38 +94,103 @@ impl KGrid {
             }
         }
 
-        Self { k_points }
+        Self { k_points, path: None }
     }
 
-    pub fn band_path(points: Vec<[f64; 3]>, points_per_segment: usize) -> Self {
-        let mut k_points = Vec::new();
+    /// Malha MP com a centragem explícita.
+    pub fn monkhorst_pack_centered(grid: [usize; 3], centering: MeshCentering) -> Self {
+        Self::monkhorst_pack(grid, centering.shift())
+    }
+
+    /// Malha Γ-centrada com densidade dada por um espaçamento máximo (Å⁻¹, com o fator 2π).
+    pub fn from_spacing(structure: &Structure, spacing_inv_angstrom: f64) -> Self {
+        Self::from_spacing_centered(structure, spacing_inv_angstrom, MeshCentering::Gamma)
+    }
+
+    pub fn from_spacing_centered(structure: &Structure, spacing_inv_angstrom: f64, centering: MeshCentering) -> Self {
+        let grid = Self::grid_dims_for_spacing(structure, spacing_inv_angstrom);
+        log::debug!("Malha K por espaçamento {:.3} Å⁻¹: {}x{}x{}", spacing_inv_angstrom, grid[0], grid[1], grid[2]);
+        Self::monkhorst_pack_centered(grid, centering)
+    }
+
+    /// Reduz por inversão temporal (E(k) = E(-k) sem SOC/campo magnético): junta k e -k
+    /// (módulo um vetor recíproco) somando os pesos. O peso total é preservado.
+    pub fn reduce_time_reversal(self) -> Self {
+        let key = |c: [f64; 3]| c.map(|x| ((x * K_KEY_RESOLUTION).round() as i64).rem_euclid(K_KEY_RESOLUTION as i64));
+
+        let mut index: HashMap<[i64; 3], usize> = HashMap::new();
+        let mut k_points: Vec<KPoint> = Vec::new();
+        for kp in self.k_points {
+            let minus = key(kp.coord.map(|x| -x));
+            if let Some(&i) = index.get(&minus) {
+                k_points[i].weight += kp.weight;
+                continue;
+            }
+            index.insert(key(kp.coord), k_points.len());
+            k_points.push(kp);
+        }
+        Self { k_points, path: None }
+    }
+
+    /// Dimensões da malha MP para um espaçamento máximo entre k-points (Å⁻¹, com o fator 2π):
+    /// n_i = max(1, ceil(|b_i| / Δk)), como o KSPACING do VASP.
+    pub fn grid_dims_for_spacing(structure: &Structure, spacing_inv_angstrom: f64) -> [usize; 3] {
+        let recip = structure.lattice.reciprocal(); // Bohr⁻¹
+        // Δk em Bohr⁻¹: 1 Å⁻¹ = (1 / ANGSTROM_TO_BOHR) Bohr⁻¹
+        let dk = spacing_inv_angstrom / ANGSTROM_TO_BOHR;
+
+        let n = |i: usize| ((recip.column(i).norm() / dk).ceil() as usize).max(1);
+        [n(0), n(1), n(2)]
+    }
+
+    /// Caminho de bandas entre pontos de alta simetria rotulados (ex: ("Γ", [0,0,0])),
+    /// com `points_per_segment` pontos por segmento e o último nó incluído.
+    /// Guarda a distância acumulada em |k| cartesiano (Bohr⁻¹) para o eixo x dos gráficos.
+    pub fn band_path(nodes: &[(&str, [f64; 3])], points_per_segment: usize, structure: &Structure) -> Self {
+        let recip = structure.lattice.reciprocal();
         let weight = 0.0; // Bandas não têm peso no cálculo de densidade (só geometria)
 
+        let mut k_points = Vec::new();
+        let mut labels = Vec::new();
         // Itera sobre pares de pontos: (P0 -> P1), (P1 -> P2), etc.
-        for i in 0..(points.len() - 1) {
-            let start = Vector3::from(points[i]);
-            let end = Vector3::from(points[i+1]);
-            
-            let vector = end - start; // Vetor direção
-            
-            // Adiciona os pontos intermediários
+        for pair in nodes.windows(2) {
+            let start = Vector3::from(pair[0].1);
+            let vector = Vector3::from(pair[1].1) - start; // Vetor direção
+            labels.push(PathLabel { index: k_points.len(), label: pair[0].0.to_string() });
+
             for step in 0..points_per_segment {
                 let t = step as f64 / points_per_segment as f64; // Fração de 0.0 a 1.0
                 let k_vec = start + vector * t;
-                
-                k_points.push(KPoint {
-                    coord: [k_vec.x, k_vec.y, k_vec.z],
-                    weight, 
-                });
+                k_points.push(KPoint { coord: [k_vec.x, k_vec.y, k_vec.z], weight });
             }
         }
-        
+
         // Adiciona o último ponto final para fechar o caminho
-        k_points.push(KPoint {
-            coord: points.last().unwrap().clone(),
-            weight,
-        });
+        if let Some((label, coord)) = nodes.last() {
+            labels.push(PathLabel { index: k_points.len(), label: label.to_string() });
+            k_points.push(KPoint { coord: *coord, weight });
+        }
+
+        let mut distances = Vec::with_capacity(k_points.len());
+        let mut total = 0.0;
+        for (i, kp) in k_points.iter().enumerate() {
+            if i > 0 {
+                let dk = Vector3::from(kp.coord) - Vector3::from(k_points[i - 1].coord);
+                total += (recip * dk).norm();
+            }
+            distances.push(total);
+        }
+
+        Self { k_points, path: Some(BandPath { labels, distances }) }
+    }
+
+    /// Distância acumulada ao longo do caminho (Bohr⁻¹); `None` para malhas.
+    pub fn distances(&self) -> Option<&[f64]> {
+        self.path.as_ref().map(|p| p.distances.as_slice())
+    }
 
-        Self { k_points }
+    /// Rótulos dos nós do caminho com o índice do ponto K; vazio para malhas.
+    pub fn labels(&self) -> &[PathLabel] {
+        self.path.as_ref().map_or(&[], |p| p.labels.as_slice())
     }
 }
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::fmt;
+use std::f64::consts::PI;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::Structure;
+use crate::dft::wavefunctions::Precision;
+
+const COMPLEX_BYTES: usize = 16; // Complex64
+const REAL_BYTES: usize = 8;     // f64
+
+/// Número de iterações guardadas pelo mixing (Broyden/Pulay) na estimativa.
+pub const DEFAULT_MIXING_HISTORY: usize = 8;
+
+/// Estimativa do consumo de memória (bytes) dos principais arrays de uma simulação.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryEstimate {
+    pub wavefunctions: usize,  // n_k x n_bands x n_pw (complexo)
+    pub fft_buffers: usize,    // buffer + scratch do FftGrid
+    pub density: usize,        // rho (e V_eff) no grid denso
+    pub mixing_history: usize, // Pares (rho_in, resíduo) guardados pelo mixing
+    pub basis: usize,          // Índices G, |k+G|^2 e mapas de scatter/gather
+}
+
+impl MemoryEstimate {
+    /// Estimativa a partir dos tamanhos reais de base e grid.
+    pub fn from_sizes(npw_per_k: &[usize], fft_grid: [usize; 3], n_bands: usize, mixing_history: usize) -> Self {
+        let n_grid = fft_grid[0] * fft_grid[1] * fft_grid[2];
+        let npw_total: usize = npw_per_k.iter().sum();
+
+        Self {
+            wavefunctions: npw_total * n_bands * COMPLEX_BYTES,
+            fft_buffers: 2 * n_grid * COMPLEX_BYTES,
+            density: 2 * n_grid * REAL_BYTES,
+            mixing_history: 2 * mixing_history * n_grid * REAL_BYTES,
+            // (i32, i32, i32) + f64 + usize por vetor G
+            basis: npw_total * (3 * 4 + REAL_BYTES + std::mem::size_of::<usize>()),
+        }
+    }
+
+    /// Estimativa analítica, antes de gerar qualquer base:
+    /// NPW ~ Ω/(2π)^3 * (4π/3) * Ecut^(3/2)   (esfera |k+G|^2 <= Ecut, em Ry)
+    pub fn for_system(structure: &Structure, ecut: f64, ecut_rho: f64, n_kpoints: usize, n_bands: usize) -> Self {
+        let fft_grid = PlaneWaveBasis::calculate_optimal_fft_grid(&structure.lattice.reciprocal(), ecut_rho);
+        Self::for_grid(structure, ecut, fft_grid, n_kpoints, n_bands)
+    }
+
+    /// Como `for_system`, mas com o grid FFT já definido (override ou padding).
+    pub fn for_grid(structure: &Structure, ecut: f64, fft_grid: [usize; 3], n_kpoints: usize, n_bands: usize) -> Self {
+        let volume = structure.lattice.volume();
+        let npw = (volume / (8.0 * PI.powi(3)) * (4.0 * PI / 3.0) * ecut.powf(1.5)).ceil() as usize;
+
+        Self::from_sizes(&vec![npw; n_kpoints], fft_grid, n_bands, DEFAULT_MIXING_HISTORY)
+    }
+
+    /// Ajusta o termo das funções de onda para a precisão de armazenamento
+    /// (as estimativas acima assumem Complex64).
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.wavefunctions = self.wavefunctions / COMPLEX_BYTES * precision.complex_bytes();
+        self
+    }
+
+    /// Modo fora do núcleo: só `resident_bands` (+ o mesmo tanto em pré-leitura) de
+    /// `n_bands` bandas ficam em RAM.
+    pub fn with_resident_bands(mut self, n_bands: usize, resident_bands: usize) -> Self {
+        let resident = (2 * resident_bands).min(n_bands);
+        self.wavefunctions = self.wavefunctions / n_bands.max(1) * resident;
+        self
+    }
+
+    pub fn total(&self) -> usize {
+        self.wavefunctions + self.fft_buffers + self.density + self.mixing_history + self.basis
+    }
+}
+
+/// Formata bytes em unidades legíveis (KB, MB, GB).
+pub fn format_bytes(bytes: usize) -> String {
+    let b = bytes as f64;
+    if b >= 1024.0_f64.powi(3) {
+        format!("{:.2} GB", b / 1024.0_f64.powi(3))
+    } else if b >= 1024.0_f64.powi(2) {
+        format!("{:.2} MB", b / 1024.0_f64.powi(2))
+    } else {
+        format!("{:.2} KB", b / 1024.0)
+    }
+}
+
+/// Memória disponível no sistema (Linux: `MemAvailable` de /proc/meminfo).
+/// Retorna None em plataformas onde não conseguimos descobrir.
+pub fn available_memory() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo.lines()
+        .find(|l| l.starts_with("MemAvailable:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<usize>().ok())
+        .map(|kb| kb * 1024)
+}
+
+impl fmt::Display for MemoryEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Estimativa de Memória:")?;
+        writeln!(f, "  Funções de onda : {:>12}", format_bytes(self.wavefunctions))?;
+        writeln!(f, "  Buffers FFT     : {:>12}", format_bytes(self.fft_buffers))?;
+        writeln!(f, "  Densidade/V_eff : {:>12}", format_bytes(self.density))?;
+        writeln!(f, "  Histórico mixing: {:>12}", format_bytes(self.mixing_history))?;
+        writeln!(f, "  Bases (G)       : {:>12}", format_bytes(self.basis))?;
+        write!(f, "  Total           : {:>12}", format_bytes(self.total()))
+    }
+}
@@ -2,4 +2,8 @@ pub mod structure;
 pub mod simulation;
 pub mod kpoints;
 pub mod basis;
-pub mod fft;
\ No newline at end of file
+pub mod fft;
+pub mod memory;
+pub mod structure_factors;
+pub mod field;
+pub mod gridops;
@@ -1,18 +1,39 @@
 // src/core/simulation.rs
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use ndarray::Array1;
+use num_complex::Complex64;
 use thiserror::Error;
-use ndarray::Array3;
 
 // Imports dos seus módulos
 use crate::core::kpoints::KGrid;
 use crate::core::structure::Structure;
 use crate::io::upf::{Pseudopotential, UpfError};
+use crate::io::pseudo_library::PseudoLibrary;
+use crate::io::qe_density::{QeChargeDensity, QeDensityError};
+use crate::io::checkpoint::{Checkpoint, CheckpointError};
 use crate::utils::welcome::print_welcome;
-use crate::core::basis::PlaneWaveBasis;
-use crate::core::fft::FftGrid;         
-use crate::dft::density::calculate_initial_density;
+use crate::core::basis::{PlaneWaveBasis, DEFAULT_DUAL};
+use crate::core::fft::{fourier_resample, FftGrid};
+use crate::core::field::{DensityField, PotentialField};
+use crate::core::memory::{self, MemoryEstimate, DEFAULT_MIXING_HISTORY};
+use crate::core::structure_factors::StructureFactors;
+use crate::dft::density::calculate_initial_density_with;
+use crate::dft::error::DftError;
+use crate::dft::hartree::{hartree_potential, ion_ion_energy, CoulombKernel, HartreeResult};
+use crate::dft::hamiltonian::{BoundHamiltonian, Hamiltonian};
+use crate::dft::initial_guess::InitialGuess;
+use crate::dft::occupations::{BandPolicy, OccupationError, Smearing};
+use crate::dft::paw::AugmentationOverlap;
+use crate::dft::solver::{BandSolverResult, Diagonalizer, Overlap, SolverError};
+use crate::dft::xc::{XcFunctional, XcPolicy};
+use crate::dft::dispersion::{D2, D2Parameters, DispersionError, DispersionResult};
+use crate::dft::scratch::OutOfCore;
+use crate::dft::species_tables::SpeciesTables;
+use crate::dft::wavefunctions::Precision;
+use crate::dft::local_potential::calculate_local_potential_with;
+use crate::utils::logger;
 
 #[derive(Error, Debug)]
 pub enum SimulationError {
@@ -30,25 +51,188 @@ pub enum SimulationError {
 
     #[error("Erro ao carregar pseudopotencial: {0}")]
     UpfLoadError(#[from] UpfError),
+
+    #[error("Memória insuficiente: a simulação requer ~{0}, limite de {1}. Reduza Ecut ou o número de k-points.")]
+    InsufficientMemory(String, String),
+
+    #[error("Ecut inválido: {0} Ry (deve ser positivo e finito).")]
+    InvalidEcut(f64),
+
+    #[error("Ecut = {1:.1} Ry abaixo do mínimo sugerido pelo pseudo de '{0}' ({2:.1} Ry).")]
+    EcutBelowSuggested(String, f64, f64),
+
+    #[error("Ecut_rho = {0} Ry inválido: deve ser pelo menos 4 x Ecut = {1} Ry.")]
+    InvalidEcutRho(f64, f64),
+
+    #[error("Grid FFT {0:?} não contém a esfera de Ecut; mínimo {1:?}.")]
+    InvalidFftGrid([usize; 3], [usize; 3]),
+
+    #[error("Pseudo de '{0}' usa o funcional '{1}', mas o cálculo pede '{2}'.")]
+    FunctionalMismatch(String, String, String),
+
+    #[error("Grid FFT {0:?} requer ~{1} apenas para buffers e densidade, limite de {2}.")]
+    FftGridTooLarge([usize; 3], String, String),
+
+    #[error("Número total de elétrons de valência é zero.")]
+    NoElectrons,
+
+    #[error("Correção de dispersão: {0}")]
+    Dispersion(#[from] DispersionError),
+
+    #[error("{0}")]
+    Dft(#[from] DftError),
+
+    #[error("{0}")]
+    Occupation(#[from] OccupationError),
 }
 
 pub struct Simulation {
     // Inputs Físicos
     pub structure: Structure,
     pub ecut: f64,
+    pub ecut_rho: f64,              // Corte da densidade (grid denso da FFT)
     pub k_grid: KGrid,
     pub pseudos: HashMap<usize, Pseudopotential>,
+    pub n_bands: usize,             // Bandas por k-point (ver `BandPolicy`)
+    pub coulomb: CoulombKernel,     // Hartree, cauda de V_loc e íon-íon (truncado no modo molécula)
+    pub precision: Precision,       // Armazenamento das funções de onda (f64 ou f32)
+    pub smearing: Smearing,         // Alargamento das ocupações (nível de Fermi e gaps)
+    pub xc: Option<XcFunctional>,   // Pedido, ou o dos pseudos (None se não reconhecido)
+    pub dispersion: Option<D2>,     // Correção DFT-D2 de Grimme (energia e forças)
+    pub out_of_core: Option<OutOfCore>, // Funções de onda em arquivos de rascunho
+    pub diagonalizer: Diagonalizer, // Eigensolver das bandas (padrão: exato)
+    pub initial_guess: InitialGuess, // Bandas de partida do eigensolver (padrão: aleatórias)
 
     // Motores de Cálculo (Adicionados)
     pub bases: Vec<PlaneWaveBasis>, // Bases de ondas planas (uma por k-point)
     pub fft_grid: FftGrid,          // Gerenciador da FFT e memória
-    pub rho: Array3<f64>,           // Densidade de carga no espaço real
+    pub smooth_fft: FftGrid,        // Grid suave de H·ψ (igual ao denso se não encolher)
+    pub rho: DensityField,          // Densidade de carga no espaço real
+    pub hamiltonian: Hamiltonian,   // Termos de H; V_eff começa zerado até o SCF
+    structure_factors: StructureFactors, // S_s(G), recalculado quando a geometria muda
+    species_tables: SpeciesTables,       // Tabelas radiais por espécie (V_loc(G), ρ_atom(r))
 }
 
 impl Simulation {
     pub fn builder() -> SimulationBuilder {
         SimulationBuilder::new()
     }
+
+    /// Estimativa de memória com os tamanhos reais de bases e grid desta simulação.
+    pub fn estimated_memory(&self) -> MemoryEstimate {
+        let npw: Vec<usize> = self.bases.iter().map(|b| b.g_vectors.len()).collect();
+        let estimate = MemoryEstimate::from_sizes(&npw, self.fft_grid.size, self.n_bands, DEFAULT_MIXING_HISTORY)
+            .with_precision(self.precision);
+        match &self.out_of_core {
+            Some(ooc) => estimate.with_resident_bands(self.n_bands, ooc.resident_bands),
+            None => estimate,
+        }
+    }
+
+    /// Bandas de partida do ponto K `ik` segundo `initial_guess` (`n_bands` bandas
+    /// S-ortonormais, com o overlap de `dft::paw` se algum pseudo tem aumento).
+    pub fn initial_wavefunctions(&self, ik: usize) -> Vec<Array1<Complex64>> {
+        let overlap = AugmentationOverlap::for_pseudos(&self.structure, &self.pseudos, self.ecut);
+        self.initial_bands(ik, overlap.as_ref().map(|s| s as &dyn Overlap))
+    }
+
+    fn initial_bands(&self, ik: usize, overlap: Option<&dyn Overlap>) -> Vec<Array1<Complex64>> {
+        self.initial_guess.generate(&self.bases[ik], &self.structure, &self.pseudos, self.n_bands, overlap)
+    }
+
+    /// Bandas do ponto K `ik` com o V_eff atual do Hamiltoniano, pelo `diagonalizer`
+    /// (Hψ = εSψ se algum pseudo tem aumento). Os solvers iterativos partem de
+    /// `initial_wavefunctions(ik)`.
+    pub fn solve_bands(&mut self, ik: usize) -> Result<BandSolverResult, SolverError> {
+        let overlap = AugmentationOverlap::for_pseudos(&self.structure, &self.pseudos, self.ecut);
+        let overlap = overlap.as_ref().map(|s| s as &dyn Overlap);
+        let initial = match self.diagonalizer {
+            Diagonalizer::Exact => Vec::new(),
+            Diagonalizer::Pcg => self.initial_bands(ik, overlap),
+        };
+        let mut h = self.hamiltonian.bind(&self.bases[ik], &mut self.smooth_fft);
+        self.diagonalizer.solve(&mut h, self.n_bands, overlap, || initial)
+    }
+
+    /// Fatores de estrutura da geometria atual (recalculados se `structure` mudou).
+    pub fn structure_factors(&mut self) -> Result<&StructureFactors, DftError> {
+        if self.structure_factors.update(&self.structure)? {
+            log::debug!("Fatores de estrutura recalculados");
+        }
+        Ok(&self.structure_factors)
+    }
+
+    /// Hamiltoniano ligado à base do ponto K `ik`, pronto para `apply(psi)`. As FFTs de ψ
+    /// usam o grid suave.
+    pub fn hamiltonian_at(&mut self, ik: usize) -> BoundHamiltonian<'_> {
+        self.hamiltonian.bind(&self.bases[ik], &mut self.smooth_fft)
+    }
+
+    /// Troca o V_eff do Hamiltoniano (no grid denso); com grid suave menor, o potencial é
+    /// interpolado por Fourier para ele.
+    pub fn set_effective_potential(&mut self, v_eff: &PotentialField) -> Result<(), DftError> {
+        let data = fourier_resample(&v_eff.data, &mut self.fft_grid, &mut self.smooth_fft)?;
+        self.hamiltonian = Hamiltonian::new(PotentialField::new(v_eff.lattice.clone(), data));
+        Ok(())
+    }
+
+    /// Potencial local iônico V_loc(r) (Ry) no grid denso, para a geometria atual.
+    pub fn local_potential(&mut self) -> Result<PotentialField, DftError> {
+        if self.structure_factors.update(&self.structure)? {
+            log::debug!("Fatores de estrutura recalculados");
+        }
+        calculate_local_potential_with(
+            &self.structure,
+            &mut self.fft_grid,
+            &self.pseudos,
+            &self.structure_factors,
+            &mut self.species_tables,
+            self.coulomb,
+        )
+    }
+
+    /// Potencial e energia de Hartree (Ry) da densidade atual, com o kernel `coulomb`.
+    pub fn hartree(&mut self) -> Result<HartreeResult, DftError> {
+        hartree_potential(&self.rho, &mut self.fft_grid, self.coulomb)
+    }
+
+    /// Energia íon-íon (Ry): Ewald no cristal, soma direta no modo molécula.
+    pub fn ion_ion_energy(&self) -> Result<f64, DftError> {
+        let charges = self.structure.atoms.iter()
+            .map(|atom| self.pseudos.get(&atom.species_id)
+                .map(|p| p.header.z_valence)
+                .ok_or(DftError::MissingPseudo(atom.species_id)))
+            .collect::<Result<Vec<f64>, _>>()?;
+        ion_ion_energy(&self.structure, &charges, self.coulomb)
+    }
+
+    /// Energia (Ry) e forças (Ry/Bohr) de dispersão da geometria atual, já com as
+    /// restrições dos átomos congelados; `None` sem correção configurada.
+    pub fn dispersion_correction(&self) -> Option<Result<DispersionResult, DispersionError>> {
+        let d2 = self.dispersion.as_ref()?;
+        Some(d2.compute(&self.structure).map(|mut result| {
+            self.structure.constrain_forces(&mut result.forces);
+            result
+        }))
+    }
+
+    /// Descarta as tabelas radiais por espécie; necessário após trocar `pseudos`.
+    pub fn clear_species_tables(&mut self) {
+        self.species_tables.clear();
+    }
+
+    /// Número de elétrons de valência (soma dos Z_valence).
+    pub fn n_electrons(&self) -> f64 {
+        self.structure.atoms.iter()
+            .filter_map(|atom| self.pseudos.get(&atom.species_id))
+            .map(|p| p.header.z_valence)
+            .sum()
+    }
+
+    /// Bandas duplamente ocupadas (isolante, sem spin).
+    pub fn n_occupied(&self) -> usize {
+        (self.n_electrons() / 2.0).ceil() as usize
+    }
     
     pub fn run(&mut self) {
         print_welcome();
@@ -57,42 +241,69 @@ impl Simulation {
         let nk = self.k_grid.k_points.len();
         let (nx, ny, nz) = (self.fft_grid.size[0], self.fft_grid.size[1], self.fft_grid.size[2]);
 
-        println!("--- Inicialização Completa ---");
-        println!("Sistema: {} átomos, {} espécies", natoms, self.pseudos.len());
-        println!("K-Points: {} pontos na ZB", nk);
-        println!("Grid FFT: {} x {} x {} (Total: {})", nx, ny, nz, nx*ny*nz);
-        println!("Cutoffs: WFC={:.1} Ry, Rho={:.1} Ry", self.ecut, self.bases[0].ecut_rho);
-        print!( "{}", self.structure.clone());
+        log::info!("--- Inicialização Completa ---");
+        log::info!("Sistema: {} átomos, {} espécies", natoms, self.pseudos.len());
+        log::info!("K-Points: {} pontos na ZB", nk);
+        log::info!("Grid FFT: {} x {} x {} (Total: {})", nx, ny, nz, nx*ny*nz);
+        log::info!("Cutoffs: WFC={:.1} Ry, Rho={:.1} Ry", self.ecut, self.ecut_rho);
+        log::info!("{}", self.structure.to_string().trim_end());
         
         // Aqui começaria o loop SCF:
         // 1. Inicializar densidade aleatória ou superposição atômica
         // 2. Loop { V_eff -> Diagonalização -> Rho_new -> Mix -> Check Convergência }
     }
 
+    /// Salva ρ atual e `v_eff` para uma etapa posterior (ex: bandas não auto-consistentes).
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P, v_eff: &PotentialField) -> Result<(), CheckpointError> {
+        Checkpoint::new(self.ecut, self.ecut_rho, self.rho.clone(), v_eff.clone())?.write(path)
+    }
+
+    /// Recarrega ρ e V_eff de um checkpoint da mesma célula e grid. ρ substitui a densidade
+    /// atual e V_eff passa a ser o potencial local do Hamiltoniano; V_eff também é devolvido.
+    pub fn load_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<PotentialField, CheckpointError> {
+        let ckpt = Checkpoint::read(path)?;
+        ckpt.check_compatible(&self.structure.lattice, self.fft_grid.size)?;
+        if (ckpt.ecut - self.ecut).abs() > 1e-10 {
+            log::warn!("Checkpoint com Ecut = {:.2} Ry, simulação com {:.2} Ry", ckpt.ecut, self.ecut);
+        }
+        self.rho = ckpt.rho;
+        self.set_effective_potential(&ckpt.v_eff)?;
+        log::info!("Checkpoint carregado: carga {:.4} e", self.rho.total_charge());
+        Ok(ckpt.v_eff)
+    }
+
+    /// Usa como densidade inicial um `charge-density.dat` do pw.x (mesma célula).
+    pub fn import_qe_density<P: AsRef<Path>>(&mut self, path: P) -> Result<(), QeDensityError> {
+        let qe = QeChargeDensity::read(path)?;
+        self.rho = qe.to_density(&self.structure, &mut self.fft_grid)?;
+        log::info!("Densidade importada do QE: {} vetores G, carga {:.4} e", qe.millers.len(), self.rho.total_charge());
+        Ok(())
+    }
+
+    /// Escreve ρ atual no formato `charge-density.dat` do pw.x (esfera de Ecut_rho).
+    pub fn export_qe_density<P: AsRef<Path>>(&mut self, path: P) -> Result<(), QeDensityError> {
+        QeChargeDensity::from_density(&self.rho, &mut self.fft_grid, self.ecut_rho)?.write(path)
+    }
+
     /// Preenche o grid rho com a superposição das densidades atômicas
-    pub fn initialize_density(&mut self) {
-        println!("Calculando densidade inicial (SAD)...");
+    pub fn initialize_density(&mut self) -> Result<(), DftError> {
+        log::info!("Calculando densidade inicial (SAD)...");
         
-        let rho_sad = calculate_initial_density(
+        let rho_sad = calculate_initial_density_with(
             &self.structure, 
             &self.fft_grid, 
-            &self.pseudos
-        );
+            &self.pseudos,
+            &mut self.species_tables,
+        )?;
         
         // Atualiza o estado da simulação
         self.rho = rho_sad;
         
         // Check de Carga Total (Integral)
-        // Carga = sum(rho) * volume_voxel
-        // volume_voxel = det(Lattice) / N_grid
-        let volume = self.structure.lattice.volume();
-        let n_grid = self.fft_grid.size[0] * self.fft_grid.size[1] * self.fft_grid.size[2];
-        let dvol = volume / n_grid as f64;
-        
-        let total_charge: f64 = self.rho.sum() * dvol;
+        let total_charge = self.rho.total_charge();
         
-        println!("Densidade inicial calculada.");
-        println!("  - Carga Total Integrada: {:.4} e", total_charge);
+        log::info!("Densidade inicial calculada.");
+        log::info!("  - Carga Total Integrada: {:.4} e", total_charge);
         
         // Verifica neutralidade (soma dos eletrons de valencia)
         let mut expected_charge = 0.0;
@@ -101,14 +312,34 @@ impl Simulation {
                 expected_charge += p.header.z_valence;
             }
         }
-        println!("  - Carga Esperada (Zval): {:.4} e", expected_charge);
+        log::info!("  - Carga Esperada (Zval): {:.4} e", expected_charge);
+        logger::record("initial_density", &[
+            ("charge", format!("{:.6}", total_charge)),
+            ("expected", format!("{:.6}", expected_charge)),
+        ]);
+        Ok(())
     }
 }
 
 pub struct SimulationBuilder {
     structure: Option<Structure>,
     ecut: Option<f64>,
+    ecut_rho: Option<f64>,
     k_grid: Option<KGrid>,
+    memory_limit: Option<usize>,
+    isolated: bool,
+    xc: Option<XcFunctional>,
+    xc_policy: XcPolicy,
+    dispersion: Option<D2Parameters>,
+    fft_grid: Option<[usize; 3]>,
+    fft_padding: usize,
+    pseudos: HashMap<usize, Pseudopotential>,
+    precision: Precision,
+    smearing: Smearing,
+    band_policy: BandPolicy,
+    out_of_core: Option<OutOfCore>,
+    diagonalizer: Diagonalizer,
+    initial_guess: InitialGuess,
 }
 
 impl SimulationBuilder {
@@ -116,7 +347,22 @@ impl SimulationBuilder {
         Self {
             structure: None,
             ecut: None,
+            ecut_rho: None,
             k_grid: None,
+            memory_limit: None,
+            isolated: false,
+            xc: None,
+            xc_policy: XcPolicy::Error,
+            dispersion: None,
+            fft_grid: None,
+            fft_padding: 0,
+            pseudos: HashMap::new(),
+            precision: Precision::Double,
+            smearing: Smearing::Fixed,
+            band_policy: BandPolicy::default(),
+            out_of_core: None,
+            diagonalizer: Diagonalizer::default(),
+            initial_guess: InitialGuess::default(),
         }
     }
 
@@ -130,16 +376,127 @@ impl SimulationBuilder {
         self
     }
 
+    /// Corte da densidade (Ry). Padrão: 4 x Ecut; ultrasoft/PAW tipicamente 8-12 x Ecut.
+    pub fn ecut_rho(mut self, ecut_rho: f64) -> Self {
+        self.ecut_rho = Some(ecut_rho);
+        self
+    }
+
     // Corrigido typo: k_drid -> k_grid
     pub fn k_grid(mut self, k_grid: KGrid) -> Self {
         self.k_grid = Some(k_grid);
         self
     }
 
+    /// Limite de memória (bytes) para a simulação. Se não definido, usa a memória
+    /// disponível no sistema (quando detectável).
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Modo molécula: caixa cúbica com `vacuum_radius` (Bohr) de vácuo em volta dos átomos,
+    /// molécula centrada, apenas Γ e Coulomb truncado (`CoulombKernel::isolated`), sem
+    /// interação com as imagens periódicas. O truncamento é exato se `vacuum_radius` for
+    /// maior que metade da extensão da molécula somada ao dobro da cauda da densidade.
+    pub fn molecule(mut self, structure: Structure, vacuum_radius: f64) -> Self {
+        self.structure = Some(structure.in_vacuum_box(vacuum_radius));
+        self.k_grid = Some(KGrid::gamma());
+        self.isolated = true;
+        self
+    }
+
+    /// Dimensões fixas do grid FFT, no lugar da escolha automática por Ecut_rho
+    /// (ex: reproduzir resultados de outro código). Grids menores que o de Ecut_rho
+    /// são aceitos com aviso (aliasing na densidade).
+    pub fn fft_grid(mut self, size: [usize; 3]) -> Self {
+        self.fft_grid = Some(size);
+        self
+    }
+
+    /// Pontos extras por direção somados ao grid FFT (arredondado para um tamanho eficiente).
+    pub fn fft_padding(mut self, extra: usize) -> Self {
+        self.fft_padding = extra;
+        self
+    }
+
+    /// Usa `pseudo` para a espécie `species_id` em vez de ler o UPF do disco
+    /// (ex: `Pseudopotential::mock` em testes e exemplos).
+    pub fn pseudo(mut self, species_id: usize, pseudo: Pseudopotential) -> Self {
+        self.pseudos.insert(species_id, pseudo);
+        self
+    }
+
+    /// Funcional de troca-correlação (ex: `"pbe".parse()?`). Os pseudos devem ter sido
+    /// gerados com o mesmo funcional (ver `xc_policy`).
+    pub fn xc(mut self, functional: XcFunctional) -> Self {
+        self.xc = Some(functional);
+        self
+    }
+
+    /// Erro (padrão) ou aviso quando o funcional de um pseudo difere do pedido.
+    pub fn xc_policy(mut self, policy: XcPolicy) -> Self {
+        self.xc_policy = policy;
+        self
+    }
+
+    /// Correção de dispersão DFT-D2; sem `s6`, usa o do funcional.
+    pub fn dispersion(mut self, params: D2Parameters) -> Self {
+        self.dispersion = Some(params);
+        self
+    }
+
+    /// Precisão das funções de onda guardadas entre pontos K (ex: NSCF). `Single` reduz
+    /// essa memória pela metade, com precisão de ~1e-6 Ry nas energias (reduções seguem em f64).
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Alargamento das ocupações (padrão: inteiras).
+    pub fn smearing(mut self, smearing: Smearing) -> Self {
+        self.smearing = smearing;
+        self
+    }
+
+    /// Número de bandas por ponto K (padrão: n_occ + 4).
+    pub fn band_policy(mut self, policy: BandPolicy) -> Self {
+        self.band_policy = policy;
+        self
+    }
+
+    /// Guarda as bandas de cada ponto K em `scratch_dir` (NSCF, ver `dft::scratch`), lidas
+    /// em blocos de `resident_bands`. A checagem de memória passa a contar só esses blocos.
+    pub fn out_of_core<P: Into<PathBuf>>(mut self, scratch_dir: P, resident_bands: usize) -> Self {
+        self.out_of_core = Some(OutOfCore { scratch_dir: scratch_dir.into(), resident_bands });
+        self
+    }
+
+    /// Eigensolver das bandas (padrão: diagonalização exata, limitada a
+    /// `solver::EXACT_DIAG_MAX_PW` ondas planas; use `Pcg` em bases maiores).
+    pub fn diagonalizer(mut self, diagonalizer: Diagonalizer) -> Self {
+        self.diagonalizer = diagonalizer;
+        self
+    }
+
+    /// Bandas de partida do eigensolver iterativo (padrão: aleatórias com semente).
+    pub fn initial_guess(mut self, guess: InitialGuess) -> Self {
+        self.initial_guess = guess;
+        self
+    }
+
     pub fn build(self) -> Result<Simulation, SimulationError> {
         // 1. Validações Básicas
         let structure = self.structure.ok_or(SimulationError::MissingStructure)?;
         let ecut = self.ecut.ok_or(SimulationError::MissingEcut)?;
+        if !(ecut.is_finite() && ecut > 0.0) {
+            return Err(SimulationError::InvalidEcut(ecut));
+        }
+        let ecut_rho = self.ecut_rho.unwrap_or(DEFAULT_DUAL * ecut);
+        if !(ecut_rho.is_finite() && ecut_rho >= DEFAULT_DUAL * ecut) {
+            return Err(SimulationError::InvalidEcutRho(ecut_rho, DEFAULT_DUAL * ecut));
+        }
+        self.smearing.validate()?;
         
         // Se K-Grid não for definido, assume Gamma Point
         let k_grid = self.k_grid.unwrap_or_else(|| KGrid::gamma());
@@ -150,51 +507,199 @@ impl SimulationBuilder {
 
         // 2. Carregamento de Pseudopotenciais
         let mut pseudos = HashMap::new();
-        println!("Carregando pseudopotenciais...");
+        log::info!("Carregando pseudopotenciais...");
         
+        let mut library = PseudoLibrary::from_env();
+        if let (Some(xc), XcPolicy::Error) = (self.xc, self.xc_policy) {
+            library = library.functional(xc);
+        }
+
+        let mut provided = self.pseudos;
         for species in &structure.species {
-            let path_str = &species.pseudo_path;
-            let path = Path::new(path_str);
+            let (upf, source) = match provided.remove(&species.id) {
+                Some(upf) => (upf, "fornecido pelo chamador".to_string()),
+                None => {
+                    let path_str = &species.pseudo_path;
 
-            if !path.exists() {
-                return Err(SimulationError::PseudoFileNotFound(
+                    // Caminho explícito tem prioridade; senão procura em BRAVIE_PSEUDO_DIR
+                    let path = if Path::new(path_str).is_file() {
+                        PathBuf::from(path_str)
+                    } else {
+                        library.resolve(&species.element).ok_or_else(|| SimulationError::PseudoFileNotFound(
+                            species.element.clone(),
+                            path_str.clone()
+                        ))?
+                    };
+                    (Pseudopotential::from_file(&path)?, path.display().to_string())
+                }
+            };
+
+            if let Some(xc) = self.xc
+                && !xc.matches_upf(&upf.header.functional) {
+                match self.xc_policy {
+                    XcPolicy::Error => return Err(SimulationError::FunctionalMismatch(
+                        species.element.clone(),
+                        upf.header.functional.clone(),
+                        xc.to_string(),
+                    )),
+                    XcPolicy::Warn => log::warn!("Pseudo de '{}' usa o funcional '{}', mas o cálculo pede '{}'",
+                        species.element, upf.header.functional.trim(), xc),
+                }
+            }
+            if ecut < upf.header.wfc_cutoff {
+                return Err(SimulationError::EcutBelowSuggested(
                     species.element.clone(),
-                    path_str.clone()
+                    ecut,
+                    upf.header.wfc_cutoff,
                 ));
             }
-
-            let upf = Pseudopotential::from_file(path)?;
+            if ecut_rho < upf.header.rho_cutoff {
+                log::warn!(
+                    "{}: Ecut_rho = {:.1} Ry abaixo do sugerido pelo pseudo ({:.1} Ry)",
+                    species.element, ecut_rho, upf.header.rho_cutoff
+                );
+            }
+            for issue in upf.warnings.iter().chain(upf.validate().iter()) {
+                log::warn!("{}: {}", species.element, issue);
+            }
+            if upf.header.is_augmented() {
+                log::warn!(
+                    "{}: pseudo {}: só o overlap S entra (NSCF); densidade aumentada, D_ij e energias on-site de `dft::paw` ainda fora do Hamiltoniano",
+                    species.element, upf.header.pseudo_type
+                );
+            }
+            if upf.spin_orb.is_some() {
+                log::warn!(
+                    "{}: pseudo totalmente relativístico: spin-órbita não suportado, PP_SPIN_ORB ignorado (projetores usados sem j)",
+                    species.element
+                );
+            }
+            if upf.units.converted() {
+                log::warn!("{}: unidades normalizadas: {}", species.element, upf.units);
+            }
             pseudos.insert(species.id, upf);
-            println!("  [OK] {} -> {}", species.element, path_str);
+            log::info!("  [OK] {} -> {}", species.element, source);
         }
 
-        // 3. Inicialização dos Motores Numéricos (Basis e FFT)
-        println!("Inicializando grids e bases...");
+        // Sem pedido explícito, adota o funcional dos pseudos se todos concordam
+        let xc = self.xc.or_else(|| {
+            let mut found = pseudos.values().map(|p| XcFunctional::from_upf(&p.header.functional));
+            let first = found.next().flatten();
+            if found.all(|f| f == first) {
+                first
+            } else {
+                log::warn!("Pseudos gerados com funcionais diferentes");
+                None
+            }
+        });
+        let dispersion = self.dispersion.map(|p| D2::new(p, xc)).transpose()?;
+        if let Some(d2) = &dispersion {
+            d2.check(&structure)?;
+        }
+
+        // 3. Número de bandas e checagem de memória (antes de qualquer alocação grande)
+        let n_electrons: f64 = structure.atoms.iter()
+            .filter_map(|atom| pseudos.get(&atom.species_id))
+            .map(|p| p.header.z_valence)
+            .sum();
+        if n_electrons <= 0.0 {
+            return Err(SimulationError::NoElectrons);
+        }
+        let n_bands = self.band_policy.n_bands(n_electrons);
+
+        // Grid FFT: automático por Ecut_rho, ou fixado pelo usuário; padding opcional
+        let recip = structure.lattice.reciprocal();
+        let auto_grid = PlaneWaveBasis::calculate_optimal_fft_grid(&recip, ecut_rho);
+        let min_grid = PlaneWaveBasis::calculate_optimal_fft_grid(&recip, ecut);
+        let mut grid = self.fft_grid.unwrap_or(auto_grid);
+        if self.fft_padding > 0 {
+            grid = grid.map(|n| PlaneWaveBasis::next_fft_size(n + self.fft_padding));
+        }
+        if grid.iter().zip(&min_grid).any(|(n, m)| n < m) {
+            return Err(SimulationError::InvalidFftGrid(grid, min_grid));
+        }
+        if grid.iter().zip(&auto_grid).any(|(n, m)| n < m) {
+            log::warn!("Grid FFT {:?} menor que o necessário para Ecut_rho ({:?}): densidade com aliasing", grid, auto_grid);
+        }
+
+        let mut estimate = MemoryEstimate::for_grid(&structure, ecut, grid, k_grid.k_points.len(), n_bands)
+            .with_precision(self.precision);
+        if let Some(ooc) = &self.out_of_core {
+            estimate = estimate.with_resident_bands(n_bands, ooc.resident_bands);
+            log::info!("Funções de onda fora do núcleo: {} bandas residentes, rascunho em {}",
+                ooc.resident_bands, ooc.scratch_dir.display());
+        }
+        log::debug!("{}", estimate);
+
+        let limit = self.memory_limit.or_else(memory::available_memory);
+        // O grid denso independe de k-points e bandas: se ele sozinho não cabe, só reduzir Ecut resolve
+        let grid_bytes = estimate.fft_buffers + estimate.density;
+        if let Some(limit) = limit
+            && grid_bytes > limit {
+            return Err(SimulationError::FftGridTooLarge(
+                grid,
+                memory::format_bytes(grid_bytes),
+                memory::format_bytes(limit),
+            ));
+        }
+
+        if let Some(limit) = limit
+            && estimate.total() > limit {
+            return Err(SimulationError::InsufficientMemory(
+                memory::format_bytes(estimate.total()),
+                memory::format_bytes(limit),
+            ));
+        }
+
+        // 4. Inicialização dos Motores Numéricos (Basis e FFT)
+        log::info!("Inicializando grids e bases...");
         
+        // Grid suave para H·ψ: só o denso precisa cobrir Ecut_rho
+        let smooth = PlaneWaveBasis::smooth_fft_grid(&recip, ecut, grid);
+        if smooth != grid {
+            log::info!("Grid suave de ψ: {:?} (denso: {:?})", smooth, grid);
+        }
+
         // Gera uma base de ondas planas para CADA ponto K
         // Precisamos acessar .coord do KPoint
         let bases: Vec<PlaneWaveBasis> = k_grid.k_points.iter()
             .map(|kp| {
-                PlaneWaveBasis::new(&structure, ecut, Some(kp.coord))
+                PlaneWaveBasis::with_grid(&structure, ecut, ecut_rho, grid, Some(kp.coord)).with_smooth_grid(smooth)
             })
             .collect();
 
-        // O Grid FFT é geométrico, independe do k-point (exceto para algoritmos avançados).
-        // Usamos a primeira base para definir as dimensões (nx, ny, nz).
-        let fft_grid = FftGrid::new(&bases[0]);
+        // O Grid FFT é geométrico e compartilhado; cada base guarda o próprio mapa G -> grid.
+        let fft_grid = FftGrid::with_size(grid)?;
+        let smooth_fft = FftGrid::with_size(smooth)?;
 
-        // 4. Alocação da Densidade (Rho)
-        let (nx, ny, nz) = (fft_grid.size[0], fft_grid.size[1], fft_grid.size[2]);
-        let rho = Array3::<f64>::zeros((nx, ny, nz));
+        // 5. Alocação da Densidade (Rho)
+        let rho = DensityField::zeros(structure.lattice.clone(), fft_grid.size);
+        let hamiltonian = Hamiltonian::new(PotentialField::zeros(structure.lattice.clone(), smooth_fft.size));
+        let structure_factors = StructureFactors::new(&structure, fft_grid.size)?;
+        let coulomb = if self.isolated { CoulombKernel::isolated(&structure.lattice) } else { CoulombKernel::Periodic };
 
         Ok(Simulation {
             structure,
             ecut,
+            ecut_rho,
             k_grid,
             pseudos,
+            n_bands,
+            coulomb,
+            precision: self.precision,
+            smearing: self.smearing,
+            xc,
+            dispersion,
+            out_of_core: self.out_of_core,
+            diagonalizer: self.diagonalizer,
+            initial_guess: self.initial_guess,
             bases,
             fft_grid,
+            smooth_fft,
             rho,
+            hamiltonian,
+            structure_factors,
+            species_tables: SpeciesTables::new(),
         })
     }
 }
\ No newline at end of file
@@ -28,6 +28,28 @@ pub struct Species {
 pub struct Atom {
     pub species_id: usize,
     pub position: Vector3<f64>,
+    pub fixed: [bool; 3], // Componentes cartesianas (x, y, z) congeladas na relaxação
+}
+
+impl Atom {
+    pub fn new(species_id: usize, position: Vector3<f64>) -> Self {
+        Self { species_id, position, fixed: [false; 3] }
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.fixed.iter().any(|&f| f)
+    }
+
+    /// Força com as componentes congeladas zeradas.
+    pub fn constrain(&self, force: Vector3<f64>) -> Vector3<f64> {
+        let mut f = force;
+        for (c, &fixed) in f.iter_mut().zip(&self.fixed) {
+            if fixed {
+                *c = 0.0;
+            }
+        }
+        f
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +68,11 @@ impl Lattice {
         self.vectors.determinant().abs()
     }
 
+    /// Volume por ponto de um grid com `n_points` pontos, dV = Ω/N (Bohr^3).
+    pub fn dvol(&self, n_points: usize) -> f64 {
+        self.volume() / n_points.max(1) as f64
+    }
+
     pub fn reciprocal(&self) -> Matrix3<f64> {
         let vol = self.volume();
         let a1 = self.vectors.column(0);
@@ -114,10 +141,14 @@ impl StructureBuilder {
     }
 
     pub fn add_atom(mut self, pos: [f64; 3], species_id: usize) -> Self {
-        self.atoms.push(Atom {
-        position: Vector3::from(pos),
-        species_id,
-        });
+        self.atoms.push(Atom::new(species_id, Vector3::from(pos)));
+        self
+    }
+
+    /// Átomo com componentes congeladas, ex: `[true, true, true]` para as camadas de
+    /// fundo de um slab ou `[false, false, true]` para mover só no plano.
+    pub fn add_fixed_atom(mut self, pos: [f64; 3], species_id: usize, fixed: [bool; 3]) -> Self {
+        self.atoms.push(Atom { species_id, position: Vector3::from(pos), fixed });
         self
     }
 
@@ -146,6 +177,87 @@ impl Structure {
     pub fn builder() -> StructureBuilder {
         StructureBuilder::new()
     }
+
+    /// Supercélula n1 x n2 x n3. Os átomos ficam ordenados com a translação no laço externo:
+    /// índice = t * N_atoms + i, com t percorrendo (t1, t2, t3) em ordem lexicográfica.
+    /// Assim os N primeiros átomos são os da célula original (t = 0).
+    pub fn supercell(&self, n: [usize; 3]) -> Structure {
+        let a = &self.lattice.vectors;
+        let lattice = Lattice::new(
+            a.column(0) * n[0] as f64,
+            a.column(1) * n[1] as f64,
+            a.column(2) * n[2] as f64,
+        );
+
+        let mut atoms = Vec::with_capacity(self.atoms.len() * n[0] * n[1] * n[2]);
+        for t1 in 0..n[0] {
+            for t2 in 0..n[1] {
+                for t3 in 0..n[2] {
+                    let shift = a * Vector3::new(t1 as f64, t2 as f64, t3 as f64);
+                    for atom in &self.atoms {
+                        atoms.push(Atom {
+                            position: atom.position + shift,
+                            ..atom.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        Structure {
+            lattice,
+            species: self.species.clone(),
+            atoms,
+        }
+    }
+
+    /// Zera as componentes congeladas de `forces` (uma por átomo, na ordem de `atoms`).
+    pub fn constrain_forces(&self, forces: &mut [Vector3<f64>]) {
+        for (atom, f) in self.atoms.iter().zip(forces.iter_mut()) {
+            *f = atom.constrain(*f);
+        }
+    }
+
+    /// Congela (x, y, z) dos átomos com coordenada cartesiana `axis` abaixo de `below`
+    /// (Bohr), ex: as camadas inferiores de um slab. Devolve quantos foram congelados.
+    pub fn freeze_below(&mut self, axis: usize, below: f64) -> usize {
+        let mut count = 0;
+        for atom in self.atoms.iter_mut().filter(|a| a.position[axis] < below) {
+            atom.fixed = [true; 3];
+            count += 1;
+        }
+        count
+    }
+
+    /// Caixa cúbica para moléculas: aresta = extensão máxima da molécula + 2 * `vacuum`
+    /// (Bohr), com o centro da caixa envolvente dos átomos no centro da célula.
+    pub fn in_vacuum_box(&self, vacuum: f64) -> Structure {
+        let mut min = Vector3::repeat(f64::INFINITY);
+        let mut max = Vector3::repeat(f64::NEG_INFINITY);
+        for atom in &self.atoms {
+            min = min.inf(&atom.position);
+            max = max.sup(&atom.position);
+        }
+        if self.atoms.is_empty() {
+            min = Vector3::zeros();
+            max = Vector3::zeros();
+        }
+
+        let side = (max - min).max() + 2.0 * vacuum;
+        let shift = Vector3::repeat(side / 2.0) - (min + max) / 2.0;
+
+        Structure {
+            lattice: Lattice::new(
+                Vector3::new(side, 0.0, 0.0),
+                Vector3::new(0.0, side, 0.0),
+                Vector3::new(0.0, 0.0, side),
+            ),
+            species: self.species.clone(),
+            atoms: self.atoms.iter()
+                .map(|atom| Atom { position: atom.position + shift, ..atom.clone() })
+                .collect(),
+        }
+    }
 }
 
 impl fmt::Display for Structure {
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use nalgebra::{Matrix3, Vector3};
+use ndarray::Array3;
+use num_complex::Complex64;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::utils::timer;
+
+/// Frequência (índice de Miller) do ponto `i` de um eixo FFT com `n` pontos.
+pub fn fft_frequency(i: usize, n: usize) -> i32 {
+    if i > n / 2 { i as i32 - n as i32 } else { i as i32 }
+}
+
+/// Fatores de estrutura por espécie no grid FFT denso: S_s(G) = Σ_{τ ∈ s} e^{-iG·τ}.
+/// Indexados como o buffer da FFT (i, j, k). Compartilhados por potencial local, SAD,
+/// Ewald e forças; guardam a geometria com que foram calculados para detectar quando
+/// ficaram obsoletos (relaxação, dinâmica molecular).
+#[derive(Debug, Clone)]
+pub struct StructureFactors {
+    pub grid: [usize; 3],
+    factors: HashMap<usize, Array3<Complex64>>,
+    lattice: Matrix3<f64>,
+    positions: Vec<(usize, Vector3<f64>)>,
+}
+
+impl StructureFactors {
+    pub fn new(structure: &Structure, grid: [usize; 3]) -> Result<Self, DftError> {
+        let mut sf = Self {
+            grid,
+            factors: HashMap::new(),
+            lattice: structure.lattice.vectors,
+            positions: Vec::new(),
+        };
+        sf.compute(structure)?;
+        Ok(sf)
+    }
+
+    /// Os fatores correspondem à geometria atual de `structure`?
+    pub fn is_valid_for(&self, structure: &Structure) -> bool {
+        self.lattice == structure.lattice.vectors
+            && self.positions.len() == structure.atoms.len()
+            && self.positions.iter().zip(&structure.atoms)
+                .all(|((id, pos), atom)| *id == atom.species_id && *pos == atom.position)
+    }
+
+    /// Recalcula se a geometria mudou. Retorna `true` se houve recálculo.
+    pub fn update(&mut self, structure: &Structure) -> Result<bool, DftError> {
+        if self.is_valid_for(structure) {
+            return Ok(false);
+        }
+        self.compute(structure)?;
+        Ok(true)
+    }
+
+    /// S_s(G) da espécie `species_id`.
+    pub fn species(&self, species_id: usize) -> Option<&Array3<Complex64>> {
+        self.factors.get(&species_id)
+    }
+
+    fn compute(&mut self, structure: &Structure) -> Result<(), DftError> {
+        let _t = timer::scope("structure_factors");
+        let [nx, ny, nz] = self.grid;
+        let lattice_inv = structure.lattice.vectors.try_inverse().ok_or(DftError::SingularLattice)?;
+
+        self.factors.clear();
+
+        // G·τ = 2π Σ_a m_a f_a (f = coordenadas fracionárias): a fase fatoriza por eixo
+        let phases_1d = |f: f64, n: usize| -> Vec<Complex64> {
+            (0..n).map(|i| Complex64::from_polar(1.0, -2.0 * PI * fft_frequency(i, n) as f64 * f)).collect()
+        };
+
+        for atom in &structure.atoms {
+            let s = self.factors.entry(atom.species_id).or_insert_with(|| Array3::zeros((nx, ny, nz)));
+            let f = lattice_inv * atom.position;
+            let (px, py, pz) = (phases_1d(f.x, nx), phases_1d(f.y, ny), phases_1d(f.z, nz));
+            for ((i, j, k), v) in s.indexed_iter_mut() {
+                *v += px[i] * py[j] * pz[k];
+            }
+        }
+
+        self.lattice = structure.lattice.vectors;
+        self.positions = structure.atoms.iter().map(|a| (a.species_id, a.position)).collect();
+        Ok(())
+    }
+}
@@ -1,73 +1,93 @@
-use std::f64::consts::PI;
-use ndarray::Array3;
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
 use nalgebra::Vector3;
-use crate::core::structure::Structure;
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::{Lattice, Structure};
 use crate::core::fft::FftGrid;
+use crate::core::field::DensityField;
+use crate::core::gridops;
 use crate::io::upf::Pseudopotential;
 use std::collections::HashMap;
+use crate::utils::{parallel, timer};
+use crate::dft::error::DftError;
+use crate::dft::solver::BandSolverResult;
+use crate::dft::species_tables::{RealSpaceTable, SpeciesTables};
 
 /// Calcula a densidade inicial (SAD) e aplica renormalização de carga.
 pub fn calculate_initial_density(
     structure: &Structure,
     fft_grid: &FftGrid,
     pseudos: &HashMap<usize, Pseudopotential>
-) -> Array3<f64> {
+) -> Result<DensityField, DftError> {
+    calculate_initial_density_with(structure, fft_grid, pseudos, &mut SpeciesTables::new())
+}
+
+/// Como `calculate_initial_density`, consultando as tabelas de ρ_atom de `tables`.
+pub fn calculate_initial_density_with(
+    structure: &Structure,
+    fft_grid: &FftGrid,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    tables: &mut SpeciesTables,
+) -> Result<DensityField, DftError> {
+    let _t = timer::scope("sad_density");
     let (nx, ny, nz) = (fft_grid.size[0], fft_grid.size[1], fft_grid.size[2]);
     let mut rho = Array3::<f64>::zeros((nx, ny, nz));
 
     // Matriz inversa para condições de contorno periódicas
     // Usa .vectors conforme sua estrutura atual
-    let lattice_inv = structure.lattice.vectors.try_inverse().expect("Lattice matrix singular");
-
-    // 1. Superposição das Densidades Atômicas
-    for i in 0..nx {
-        for j in 0..ny {
-            for k in 0..nz {
-                // Posição fracionária [0, 1]
-                let frac_pos = Vector3::new(
-                    i as f64 / nx as f64,
-                    j as f64 / ny as f64,
-                    k as f64 / nz as f64,
-                );
-                
-                // Posição cartesiana real
-                let r_grid = structure.lattice.vectors * frac_pos;
-                let mut rho_val = 0.0;
-
-                for atom in &structure.atoms {
-                    let pseudo = pseudos.get(&atom.species_id)
-                        .expect("Pseudopotencial não encontrado");
-
-                    // Vetor diferença
-                    let diff = r_grid - atom.position;
-
+    let lattice = structure.lattice.vectors;
+    let lattice_inv = lattice.try_inverse().ok_or(DftError::SingularLattice)?;
+
+    // Átomos com a tabela da espécie e o raio da malha radial (ρ_atom = 0 além dele)
+    let rho_tables = tables.rho_atom(structure, pseudos)?;
+    let atoms: Vec<(Vector3<f64>, &RealSpaceTable, f64)> = structure.atoms.iter()
+        .map(|atom| {
+            let table = &rho_tables[&atom.species_id];
+            (lattice_inv * atom.position, table, table.r_max())
+        })
+        .collect();
+
+    // Distância entre planos i = const: Ω / |a2 × a3|. Um átomo a fração δ do plano
+    // da fatia está a pelo menos |δ|·d1 de todos os seus pontos.
+    let d1 = structure.lattice.volume() / lattice.column(1).cross(&lattice.column(2)).norm();
+
+    // 1. Superposição das Densidades Atômicas, paralela por fatias i = const
+    let slabs = rho.as_slice_mut().ok_or(DftError::NonContiguous("densidade"))?;
+    parallel::for_each_chunk_mut(slabs, ny * nz, |i, slab| {
+        let x = i as f64 / nx as f64;
+        // Lista de vizinhos da fatia: só átomos que alcançam o plano
+        let near: Vec<&(Vector3<f64>, &RealSpaceTable, f64)> = atoms.iter()
+            .filter(|(pos, _, r_cut)| {
+                let dx = x - pos.x;
+                (dx - dx.round()).abs() * d1 <= *r_cut
+            })
+            .collect();
+
+        for (jk, rho_val) in slab.iter_mut().enumerate() {
+            let (j, k) = (jk / nz, jk % nz);
+            // Posição fracionária [0, 1]
+            let frac_pos = Vector3::new(x, j as f64 / ny as f64, k as f64 / nz as f64);
+
+            *rho_val = near.iter()
+                .map(|(pos, table, r_cut)| {
                     // Minimum Image Convention (MIC)
-                    let mut d_frac = lattice_inv * diff;
-                    d_frac.x -= d_frac.x.round();
-                    d_frac.y -= d_frac.y.round();
-                    d_frac.z -= d_frac.z.round();
-
-                    let d_cart = structure.lattice.vectors * d_frac;
-                    let dist = d_cart.norm();
-
-                    // Interpola valor do UPF
-                    rho_val += interpolate_rho_atom(dist, pseudo);
-                }
-
-                rho[[i, j, k]] = rho_val;
-            }
+                    let mut d_frac = frac_pos - pos;
+                    d_frac.apply(|d| *d -= d.round());
+                    let dist = (lattice * d_frac).norm();
+                    if dist > *r_cut { 0.0 } else { table.eval(dist) }
+                })
+                .sum();
         }
-    }
+    });
 
-    // 2. Renormalização de Carga (CRÍTICO)
-    // Corrige erros de amostragem do grid garantindo neutralidade exata.
-    
-    let volume = structure.lattice.volume();
-    let n_points = (nx * ny * nz) as f64;
-    let dvol = volume / n_points; // Volume por voxel
+    // 2. Renormalização de Carga
+    // Com a interpolação por spline o erro de amostragem é pequeno; o fator apenas
+    // garante neutralidade exata antes do SCF.
     
+    let mut rho = DensityField::new(structure.lattice.clone(), rho);
+
     // Carga integrada numericamente (geralmente menor que o esperado)
-    let current_charge: f64 = rho.sum() * dvol;
+    let current_charge = rho.total_charge();
     
     // Carga total esperada (soma dos Z_valence dos átomos)
     let mut target_charge = 0.0;
@@ -77,62 +97,140 @@ pub fn calculate_initial_density(
         }
     }
 
-    println!("   > Carga SAD Calculada: {:.6} e", current_charge);
-    println!("   > Carga Alvo (Z_val) : {:.6} e", target_charge);
+    log::debug!("   > Carga SAD Calculada: {:.6} e", current_charge);
+    log::debug!("   > Carga Alvo (Z_val) : {:.6} e", target_charge);
 
     if current_charge.abs() > 1e-9 {
         let scale = target_charge / current_charge;
-        println!("   > Aplicando Fator de Renormalização: {:.6}", scale);
+        log::debug!("   > Aplicando Fator de Renormalização: {:.6}", scale);
         
         // Multiplica todo o grid pelo fator de correção
         // rho *= scale (ndarray suporta ops escalares)
-        rho.mapv_inplace(|v| v * scale);
+        gridops::scale(scale, rho.data.as_slice_mut().ok_or(DftError::NonContiguous("densidade"))?);
     } else {
-        println!("   > AVISO: Carga zero detectada, pulando renormalização.");
+        log::warn!("Carga SAD zero detectada, pulando renormalização.");
     }
 
-    rho
+    Ok(rho)
 }
 
-fn interpolate_rho_atom(r: f64, pseudo: &Pseudopotential) -> f64 {
-    let mesh = &pseudo.mesh;
-    let rho_data = &pseudo.rho_atom; // Lembre-se: UPF armazena 4*pi*r^2 * rho
+/// Acumula a densidade de valência a partir das funções de onda:
+///
+/// ρ_σ(r) = Σ_k w_k Σ_n f_nkσ |ψ_nkσ(r)|²
+///
+/// com Σ w_k = 1 e ocupações f já contendo o fator de spin (0 a 2 sem polarização,
+/// 0 a 1 por canal com `n_spin = 2`). Pontos K, bandas e spins entram pelo mesmo caminho;
+/// o grid FFT (o denso da simulação, emprestado) é único para todos os pontos K.
+pub struct DensityBuilder<'a> {
+    lattice: Lattice,
+    fft: &'a mut FftGrid,
+    channels: Vec<Array3<f64>>,
+    gamma_half_sphere: bool,
+}
 
-    if r > mesh.r.last().copied().unwrap_or(0.0) {
-        return 0.0;
+impl<'a> DensityBuilder<'a> {
+    pub fn new(lattice: Lattice, fft: &'a mut FftGrid, n_spin: usize) -> Self {
+        let [nx, ny, nz] = fft.size;
+        Self {
+            lattice,
+            fft,
+            channels: vec![Array3::zeros((nx, ny, nz)); n_spin.max(1)],
+            gamma_half_sphere: false,
+        }
     }
 
-    // Tratamento de singularidade r -> 0
-    // Densidade deve ser finita, mas UPF guarda r^2 * rho.
-    // Usamos o segundo ponto para evitar divisão por zero
-    if r < 1e-6 {
-        if mesh.r.len() > 1 {
-             let r_safe = mesh.r[1];
-             let val = rho_data[1];
-             return val / (4.0 * PI * r_safe * r_safe);
-        }
-        return 0.0;
+    /// Truque de Γ: em k = 0 os coeficientes cobrem só metade da esfera (ψ real, c_{-G} = c_G*).
+    /// A outra metade é reconstruída antes da FFT, o que equivale a contar cada G ≠ 0 duas vezes.
+    pub fn gamma_half_sphere(mut self, enabled: bool) -> Self {
+        self.gamma_half_sphere = enabled;
+        self
     }
 
-    // Busca Binária
-    let idx = match mesh.r.binary_search_by(|val| val.partial_cmp(&r).unwrap()) {
-        Ok(i) => i,
-        Err(i) => if i > 0 { i - 1 } else { 0 },
-    };
+    /// Soma w · f · |ψ(r)|² ao canal `spin`.
+    pub fn add_state(
+        &mut self,
+        basis: &PlaneWaveBasis,
+        psi: &Array1<Complex64>,
+        weight: f64,
+        occupation: f64,
+        spin: usize,
+    ) -> Result<(), DftError> {
+        let n_spin = self.channels.len();
+        if spin >= n_spin {
+            return Err(DftError::SizeMismatch("canal de spin", spin + 1, n_spin));
+        }
+        let wf = weight * occupation;
+        if wf == 0.0 {
+            return Ok(());
+        }
+
+        if self.gamma_half_sphere && basis.k_point.norm() < 1e-12 {
+            self.scatter_gamma(basis, psi)?;
+        } else {
+            self.fft.to_real_space(basis, psi)?;
+        }
 
-    if idx >= mesh.r.len() - 1 {
-        return 0.0;
+        // ifft é normalizada por 1/N: ψ(r) = N·buffer / √Ω
+        let [nx, ny, nz] = self.fft.size;
+        let n_points = (nx * ny * nz) as f64;
+        let factor = wf * n_points * n_points / self.lattice.volume();
+        gridops::axpy_norm_sqr(
+            factor,
+            self.fft.buffer.as_slice().ok_or(DftError::NonContiguous("buffer da FFT"))?,
+            self.channels[spin].as_slice_mut().ok_or(DftError::NonContiguous("densidade"))?,
+        )?;
+        Ok(())
     }
 
-    // Interpolação Linear
-    let r1 = mesh.r[idx];
-    let r2 = mesh.r[idx+1];
-    let y1 = rho_data[idx];
-    let y2 = rho_data[idx+1];
+    /// Soma todas as bandas de um ponto K com as ocupações `occupations[n]`.
+    pub fn add_bands(
+        &mut self,
+        basis: &PlaneWaveBasis,
+        bands: &BandSolverResult,
+        weight: f64,
+        occupations: &[f64],
+        spin: usize,
+    ) -> Result<(), DftError> {
+        for (psi, &f) in bands.eigenvectors.iter().zip(occupations) {
+            self.add_state(basis, psi, weight, f, spin)?;
+        }
+        Ok(())
+    }
 
-    let t = (r - r1) / (r2 - r1);
-    let y_interp = y1 + t * (y2 - y1);
+    /// Densidade de cada canal de spin.
+    pub fn spin_densities(self) -> Vec<DensityField> {
+        let lattice = self.lattice;
+        self.channels.into_iter().map(|rho| DensityField::new(lattice.clone(), rho)).collect()
+    }
 
-    // Converte de Radial Charge (UPF) para Volumetric Charge
-    y_interp / (4.0 * PI * r * r)
-}
\ No newline at end of file
+    /// Densidade total (soma dos canais).
+    pub fn build(self) -> DensityField {
+        let mut channels = self.channels.into_iter();
+        let mut total = channels.next().unwrap_or_default();
+        for rho in channels {
+            total += &rho;
+        }
+        DensityField::new(self.lattice, total)
+    }
+
+    /// Scatter com c_{-G} = c_G* e FFT inversa.
+    fn scatter_gamma(&mut self, basis: &PlaneWaveBasis, psi: &Array1<Complex64>) -> Result<(), DftError> {
+        if basis.fft_grid != self.fft.size {
+            return Err(DftError::GridMismatch(basis.fft_grid, self.fft.size));
+        }
+        if psi.len() > basis.g_vectors.len() {
+            return Err(DftError::SizeMismatch("coeficientes de entrada", psi.len(), basis.g_vectors.len()));
+        }
+        let [nx, ny, nz] = self.fft.size;
+        let wrap = |g: i32, n: usize| g.rem_euclid(n as i32) as usize;
+        self.fft.buffer.fill(Complex64::new(0.0, 0.0));
+        let buffer = self.fft.buffer.as_slice_mut().ok_or(DftError::NonContiguous("buffer da FFT"))?;
+        for ((&(i, j, k), &pos), c) in basis.g_vectors.iter().zip(&basis.fft_map).zip(psi) {
+            buffer[pos] = *c;
+            if (i, j, k) != (0, 0, 0) {
+                buffer[wrap(-i, nx) * ny * nz + wrap(-j, ny) * nz + wrap(-k, nz)] = c.conj();
+            }
+        }
+        self.fft.inverse_in_place()
+    }
+}
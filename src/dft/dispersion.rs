@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use nalgebra::{Matrix3, Vector3};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::structure::Structure;
+use crate::dft::xc::XcFunctional;
+use crate::utils::constants::{ANGSTROM_TO_BOHR, HA_TO_RY, JOULE_TO_HA, NM_TO_BOHR};
+
+/// Raio de corte padrão da soma de pares (Bohr), o `rthr = 9000 Bohr²` do dftd3.
+pub const DEFAULT_CUTOFF: f64 = 94.868;
+
+/// Inclinação do amortecimento de Fermi do D2.
+pub const DEFAULT_STEEPNESS: f64 = 20.0;
+
+const AVOGADRO: f64 = 6.02214076e23;
+
+/// Tabela de Grimme (2006): elemento, C6 (J·nm⁶/mol) e raio de van der Waals R0 (Å),
+/// de H a Xe. Metais de transição da mesma linha compartilham os valores.
+const GRIMME_D2: &[(&str, f64, f64)] = &[
+    ("H", 0.14, 1.001), ("He", 0.08, 1.012),
+    ("Li", 1.61, 0.825), ("Be", 1.61, 1.408), ("B", 3.13, 1.485), ("C", 1.75, 1.452),
+    ("N", 1.23, 1.397), ("O", 0.70, 1.342), ("F", 0.75, 1.287), ("Ne", 0.63, 1.243),
+    ("Na", 5.71, 1.144), ("Mg", 5.71, 1.364), ("Al", 10.79, 1.639), ("Si", 9.23, 1.716),
+    ("P", 7.84, 1.705), ("S", 5.57, 1.683), ("Cl", 5.07, 1.639), ("Ar", 4.61, 1.595),
+    ("K", 10.80, 1.485), ("Ca", 10.80, 1.474),
+    ("Sc", 10.80, 1.562), ("Ti", 10.80, 1.562), ("V", 10.80, 1.562), ("Cr", 10.80, 1.562),
+    ("Mn", 10.80, 1.562), ("Fe", 10.80, 1.562), ("Co", 10.80, 1.562), ("Ni", 10.80, 1.562),
+    ("Cu", 10.80, 1.562), ("Zn", 10.80, 1.562),
+    ("Ga", 16.99, 1.649), ("Ge", 17.10, 1.727), ("As", 16.37, 1.760), ("Se", 12.64, 1.771),
+    ("Br", 12.47, 1.749), ("Kr", 12.01, 1.727),
+    ("Rb", 24.67, 1.628), ("Sr", 24.67, 1.606),
+    ("Y", 24.67, 1.639), ("Zr", 24.67, 1.639), ("Nb", 24.67, 1.639), ("Mo", 24.67, 1.639),
+    ("Tc", 24.67, 1.639), ("Ru", 24.67, 1.639), ("Rh", 24.67, 1.639), ("Pd", 24.67, 1.639),
+    ("Ag", 24.67, 1.639), ("Cd", 24.67, 1.639),
+    ("In", 37.32, 1.672), ("Sn", 38.71, 1.804), ("Sb", 38.44, 1.881), ("Te", 31.74, 1.892),
+    ("I", 31.50, 1.892), ("Xe", 29.99, 1.881),
+];
+
+#[derive(Error, Debug)]
+pub enum DispersionError {
+    #[error("DFT-D2 sem s6 para o funcional {0}; informe s6 em [dispersion].")]
+    NoScaling(String),
+
+    #[error("DFT-D2 sem C6/R0 para o elemento '{0}' (tabela de Grimme vai até Xe); informe em [dispersion.species].")]
+    MissingSpecies(String),
+
+    #[error("Espécie {0} inexistente na estrutura.")]
+    UnknownSpecies(usize),
+}
+
+/// Dados atômicos do D2, nas unidades da tabela de Grimme: C6 (J·nm⁶/mol) e R0 (Å).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct D2Species {
+    pub c6: f64,
+    pub r0: f64,
+}
+
+impl D2Species {
+    /// Valores tabelados por Grimme (2006), de H a Xe.
+    pub fn reference(element: &str) -> Option<Self> {
+        GRIMME_D2.iter()
+            .find(|(symbol, _, _)| symbol.eq_ignore_ascii_case(element.trim()))
+            .map(|&(_, c6, r0)| Self { c6, r0 })
+    }
+
+    /// C6 em Ha·Bohr⁶.
+    fn c6_au(&self) -> f64 {
+        self.c6 * JOULE_TO_HA / AVOGADRO * NM_TO_BOHR.powi(6)
+    }
+
+    /// R0 em Bohr.
+    fn r0_au(&self) -> f64 {
+        self.r0 * ANGSTROM_TO_BOHR
+    }
+}
+
+/// s6 publicado por Grimme para cada funcional.
+pub fn s6_for_functional(xc: XcFunctional) -> Option<f64> {
+    match xc {
+        XcFunctional::Pbe => Some(0.75),
+        XcFunctional::RevPbe => Some(1.25),
+        XcFunctional::Blyp => Some(1.2),
+        // Sem parametrização D2
+        XcFunctional::PbeSol | XcFunctional::LdaPz | XcFunctional::LdaPw => None,
+    }
+}
+
+fn default_cutoff() -> f64 {
+    DEFAULT_CUTOFF
+}
+
+fn default_steepness() -> f64 {
+    DEFAULT_STEEPNESS
+}
+
+/// Configuração da correção, como no input:
+///
+/// ```toml
+/// [dispersion]
+/// s6 = 0.75                             # opcional: padrão do funcional
+/// species = { C = { c6 = 1.75, r0 = 1.452 } } # opcional: substitui a tabela de Grimme
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct D2Parameters {
+    #[serde(default)]
+    pub s6: Option<f64>,
+    #[serde(default = "default_steepness")]
+    pub d: f64,
+    #[serde(default)]
+    pub species: HashMap<String, D2Species>,
+    #[serde(default = "default_cutoff")]
+    pub cutoff: f64, // Bohr
+}
+
+impl Default for D2Parameters {
+    fn default() -> Self {
+        Self { s6: None, d: DEFAULT_STEEPNESS, species: HashMap::new(), cutoff: DEFAULT_CUTOFF }
+    }
+}
+
+/// Energia (Ry), forças (Ry/Bohr) e tensão (Ry/Bohr³) de dispersão.
+#[derive(Debug, Clone)]
+pub struct DispersionResult {
+    pub energy: f64,
+    pub forces: Vec<Vector3<f64>>,
+    pub stress: Matrix3<f64>, // σ = -(1/Ω) ∂E/∂ε (positivo = compressão, como no pw.x)
+}
+
+/// Correção de dispersão DFT-D2 de Grimme:
+///
+/// E = -½ s6 Σ_{i,j,T}' C6_ij / r⁶ · f(r),   f(r) = 1 / (1 + e^{-d (r/R_ij - 1)})
+///
+/// com C6_ij = √(C6_i·C6_j) e R_ij = R0_i + R0_j, somada sobre imagens periódicas T até
+/// `cutoff`. Os C6 são fixos por elemento (tabela de Grimme ou `D2Parameters::species`).
+/// *Ref: Grimme, S. (2006). J. Comput. Chem., 27(15), 1787-1799.*
+#[derive(Debug, Clone)]
+pub struct D2 {
+    pub s6: f64,
+    pub d: f64,
+    pub species: HashMap<String, D2Species>, // Substituições da tabela
+    pub cutoff: f64,
+}
+
+impl D2 {
+    /// Resolve s6 (explícito ou o do funcional `xc`).
+    pub fn new(params: D2Parameters, xc: Option<XcFunctional>) -> Result<Self, DispersionError> {
+        let s6 = match params.s6 {
+            Some(s6) => s6,
+            None => xc.and_then(s6_for_functional)
+                .ok_or_else(|| DispersionError::NoScaling(xc.map_or("desconhecido".into(), |f| f.to_string())))?,
+        };
+        Ok(Self { s6, d: params.d, species: params.species, cutoff: params.cutoff })
+    }
+
+    /// Dados de um elemento: os do input, senão os da tabela de Grimme.
+    pub fn species_data(&self, element: &str) -> Result<D2Species, DispersionError> {
+        self.species.get(element).copied()
+            .or_else(|| D2Species::reference(element))
+            .ok_or_else(|| DispersionError::MissingSpecies(element.to_string()))
+    }
+
+    /// Confere se todas as espécies da estrutura têm dados.
+    pub fn check(&self, structure: &Structure) -> Result<(), DispersionError> {
+        for sp in &structure.species {
+            self.species_data(&sp.element)?;
+        }
+        Ok(())
+    }
+
+    /// Energia, forças e tensão para a geometria atual.
+    pub fn compute(&self, structure: &Structure) -> Result<DispersionResult, DispersionError> {
+        let data: Vec<D2Species> = structure.atoms.iter()
+            .map(|atom| {
+                let sp = structure.species.iter()
+                    .find(|s| s.id == atom.species_id)
+                    .ok_or(DispersionError::UnknownSpecies(atom.species_id))?;
+                self.species_data(&sp.element)
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Imagens necessárias para cobrir a esfera de corte: |n_i| <= rc·|b_i|/2π
+        let lattice = &structure.lattice.vectors;
+        let recip = structure.lattice.reciprocal();
+        let n_max: Vec<i32> = (0..3)
+            .map(|i| (self.cutoff * recip.column(i).norm() / (2.0 * PI)).ceil() as i32)
+            .collect();
+
+        let cutoff_sq = self.cutoff * self.cutoff;
+        let n_atoms = structure.atoms.len();
+        let mut energy = 0.0;
+        let mut forces = vec![Vector3::zeros(); n_atoms];
+        let mut virial = Matrix3::zeros();
+
+        for i in 0..n_atoms {
+            for j in 0..n_atoms {
+                let c6 = self.s6 * (data[i].c6_au() * data[j].c6_au()).sqrt();
+                let r_vdw = data[i].r0_au() + data[j].r0_au();
+                let base = structure.atoms[j].position - structure.atoms[i].position;
+
+                for n1 in -n_max[0]..=n_max[0] {
+                    for n2 in -n_max[1]..=n_max[1] {
+                        for n3 in -n_max[2]..=n_max[2] {
+                            if i == j && n1 == 0 && n2 == 0 && n3 == 0 {
+                                continue;
+                            }
+                            let d = base + lattice * Vector3::new(n1 as f64, n2 as f64, n3 as f64);
+                            let r2 = d.norm_squared();
+                            if r2 > cutoff_sq {
+                                continue;
+                            }
+                            let r = r2.sqrt();
+                            let r6 = r2 * r2 * r2;
+                            let damp = 1.0 / (1.0 + (-self.d * (r / r_vdw - 1.0)).exp());
+                            let e_pair = -c6 / r6 * damp;
+                            energy += 0.5 * e_pair;
+                            // dE_par/dr = E_par [f'/f - 6/r], f'/f = d (1 - f) / R_ij
+                            let de_dr = e_pair * (self.d * (1.0 - damp) / r_vdw - 6.0 / r);
+                            // F_i = -∂E/∂x_i e ∂r/∂x_i = -d/r; cada par aparece nas somas de i e de j
+                            forces[i] += d * (de_dr / r);
+                            // ∂r/∂ε_ab = d_a d_b / r
+                            virial += d * d.transpose() * (0.5 * de_dr / r);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Unidades atômicas de Hartree -> Ry
+        Ok(DispersionResult {
+            energy: energy * HA_TO_RY,
+            forces: forces.into_iter().map(|f| f * HA_TO_RY).collect(),
+            stress: virial * (-HA_TO_RY / structure.lattice.volume()),
+        })
+    }
+}
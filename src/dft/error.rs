@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+/// Erros dos kernels numéricos (densidade, potenciais, FFT), para que quem embute o
+/// Bravie possa se recuperar em vez de abortar.
+#[derive(Error, Debug)]
+pub enum DftError {
+    #[error("Rede cristalina singular (vetores linearmente dependentes).")]
+    SingularLattice,
+
+    #[error("Pseudopotencial não encontrado para a espécie {0}.")]
+    MissingPseudo(usize),
+
+    #[error("Array não contíguo na memória: {0}.")]
+    NonContiguous(&'static str),
+
+    #[error("Dimensões incompatíveis: {0} com {1} elementos, esperado {2}.")]
+    SizeMismatch(&'static str, usize, usize),
+
+    #[error("Base definida no grid {0:?}, mas o grid FFT é {1:?}.")]
+    GridMismatch([usize; 3], [usize; 3]),
+
+    #[error("Falha no backend da FFT: {0}.")]
+    FftBackend(String),
+
+    #[error("Kerker ativo no mixer: o passo precisa do grid FFT (use `mix_preconditioned`).")]
+    KerkerNeedsGrid,
+}
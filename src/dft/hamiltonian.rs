@@ -0,0 +1,438 @@
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::fft::FftGrid;
+use crate::core::field::PotentialField;
+use crate::core::gridops;
+use crate::dft::error::DftError;
+use crate::dft::solver::SolverError;
+use crate::utils::constants::FINE_STRUCTURE_CONST;
+use crate::utils::radial::erf;
+use crate::utils::timer;
+
+/// Dispersão da energia cinética T(|k+G|^2) (Ry). Implemente para testar modelos
+/// novos sem alterar os solvers; ligue ao Hamiltoniano via `KineticTerm::new`.
+pub trait HamiltonianModel: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &str;
+    fn kinetic(&self, g2: f64) -> f64;
+}
+
+/// T = |k+G|^2
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Schrodinger;
+
+impl HamiltonianModel for Schrodinger {
+    fn name(&self) -> &str {
+        "schrodinger"
+    }
+
+    fn kinetic(&self, g2: f64) -> f64 {
+        g2
+    }
+}
+
+/// Funcional de corte constante para relaxação de célula (qcutz do pw.x):
+/// T = |k+G|^2 + A [1 + erf((|k+G|^2 - E0)/σ)], penaliza ondas perto do Ecut
+/// e suaviza a dependência da energia com o número de ondas planas.
+/// *Ref: Bernasconi, M., et al. (1995). J. Phys. Chem. Solids, 56(3-4), 501-505.*
+#[derive(Debug, Clone, Copy)]
+pub struct ConstantCutoff {
+    pub a: f64,
+    pub e0: f64,
+    pub sigma: f64,
+}
+
+impl HamiltonianModel for ConstantCutoff {
+    fn name(&self) -> &str {
+        "constant-cutoff"
+    }
+
+    fn kinetic(&self, g2: f64) -> f64 {
+        g2 + self.a * (1.0 + erf((g2 - self.e0) / self.sigma))
+    }
+}
+
+/// Dispersão relativística da partícula livre (limite ZORA com V = 0), em Ry:
+/// T = mc² [√(1 + 4|k+G|²/c²) - 1], com m = 1/2 e c = 2/α.
+/// Recupera |k+G|^2 para |k+G| ≪ c; só o termo cinético é corrigido.
+#[derive(Debug, Clone, Copy)]
+pub struct ScalarRelativistic {
+    pub c: f64,
+}
+
+impl Default for ScalarRelativistic {
+    fn default() -> Self {
+        Self { c: 2.0 / FINE_STRUCTURE_CONST }
+    }
+}
+
+impl HamiltonianModel for ScalarRelativistic {
+    fn name(&self) -> &str {
+        "scalar-relativistic"
+    }
+
+    fn kinetic(&self, g2: f64) -> f64 {
+        let c2 = self.c * self.c;
+        // Forma estável para g2 ≪ c²: mc²(√(1+x) - 1) = mc² x / (√(1+x) + 1)
+        let x = 4.0 * g2 / c2;
+        0.5 * c2 * x / ((1.0 + x).sqrt() + 1.0)
+    }
+}
+
+/// Massa efetiva: T = |k+G|^2 / m*, com m* em unidades de m_e.
+#[derive(Debug, Clone, Copy)]
+pub struct EffectiveMass {
+    pub m_star: f64,
+}
+
+impl HamiltonianModel for EffectiveMass {
+    fn name(&self) -> &str {
+        "effective-mass"
+    }
+
+    fn kinetic(&self, g2: f64) -> f64 {
+        g2 / self.m_star
+    }
+}
+
+/// Dispersão definida por uma função qualquer, para experimentos rápidos.
+pub struct CustomDispersion<F: Fn(f64) -> f64 + Send + Sync> {
+    pub name: String,
+    pub dispersion: F,
+}
+
+impl<F: Fn(f64) -> f64 + Send + Sync> std::fmt::Debug for CustomDispersion<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomDispersion").field("name", &self.name).finish()
+    }
+}
+
+impl<F: Fn(f64) -> f64 + Send + Sync> HamiltonianModel for CustomDispersion<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kinetic(&self, g2: f64) -> f64 {
+        (self.dispersion)(g2)
+    }
+}
+
+/// Um termo do Hamiltoniano (cinético, potencial local, não-local, SOC, +U, ...).
+/// Termos novos entram implementando este trait, sem alterar os solvers.
+pub trait HamiltonianTerm: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Soma H_termo|ψ⟩ em `out` (coeficientes de onda plana da base).
+    /// `work` tem o tamanho da base e pode ser sobrescrito livremente.
+    fn apply_add(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError>;
+}
+
+/// Energia cinética, diagonal em G.
+#[derive(Debug)]
+pub struct KineticTerm {
+    pub model: Box<dyn HamiltonianModel>,
+}
+
+impl KineticTerm {
+    pub fn new(model: Box<dyn HamiltonianModel>) -> Self {
+        Self { model }
+    }
+}
+
+impl Default for KineticTerm {
+    fn default() -> Self {
+        Self::new(Box::new(Schrodinger))
+    }
+}
+
+impl HamiltonianTerm for KineticTerm {
+    fn name(&self) -> &str {
+        "cinético"
+    }
+
+    fn apply_add(
+        &self,
+        basis: &PlaneWaveBasis,
+        _fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        _work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError> {
+        for ((o, c), &g2) in out.iter_mut().zip(psi).zip(&basis.g_norm_sq) {
+            *o += c * self.model.kinetic(g2);
+        }
+        Ok(())
+    }
+}
+
+/// Potencial local efetivo V_eff(r) (Ry) no grid suave das funções de onda, aplicado pela
+/// FFT. O V_eff do grid denso é reamostrado para ele com `fourier_resample`
+/// (ver `Simulation::set_effective_potential`).
+#[derive(Debug, Clone)]
+pub struct LocalPotentialTerm {
+    pub v_eff: PotentialField,
+}
+
+impl HamiltonianTerm for LocalPotentialTerm {
+    fn name(&self) -> &str {
+        "potencial local"
+    }
+
+    fn apply_add(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError> {
+        apply_local_potential_add(basis, fft, &self.v_eff.data, psi, out, work)
+    }
+}
+
+/// out += FFT[V(r) · IFFT[ψ](r)]. A IFFT normaliza por 1/N e a FFT direta não:
+/// o produto já sai como Σ_G' V(G-G') c_G'. `work` recebe V|ψ⟩ (tamanho da base).
+pub fn apply_local_potential_add(
+    basis: &PlaneWaveBasis,
+    fft: &mut FftGrid,
+    v: &Array3<f64>,
+    psi: &Array1<Complex64>,
+    out: &mut Array1<Complex64>,
+    work: &mut Array1<Complex64>,
+) -> Result<(), SolverError> {
+    let v_dim = v.dim();
+    if [v_dim.0, v_dim.1, v_dim.2] != fft.size {
+        return Err(SolverError::GridMismatch([v_dim.0, v_dim.1, v_dim.2], fft.size));
+    }
+    fft.to_real_space(basis, psi)?;
+    gridops::mul_real(
+        fft.buffer.as_slice_mut().ok_or(DftError::NonContiguous("buffer da FFT"))?,
+        v.as_slice().ok_or(DftError::NonContiguous("potencial local"))?,
+    )?;
+    fft.to_recip_space(basis, work)?;
+    *out += &*work;
+    Ok(())
+}
+
+/// Hamiltoniano como soma de termos. Por padrão: cinético + V_eff local.
+pub struct Hamiltonian {
+    terms: Vec<Box<dyn HamiltonianTerm>>,
+}
+
+impl Hamiltonian {
+    /// H = T + V_eff, com dispersão padrão.
+    pub fn new(v_eff: PotentialField) -> Self {
+        Self::with_model(Box::new(Schrodinger), v_eff)
+    }
+
+    /// H = T + V_eff, com a dispersão `model`.
+    pub fn with_model(model: Box<dyn HamiltonianModel>, v_eff: PotentialField) -> Self {
+        Self::empty()
+            .with_term(Box::new(KineticTerm::new(model)))
+            .with_term(Box::new(LocalPotentialTerm { v_eff }))
+    }
+
+    /// Sem termos (H = 0); monte com `with_term`.
+    pub fn empty() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    pub fn with_term(mut self, term: Box<dyn HamiltonianTerm>) -> Self {
+        self.terms.push(term);
+        self
+    }
+
+    /// Nomes dos termos, na ordem de aplicação.
+    pub fn term_names(&self) -> Vec<&str> {
+        self.terms.iter().map(|t| t.name()).collect()
+    }
+
+    /// H|ψ⟩ somando todos os termos. Aloca a saída; nos laços dos solvers prefira `apply_into`.
+    pub fn apply(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+    ) -> Result<Array1<Complex64>, SolverError> {
+        let mut out = Array1::<Complex64>::zeros(psi.len());
+        let mut work = Array1::<Complex64>::zeros(psi.len());
+        self.apply_into(basis, fft, psi, &mut out, &mut work)?;
+        Ok(out)
+    }
+
+    /// H|ψ⟩ em `out`, sem alocar: `out` e `work` são buffers do chamador com o tamanho da base.
+    pub fn apply_into(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError> {
+        let _t = timer::scope("h_psi");
+        check_buffers(psi, out, work)?;
+        out.fill(Complex64::new(0.0, 0.0));
+        for term in &self.terms {
+            term.apply_add(basis, fft, psi, out, work)?;
+        }
+        Ok(())
+    }
+
+    /// ⟨ψ|H_termo|ψ⟩/⟨ψ|ψ⟩ de cada termo (Ry), na ordem de aplicação; a soma é ⟨ψ|H|ψ⟩.
+    pub fn term_expectations(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        psi: &Array1<Complex64>,
+    ) -> Result<Vec<(String, f64)>, SolverError> {
+        let norm = psi.iter().map(|c| c.norm_sqr()).sum::<f64>();
+        let mut out = Array1::<Complex64>::zeros(psi.len());
+        let mut work = Array1::<Complex64>::zeros(psi.len());
+        self.terms.iter()
+            .map(|term| {
+                out.fill(Complex64::new(0.0, 0.0));
+                term.apply_add(basis, fft, psi, &mut out, &mut work)?;
+                Ok((term.name().to_string(), overlap(psi, &out) / norm))
+            })
+            .collect()
+    }
+
+    /// Decompõe cada autovalor nos termos de H (⟨T⟩, ⟨V_eff⟩, ⟨V_NL⟩, ...). Os potenciais
+    /// de `components` (ex: [("V_loc", ...), ("V_H", ...), ("V_xc", ...)]) detalham o termo
+    /// local, cuja soma eles devem reproduzir.
+    pub fn decompose(
+        &self,
+        basis: &PlaneWaveBasis,
+        fft: &mut FftGrid,
+        eigenvalues: &[f64],
+        vectors: &[Array1<Complex64>],
+        components: &[(&str, &Array3<f64>)],
+    ) -> Result<Vec<BandDecomposition>, SolverError> {
+        eigenvalues.iter().zip(vectors).enumerate()
+            .map(|(band, (&eigenvalue, psi))| {
+                let terms = self.term_expectations(basis, fft, psi)?;
+                let local_components = components.iter()
+                    .map(|&(name, v)| Ok((name.to_string(), potential_expectation(basis, fft, v, psi)?)))
+                    .collect::<Result<_, SolverError>>()?;
+                Ok(BandDecomposition { band, eigenvalue, terms, local_components })
+            })
+            .collect()
+    }
+
+    /// Liga o Hamiltoniano a um ponto K (base + grid FFT) para uso pelos solvers.
+    pub fn bind<'a>(&'a self, basis: &'a PlaneWaveBasis, fft: &'a mut FftGrid) -> BoundHamiltonian<'a> {
+        BoundHamiltonian { hamiltonian: self, basis, fft }
+    }
+}
+
+/// Re⟨a|b⟩ nos coeficientes de onda plana.
+fn overlap(a: &Array1<Complex64>, b: &Array1<Complex64>) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x.conj() * y).re).sum()
+}
+
+/// ⟨ψ|V|ψ⟩/⟨ψ|ψ⟩ (Ry) de um potencial local `v` no grid de `fft` (o suave, para as
+/// bandas da simulação: reamostre `v` do grid denso com `fourier_resample`).
+pub fn potential_expectation(
+    basis: &PlaneWaveBasis,
+    fft: &mut FftGrid,
+    v: &Array3<f64>,
+    psi: &Array1<Complex64>,
+) -> Result<f64, SolverError> {
+    let mut out = Array1::<Complex64>::zeros(psi.len());
+    let mut work = Array1::<Complex64>::zeros(psi.len());
+    apply_local_potential_add(basis, fft, v, psi, &mut out, &mut work)?;
+    Ok(overlap(psi, &out) / psi.iter().map(|c| c.norm_sqr()).sum::<f64>())
+}
+
+/// Contribuições de cada termo de H para um autovalor (Ry).
+#[derive(Debug, Clone)]
+pub struct BandDecomposition {
+    pub band: usize,
+    pub eigenvalue: f64,
+    pub terms: Vec<(String, f64)>,            // Soma = ⟨ψ|H|ψ⟩
+    pub local_components: Vec<(String, f64)>, // Detalhamento do potencial local
+}
+
+impl BandDecomposition {
+    /// ε - Σ termos: ~0 para autovetores convergidos; grande indica ψ ou H inconsistentes.
+    pub fn residual(&self) -> f64 {
+        self.eigenvalue - self.terms.iter().map(|(_, v)| v).sum::<f64>()
+    }
+}
+
+impl std::fmt::Display for BandDecomposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "banda {:>3}: ε = {:>12.6} Ry", self.band + 1, self.eigenvalue)?;
+        for (name, value) in &self.terms {
+            write!(f, " | {} {:>10.6}", name, value)?;
+        }
+        for (name, value) in &self.local_components {
+            write!(f, " | ⟨{}⟩ {:>10.6}", name, value)?;
+        }
+        write!(f, " | resíduo {:.1e}", self.residual())
+    }
+}
+
+/// Confere que `out` e `work` têm o tamanho de `psi`.
+pub(crate) fn check_buffers(
+    psi: &Array1<Complex64>,
+    out: &Array1<Complex64>,
+    work: &Array1<Complex64>,
+) -> Result<(), DftError> {
+    for (name, buf) in [("buffer de saída de H|ψ⟩", out), ("buffer de trabalho de H|ψ⟩", work)] {
+        if buf.len() != psi.len() {
+            return Err(DftError::SizeMismatch(name, buf.len(), psi.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Operador H|ψ⟩ visto pelos solvers: não expõe quais termos o compõem.
+pub trait HamiltonianOperator {
+    fn basis(&self) -> &PlaneWaveBasis;
+
+    /// H|ψ⟩ em `out`, usando `work` como rascunho (ambos com o tamanho da base).
+    fn apply_into(
+        &mut self,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError>;
+
+    fn apply(&mut self, psi: &Array1<Complex64>) -> Result<Array1<Complex64>, SolverError> {
+        let mut out = Array1::<Complex64>::zeros(psi.len());
+        let mut work = Array1::<Complex64>::zeros(psi.len());
+        self.apply_into(psi, &mut out, &mut work)?;
+        Ok(out)
+    }
+}
+
+/// `Hamiltonian` ligado à base e ao grid FFT de um ponto K.
+pub struct BoundHamiltonian<'a> {
+    hamiltonian: &'a Hamiltonian,
+    basis: &'a PlaneWaveBasis,
+    fft: &'a mut FftGrid,
+}
+
+impl HamiltonianOperator for BoundHamiltonian<'_> {
+    fn basis(&self) -> &PlaneWaveBasis {
+        self.basis
+    }
+
+    fn apply_into(
+        &mut self,
+        psi: &Array1<Complex64>,
+        out: &mut Array1<Complex64>,
+        work: &mut Array1<Complex64>,
+    ) -> Result<(), SolverError> {
+        self.hamiltonian.apply_into(self.basis, self.fft, psi, out, work)
+    }
+}
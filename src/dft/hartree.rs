@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+use std::fmt;
+use nalgebra::Vector3;
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::field::{DensityField, PotentialField};
+use crate::core::structure::{Lattice, Structure};
+use crate::core::structure_factors::fft_frequency;
+use crate::dft::error::DftError;
+use crate::utils::radial::erf;
+use crate::utils::timer;
+
+/// Tolerância das somas de Ewald (erfc e gaussiana desprezadas abaixo disso).
+const EWALD_TOLERANCE: f64 = 1e-14;
+
+/// Interação de Coulomb e²/r em espaço recíproco (Ry: e² = 2).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoulombKernel {
+    /// Cristal: K(G) = 8π/G², com o termo G = 0 omitido (fundo neutralizante).
+    Periodic,
+    /// Sistema isolado: interação cortada em |r - r'| > `radius`,
+    /// K(G) = 8π(1 - cos(G R_c))/G², finito em G = 0 (4π R_c²).
+    /// *Ref: Jarvis, M. R., White, I. D., Godby, R. W., & Payne, M. C. (1997). Phys. Rev. B, 56(23), 14972.*
+    Truncated { radius: f64 },
+}
+
+impl CoulombKernel {
+    /// Kernel truncado com R_c igual à metade da menor altura da célula. É exato se a
+    /// densidade couber em uma região de diâmetro ≤ R_c (vácuo ≥ metade da célula).
+    pub fn isolated(lattice: &Lattice) -> Self {
+        let recip = lattice.reciprocal();
+        // Altura da célula ao longo de a_i: 2π/|b_i|
+        let height = (0..3)
+            .map(|i| 2.0 * PI / recip.column(i).norm())
+            .fold(f64::INFINITY, f64::min);
+        CoulombKernel::Truncated { radius: 0.5 * height }
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, CoulombKernel::Truncated { .. })
+    }
+
+    /// K(G) (Ry·Bohr³) para |G|² = `g2`.
+    pub fn eval(&self, g2: f64) -> f64 {
+        match *self {
+            CoulombKernel::Periodic if g2 < 1e-12 => 0.0,
+            CoulombKernel::Periodic => 8.0 * PI / g2,
+            CoulombKernel::Truncated { radius } if g2 < 1e-12 => 4.0 * PI * radius * radius,
+            CoulombKernel::Truncated { radius } => 8.0 * PI * (1.0 - (g2.sqrt() * radius).cos()) / g2,
+        }
+    }
+}
+
+impl fmt::Display for CoulombKernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoulombKernel::Periodic => write!(f, "periódico"),
+            CoulombKernel::Truncated { radius } => write!(f, "truncado (R_c = {:.4} Bohr)", radius),
+        }
+    }
+}
+
+/// Potencial de Hartree no grid denso e sua energia (Ry).
+#[derive(Debug, Clone)]
+pub struct HartreeResult {
+    pub potential: PotentialField,
+    pub energy: f64,
+}
+
+/// Resolve a equação de Poisson em espaço recíproco:
+///
+/// V_H(G) = K(G) ρ(G),   E_H = Ω/2 Σ_G K(G) |ρ(G)|²
+///
+/// Com `CoulombKernel::Periodic` o termo G = 0 some (célula neutra com os íons).
+/// Usa o buffer de `fft` como área de trabalho.
+pub fn hartree_potential(rho: &DensityField, fft: &mut FftGrid, kernel: CoulombKernel) -> Result<HartreeResult, DftError> {
+    let _t = timer::scope("hartree");
+    let rho_g = rho.to_gspace(fft)?;
+    let [nx, ny, nz] = fft.size;
+    let recip = rho.lattice.reciprocal();
+    let volume = rho.lattice.volume();
+
+    let mut energy = 0.0;
+    for (((i, j, k), v), &rg) in fft.buffer.indexed_iter_mut().zip(&rho_g) {
+        let g = recip * Vector3::new(
+            fft_frequency(i, nx) as f64,
+            fft_frequency(j, ny) as f64,
+            fft_frequency(k, nz) as f64,
+        );
+        let kg = kernel.eval(g.norm_squared());
+        energy += 0.5 * volume * kg * rg.norm_sqr();
+        *v = rg * kg;
+    }
+
+    // ifft normaliza por 1/N
+    fft.inverse_in_place()?;
+    let scale = (nx * ny * nz) as f64;
+    Ok(HartreeResult {
+        potential: PotentialField::new(rho.lattice.clone(), fft.buffer.mapv(|c| c.re * scale)),
+        energy,
+    })
+}
+
+/// Energia de interação entre os íons (cargas pontuais `charges`, na ordem de
+/// `structure.atoms`), em Ry: soma de Ewald no cristal ou soma direta no sistema isolado.
+pub fn ion_ion_energy(structure: &Structure, charges: &[f64], kernel: CoulombKernel) -> Result<f64, DftError> {
+    if charges.len() != structure.atoms.len() {
+        return Err(DftError::SizeMismatch("cargas iônicas", charges.len(), structure.atoms.len()));
+    }
+    match kernel {
+        CoulombKernel::Periodic => Ok(ewald_energy(structure, charges)),
+        CoulombKernel::Truncated { .. } => {
+            let mut energy = 0.0;
+            for (i, a) in structure.atoms.iter().enumerate() {
+                for (b, &zb) in structure.atoms.iter().zip(charges).skip(i + 1) {
+                    energy += 2.0 * charges[i] * zb / (b.position - a.position).norm();
+                }
+            }
+            Ok(energy)
+        }
+    }
+}
+
+/// Soma de Ewald (Ry) para cargas pontuais em fundo neutralizante:
+///
+/// E = Σ'_{i,j,T} Z_iZ_j erfc(η r)/r + 4π/Ω Σ_{G≠0} |S(G)|² e^{-G²/4η²}/G²
+///     - 2η/√π Σ Z_i² - π (Σ Z_i)²/(Ω η²)
+///
+/// O resultado independe de η; η equilibra as duas somas para a célula dada.
+/// *Ref: Ewald, P. P. (1921). Ann. Phys., 369(3), 253-287.*
+pub fn ewald_energy(structure: &Structure, charges: &[f64]) -> f64 {
+    let lattice = &structure.lattice.vectors;
+    let recip = structure.lattice.reciprocal();
+    let volume = structure.lattice.volume();
+    let eta = PI.sqrt() / volume.cbrt();
+    let log_tol = -EWALD_TOLERANCE.ln();
+
+    // Cortes: erfc(η r_max) e e^{-G_max²/4η²} abaixo da tolerância
+    let r_max = log_tol.sqrt() / eta;
+    let g_max = 2.0 * eta * log_tol.sqrt();
+    let n_real: Vec<i32> = (0..3)
+        .map(|i| (r_max * recip.column(i).norm() / (2.0 * PI)).ceil() as i32)
+        .collect();
+    let n_recip: Vec<i32> = (0..3)
+        .map(|i| (g_max * lattice.column(i).norm() / (2.0 * PI)).ceil() as i32)
+        .collect();
+
+    let mut real = 0.0;
+    for (i, a) in structure.atoms.iter().enumerate() {
+        for (j, b) in structure.atoms.iter().enumerate() {
+            let base = b.position - a.position;
+            for n1 in -n_real[0]..=n_real[0] {
+                for n2 in -n_real[1]..=n_real[1] {
+                    for n3 in -n_real[2]..=n_real[2] {
+                        if i == j && n1 == 0 && n2 == 0 && n3 == 0 {
+                            continue;
+                        }
+                        let r = (base + lattice * Vector3::new(n1 as f64, n2 as f64, n3 as f64)).norm();
+                        if r < r_max {
+                            real += charges[i] * charges[j] * (1.0 - erf(eta * r)) / r;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut reciprocal = 0.0;
+    for n1 in -n_recip[0]..=n_recip[0] {
+        for n2 in -n_recip[1]..=n_recip[1] {
+            for n3 in -n_recip[2]..=n_recip[2] {
+                if n1 == 0 && n2 == 0 && n3 == 0 {
+                    continue;
+                }
+                let g = recip * Vector3::new(n1 as f64, n2 as f64, n3 as f64);
+                let g2 = g.norm_squared();
+                if g2 > g_max * g_max {
+                    continue;
+                }
+                let s = structure.atoms.iter().zip(charges).fold(Complex64::new(0.0, 0.0), |acc, (atom, &z)| {
+                    acc + z * Complex64::from_polar(1.0, g.dot(&atom.position))
+                });
+                reciprocal += s.norm_sqr() * (-g2 / (4.0 * eta * eta)).exp() / g2;
+            }
+        }
+    }
+    reciprocal *= 4.0 * PI / volume;
+
+    let z_sum: f64 = charges.iter().sum();
+    let z2_sum: f64 = charges.iter().map(|z| z * z).sum();
+    real + reciprocal - 2.0 * eta / PI.sqrt() * z2_sum - PI * z_sum * z_sum / (volume * eta * eta)
+}
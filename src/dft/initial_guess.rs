@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use nalgebra::Vector3;
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::Structure;
+use crate::dft::linalg::{orthonormalize, Orthonormalization};
+use crate::dft::solver::Overlap;
+use crate::io::upf::Pseudopotential;
+use crate::utils::radial::{pswfc_table, RadialTable, DEFAULT_DQ};
+use crate::utils::parallel;
+use crate::utils::rng::Rng;
+use crate::utils::ylm::real_ylm;
+
+/// Chute inicial das bandas do eigensolver iterativo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitialGuess {
+    /// `random_wavefunctions`.
+    #[default]
+    Random,
+    /// `atomic_wavefunctions` (PP_PSWFC), completado com bandas aleatórias.
+    Atomic,
+}
+
+impl InitialGuess {
+    /// `n_bands` bandas S-ortonormais em `basis`. A semente vem de
+    /// `parallel::starting_seed()`: fixa no modo determinístico, senão do relógio.
+    pub fn generate(
+        self,
+        basis: &PlaneWaveBasis,
+        structure: &Structure,
+        pseudos: &HashMap<usize, Pseudopotential>,
+        n_bands: usize,
+        overlap: Option<&dyn Overlap>,
+    ) -> Vec<Array1<Complex64>> {
+        let seed = parallel::starting_seed();
+        match self {
+            InitialGuess::Random => random_wavefunctions(basis, n_bands, seed, overlap),
+            InitialGuess::Atomic => atomic_wavefunctions(basis, structure, pseudos, n_bands, seed, overlap),
+        }
+    }
+}
+
+/// Bandas iniciais aleatórias para o eigensolver iterativo.
+///
+/// c_G = ξ_G / (1 + |k+G|^2), ξ_G complexo aleatório: o amortecimento concentra o peso em
+/// G pequeno (onde estão as bandas baixas) e a fase aleatória quebra as degenerescências
+/// que travam chutes constantes. As bandas saem S-ortonormais (S = 1 se `overlap` for `None`).
+/// A mesma `seed` gera sempre os mesmos vetores (ver `utils::parallel::starting_seed`).
+pub fn random_wavefunctions(
+    basis: &PlaneWaveBasis,
+    n_bands: usize,
+    seed: u64,
+    overlap: Option<&dyn Overlap>,
+) -> Vec<Array1<Complex64>> {
+    let mut rng = Rng::new(seed);
+    let mut bands: Vec<Array1<Complex64>> = (0..n_bands).map(|_| random_band(basis, &mut rng)).collect();
+    orthonormalize_or_gram_schmidt(basis, &mut bands, overlap);
+    bands
+}
+
+/// Subespaço inicial de orbitais pseudo-atômicos (PP_PSWFC), mais próximo das bandas de
+/// valência que o chute aleatório e por isso com menos iterações no primeiro passo SCF:
+///
+/// φ_I,im(k+G) = 4π (-i)^l χ_i(|k+G|) Y_lm(k+G) e^{-i(k+G)·τ_I} / √Ω
+///
+/// Os orbitais entram na ordem dos átomos; se houver mais que `n_bands`, sobram os
+/// últimos, e se faltarem, o bloco é completado com bandas aleatórias de `seed`
+/// (como em `random_wavefunctions`). Espécies sem PP_PSWFC só contribuem com as
+/// aleatórias, assim como orbitais com l > `ylm::LMAX`. As bandas saem S-ortonormais.
+pub fn atomic_wavefunctions(
+    basis: &PlaneWaveBasis,
+    structure: &Structure,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    n_bands: usize,
+    seed: u64,
+    overlap: Option<&dyn Overlap>,
+) -> Vec<Array1<Complex64>> {
+    let recip = structure.lattice.reciprocal();
+    let inv_sqrt_vol = 1.0 / structure.lattice.volume().sqrt();
+    let kg: Vec<Vector3<f64>> = basis.g_vectors.iter()
+        .map(|&(i, j, k)| recip * (basis.k_point + Vector3::new(i as f64, j as f64, k as f64)))
+        .collect();
+    let q_max = kg.iter().map(|q| q.norm()).fold(0.0, f64::max) + DEFAULT_DQ;
+
+    let mut tables: HashMap<usize, Vec<RadialTable>> = HashMap::new();
+    let mut bands: Vec<Array1<Complex64>> = Vec::with_capacity(n_bands);
+    'atoms: for atom in &structure.atoms {
+        let Some(pp) = pseudos.get(&atom.species_id) else {
+            continue;
+        };
+        let species_tables = tables.entry(atom.species_id)
+            .or_insert_with(|| (0..pp.pswfc.len()).map(|i| pswfc_table(pp, i, q_max, DEFAULT_DQ)).collect());
+        for (chi, table) in pp.pswfc.iter().zip(species_tables.iter()) {
+            let Some(ylm) = kg.iter().map(|q| real_ylm(chi.l, q)).collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+            let prefactor = Complex64::new(0.0, -1.0).powu(chi.l as u32) * inv_sqrt_vol;
+            let radial = basis.shells.expand(&basis.shells.map(|q| table.interpolate(q)));
+            for m in 0..2 * chi.l + 1 {
+                if bands.len() == n_bands {
+                    break 'atoms;
+                }
+                let phi = kg.iter().zip(&radial).zip(&ylm)
+                    .map(|((q, r), y)| prefactor * Complex64::from_polar(r * y[m], -q.dot(&atom.position)))
+                    .collect();
+                bands.push(phi);
+            }
+        }
+    }
+
+    let n_atomic = bands.len();
+    let mut rng = Rng::new(seed);
+    while bands.len() < n_bands {
+        bands.push(random_band(basis, &mut rng));
+    }
+    log::debug!("Chute inicial: {} orbitais atômicos e {} bandas aleatórias", n_atomic, n_bands - n_atomic);
+    orthonormalize_or_gram_schmidt(basis, &mut bands, overlap);
+    bands
+}
+
+/// c_G = ξ_G / (1 + |k+G|^2), ξ_G complexo aleatório.
+fn random_band(basis: &PlaneWaveBasis, rng: &mut Rng) -> Array1<Complex64> {
+    basis.g_norm_sq.iter().map(|&g2| rng.next_complex() / (1.0 + g2)).collect()
+}
+
+/// Ortonormalização em bloco (Cholesky), com Gram–Schmidt se o bloco for mal condicionado.
+fn orthonormalize_or_gram_schmidt(basis: &PlaneWaveBasis, bands: &mut [Array1<Complex64>], overlap: Option<&dyn Overlap>) {
+    if let Err(e) = orthonormalize(basis, bands, overlap, Orthonormalization::Cholesky) {
+        log::warn!("{} Usando Gram-Schmidt.", e);
+        gram_schmidt(basis, bands, overlap);
+    }
+}
+
+/// Gram–Schmidt modificado, aplicado duas vezes ("twice is enough") para manter a
+/// ortogonalidade em precisão de máquina: cada banda é ortogonalizada contra todas as
+/// anteriores e normalizada com ⟨ψ|S|ψ⟩ = 1. Bandas linearmente dependentes viram zero.
+/// Para blocos bem condicionados prefira `linalg::orthonormalize`; este é o fallback
+/// quando a fatoração em bloco falha.
+pub fn gram_schmidt(basis: &PlaneWaveBasis, bands: &mut [Array1<Complex64>], overlap: Option<&dyn Overlap>) {
+    let apply_s = |psi: &Array1<Complex64>| match overlap {
+        Some(op) => op.apply(basis, psi),
+        None => psi.clone(),
+    };
+
+    let mut s_bands: Vec<Array1<Complex64>> = Vec::with_capacity(bands.len());
+    for n in 0..bands.len() {
+        let (lower, rest) = bands.split_at_mut(n);
+        let psi = &mut rest[0];
+        for _ in 0..2 {
+            for (phi, s_phi) in lower.iter().zip(&s_bands) {
+                // ⟨φ|S|ψ⟩
+                let proj: Complex64 = s_phi.iter().zip(psi.iter()).map(|(a, b)| a.conj() * b).sum();
+                psi.zip_mut_with(phi, |p, &f| *p -= proj * f);
+            }
+        }
+
+        let s_psi = apply_s(&*psi);
+        let norm_sq: f64 = psi.iter().zip(&s_psi).map(|(a, b)| (a.conj() * b).re).sum();
+        if norm_sq > 1e-28 {
+            let inv = 1.0 / norm_sq.sqrt();
+            psi.mapv_inplace(|c| c * inv);
+            s_bands.push(s_psi.mapv(|c| c * inv));
+        } else {
+            log::warn!("Gram-Schmidt: banda {} linearmente dependente das anteriores", n);
+            psi.fill(Complex64::new(0.0, 0.0));
+            s_bands.push(Array1::zeros(psi.len()));
+        }
+    }
+}
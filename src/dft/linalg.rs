@@ -0,0 +1,244 @@
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use ndarray::{Array1, Array2};
+use num_complex::Complex64;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::dft::solver::Overlap;
+use crate::utils::timer;
+
+/// Autovalor (Löwdin) ou pivô (Cholesky) mínimo da matriz de overlap, relativo ao maior,
+/// abaixo do qual as bandas contam como linearmente dependentes.
+pub const DEPENDENCE_TOLERANCE: f64 = 1e-12;
+
+#[derive(Error, Debug)]
+pub enum LinalgError {
+    #[error("Matriz de overlap das bandas não é positiva definida (bandas linearmente dependentes).")]
+    NotPositiveDefinite,
+
+    #[error("Bandas com tamanhos diferentes: {0} e {1} coeficientes.")]
+    SizeMismatch(usize, usize),
+}
+
+/// Método de ortonormalização em bloco.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orthonormalization {
+    /// O = L L†, Ψ ← Ψ L⁻†: triangular, preserva o espaço gerado pelas primeiras bandas
+    /// (equivale a Gram–Schmidt). Mais barato.
+    #[default]
+    Cholesky,
+    /// Ψ ← Ψ O^{-1/2}: simétrico, a menor mudança possível nas bandas. Mais estável
+    /// com bandas quase dependentes.
+    Lowdin,
+}
+
+/// Empacota as bandas como colunas de uma matriz NPW x N.
+pub fn pack_bands(bands: &[Array1<Complex64>]) -> Result<Array2<Complex64>, LinalgError> {
+    let npw = bands.first().map_or(0, |b| b.len());
+    let mut block = Array2::zeros((npw, bands.len()));
+    for (mut column, band) in block.columns_mut().into_iter().zip(bands) {
+        if band.len() != npw {
+            return Err(LinalgError::SizeMismatch(npw, band.len()));
+        }
+        column.assign(band);
+    }
+    Ok(block)
+}
+
+/// Inverso de `pack_bands`: escreve as colunas de volta nas bandas.
+pub fn unpack_bands(block: &Array2<Complex64>, bands: &mut [Array1<Complex64>]) {
+    for (band, column) in bands.iter_mut().zip(block.columns()) {
+        band.assign(&column);
+    }
+}
+
+/// O = Ψ† Φ (N x N) num único produto de matrizes.
+pub fn overlap_matrix(psi: &Array2<Complex64>, phi: &Array2<Complex64>) -> Array2<Complex64> {
+    let _t = timer::scope("overlap_matrix");
+    psi.t().mapv(|c| c.conj()).dot(phi)
+}
+
+/// Ortonormaliza um bloco de bandas de uma vez: monta O = Ψ† S Ψ, fatora e aplica
+/// Ψ ← Ψ T com uma única multiplicação de matrizes (T = L⁻† ou O^{-1/2}). Substitui o
+/// Gram–Schmidt banda a banda, que faz O(N²) produtos escalares de vetores.
+/// Com `overlap`, as bandas saem S-ortonormais.
+pub fn orthonormalize(
+    basis: &PlaneWaveBasis,
+    bands: &mut [Array1<Complex64>],
+    overlap: Option<&dyn Overlap>,
+    method: Orthonormalization,
+) -> Result<(), LinalgError> {
+    let _t = timer::scope("orthonormalize");
+    if bands.is_empty() {
+        return Ok(());
+    }
+    let psi = pack_bands(bands)?;
+    let o = match overlap {
+        Some(op) => {
+            let s_bands: Vec<Array1<Complex64>> = bands.iter().map(|b| op.apply(basis, b)).collect();
+            overlap_matrix(&psi, &pack_bands(&s_bands)?)
+        }
+        None => overlap_matrix(&psi, &psi),
+    };
+    let t = match method {
+        Orthonormalization::Cholesky => cholesky_transform(&o)?,
+        Orthonormalization::Lowdin => lowdin_transform(&o)?,
+    };
+    unpack_bands(&psi.dot(&t), bands);
+    Ok(())
+}
+
+/// T = L⁻† com O = L L†. Pivôs L_kk² quase nulos (bandas quase dependentes) são
+/// rejeitados: a fatoração "passaria", mas T explodiria.
+pub fn cholesky_transform(o: &Array2<Complex64>) -> Result<Array2<Complex64>, LinalgError> {
+    let n = o.nrows();
+    let l = to_dmatrix(o).cholesky().ok_or(LinalgError::NotPositiveDefinite)?.unpack();
+    let max = o.diag().iter().map(|c| c.re).fold(0.0, f64::max);
+    if l.diagonal().iter().any(|d| !(d.norm_sqr() > DEPENDENCE_TOLERANCE * max)) {
+        return Err(LinalgError::NotPositiveDefinite);
+    }
+    // L† T = 1
+    let t = l.adjoint()
+        .solve_upper_triangular(&DMatrix::identity(n, n))
+        .ok_or(LinalgError::NotPositiveDefinite)?;
+    Ok(from_dmatrix(&t))
+}
+
+/// T = O^{-1/2} = U diag(λ^{-1/2}) U†.
+pub fn lowdin_transform(o: &Array2<Complex64>) -> Result<Array2<Complex64>, LinalgError> {
+    let eigen = SymmetricEigen::new(to_dmatrix(o));
+    let max = eigen.eigenvalues.iter().copied().fold(0.0, f64::max);
+    if eigen.eigenvalues.iter().any(|&l| l <= DEPENDENCE_TOLERANCE * max) {
+        return Err(LinalgError::NotPositiveDefinite);
+    }
+    let u = &eigen.eigenvectors;
+    let scaled = DMatrix::from_fn(u.nrows(), u.ncols(), |i, j| u[(i, j)] / eigen.eigenvalues[j].sqrt());
+    Ok(from_dmatrix(&(scaled * u.adjoint())))
+}
+
+/// Resolve o sistema denso pequeno A x = b (ex: coeficientes do Anderson) por
+/// fatoração LU, sem inverter A. Com as features `openblas`/`netlib` usa o `dgesv` do
+/// LAPACK; senão, a LU do nalgebra. `None` se A for singular.
+pub fn solve(a: DMatrix<f64>, b: DVector<f64>) -> Option<DVector<f64>> {
+    let _t = timer::scope("dense_solve");
+    if a.nrows() != a.ncols() || a.nrows() != b.len() {
+        return None;
+    }
+    solve_backend(a, b)
+}
+
+#[cfg(not(any(feature = "openblas", feature = "netlib")))]
+fn solve_backend(a: DMatrix<f64>, b: DVector<f64>) -> Option<DVector<f64>> {
+    a.lu().solve(&b)
+}
+
+#[cfg(any(feature = "openblas", feature = "netlib"))]
+fn solve_backend(mut a: DMatrix<f64>, mut b: DVector<f64>) -> Option<DVector<f64>> {
+    let n = a.nrows() as i32;
+    let mut ipiv = vec![0i32; a.nrows()];
+    let mut info = 0;
+    // DMatrix é column-major, como o LAPACK espera
+    unsafe {
+        lapack::dgesv(n, 1, a.as_mut_slice(), n.max(1), &mut ipiv, b.as_mut_slice(), n.max(1), &mut info);
+    }
+    (info == 0).then_some(b)
+}
+
+fn to_dmatrix(a: &Array2<Complex64>) -> DMatrix<Complex64> {
+    DMatrix::from_fn(a.nrows(), a.ncols(), |i, j| a[[i, j]])
+}
+
+fn from_dmatrix(m: &DMatrix<Complex64>) -> Array2<Complex64> {
+    Array2::from_shape_fn((m.nrows(), m.ncols()), |(i, j)| m[(i, j)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dft::initial_guess::gram_schmidt;
+    use crate::testkit::empty_cubic_box;
+
+    /// Bandas determinísticas, não ortogonais, com `n_bands` x `npw` coeficientes.
+    fn sample_bands(n_bands: usize, npw: usize) -> Vec<Array1<Complex64>> {
+        (0..n_bands)
+            .map(|n| Array1::from_shape_fn(npw, |g| {
+                let x = (n * npw + g) as f64;
+                Complex64::new((1.3 * x).sin() + 0.5, (0.7 * x).cos())
+            }))
+            .collect()
+    }
+
+    fn max_deviation_from_identity(bands: &[Array1<Complex64>]) -> f64 {
+        let psi = pack_bands(bands).unwrap();
+        let o = overlap_matrix(&psi, &psi);
+        o.indexed_iter()
+            .map(|((i, j), c)| (c - if i == j { Complex64::new(1.0, 0.0) } else { Complex64::new(0.0, 0.0) }).norm())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn orthonormalize_gives_identity_overlap() {
+        let basis = PlaneWaveBasis::new(&empty_cubic_box(6.0), 4.0, None);
+        let npw = basis.g_vectors.len();
+        for method in [Orthonormalization::Cholesky, Orthonormalization::Lowdin] {
+            let mut bands = sample_bands(5, npw);
+            orthonormalize(&basis, &mut bands, None, method).unwrap();
+            let dev = max_deviation_from_identity(&bands);
+            assert!(dev < 1e-10, "{:?}: |Ψ†Ψ - 1| = {:.3e}", method, dev);
+        }
+    }
+
+    #[test]
+    fn cholesky_matches_gram_schmidt() {
+        let basis = PlaneWaveBasis::new(&empty_cubic_box(6.0), 4.0, None);
+        let npw = basis.g_vectors.len();
+        let original = sample_bands(5, npw);
+        let mut cholesky = original.clone();
+        orthonormalize(&basis, &mut cholesky, None, Orthonormalization::Cholesky).unwrap();
+        let mut reference = original;
+        gram_schmidt(&basis, &mut reference, None);
+
+        // T triangular: ψ'_1 ∝ ψ_1, span(ψ'_1..ψ'_k) = span(ψ_1..ψ_k)
+        for (n, (c, r)) in cholesky.iter().zip(&reference).enumerate() {
+            let diff = c.iter().zip(r).map(|(a, b)| (a - b).norm()).fold(0.0, f64::max);
+            assert!(diff < 1e-10, "banda {}: diferença {:.3e}", n, diff);
+        }
+    }
+
+    #[test]
+    fn near_dependent_bands_are_rejected() {
+        let basis = PlaneWaveBasis::new(&empty_cubic_box(6.0), 4.0, None);
+        let npw = basis.g_vectors.len();
+        let mut original = sample_bands(3, npw);
+        let perturbation = original[0].mapv(|c| c * 1e-9);
+        original[2] = &original[1] + &perturbation;
+
+        for method in [Orthonormalization::Cholesky, Orthonormalization::Lowdin] {
+            let mut bands = original.clone();
+            let result = orthonormalize(&basis, &mut bands, None, method);
+            assert!(matches!(result, Err(LinalgError::NotPositiveDefinite)), "{:?}: {:?}", method, result);
+            assert!(bands.iter().flatten().all(|c| c.is_finite()), "{:?}: NaN nas bandas", method);
+            assert_eq!(bands, original, "{:?}: bandas alteradas após a falha", method);
+        }
+    }
+
+    #[test]
+    fn solve_rejects_singular_matrix() {
+        let a = DMatrix::from_row_slice(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 0.0, 1.0, 1.0]);
+        assert!(solve(a, DVector::from_vec(vec![1.0, 2.0, 3.0])).is_none());
+    }
+
+    #[test]
+    fn solve_matches_lu_on_known_system() {
+        let a = DMatrix::from_row_slice(3, 3, &[4.0, -2.0, 1.0, -2.0, 4.0, -2.0, 1.0, -2.0, 4.0]);
+        let x_exact = DVector::from_vec(vec![1.0, -1.0, 2.0]);
+        let b = &a * &x_exact;
+
+        let x = solve(a.clone(), b.clone()).expect("sistema regular");
+        let x_lu = a.lu().solve(&b).expect("sistema regular");
+        assert!((&x - &x_exact).amax() < 1e-12);
+        assert!((&x - &x_lu).amax() < 1e-12);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use nalgebra::Vector3;
+use ndarray::Array3;
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::field::PotentialField;
+use crate::core::structure::Structure;
+use crate::core::structure_factors::{fft_frequency, StructureFactors};
+use crate::dft::error::DftError;
+use crate::dft::hartree::CoulombKernel;
+use crate::dft::species_tables::SpeciesTables;
+use crate::io::upf::Pseudopotential;
+use crate::utils::radial::{local_long_range, local_short_range_table, RadialTable, DEFAULT_DQ};
+use crate::utils::{parallel, timer};
+
+/// Fator de forma do potencial local de uma espécie, com a cauda -2Z/r tratada analiticamente:
+///
+/// v(G) = [V_sr(|G|) - 8πZ e^{-G²/4}/G²] / Ω
+///
+/// V_sr (curto alcance, V_loc + 2Z erf(r)/r) vai a zero dentro da malha radial e é tabelado;
+/// a parte erf(r)/r tem transformada exata, então o truncamento da malha não desloca a energia.
+pub struct LocalFormFactor {
+    pub z_valence: f64,
+    short_range: RadialTable,
+}
+
+impl LocalFormFactor {
+    /// `q_max` (Bohr⁻¹) deve cobrir o maior |G| do grid denso.
+    pub fn new(pseudo: &Pseudopotential, q_max: f64) -> Self {
+        Self {
+            z_valence: pseudo.header.z_valence,
+            short_range: local_short_range_table(pseudo, q_max, DEFAULT_DQ),
+        }
+    }
+
+    /// v(|G|) em Ry, sem o fator 1/Ω. Em G = 0 o termo divergente -8πZ/G² é omitido
+    /// (cancela com Hartree e Ewald) e fica o limite regular de e^{-G²/4}: +2πZ,
+    /// ou seja, o termo "αZ" = 4π∫r²[V_loc(r) + 2Z/r]dr.
+    pub fn eval(&self, g: f64) -> f64 {
+        if g < 1e-10 {
+            return self.short_range.interpolate(0.0) + 2.0 * PI * self.z_valence;
+        }
+        self.short_range.interpolate(g) + local_long_range(self.z_valence, g)
+    }
+
+    /// Como `eval`, com a cauda -2Z erf(r)/r pela interação `kernel`: -Z K(G) e^{-G²/4}.
+    /// No kernel truncado o termo G = 0 é finito e entra inteiro.
+    pub fn eval_with(&self, g: f64, kernel: CoulombKernel) -> f64 {
+        match kernel {
+            CoulombKernel::Periodic => self.eval(g),
+            CoulombKernel::Truncated { .. } => {
+                let g2 = g * g;
+                self.short_range.interpolate(g) - self.z_valence * kernel.eval(g2) * (-g2 / 4.0).exp()
+            }
+        }
+    }
+}
+
+/// Fatores de forma das espécies presentes em `structure`.
+pub fn local_form_factors(
+    structure: &Structure,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    q_max: f64,
+) -> Result<HashMap<usize, LocalFormFactor>, DftError> {
+    let mut factors = HashMap::new();
+    for atom in &structure.atoms {
+        if factors.contains_key(&atom.species_id) {
+            continue;
+        }
+        let pseudo = pseudos.get(&atom.species_id).ok_or(DftError::MissingPseudo(atom.species_id))?;
+        factors.insert(atom.species_id, LocalFormFactor::new(pseudo, q_max));
+    }
+    Ok(factors)
+}
+
+/// Potencial local iônico no grid denso (Ry), montado em espaço recíproco:
+///
+/// V_loc(r) = Σ_G Σ_s S_s(G) v_s(|G|) e^{iG·r}
+///
+/// `structure_factors` deve estar atualizado para `structure` e no mesmo grid de `fft`.
+/// Usa o buffer de `fft` como área de trabalho.
+pub fn calculate_local_potential(
+    structure: &Structure,
+    fft: &mut FftGrid,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    structure_factors: &StructureFactors,
+) -> Result<PotentialField, DftError> {
+    calculate_local_potential_with(structure, fft, pseudos, structure_factors, &mut SpeciesTables::new(), CoulombKernel::Periodic)
+}
+
+/// Como `calculate_local_potential`, reaproveitando os fatores de forma de `tables` e com a
+/// cauda Coulombiana pela interação `kernel` (truncada em sistemas isolados).
+pub fn calculate_local_potential_with(
+    structure: &Structure,
+    fft: &mut FftGrid,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    structure_factors: &StructureFactors,
+    tables: &mut SpeciesTables,
+    kernel: CoulombKernel,
+) -> Result<PotentialField, DftError> {
+    let _t = timer::scope("local_potential");
+    let [nx, ny, nz] = fft.size;
+    let n_points = nx * ny * nz;
+    if structure_factors.grid != fft.size {
+        return Err(DftError::SizeMismatch(
+            "fatores de estrutura",
+            structure_factors.grid.iter().product(),
+            n_points,
+        ));
+    }
+
+    let recip = structure.lattice.reciprocal();
+    let g_of = |i: usize, j: usize, k: usize| {
+        recip * Vector3::new(
+            fft_frequency(i, nx) as f64,
+            fft_frequency(j, ny) as f64,
+            fft_frequency(k, nz) as f64,
+        )
+    };
+    // Maior |G| da caixa FFT (rede não ortogonal: não é necessariamente um canto fixo)
+    let q_max = fft.buffer.indexed_iter()
+        .map(|((i, j, k), _)| g_of(i, j, k).norm())
+        .fold(0.0, f64::max)
+        + 2.0 * DEFAULT_DQ;
+    let factors = tables.local_form_factors(structure, pseudos, q_max)?;
+    let inv_volume = 1.0 / structure.lattice.volume();
+
+    let species: Vec<(&LocalFormFactor, &Array3<Complex64>)> = factors.iter()
+        .filter_map(|(id, form)| structure_factors.species(*id).map(|s| (form, s)))
+        .collect();
+
+    // Paralelo por fatias i = const (pontos independentes)
+    let buffer = fft.buffer.as_slice_mut().ok_or(DftError::NonContiguous("buffer da FFT"))?;
+    parallel::for_each_chunk_mut(buffer, ny * nz, |i, slab| {
+        for (jk, v) in slab.iter_mut().enumerate() {
+            let (j, k) = (jk / nz, jk % nz);
+            let g = g_of(i, j, k).norm();
+            *v = species.iter()
+                .fold(Complex64::new(0.0, 0.0), |acc, (form, s)| acc + s[[i, j, k]] * form.eval_with(g, kernel))
+                * inv_volume;
+        }
+    });
+
+    // ifft normaliza por 1/N
+    fft.inverse_in_place()?;
+    let scale = n_points as f64;
+    Ok(PotentialField::new(structure.lattice.clone(), fft.buffer.mapv(|c| c.re * scale)))
+}
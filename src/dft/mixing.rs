@@ -0,0 +1,384 @@
+use std::collections::VecDeque;
+use std::fmt;
+use nalgebra::{DMatrix, DVector, Matrix3, Vector3};
+use ndarray::{Array3, Zip};
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::memory::DEFAULT_MIXING_HISTORY;
+use crate::dft::error::DftError;
+use crate::dft::linalg;
+use crate::utils::timer;
+
+/// Mixing de Anderson (Pulay) da densidade.
+///
+/// Com os pares (ρ_in, F = ρ_out - ρ_in) das últimas `history` iterações, procura a
+/// combinação de resíduos de menor norma:
+///
+/// (ΔFᵀ ΔF) γ = ΔFᵀ F_n,  ρ_new = ρ_n + β F_n - Σ_j γ_j (Δρ_j + β ΔF_j)
+///
+/// O sistema m x m é resolvido por fatoração (`linalg::solve`), não por inversão: com
+/// históricos longos ΔFᵀΔF fica mal condicionada e a inversa explícita perde precisão.
+/// Se o sistema for singular, o histórico é descartado e a iteração vira mixing linear.
+/// Com `kerker`, o passo β·F é pré-condicionado (ver `Kerker`).
+/// *Ref: Anderson, D. G. (1965). J. ACM, 12(4), 547; Pulay, P. (1980). Chem. Phys. Lett., 73(2), 393.*
+#[derive(Debug, Clone)]
+pub struct AndersonMixer {
+    pub beta: f64,
+    pub history: usize,
+    pub kerker: Option<Kerker>,
+    pairs: VecDeque<(Array3<f64>, Array3<f64>)>, // (ρ_in, F)
+}
+
+impl AndersonMixer {
+    pub fn new(beta: f64, history: usize) -> Self {
+        Self { beta, history, kerker: None, pairs: VecDeque::with_capacity(history + 1) }
+    }
+
+    /// Pares guardados no histórico.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Descarta o histórico (ex: após mudar a geometria).
+    pub fn reset(&mut self) {
+        self.pairs.clear();
+    }
+
+    /// Nova densidade de entrada a partir de ρ_in e ρ_out da iteração atual.
+    /// Com `kerker` ativo (ex: ligado pelo `SloshingDetector`) devolve erro sem tocar no
+    /// histórico: o passo precisa do grid FFT, ver `mix_preconditioned`.
+    pub fn mix(&mut self, rho_in: &Array3<f64>, rho_out: &Array3<f64>) -> Result<Array3<f64>, DftError> {
+        if self.kerker.is_some() {
+            return Err(DftError::KerkerNeedsGrid);
+        }
+        let (rho_opt, residual_opt) = self.extrapolate(rho_in, rho_out)?;
+        Ok(rho_opt + &(residual_opt * self.beta))
+    }
+
+    /// Como `mix`, com o passo pré-condicionado por `kerker` (se houver) no grid `fft`.
+    pub fn mix_preconditioned(
+        &mut self,
+        rho_in: &Array3<f64>,
+        rho_out: &Array3<f64>,
+        fft: &mut FftGrid,
+    ) -> Result<Array3<f64>, DftError> {
+        let (rho_opt, residual_opt) = self.extrapolate(rho_in, rho_out)?;
+        let step = match &self.kerker {
+            Some(kerker) => kerker.apply(fft, &residual_opt)?,
+            None => residual_opt,
+        };
+        Ok(rho_opt + &(step * self.beta))
+    }
+
+    /// Guarda o par atual e devolve a melhor combinação do histórico,
+    /// ρ_opt = ρ_n - Σ γ_j Δρ_j e F_opt = F_n - Σ γ_j ΔF_j.
+    fn extrapolate(&mut self, rho_in: &Array3<f64>, rho_out: &Array3<f64>) -> Result<(Array3<f64>, Array3<f64>), DftError> {
+        let _t = timer::scope("mixing");
+        let (d_in, d_out) = (rho_in.dim(), rho_out.dim());
+        if d_in != d_out {
+            return Err(DftError::GridMismatch([d_out.0, d_out.1, d_out.2], [d_in.0, d_in.1, d_in.2]));
+        }
+        if let Some((previous, _)) = self.pairs.back() {
+            if previous.dim() != d_in {
+                self.reset();
+            }
+        }
+
+        let residual = rho_out - rho_in;
+        self.pairs.push_back((rho_in.clone(), residual));
+        while self.pairs.len() > self.history + 1 {
+            self.pairs.pop_front();
+        }
+
+        let (x_n, f_n) = self.pairs.back().expect("par recém-inserido");
+        let (mut rho_opt, mut residual_opt) = (x_n.clone(), f_n.clone());
+        let m = self.pairs.len() - 1;
+        if m == 0 {
+            return Ok((rho_opt, residual_opt));
+        }
+
+        // Diferenças consecutivas Δρ_j, ΔF_j
+        let deltas: Vec<(Array3<f64>, Array3<f64>)> = self.pairs.iter().zip(self.pairs.iter().skip(1))
+            .map(|((x0, f0), (x1, f1))| (x1 - x0, f1 - f0))
+            .collect();
+        let dot = |a: &Array3<f64>, b: &Array3<f64>| Zip::from(a).and(b).fold(0.0, |acc, &x, &y| acc + x * y);
+        let a = DMatrix::from_fn(m, m, |i, j| dot(&deltas[i].1, &deltas[j].1));
+        let b = DVector::from_fn(m, |i, _| dot(&deltas[i].1, f_n));
+
+        match linalg::solve(a, b) {
+            Some(gamma) => {
+                for ((dx, df), &g) in deltas.iter().zip(gamma.iter()) {
+                    rho_opt.scaled_add(-g, dx);
+                    residual_opt.scaled_add(-g, df);
+                }
+            }
+            None => {
+                log::warn!("Mixing de Anderson: sistema singular com {} pares, histórico descartado", m);
+                let last = self.pairs.pop_back().expect("histórico não vazio");
+                self.pairs.clear();
+                self.pairs.push_back(last);
+            }
+        }
+        Ok((rho_opt, residual_opt))
+    }
+}
+
+impl Default for AndersonMixer {
+    fn default() -> Self {
+        Self::new(0.3, DEFAULT_MIXING_HISTORY)
+    }
+}
+
+/// q0 padrão do Kerker (Bohr⁻¹), ~1.5 Å⁻¹.
+pub const DEFAULT_KERKER_Q0: f64 = 0.8;
+
+/// Pré-condicionador de Kerker: F(G) ← F(G)·G²/(G² + q0²).
+///
+/// Amortece as componentes de G pequeno do resíduo, responsáveis pelo "charge sloshing"
+/// em metais e células longas (a resposta dielétrica ~ 1 + q0²/G² amplifica dρ nelas).
+/// F(G = 0) é zerado: o passo não altera o número de elétrons.
+/// *Ref: Kerker, G. P. (1981). Physical Review B, 23(6), 3082.*
+#[derive(Debug, Clone)]
+pub struct Kerker {
+    pub q0: f64,
+    factors: Array3<f64>,
+}
+
+impl Kerker {
+    /// Fatores G²/(G² + q0²) no grid `size` da rede recíproca `recip` (colunas b_i).
+    pub fn new(recip: &Matrix3<f64>, size: [usize; 3], q0: f64) -> Self {
+        let freq = |i: usize, n: usize| if i > n / 2 { i as i64 - n as i64 } else { i as i64 };
+        let q0_sq = q0 * q0;
+        let factors = Array3::from_shape_fn((size[0], size[1], size[2]), |(i, j, k)| {
+            let m = Vector3::new(freq(i, size[0]) as f64, freq(j, size[1]) as f64, freq(k, size[2]) as f64);
+            let g2 = (recip * m).norm_squared();
+            g2 / (g2 + q0_sq)
+        });
+        Self { q0, factors }
+    }
+
+    /// P·F para um resíduo no espaço real.
+    pub fn apply(&self, fft: &mut FftGrid, residual: &Array3<f64>) -> Result<Array3<f64>, DftError> {
+        let dim = residual.dim();
+        if [dim.0, dim.1, dim.2] != fft.size || residual.dim() != self.factors.dim() {
+            return Err(DftError::GridMismatch([dim.0, dim.1, dim.2], fft.size));
+        }
+        fft.buffer.zip_mut_with(residual, |c, &r| *c = Complex64::new(r, 0.0));
+        fft.forward_in_place()?;
+        fft.buffer.zip_mut_with(&self.factors, |c, &f| *c *= f);
+        fft.inverse_in_place()?;
+        Ok(fft.buffer.mapv(|c| c.re))
+    }
+}
+
+/// Padrão de dρ que indica que o mixing não está convergindo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sloshing {
+    /// dρ cresceu mais que `GROWTH_FACTOR` vezes o mínimo da janela.
+    Growing,
+    /// dρ alterna sobe/desce a cada iteração sem cair pela metade na janela.
+    Oscillating,
+}
+
+impl fmt::Display for Sloshing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sloshing::Growing => f.write_str("dρ crescente"),
+            Sloshing::Oscillating => f.write_str("dρ oscilante"),
+        }
+    }
+}
+
+/// Correção aplicada ao mixer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MixingAction {
+    ReduceBeta { from: f64, to: f64 },
+    EnableKerker { q0: f64 },
+    ResetHistory,
+}
+
+impl fmt::Display for MixingAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MixingAction::ReduceBeta { from, to } => write!(f, "β reduzido de {:.3} para {:.3}", from, to),
+            MixingAction::EnableKerker { q0 } => write!(f, "pré-condicionador de Kerker ativado (q0 = {:.2} Bohr⁻¹)", q0),
+            MixingAction::ResetHistory => f.write_str("histórico do mixing descartado"),
+        }
+    }
+}
+
+/// dρ acima deste múltiplo do mínimo da janela conta como crescimento.
+pub const GROWTH_FACTOR: f64 = 2.0;
+
+/// Detecta "charge sloshing" nos últimos `window` resíduos de densidade e escolhe a
+/// correção, em escada: reduzir β, ativar Kerker, descartar o histórico (ex: Anderson
+/// extrapolando com pares de uma fase divergente); depois só reduz β até `min_beta`
+/// e, no piso, descarta o histórico. Após cada ação a janela recomeça, para que o
+/// efeito seja avaliado antes da próxima.
+#[derive(Debug, Clone)]
+pub struct SloshingDetector {
+    pub window: usize,
+    pub min_beta: f64,
+    pub kerker_q0: f64,
+    residuals: VecDeque<f64>,
+    stage: usize,
+}
+
+impl SloshingDetector {
+    pub fn new(window: usize, min_beta: f64, kerker_q0: f64) -> Self {
+        Self { window: window.max(3), min_beta, kerker_q0, residuals: VecDeque::new(), stage: 0 }
+    }
+
+    /// Registra o dρ da iteração e diz se a janela mostra sloshing.
+    pub fn observe(&mut self, density_residual: f64) -> Option<Sloshing> {
+        self.residuals.push_back(density_residual);
+        while self.residuals.len() > self.window {
+            self.residuals.pop_front();
+        }
+        if self.residuals.len() < self.window {
+            return None;
+        }
+        let r: Vec<f64> = self.residuals.iter().copied().collect();
+        let (first, last) = (r[0], r[r.len() - 1]);
+        let min = r.iter().copied().fold(f64::INFINITY, f64::min);
+        if !last.is_finite() || last > GROWTH_FACTOR * min {
+            return Some(Sloshing::Growing);
+        }
+        let diffs: Vec<f64> = r.windows(2).map(|w| w[1] - w[0]).collect();
+        let alternating = diffs.windows(2).all(|d| d[0] * d[1] < 0.0);
+        if alternating && last > 0.5 * first {
+            return Some(Sloshing::Oscillating);
+        }
+        None
+    }
+
+    /// Aplica ao mixer a próxima correção da escada. `recip` e `size` montam o Kerker.
+    pub fn respond(&mut self, mixer: &mut AndersonMixer, recip: &Matrix3<f64>, size: [usize; 3]) -> MixingAction {
+        self.residuals.clear();
+        let stage = self.stage;
+        self.stage += 1;
+        let reduce = |mixer: &mut AndersonMixer, min_beta: f64| {
+            let from = mixer.beta;
+            mixer.beta = (0.5 * from).max(min_beta);
+            (mixer.beta < from).then_some(MixingAction::ReduceBeta { from, to: mixer.beta })
+        };
+        match stage {
+            0 => if let Some(action) = reduce(mixer, self.min_beta) {
+                return action;
+            },
+            1 if mixer.kerker.is_none() => {
+                mixer.kerker = Some(Kerker::new(recip, size, self.kerker_q0));
+                return MixingAction::EnableKerker { q0: self.kerker_q0 };
+            }
+            2 => {}
+            _ => if let Some(action) = reduce(mixer, self.min_beta) {
+                return action;
+            },
+        }
+        mixer.reset();
+        MixingAction::ResetHistory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dft::scf::ScfParameters;
+
+    /// Ponto fixo ρ = Aρ + b com A diagonal: F = (A - 1)ρ + b.
+    const EIGENVALUES: [f64; 4] = [0.5, 0.2, -0.3, 0.6];
+    const OFFSET: [f64; 4] = [1.0, -2.0, 0.5, 3.0];
+
+    fn linear_map(rho: &Array3<f64>) -> Array3<f64> {
+        Array3::from_shape_fn(rho.dim(), |(i, _, _)| EIGENVALUES[i] * rho[[i, 0, 0]] + OFFSET[i])
+    }
+
+    fn residual_norm(rho: &Array3<f64>) -> f64 {
+        (linear_map(rho) - rho).iter().map(|f| f * f).sum::<f64>().sqrt()
+    }
+
+    /// Iterações até |F| < `tol` (ou `max`).
+    fn iterations_to_converge(mut mixer: AndersonMixer, tol: f64, max: usize) -> usize {
+        let mut rho = Array3::zeros((4, 1, 1));
+        for n in 0..max {
+            if residual_norm(&rho) < tol {
+                return n;
+            }
+            rho = mixer.mix(&rho, &linear_map(&rho)).unwrap();
+        }
+        max
+    }
+
+    #[test]
+    fn anderson_beats_linear_mixing_on_linear_map() {
+        let linear = iterations_to_converge(AndersonMixer::new(0.5, 0), 1e-8, 500);
+        let anderson = iterations_to_converge(AndersonMixer::new(0.5, 5), 1e-8, 500);
+        assert!(linear < 500, "mixing linear não convergiu");
+        assert!(anderson < linear, "Anderson: {} iterações, linear: {}", anderson, linear);
+        // Em dimensão 4 o Anderson é exato após no máximo 5 passos (equivale a GMRES)
+        assert!(anderson <= 8, "Anderson: {} iterações", anderson);
+    }
+
+    #[test]
+    fn history_evicts_oldest_pairs() {
+        let params = ScfParameters { mixing_history: 2, ..Default::default() };
+        let inputs: Vec<Array3<f64>> = (0..6)
+            .map(|n| Array3::from_shape_fn((4, 1, 1), |(i, _, _)| ((n * 4 + i) as f64).sin()))
+            .collect();
+
+        let mut full = params.mixer();
+        let mut last = None;
+        for (n, rho) in inputs.iter().enumerate() {
+            last = Some(full.mix(rho, &linear_map(rho)).unwrap());
+            assert_eq!(full.len(), (n + 1).min(params.mixing_history + 1));
+        }
+
+        // Um mixer que só viu os últimos `mixing_history + 1` pares dá o mesmo resultado
+        let mut recent = params.mixer();
+        let mut expected = None;
+        for rho in &inputs[inputs.len() - params.mixing_history - 1..] {
+            expected = Some(recent.mix(rho, &linear_map(rho)).unwrap());
+        }
+        let diff = (last.unwrap() - expected.unwrap()).iter().fold(0.0_f64, |m, d| m.max(d.abs()));
+        assert!(diff < 1e-12, "diferença {:.3e}", diff);
+    }
+
+    #[test]
+    fn mix_refuses_active_kerker() {
+        let mut mixer = AndersonMixer::new(0.5, 3);
+        mixer.kerker = Some(Kerker::new(&Matrix3::identity(), [4, 1, 1], DEFAULT_KERKER_Q0));
+        let rho = Array3::zeros((4, 1, 1));
+        assert!(matches!(mixer.mix(&rho, &linear_map(&rho)), Err(DftError::KerkerNeedsGrid)));
+        assert!(mixer.is_empty());
+    }
+
+    #[test]
+    fn kerker_zeroes_g0_and_conserves_charge() {
+        let size = [6, 5, 4];
+        let recip = Matrix3::from_diagonal(&Vector3::new(0.9, 1.1, 1.4));
+        let kerker = Kerker::new(&recip, size, DEFAULT_KERKER_Q0);
+        let mut fft = FftGrid::with_size(size).unwrap();
+        let residual = Array3::from_shape_fn((6, 5, 4), |(i, j, k)| 1.0 + (i as f64).sin() + 0.5 * ((j + 2 * k) as f64).cos());
+        let step = kerker.apply(&mut fft, &residual).unwrap();
+
+        // Σ_r P·F = N·(P·F)(G = 0) = 0: o passo não altera a carga
+        let total: f64 = step.sum();
+        assert!(total.abs() < 1e-10, "carga do passo {:.3e}", total);
+
+        // Componentes G ≠ 0 são só amortecidas por G²/(G² + q0²)
+        let mut zero_mean = residual.clone();
+        let mean = residual.mean().unwrap();
+        zero_mean.mapv_inplace(|r| r - mean);
+        let (norm_in, norm_out) = (zero_mean.iter().map(|r| r * r).sum::<f64>(), step.iter().map(|r| r * r).sum::<f64>());
+        assert!(norm_out > 0.0 && norm_out < norm_in);
+
+        // Resíduo constante (só G = 0) vira zero
+        let flat = kerker.apply(&mut fft, &Array3::from_elem((6, 5, 4), 2.0)).unwrap();
+        assert!(flat.iter().all(|r| r.abs() < 1e-12));
+    }
+}
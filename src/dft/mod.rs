@@ -1 +1,20 @@
-pub mod density;
\ No newline at end of file
+pub mod error;
+pub mod density;
+pub mod local_potential;
+pub mod hartree;
+pub mod preconditioner;
+pub mod pcg;
+pub mod solver;
+pub mod hamiltonian;
+pub mod wavefunctions;
+pub mod initial_guess;
+pub mod scf;
+pub mod occupations;
+pub mod nscf;
+pub mod paw;
+pub mod species_tables;
+pub mod scratch;
+pub mod xc;
+pub mod dispersion;
+pub mod linalg;
+pub mod mixing;
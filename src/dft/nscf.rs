@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::fft::FftGrid;
+use crate::core::field::PotentialField;
+use crate::core::kpoints::KGrid;
+use crate::core::simulation::Simulation;
+use crate::core::structure::Structure;
+use crate::dft::hamiltonian::Hamiltonian;
+use crate::dft::initial_guess::InitialGuess;
+use crate::dft::occupations::{Occupations, Smearing};
+use crate::dft::paw::AugmentationOverlap;
+use crate::dft::solver::{BandSolverResult, Diagonalizer, Overlap, SolverError};
+use crate::dft::scratch::{OutOfCore, StoredBands};
+use crate::dft::wavefunctions::Precision;
+use crate::io::upf::Pseudopotential;
+use crate::utils::timer;
+
+/// Bandas não auto-consistentes em um conjunto arbitrário de pontos K.
+pub struct NscfResult {
+    pub k_grid: KGrid,
+    pub bases: Vec<PlaneWaveBasis>,
+    pub bands: Vec<BandSolverResult>,      // Autovalores e resíduos; autovetores em `wavefunctions`
+    pub wavefunctions: Vec<StoredBands>,  // Autovetores por ponto K (RAM ou rascunho)
+}
+
+impl NscfResult {
+    /// Autovalores por ponto K (Ry), ex: para DOS densa ou superfície de Fermi.
+    pub fn eigenvalues(&self) -> Vec<Vec<f64>> {
+        self.bands.iter().map(|b| b.eigenvalues.clone()).collect()
+    }
+
+    /// Ocupações por ponto K e banda para `n_electrons` elétrons.
+    pub fn occupations(&self, n_electrons: f64, smearing: Smearing) -> Occupations {
+        let weights: Vec<f64> = self.k_grid.k_points.iter().map(|kp| kp.weight).collect();
+        Occupations::compute(&self.eigenvalues(), &weights, n_electrons, smearing)
+    }
+}
+
+/// Cortes, bandas, eigensolver e armazenamento dos autovetores de um NSCF.
+#[derive(Debug, Clone, Copy)]
+pub struct NscfParameters<'a> {
+    pub ecut: f64,
+    pub ecut_rho: f64,
+    pub n_bands: usize,
+    pub diagonalizer: Diagonalizer,
+    pub initial_guess: InitialGuess,         // Bandas de partida dos solvers iterativos
+    pub precision: Precision,                // f32 reduz a memória dos autovetores pela metade
+    pub out_of_core: Option<&'a OutOfCore>,  // Autovetores em arquivos de rascunho
+}
+
+impl<'a> NscfParameters<'a> {
+    /// Parâmetros da simulação.
+    pub fn from_simulation(sim: &'a Simulation) -> Self {
+        Self {
+            ecut: sim.ecut,
+            ecut_rho: sim.ecut_rho,
+            n_bands: sim.n_bands,
+            diagonalizer: sim.diagonalizer,
+            initial_guess: sim.initial_guess,
+            precision: sim.precision,
+            out_of_core: sim.out_of_core.as_ref(),
+        }
+    }
+}
+
+/// Diagonaliza `hamiltonian` em cada ponto K de `k_grid` com V_eff fixo (obtido de um
+/// SCF convergido em malha grossa). Não há atualização da densidade, então malhas densas
+/// saem pelo custo de uma única diagonalização por ponto. `fft` (compartilhado por todos
+/// os pontos K) deve ser o grid dos potenciais locais de `hamiltonian`.
+/// Com `overlap` (ultrasoft/PAW) resolve Hψ = εSψ. `pseudos` só alimenta o chute
+/// `InitialGuess::Atomic` dos solvers iterativos.
+pub fn run_nscf(
+    structure: &Structure,
+    pseudos: &HashMap<usize, Pseudopotential>,
+    hamiltonian: &Hamiltonian,
+    fft: &mut FftGrid,
+    k_grid: &KGrid,
+    params: &NscfParameters,
+    overlap: Option<&dyn Overlap>,
+) -> Result<NscfResult, SolverError> {
+    let _t = timer::scope("nscf");
+    let n_k = k_grid.k_points.len();
+    let mut bases = Vec::with_capacity(n_k);
+    let mut bands = Vec::with_capacity(n_k);
+    let mut wavefunctions = Vec::with_capacity(n_k);
+    for (ik, kp) in k_grid.k_points.iter().enumerate() {
+        let basis = PlaneWaveBasis::with_grid(structure, params.ecut, params.ecut_rho, fft.size, Some(kp.coord));
+        let mut result = params.diagonalizer.solve(&mut hamiltonian.bind(&basis, fft), params.n_bands, overlap, || {
+            params.initial_guess.generate(&basis, structure, pseudos, params.n_bands, overlap)
+        })?;
+        log::debug!(
+            "NSCF k {:>4}/{} [{:.4}, {:.4}, {:.4}]: {} PWs",
+            ik + 1, n_k, kp.coord[0], kp.coord[1], kp.coord[2], basis.g_vectors.len()
+        );
+        let eigenvectors = std::mem::take(&mut result.eigenvectors);
+        wavefunctions.push(StoredBands::store(eigenvectors, params.precision, params.out_of_core, ik)?);
+        bases.push(basis);
+        bands.push(result);
+    }
+
+    log::info!("NSCF: {} pontos K, {} bandas", n_k, params.n_bands);
+    Ok(NscfResult { k_grid: k_grid.clone(), bases, bands, wavefunctions })
+}
+
+/// NSCF com estrutura, cortes e armazenamento das bandas da simulação, com H = T + `v_eff`
+/// no grid denso. Se algum pseudo tem aumento (ultrasoft/PAW), aplica o overlap S de `dft::paw`.
+pub fn run_nscf_for(sim: &Simulation, v_eff: &PotentialField, k_grid: &KGrid) -> Result<NscfResult, SolverError> {
+    let overlap = AugmentationOverlap::for_pseudos(&sim.structure, &sim.pseudos, sim.ecut);
+    // Grid denso compartilhado: depende só da célula e de ecut_rho, não do ponto K
+    let mut fft = FftGrid::with_size(v_eff.dims())?;
+    let hamiltonian = Hamiltonian::new(v_eff.clone());
+    run_nscf(
+        &sim.structure,
+        &sim.pseudos,
+        &hamiltonian,
+        &mut fft,
+        k_grid,
+        &NscfParameters::from_simulation(sim),
+        overlap.as_ref().map(|s| s as &dyn Overlap),
+    )
+}
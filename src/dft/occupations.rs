@@ -0,0 +1,314 @@
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::utils::radial::erf;
+
+/// Ocupação da banda mais alta acima da qual faltam bandas vazias.
+pub const TOP_BAND_TOLERANCE: f64 = 1e-3;
+
+#[derive(Error, Debug)]
+pub enum OccupationError {
+    #[error("Ponto K {0} inexistente ({1} pontos)")]
+    KPointOutOfRange(usize, usize),
+    #[error("Banda {0} inexistente ({1} bandas)")]
+    BandOutOfRange(usize, usize),
+    #[error("Promoção inválida no ponto K {0}: banda {1} ficaria com ocupação {2:.3} (fora de [0, 2])")]
+    InvalidOccupation(usize, usize, f64),
+    #[error("Largura de alargamento inválida: {0} Ry (deve ser positiva e finita)")]
+    InvalidSmearingWidth(f64),
+}
+
+/// Promove `amount` elétrons da banda `from_band` para `to_band` no ponto K `k_index`.
+#[derive(Debug, Clone, Copy)]
+pub struct Promotion {
+    pub k_index: usize,
+    pub from_band: usize,
+    pub to_band: usize,
+    pub amount: f64,
+}
+
+/// Ocupações não-Aufbau mantidas fixas durante o SCF (ΔSCF, DFT restrito).
+/// A energia de excitação é estimada por ΔE = E[excitado] - E[fundamental].
+#[derive(Debug, Clone, Default)]
+pub struct OccupationConstraints {
+    pub promotions: Vec<Promotion>,
+}
+
+impl OccupationConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Promove um elétron (ex: HOMO -> LUMO em Γ).
+    pub fn promote(mut self, k_index: usize, from_band: usize, to_band: usize) -> Self {
+        self.promotions.push(Promotion { k_index, from_band, to_band, amount: 1.0 });
+        self
+    }
+
+    /// Promoção de carga fracionária (ex: meio elétron, estado de transição de Slater).
+    pub fn promote_fraction(mut self, k_index: usize, from_band: usize, to_band: usize, amount: f64) -> Self {
+        self.promotions.push(Promotion { k_index, from_band, to_band, amount });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.promotions.is_empty()
+    }
+
+    /// Aplica as promoções sobre ocupações `occ[k][n]`.
+    pub fn apply(&self, occ: &mut [Vec<f64>]) -> Result<(), OccupationError> {
+        let n_k = occ.len();
+        for p in &self.promotions {
+            let bands = occ.get_mut(p.k_index).ok_or(OccupationError::KPointOutOfRange(p.k_index, n_k))?;
+            let n_bands = bands.len();
+            for band in [p.from_band, p.to_band] {
+                if band >= n_bands {
+                    return Err(OccupationError::BandOutOfRange(band, n_bands));
+                }
+            }
+            bands[p.from_band] -= p.amount;
+            bands[p.to_band] += p.amount;
+            for band in [p.from_band, p.to_band] {
+                if !(-1e-12..=2.0 + 1e-12).contains(&bands[band]) {
+                    return Err(OccupationError::InvalidOccupation(p.k_index, band, bands[band]));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ocupações Aufbau (sem spin, máximo 2 por banda): as bandas mais baixas são preenchidas
+/// em todos os pontos K; a última pode ficar parcialmente ocupada.
+pub fn aufbau(n_electrons: f64, n_k: usize, n_bands: usize) -> Vec<Vec<f64>> {
+    let mut bands = vec![0.0; n_bands];
+    let mut remaining = n_electrons;
+    for f in bands.iter_mut() {
+        *f = remaining.clamp(0.0, 2.0);
+        remaining -= *f;
+    }
+    vec![bands; n_k]
+}
+
+/// Ocupações Aufbau com as restrições aplicadas.
+pub fn constrained_occupations(
+    n_electrons: f64,
+    n_k: usize,
+    n_bands: usize,
+    constraints: &OccupationConstraints,
+) -> Result<Vec<Vec<f64>>, OccupationError> {
+    let mut occ = aufbau(n_electrons, n_k, n_bands);
+    constraints.apply(&mut occ)?;
+    Ok(occ)
+}
+
+/// Alargamento das ocupações em torno do nível de Fermi. `width` em Ry.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(try_from = "RawSmearing")]
+pub enum Smearing {
+    /// Ocupações inteiras (isolantes).
+    #[default]
+    Fixed,
+    /// f = ½ erfc((ε - E_F)/σ).
+    Gaussian { width: f64 },
+    /// f = 1 / (1 + e^{(ε - E_F)/kT}).
+    FermiDirac { width: f64 },
+}
+
+/// Forma lida do arquivo de entrada, validada em `Smearing::try_from`.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum RawSmearing {
+    Fixed,
+    Gaussian { width: f64 },
+    FermiDirac { width: f64 },
+}
+
+impl TryFrom<RawSmearing> for Smearing {
+    type Error = OccupationError;
+
+    fn try_from(raw: RawSmearing) -> Result<Self, Self::Error> {
+        let smearing = match raw {
+            RawSmearing::Fixed => Smearing::Fixed,
+            RawSmearing::Gaussian { width } => Smearing::Gaussian { width },
+            RawSmearing::FermiDirac { width } => Smearing::FermiDirac { width },
+        };
+        smearing.validate()?;
+        Ok(smearing)
+    }
+}
+
+impl Smearing {
+    /// Erro se a largura não for positiva e finita (σ = 0 divide por zero nas ocupações).
+    pub fn validate(&self) -> Result<(), OccupationError> {
+        match *self {
+            Smearing::Gaussian { width } | Smearing::FermiDirac { width } if !(width.is_finite() && width > 0.0) => {
+                Err(OccupationError::InvalidSmearingWidth(width))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ocupação por spin (em [0, 1]) de um estado de energia `e` para o nível `e_fermi`.
+    pub fn occupation(&self, e: f64, e_fermi: f64) -> f64 {
+        let x = e - e_fermi;
+        match *self {
+            Smearing::Fixed => if x <= 0.0 { 1.0 } else { 0.0 },
+            Smearing::Gaussian { width } => 0.5 * (1.0 - erf(x / width)),
+            Smearing::FermiDirac { width } => {
+                let t = x / width;
+                // Evita overflow de exp() longe de E_F
+                if t > 40.0 { 0.0 } else if t < -40.0 { 1.0 } else { 1.0 / (1.0 + t.exp()) }
+            }
+        }
+    }
+}
+
+/// Quantas bandas calcular por ponto K, a partir do número de bandas ocupadas
+/// n_occ = ⌈N_elétrons / 2⌉. Usada igualmente por scf, bands e dos.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BandPolicy {
+    /// Exatamente `n` bandas (no mínimo n_occ).
+    Fixed(usize),
+    /// n_occ + `n` bandas vazias.
+    Extra(usize),
+    /// n_occ + ⌈f·n_occ⌉ (ao menos uma banda vazia).
+    ExtraFraction(f64),
+    /// n_occ + max(`min_extra`, ⌈0.2·n_occ⌉): folga para ocupações alargadas.
+    Metallic { min_extra: usize },
+}
+
+impl Default for BandPolicy {
+    /// n_occ + 4, o padrão histórico. Para metais grandes, `Metallic` dá mais folga.
+    fn default() -> Self {
+        BandPolicy::Extra(4)
+    }
+}
+
+impl BandPolicy {
+    pub fn n_bands(&self, n_electrons: f64) -> usize {
+        let n_occ = (n_electrons / 2.0).ceil() as usize;
+        let fraction = |f: f64| (f * n_occ as f64).ceil() as usize;
+        match *self {
+            BandPolicy::Fixed(n) => {
+                if n < n_occ {
+                    log::warn!("Política de bandas pede {} bandas, mas {} são ocupadas: usando {}", n, n_occ, n_occ);
+                }
+                n.max(n_occ)
+            }
+            BandPolicy::Extra(n) => n_occ + n,
+            BandPolicy::ExtraFraction(f) => n_occ + fraction(f).max(1),
+            BandPolicy::Metallic { min_extra } => n_occ + fraction(0.2).max(min_extra),
+        }
+    }
+}
+
+/// Pesos normalizados (Σ w = 1). Caminhos de bandas têm peso zero em todos os pontos;
+/// nesse caso os pontos entram com peso igual.
+fn normalized_weights(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    if total > 1e-12 {
+        weights.iter().map(|w| w / total).collect()
+    } else {
+        vec![1.0 / weights.len().max(1) as f64; weights.len()]
+    }
+}
+
+/// Nível de Fermi (Ry) para `n_electrons` sobre autovalores `eigenvalues[k][n]` (sem spin,
+/// 2 elétrons por banda).
+///
+/// Com `Fixed`, as bandas são preenchidas por Aufbau em cada ponto K e E_F fica no meio do
+/// gap (ou no topo da última banda, se ela estiver parcialmente ocupada). Com alargamento,
+/// E_F é obtido por bisseção de N(E_F) = Σ_k w_k Σ_n 2 f(ε_nk) = N_elétrons.
+pub fn fermi_level(eigenvalues: &[Vec<f64>], weights: &[f64], n_electrons: f64, smearing: Smearing) -> f64 {
+    let all = || eigenvalues.iter().flatten().copied();
+    let e_min = all().fold(f64::INFINITY, f64::min);
+    let e_max = all().fold(f64::NEG_INFINITY, f64::max);
+    if !e_min.is_finite() {
+        return 0.0;
+    }
+
+    if smearing == Smearing::Fixed {
+        let n_bands = eigenvalues.iter().map(Vec::len).min().unwrap_or(0);
+        let occ = aufbau(n_electrons, 1, n_bands).remove(0);
+        let homo = occ.iter().rposition(|&f| f > 0.0);
+        let partial = homo.is_some_and(|n| occ[n] < 2.0);
+        let band_max = |n: usize| eigenvalues.iter().map(|e| e[n]).fold(f64::NEG_INFINITY, f64::max);
+        let band_min = |n: usize| eigenvalues.iter().map(|e| e[n]).fold(f64::INFINITY, f64::min);
+        return match homo {
+            None => e_min,
+            Some(n) if partial || n + 1 >= n_bands => band_max(n),
+            Some(n) => 0.5 * (band_max(n) + band_min(n + 1)),
+        };
+    }
+
+    let weights = normalized_weights(weights);
+    let count = |e_f: f64| -> f64 {
+        eigenvalues.iter().zip(&weights)
+            .map(|(bands, w)| w * bands.iter().map(|&e| 2.0 * smearing.occupation(e, e_f)).sum::<f64>())
+            .sum()
+    };
+
+    let width = match smearing {
+        Smearing::Gaussian { width } | Smearing::FermiDirac { width } => width,
+        Smearing::Fixed => 0.0,
+    };
+    let (mut lo, mut hi) = (e_min - 10.0 * width - 1.0, e_max + 10.0 * width + 1.0);
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if count(mid) < n_electrons { lo = mid } else { hi = mid }
+        if hi - lo < 1e-12 {
+            break;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Ocupações por ponto K e banda (0 a 2, sem spin) e o nível de Fermi que as gerou.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occupations {
+    pub fermi_energy: f64,    // Ry
+    pub values: Vec<Vec<f64>>, // values[k][n]
+}
+
+impl Occupations {
+    /// Ocupações de autovalores `eigenvalues[k][n]` (Ry). Com `Fixed`, Aufbau em cada ponto K;
+    /// com alargamento, f_nk = 2 f((ε_nk - E_F)/σ), o que pode deixar bandas degeneradas
+    /// parcialmente ocupadas.
+    pub fn compute(eigenvalues: &[Vec<f64>], weights: &[f64], n_electrons: f64, smearing: Smearing) -> Self {
+        let fermi_energy = fermi_level(eigenvalues, weights, n_electrons, smearing);
+        let values = match smearing {
+            Smearing::Fixed => eigenvalues.iter()
+                .map(|bands| aufbau(n_electrons, 1, bands.len()).remove(0))
+                .collect(),
+            _ => eigenvalues.iter()
+                .map(|bands| bands.iter().map(|&e| 2.0 * smearing.occupation(e, fermi_energy)).collect())
+                .collect(),
+        };
+        Self { fermi_energy, values }
+    }
+
+    /// Maior ocupação da última banda calculada, entre os pontos K.
+    pub fn top_band_occupation(&self) -> f64 {
+        self.values.iter().filter_map(|bands| bands.last()).copied().fold(0.0, f64::max)
+    }
+
+    /// Avisa quando a banda mais alta tem ocupação acima de `tol`: faltam bandas vazias
+    /// e E_F (e as ocupações) dependem do corte em `n_bands`.
+    pub fn warn_if_too_few_bands(&self, tol: f64) -> bool {
+        let top = self.top_band_occupation();
+        if top > tol {
+            log::warn!("Banda mais alta ocupada (f = {:.4}): poucas bandas, aumente a política de bandas", top);
+        }
+        top > tol
+    }
+
+    /// Estados com ocupação fracionária (tol < f < 2 - tol), como (k, banda, f).
+    pub fn fractional(&self, tol: f64) -> Vec<(usize, usize, f64)> {
+        self.values.iter().enumerate()
+            .flat_map(|(ik, bands)| bands.iter().enumerate().map(move |(n, &f)| (ik, n, f)))
+            .filter(|&(_, _, f)| f > tol && f < 2.0 - tol)
+            .collect()
+    }
+}
@@ -0,0 +1,165 @@
+//! Ultrasoft/PAW: operador de overlap S e momentos das cargas de compensação.
+//!
+//! S entra no problema generalizado Hψ = εSψ do NSCF (`nscf::run_nscf_for`). A densidade
+//! aumentada (Σ ρ_ij Q_ij(r) no grid denso), os D_ij auto-consistentes e as energias
+//! on-site de Hartree e XC na malha radial ainda não existem: dependem do potencial de
+//! troca-correlação e do ciclo SCF com Hartree, que o Bravie ainda não tem.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use nalgebra::Vector3;
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::Structure;
+use crate::dft::solver::Overlap;
+use crate::io::upf::Pseudopotential;
+use crate::utils::radial::{beta_table, simpson, RadialTable, DEFAULT_DQ};
+use crate::utils::ylm::{real_ylm, LMAX};
+
+/// Multipolos das cargas de compensação de um par de projetores:
+/// Q_ij^L = ∫ Q_ij^L(r) r^L dr (Q_ij^L(r) já inclui r²). Retorna None se o par/L não existe.
+pub fn compensation_moment(pseudo: &Pseudopotential, i: usize, j: usize, l: usize) -> Option<f64> {
+    let aug = pseudo.augmentation.as_ref()?;
+    let (i, j) = if i <= j { (i, j) } else { (j, i) };
+    let f = aug.functions.iter()
+        .find(|f| f.i.min(f.j) == i && f.i.max(f.j) == j && f.l.is_none_or(|fl| fl == l))?;
+    let mesh = &pseudo.mesh;
+    let n = f.data.len().min(mesh.r.len());
+    let integrand: Vec<f64> = (0..n).map(|k| f.data[k] * mesh.r[k].powi(l as i32)).collect();
+    Some(simpson(&integrand, &mesh.rab[..n]))
+}
+
+/// Operador de overlap de ultrasoft/PAW em ondas planas:
+///
+/// S = 1 + Σ_I Σ_ij q_ij Σ_m |β_im^I⟩⟨β_jm^I|,   β_im^I(k+G) = β_i(|k+G|) Y_lm(k+G) e^{-i(k+G)·τ_I} / √Ω
+///
+/// Apenas pares com o mesmo l contribuem (q_ij é o momento L = 0 de Q_ij).
+/// As tabelas β_i(q) são calculadas uma vez por espécie e os projetores β_im^I(k+G)
+/// uma vez por base (ponto K), na primeira aplicação.
+pub struct AugmentationOverlap<'a> {
+    structure: &'a Structure,
+    pseudos: &'a HashMap<usize, Pseudopotential>,
+    tables: HashMap<usize, Vec<RadialTable>>,
+    cache: Mutex<HashMap<BasisKey, Arc<Vec<AtomProjectors>>>>,
+}
+
+/// Identifica uma base: ponto K (bits de cada componente) e número de ondas planas.
+type BasisKey = ([u64; 3], usize);
+
+/// Projetores de um átomo aumentado: (índice do β, m, β_im(k+G)).
+struct AtomProjectors {
+    atom: usize,
+    projectors: Vec<(usize, usize, Vec<Complex64>)>,
+}
+
+impl<'a> AugmentationOverlap<'a> {
+    /// `q_max` (Bohr⁻¹) deve cobrir √Ecut de todas as bases.
+    pub fn new(structure: &'a Structure, pseudos: &'a HashMap<usize, Pseudopotential>, q_max: f64) -> Self {
+        let tables = pseudos.iter()
+            .filter(|(_, pp)| pp.augmentation.is_some())
+            .map(|(&id, pp)| {
+                for (ib, beta) in pp.nonlocal.iter().enumerate() {
+                    if beta.angular_momentum.max(0) as usize > LMAX {
+                        log::warn!("{}: projetor β_{} com l = {} > {} ignorado no overlap S",
+                            pp.header.element, ib + 1, beta.angular_momentum, LMAX);
+                    }
+                }
+                let t = (0..pp.nonlocal.len()).map(|i| beta_table(pp, i, q_max, DEFAULT_DQ)).collect();
+                (id, t)
+            })
+            .collect();
+        Self { structure, pseudos, tables, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Overlap S para bases de corte `ecut` (Ry), ou `None` se nenhum pseudo tem aumento
+    /// (norma-conservantes: S = 1).
+    pub fn for_pseudos(structure: &'a Structure, pseudos: &'a HashMap<usize, Pseudopotential>, ecut: f64) -> Option<Self> {
+        let augmented = pseudos.values().any(|pp| pp.augmentation.is_some());
+        // |k+G| ≤ √Ecut, com folga para k fora da primeira zona
+        augmented.then(|| Self::new(structure, pseudos, 1.5 * ecut.sqrt()))
+    }
+
+    /// Projetores de todos os átomos aumentados para `basis`, calculados na primeira chamada.
+    fn projectors_for(&self, basis: &PlaneWaveBasis) -> Arc<Vec<AtomProjectors>> {
+        let key = (basis.k_point.map(f64::to_bits).into(), basis.g_vectors.len());
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry(key)
+            .or_insert_with(|| {
+                let atoms = self.structure.atoms.iter().enumerate()
+                    .filter_map(|(ia, atom)| {
+                        let pp = self.pseudos.get(&atom.species_id)?;
+                        let tables = self.tables.get(&atom.species_id)?;
+                        Some(AtomProjectors { atom: ia, projectors: self.projectors(basis, pp, tables, &atom.position) })
+                    })
+                    .collect();
+                Arc::new(atoms)
+            })
+            .clone()
+    }
+
+    /// Projetores β_im(k+G) de um átomo (uma entrada por (i, m), na ordem de `nonlocal`;
+    /// β com l > `ylm::LMAX` ficam de fora).
+    fn projectors(&self, basis: &PlaneWaveBasis, pp: &Pseudopotential, tables: &[RadialTable], tau: &Vector3<f64>)
+        -> Vec<(usize, usize, Vec<Complex64>)>
+    {
+        let recip = self.structure.lattice.reciprocal();
+        let inv_sqrt_vol = 1.0 / self.structure.lattice.volume().sqrt();
+        let kg: Vec<Vector3<f64>> = basis.g_vectors.iter()
+            .map(|&(i, j, k)| recip * (basis.k_point + Vector3::new(i as f64, j as f64, k as f64)))
+            .collect();
+
+        let mut out = Vec::new();
+        for (ib, beta) in pp.nonlocal.iter().enumerate() {
+            let l = beta.angular_momentum.max(0) as usize;
+            let Some(ylm) = kg.iter().map(|q| real_ylm(l, q)).collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+            let radial = basis.shells.expand(&basis.shells.map(|q| tables[ib].interpolate(q) * inv_sqrt_vol));
+            for m in 0..2 * l + 1 {
+                let p = kg.iter().zip(&radial).zip(&ylm)
+                    .map(|((q, r), y)| Complex64::from_polar(r * y[m], -q.dot(tau)))
+                    .collect();
+                out.push((ib, m, p));
+            }
+        }
+        out
+    }
+}
+
+impl Overlap for AugmentationOverlap<'_> {
+    fn apply(&self, basis: &PlaneWaveBasis, psi: &Array1<Complex64>) -> Array1<Complex64> {
+        let mut result = psi.clone();
+        for entry in self.projectors_for(basis).iter() {
+            let atom = &self.structure.atoms[entry.atom];
+            let Some(pp) = self.pseudos.get(&atom.species_id) else {
+                continue;
+            };
+            let Some(aug) = &pp.augmentation else {
+                continue;
+            };
+
+            let proj = &entry.projectors;
+            // ⟨β_jm|ψ⟩
+            let overlaps: Vec<Complex64> = proj.iter()
+                .map(|(_, _, p)| p.iter().zip(psi.iter()).map(|(b, c)| b.conj() * c).sum())
+                .collect();
+
+            for (i, m_i, p_i) in proj.iter() {
+                let l_i = pp.nonlocal[*i].angular_momentum;
+                let coeff: Complex64 = proj.iter().enumerate()
+                    .filter(|(_, (j, m_j, _))| m_j == m_i && pp.nonlocal[*j].angular_momentum == l_i)
+                    .map(|(b, (j, _, _))| aug.q[[*i, *j]] * overlaps[b])
+                    .sum();
+                if coeff.norm_sqr() == 0.0 {
+                    continue;
+                }
+                for (r, p) in result.iter_mut().zip(p_i) {
+                    *r += coeff * p;
+                }
+            }
+        }
+        result
+    }
+}
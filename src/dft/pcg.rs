@@ -0,0 +1,199 @@
+use nalgebra::DMatrix;
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use crate::dft::error::DftError;
+use crate::dft::hamiltonian::HamiltonianOperator;
+use crate::dft::preconditioner::precondition_residual;
+use crate::dft::solver::{rayleigh_ritz, BandSolverResult, Overlap, SolverError};
+use crate::utils::timer;
+
+/// Critérios do gradiente conjugado pré-condicionado.
+#[derive(Debug, Clone, Copy)]
+pub struct PcgParameters {
+    pub tolerance: f64,        // ||Hψ - εSψ|| máximo por banda (Ry)
+    pub max_sweeps: usize,     // Varreduras por todas as bandas (com Rayleigh–Ritz no fim de cada)
+    pub steps_per_band: usize, // Passos de CG por banda em cada varredura
+}
+
+impl Default for PcgParameters {
+    fn default() -> Self {
+        Self { tolerance: 1e-6, max_sweeps: 100, steps_per_band: 4 }
+    }
+}
+
+/// Autobandas por gradiente conjugado pré-condicionado, banda a banda (Payne et al.):
+///
+/// 1. r = Hψ_n - ε_n Sψ_n, pré-condicionado com Teter-Payne-Allan e projetado fora de
+///    ψ_0..ψ_n (S-ortogonal);
+/// 2. direção conjugada d = Kr + (γ/γ_anterior) d_anterior, γ = Re⟨Kr|r⟩;
+/// 3. ψ_n ← cos θ ψ_n + sin θ φ (φ = d normalizado), com θ que minimiza ⟨ψ|H|ψ⟩
+///    exatamente (Rayleigh–Ritz 2x2; Hψ é atualizado sem nova aplicação de H).
+///
+/// Cada varredura termina com uma rotação de Rayleigh–Ritz no subespaço das bandas, que
+/// ordena os autovalores e desfaz misturas entre bandas quase degeneradas. `initial`
+/// (ex: `Simulation::initial_wavefunctions`) define o número de bandas e não precisa ser
+/// ortonormal. Sem convergência em `max_sweeps`, devolve as bandas com aviso: confira
+/// `BandSolverResult::is_reliable`.
+/// *Ref: Payne, M. C., et al. (1992). Rev. Mod. Phys., 64(4), 1045.*
+pub fn solve_bands_pcg(
+    h: &mut dyn HamiltonianOperator,
+    initial: Vec<Array1<Complex64>>,
+    params: &PcgParameters,
+    overlap: Option<&dyn Overlap>,
+) -> Result<BandSolverResult, SolverError> {
+    let _t = timer::scope("pcg");
+    let npw = h.basis().g_vectors.len();
+    let n_bands = initial.len();
+    if n_bands == 0 || n_bands > npw {
+        return Err(SolverError::InvalidBandCount(n_bands, npw));
+    }
+    if let Some(band) = initial.iter().find(|b| b.len() != npw) {
+        return Err(DftError::SizeMismatch("banda inicial", band.len(), npw).into());
+    }
+    let g_norm_sq = h.basis().g_norm_sq.clone();
+
+    let mut work = Array1::<Complex64>::zeros(npw);
+
+    let mut bands = initial;
+    let mut h_bands = bands.iter().map(|psi| apply_h(h, psi, &mut work)).collect::<Result<Vec<_>, _>>()?;
+    let mut s_bands: Vec<Array1<Complex64>> = bands.iter().map(|psi| apply_s(h, overlap, psi)).collect();
+    let mut eigenvalues = subspace_rotation(&mut bands, &mut h_bands, &mut s_bands)?;
+    let mut residual_norms = residuals(&eigenvalues, &h_bands, &s_bands);
+
+    let mut sweeps = 0;
+    while sweeps < params.max_sweeps && residual_norms.iter().any(|&r| r > params.tolerance) {
+        sweeps += 1;
+        for n in 0..n_bands {
+            let mut eps = eigenvalues[n];
+            let mut direction: Option<(Array1<Complex64>, f64)> = None;
+
+            for _ in 0..params.steps_per_band {
+                let mut residual = &h_bands[n] - &(&s_bands[n] * eps);
+                if norm(&residual) <= params.tolerance {
+                    break;
+                }
+
+                // Gradiente pré-condicionado, S-ortogonal às bandas de baixo e a ψ_n
+                precondition_residual(&mut residual, &bands[n], &g_norm_sq);
+                let mut gradient = residual;
+                for (phi, s_phi) in bands.iter().zip(&s_bands).take(n + 1) {
+                    let proj = inner(s_phi, &gradient);
+                    gradient.zip_mut_with(phi, |g, &f| *g -= proj * f);
+                }
+                let r = &h_bands[n] - &(&s_bands[n] * eps);
+                let gamma = inner(&gradient, &r).re;
+
+                let mut d = match &direction {
+                    Some((previous, gamma_previous)) if *gamma_previous > 0.0 => &gradient + &(previous * (gamma / gamma_previous)),
+                    _ => gradient,
+                };
+                // A direção anterior não é ortogonal ao ψ_n atualizado
+                let proj = inner(&s_bands[n], &d);
+                d.zip_mut_with(&bands[n], |x, &p| *x -= proj * p);
+
+                let s_d = apply_s(h, overlap, &d);
+                let d_norm = inner(&d, &s_d).re.sqrt();
+                if !d_norm.is_finite() || d_norm <= 1e-14 {
+                    break;
+                }
+                let phi = d.mapv(|c| c / d_norm);
+                let s_phi = s_d.mapv(|c| c / d_norm);
+                let h_phi = apply_h(h, &phi, &mut work)?;
+                direction = Some((d, gamma));
+
+                // E(θ) = (ε+c)/2 + (ε-c)/2 cos 2θ + b sin 2θ, mínimo em 2θ = atan2(-2b, c-ε)
+                let b = inner(&bands[n], &h_phi).re;
+                let c = inner(&phi, &h_phi).re;
+                let theta = 0.5 * (-2.0 * b).atan2(c - eps);
+                let (sin, cos) = theta.sin_cos();
+                bands[n] = &bands[n] * cos + &(&phi * sin);
+                h_bands[n] = &h_bands[n] * cos + &(&h_phi * sin);
+                s_bands[n] = &s_bands[n] * cos + &(&s_phi * sin);
+                eps = inner(&bands[n], &h_bands[n]).re / inner(&bands[n], &s_bands[n]).re;
+            }
+            eigenvalues[n] = eps;
+        }
+
+        eigenvalues = subspace_rotation(&mut bands, &mut h_bands, &mut s_bands)?;
+        residual_norms = residuals(&eigenvalues, &h_bands, &s_bands);
+        log::debug!("PCG varredura {}: resíduo máximo {:.3e} Ry", sweeps, residual_norms.iter().copied().fold(0.0, f64::max));
+    }
+
+    // Resíduos finais com H aplicado de novo (Hψ acumulado nas rotações perde precisão)
+    for (hb, psi) in h_bands.iter_mut().zip(&bands) {
+        *hb = apply_h(h, psi, &mut work)?;
+    }
+    let residual_norms = residuals(&eigenvalues, &h_bands, &s_bands);
+    let max_residual = residual_norms.iter().copied().fold(0.0, f64::max);
+    if max_residual > params.tolerance {
+        log::warn!("PCG sem convergência em {} varreduras: resíduo máximo {:.3e} Ry (tolerância {:.1e})",
+            sweeps, max_residual, params.tolerance);
+    }
+
+    Ok(BandSolverResult { eigenvalues, eigenvectors: bands, residual_norms, iterations: sweeps })
+}
+
+fn apply_h(
+    h: &mut dyn HamiltonianOperator,
+    psi: &Array1<Complex64>,
+    work: &mut Array1<Complex64>,
+) -> Result<Array1<Complex64>, SolverError> {
+    let mut out = Array1::<Complex64>::zeros(psi.len());
+    h.apply_into(psi, &mut out, work)?;
+    Ok(out)
+}
+
+/// Sψ (ψ se `overlap` for `None`).
+fn apply_s(h: &dyn HamiltonianOperator, overlap: Option<&dyn Overlap>, psi: &Array1<Complex64>) -> Array1<Complex64> {
+    match overlap {
+        Some(op) => op.apply(h.basis(), psi),
+        None => psi.clone(),
+    }
+}
+
+/// ⟨a|b⟩.
+fn inner(a: &Array1<Complex64>, b: &Array1<Complex64>) -> Complex64 {
+    a.iter().zip(b).map(|(x, y)| x.conj() * y).sum()
+}
+
+fn norm(a: &Array1<Complex64>) -> f64 {
+    a.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt()
+}
+
+fn residuals(eigenvalues: &[f64], h_bands: &[Array1<Complex64>], s_bands: &[Array1<Complex64>]) -> Vec<f64> {
+    eigenvalues.iter().zip(h_bands).zip(s_bands)
+        .map(|((&e, hb), sb)| hb.iter().zip(sb).map(|(h, s)| (h - s * e).norm_sqr()).sum::<f64>().sqrt())
+        .collect()
+}
+
+/// Rayleigh–Ritz no subespaço {ψ_n}: diagonaliza ⟨ψ_m|H|ψ_n⟩ com overlap ⟨ψ_m|S|ψ_n⟩ e
+/// gira ψ, Hψ e Sψ para os autovetores (S-ortonormais, autovalores crescentes).
+fn subspace_rotation(
+    bands: &mut [Array1<Complex64>],
+    h_bands: &mut [Array1<Complex64>],
+    s_bands: &mut [Array1<Complex64>],
+) -> Result<Vec<f64>, SolverError> {
+    let n = bands.len();
+    let h_sub = DMatrix::from_fn(n, n, |i, j| inner(&bands[i], &h_bands[j]));
+    let h_sub = (&h_sub + h_sub.adjoint()).map(|z| z * 0.5);
+    let s_sub = DMatrix::from_fn(n, n, |i, j| inner(&bands[i], &s_bands[j]));
+    let s_sub = (&s_sub + s_sub.adjoint()).map(|z| z * 0.5);
+    let (eigenvalues, c) = rayleigh_ritz(h_sub, Some(s_sub), n)?;
+
+    for set in [bands, h_bands, s_bands] {
+        let rotated: Vec<Array1<Complex64>> = (0..n)
+            .map(|j| {
+                let mut out = Array1::<Complex64>::zeros(set[0].len());
+                for (i, v) in set.iter().enumerate() {
+                    out.scaled_add(c[(i, j)], v);
+                }
+                out
+            })
+            .collect();
+        for (v, r) in set.iter_mut().zip(rotated) {
+            *v = r;
+        }
+    }
+    Ok(eigenvalues)
+}
@@ -0,0 +1,46 @@
+use ndarray::Array1;
+use num_complex::Complex64;
+
+/// Energia cinética de uma banda: E_kin = sum_G |k+G|^2 |c_G|^2 / sum_G |c_G|^2 (Ry).
+/// É a escala de energia usada pelo pré-condicionador de Teter-Payne-Allan.
+pub fn band_kinetic_energy(psi: &Array1<Complex64>, g_norm_sq: &[f64]) -> f64 {
+    let mut ekin = 0.0;
+    let mut norm = 0.0;
+    for (c, &g2) in psi.iter().zip(g_norm_sq) {
+        let w = c.norm_sqr();
+        ekin += g2 * w;
+        norm += w;
+    }
+
+    if norm > 0.0 { ekin / norm } else { 0.0 }
+}
+
+/// Pré-condicionador de Teter-Payne-Allan (TPA) para uma banda.
+///
+/// K(x) = (27 + 18x + 12x^2 + 8x^3) / (27 + 18x + 12x^2 + 8x^3 + 16x^4),  x = |k+G|^2 / E_kin
+///
+/// Para G pequeno K -> 1 (sem alteração); para G grande K -> 1/(2x), amortecendo os
+/// componentes de alta energia cinética que dominam o resíduo.
+/// *Ref: Teter, M. P., Payne, M. C., & Allan, D. C. (1989). Physical Review B, 40(18), 12255.*
+pub fn teter_payne_allan(g_norm_sq: &[f64], ekin_band: f64) -> Array1<f64> {
+    // Evita divisão por zero para bandas constantes (ex: k = Gamma, G = 0)
+    let ekin = ekin_band.max(1e-8);
+
+    g_norm_sq.iter()
+        .map(|&g2| {
+            let x = g2 / ekin;
+            let num = 27.0 + x * (18.0 + x * (12.0 + 8.0 * x));
+            num / (num + 16.0 * x.powi(4))
+        })
+        .collect()
+}
+
+/// Aplica o pré-condicionador TPA ao resíduo R = (H - ε)ψ da banda `psi`, in-place.
+pub fn precondition_residual(residual: &mut Array1<Complex64>, psi: &Array1<Complex64>, g_norm_sq: &[f64]) {
+    let ekin = band_kinetic_energy(psi, g_norm_sq);
+    let k = teter_payne_allan(g_norm_sq, ekin);
+
+    residual.iter_mut()
+        .zip(k.iter())
+        .for_each(|(r, &kg)| *r *= kg);
+}
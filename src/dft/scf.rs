@@ -0,0 +1,436 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use ndarray::Array3;
+use serde::Serialize;
+
+use crate::core::fft::FftGrid;
+use crate::core::field::{DensityField, PotentialField};
+use crate::core::memory::DEFAULT_MIXING_HISTORY;
+use crate::core::structure::Lattice;
+use crate::dft::error::DftError;
+use crate::dft::mixing::{AndersonMixer, MixingAction, SloshingDetector, DEFAULT_KERKER_Q0};
+use crate::dft::occupations::Occupations;
+use crate::io::checkpoint::Checkpoint;
+use crate::utils::parallel::{self, ParallelConfig};
+use crate::utils::timer;
+
+/// Parâmetros do ciclo auto-consistente.
+#[derive(Debug, Clone)]
+pub struct ScfParameters {
+    pub max_iterations: usize,
+    pub energy_tolerance: f64,  // |ΔE| entre iterações (Ry)
+    pub density_tolerance: f64, // ∫|ρ_out - ρ_in| dr por elétron
+    pub mixing_beta: f64,
+    pub mixing_history: usize,  // Pares guardados pelo Anderson (ver `mixing::AndersonMixer`)
+    pub sloshing_window: usize, // Iterações analisadas pelo detector de sloshing (0 desliga)
+    pub min_mixing_beta: f64,   // Piso de β nas correções automáticas
+    pub kerker_q0: f64,         // q0 do Kerker ativado pelo detector (Bohr⁻¹)
+    pub solver_tol_max: f64,    // Tolerância relativa do eigensolver na primeira iteração
+    pub solver_tol_min: f64,    // Piso da tolerância do eigensolver perto da convergência
+    pub iteration_observer: Option<IterationObserver>, // Ver `on_iteration`
+    pub cancel: Option<Arc<AtomicBool>>,               // Ver `cancel_token`
+    pub checkpoint: Option<ScfCheckpoint>,             // Gravado se o SCF for cancelado
+    pub parallel: Option<ParallelConfig>,              // Ver `parallel`
+}
+
+/// Onde e com quais cortes gravar o checkpoint de um SCF cancelado.
+#[derive(Debug, Clone)]
+pub struct ScfCheckpoint {
+    pub path: PathBuf,
+    pub ecut: f64,
+    pub ecut_rho: f64,
+}
+
+/// Função chamada por `run_scf_loop` a cada iteração registrada.
+pub type IterationCallback = Box<dyn FnMut(&ScfIteration) + Send>;
+
+/// Observador das iterações do SCF, compartilhado entre cópias de `ScfParameters`.
+#[derive(Clone)]
+pub struct IterationObserver(Arc<Mutex<IterationCallback>>);
+
+impl IterationObserver {
+    pub fn new(callback: IterationCallback) -> Self {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+
+    pub fn notify(&self, iteration: &ScfIteration) {
+        let mut callback = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        callback(iteration);
+    }
+}
+
+impl fmt::Debug for IterationObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IterationObserver")
+    }
+}
+
+impl Default for ScfParameters {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            energy_tolerance: 1e-8,
+            density_tolerance: 1e-6,
+            mixing_beta: 0.3,
+            mixing_history: DEFAULT_MIXING_HISTORY,
+            sloshing_window: 6,
+            min_mixing_beta: 0.02,
+            kerker_q0: DEFAULT_KERKER_Q0,
+            solver_tol_max: 1e-2,
+            solver_tol_min: 1e-10,
+            iteration_observer: None,
+            cancel: None,
+            checkpoint: None,
+            parallel: None,
+        }
+    }
+}
+
+impl ScfParameters {
+    /// Mixer de Anderson com β e histórico destes parâmetros.
+    pub fn mixer(&self) -> AndersonMixer {
+        AndersonMixer::new(self.mixing_beta, self.mixing_history)
+    }
+
+    /// Faixa da tolerância adaptativa do eigensolver.
+    pub fn solver_tolerance(mut self, min: f64, max: f64) -> Self {
+        self.solver_tol_min = min;
+        self.solver_tol_max = max;
+        self
+    }
+
+    /// Chama `callback` a cada iteração do SCF (energia, dρ, E_F, tempo), para interfaces
+    /// gráficas, notebooks ou gráficos de convergência acompanharem o ciclo sem ler o log.
+    pub fn on_iteration(mut self, callback: IterationCallback) -> Self {
+        self.iteration_observer = Some(IterationObserver::new(callback));
+        self
+    }
+
+    /// Cancelamento cooperativo: quando `token` vira `true` (ex: num handler de Ctrl-C ou
+    /// num botão da interface), `run_scf_loop` termina a iteração corrente, grava o
+    /// checkpoint (se configurado) e retorna com `cancelled = true`.
+    pub fn cancel_token(mut self, token: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Threads e modo determinístico aplicados no início de `run_scf_loop` (ver
+    /// `parallel::configure`). O pool global do Rayon só é criado uma vez por processo:
+    /// se já existir, o número de threads fica como está (com aviso no log).
+    pub fn parallel(mut self, config: ParallelConfig) -> Self {
+        self.parallel = Some(config);
+        self
+    }
+
+    /// Checkpoint gravado em `path` se o SCF for cancelado.
+    pub fn checkpoint_on_cancel(mut self, path: impl Into<PathBuf>, ecut: f64, ecut_rho: f64) -> Self {
+        self.checkpoint = Some(ScfCheckpoint { path: path.into(), ecut, ecut_rho });
+        self
+    }
+}
+
+/// Tolerância adaptativa do eigensolver ao longo do SCF.
+///
+/// Nas primeiras iterações ρ ainda está longe do ponto fixo e diagonalizar com precisão
+/// é desperdício; a tolerância acompanha o resíduo de densidade:
+///
+/// tol_{i+1} = clamp(0.1 · dρ_i / N_elétrons, tol_min, tol_i)
+///
+/// Nunca afrouxa entre iterações (dρ oscilante não desfaz a precisão já paga).
+/// Mesma estratégia do pw.x (`ethr`), que costuma reduzir pela metade as aplicações de H·ψ.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveTolerance {
+    current: f64,
+    min: f64,
+    n_electrons: f64,
+}
+
+impl AdaptiveTolerance {
+    pub fn new(params: &ScfParameters, n_electrons: f64) -> Self {
+        // f64::min/max ignoram um NaN; limites trocados são reordenados
+        let (lo, hi) = (params.solver_tol_min, params.solver_tol_max);
+        Self {
+            current: hi.max(lo),
+            min: lo.min(hi),
+            n_electrons: n_electrons.max(1.0),
+        }
+    }
+
+    /// Tolerância para a próxima diagonalização.
+    pub fn current(&self) -> f64 {
+        self.current
+    }
+
+    /// Atualiza com o resíduo de densidade da iteração que terminou. Um dρ não finito
+    /// mantém a tolerância atual.
+    pub fn update(&mut self, density_residual: f64) -> f64 {
+        let target = 0.1 * density_residual / self.n_electrons;
+        if target.is_finite() {
+            // min/max em vez de `clamp`, que entra em pânico com limites NaN
+            self.current = target.min(self.current).max(self.min);
+        }
+        self.current
+    }
+
+    /// O eigensolver convergiu com folga suficiente para o dρ atual? Se não (ex: dρ caiu
+    /// muito numa única iteração), a diagonalização deve ser refeita com `current()`.
+    pub fn needs_rediagonalization(&self, used_tolerance: f64) -> bool {
+        used_tolerance > self.current * 10.0
+    }
+}
+
+/// Uma iteração do SCF.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScfIteration {
+    pub iteration: usize,
+    pub energy: f64,                    // Energia total (Ry)
+    pub delta_energy: f64,              // E_i - E_{i-1} (Ry); NaN na primeira
+    pub density_residual: f64,          // ∫|ρ_out - ρ_in| dr
+    pub fermi_energy: Option<f64>,      // Ry
+    pub solver_tolerance: f64,          // Tolerância usada pelo eigensolver
+    pub time: f64,                      // Duração da iteração (s)
+}
+
+/// Histórico de convergência do SCF. `label` identifica a configuração (ex: "pulay β=0.3")
+/// para comparar misturadores e eigensolvers lado a lado no mesmo CSV.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScfHistory {
+    pub label: String,
+    pub iterations: Vec<ScfIteration>,
+    #[serde(skip)]
+    last: Option<Instant>,
+}
+
+impl ScfHistory {
+    /// Começa a contar o tempo da primeira iteração.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self { label: label.into(), iterations: Vec::new(), last: timer::now() }
+    }
+
+    /// Registra a iteração que terminou; ΔE e o tempo são relativos à anterior.
+    pub fn record(&mut self, energy: f64, density_residual: f64, fermi_energy: Option<f64>, solver_tolerance: f64) -> &ScfIteration {
+        let now = timer::now();
+        let time = match (self.last, now) {
+            (Some(last), Some(now)) => now.duration_since(last).as_secs_f64(),
+            _ => 0.0,
+        };
+        self.last = now;
+        let delta_energy = self.iterations.last().map_or(f64::NAN, |prev| energy - prev.energy);
+        self.iterations.push(ScfIteration {
+            iteration: self.iterations.len() + 1,
+            energy,
+            delta_energy,
+            density_residual,
+            fermi_energy,
+            solver_tolerance,
+            time,
+        });
+        let it = &self.iterations[self.iterations.len() - 1];
+        log::info!("SCF {:3}: E = {:.10} Ry | dE = {:.3e} | dρ = {:.3e} | {:.2} s",
+            it.iteration, it.energy, it.delta_energy, it.density_residual, it.time);
+        it
+    }
+
+    pub fn len(&self) -> usize {
+        self.iterations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iterations.is_empty()
+    }
+
+    /// A última iteração satisfaz os critérios de `params`?
+    pub fn converged(&self, params: &ScfParameters) -> bool {
+        self.iterations.last().is_some_and(|it| {
+            it.delta_energy.abs() < params.energy_tolerance && it.density_residual < params.density_tolerance
+        })
+    }
+
+    /// Tempo total (s).
+    pub fn total_time(&self) -> f64 {
+        self.iterations.iter().map(|it| it.time).sum()
+    }
+
+    pub fn to_csv(&self) -> String {
+        to_csv(std::slice::from_ref(self))
+    }
+
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
+
+/// CSV de um ou mais históricos (uma linha por iteração, coluna `label` para separar as
+/// execuções). Campos ausentes (ΔE da primeira iteração, E_F) ficam vazios.
+pub fn to_csv(histories: &[ScfHistory]) -> String {
+    let mut out = String::from("label,iteration,energy_ry,delta_energy_ry,density_residual,fermi_energy_ry,solver_tolerance,time_s\n");
+    let optional = |x: Option<f64>| x.filter(|v| v.is_finite()).map_or(String::new(), |v| format!("{:.6e}", v));
+    for history in histories {
+        for it in &history.iterations {
+            out.push_str(&format!("{},{},{:.10},{},{:.6e},{},{:.3e},{:.4}\n",
+                history.label,
+                it.iteration,
+                it.energy,
+                optional(Some(it.delta_energy)),
+                it.density_residual,
+                optional(it.fermi_energy),
+                it.solver_tolerance,
+                it.time,
+            ));
+        }
+    }
+    out
+}
+
+pub fn write_csv<P: AsRef<Path>>(histories: &[ScfHistory], path: P) -> io::Result<()> {
+    fs::write(path, to_csv(histories))
+}
+
+/// Saída de um passo de Kohn-Sham ρ_in -> ρ_out.
+#[derive(Debug, Clone)]
+pub struct ScfStep {
+    pub rho_out: Array3<f64>,
+    pub energy: f64,               // Ry
+    pub fermi_energy: Option<f64>, // Ry
+    pub occupations: Option<Occupations>, // f_nk do passo (com alargamento, fracionárias)
+    pub v_eff: Option<Array3<f64>>, // V_eff do passo (Ry), para o checkpoint de cancelamento
+}
+
+/// Resultado de `run_scf_loop`.
+#[derive(Debug, Clone)]
+pub struct ScfOutcome {
+    pub rho: Array3<f64>, // ρ_out da última iteração
+    pub history: ScfHistory,
+    pub converged: bool,
+    pub cancelled: bool,  // Interrompido por `ScfParameters::cancel_token`
+    pub fermi_energy: Option<f64>,        // Ry, da última iteração
+    pub occupations: Option<Occupations>, // f_nk da última iteração (ver `RunResults::set_occupations`)
+    /// Correções automáticas do mixing, com a iteração em que foram aplicadas.
+    pub mixing_actions: Vec<(usize, MixingAction)>,
+}
+
+/// Ciclo auto-consistente sobre o passo de Kohn-Sham `step(ρ_in, tolerância, fft)`, que
+/// diagonaliza com a tolerância dada e devolve ρ_out e a energia.
+///
+/// A densidade é misturada com Anderson (`params.mixer()`, Kerker no grid `fft` quando
+/// ativado). Se dρ crescer ou oscilar ao longo de `params.sloshing_window` iterações,
+/// `SloshingDetector` corrige o mixer (β menor, Kerker ou histórico limpo) e a ação é
+/// registrada no log e em `ScfOutcome::mixing_actions`, em vez de o ciclo divergir em
+/// silêncio até `max_iterations`.
+///
+/// Com `params.cancel`, o token é verificado ao fim de cada iteração; se cancelado, a
+/// densidade já misturada (entrada da próxima iteração) e o último V_eff vão para
+/// `params.checkpoint` e o ciclo retorna sem convergir.
+pub fn run_scf_loop<F>(
+    params: &ScfParameters,
+    label: &str,
+    lattice: &Lattice,
+    fft: &mut FftGrid,
+    n_electrons: f64,
+    rho: Array3<f64>,
+    mut step: F,
+) -> Result<ScfOutcome, DftError>
+where
+    F: FnMut(&Array3<f64>, f64, &mut FftGrid) -> Result<ScfStep, DftError>,
+{
+    if let Some(config) = &params.parallel {
+        if let Err(e) = parallel::configure(config) {
+            log::warn!("{}; mantendo {} threads", e, parallel::num_threads());
+        }
+    }
+    let mut mixer = params.mixer();
+    let mut tolerance = AdaptiveTolerance::new(params, n_electrons);
+    let mut detector = (params.sloshing_window > 0)
+        .then(|| SloshingDetector::new(params.sloshing_window, params.min_mixing_beta, params.kerker_q0));
+    let mut history = ScfHistory::new(label);
+    let mut mixing_actions = Vec::new();
+    let recip = lattice.reciprocal();
+    let d_volume = lattice.dvol(fft.size.iter().product());
+
+    let mut rho_in = rho;
+    let mut rho_out = rho_in.clone();
+    let mut v_eff = None;
+    let mut fermi_energy = None;
+    let mut occupations = None;
+    for _ in 0..params.max_iterations {
+        let mut solver_tolerance = tolerance.current();
+        let mut out = step(&rho_in, solver_tolerance, fft)?;
+        // ∫|ρ_out - ρ_in| dr
+        let residual = |out: &ScfStep| {
+            let diff: Vec<f64> = out.rho_out.iter().zip(&rho_in).map(|(o, i)| (o - i).abs()).collect();
+            parallel::sum(&diff) * d_volume
+        };
+        let mut density_residual = residual(&out);
+        tolerance.update(density_residual);
+        // dρ caiu mais rápido que a tolerância: a diagonalização frouxa contamina ρ_out
+        if tolerance.needs_rediagonalization(solver_tolerance) {
+            log::debug!(
+                "SCF {}: rediagonalizando com tolerância {:.1e} (usada {:.1e})",
+                history.iterations.len() + 1, tolerance.current(), solver_tolerance
+            );
+            solver_tolerance = tolerance.current();
+            out = step(&rho_in, solver_tolerance, fft)?;
+            density_residual = residual(&out);
+            tolerance.update(density_residual);
+        }
+        let step_fermi = out.fermi_energy.or(out.occupations.as_ref().map(|o| o.fermi_energy));
+        let record = history.record(out.energy, density_residual, step_fermi, solver_tolerance);
+        if let Some(observer) = &params.iteration_observer {
+            observer.notify(record);
+        }
+        let iteration = record.iteration;
+        rho_out = out.rho_out;
+        v_eff = out.v_eff.or(v_eff);
+        fermi_energy = step_fermi;
+        occupations = out.occupations;
+
+        if history.converged(params) {
+            return Ok(ScfOutcome { rho: rho_out, history, converged: true, cancelled: false, fermi_energy, occupations, mixing_actions });
+        }
+
+        if let Some(detector) = detector.as_mut() {
+            if let Some(kind) = detector.observe(density_residual) {
+                let action = detector.respond(&mut mixer, &recip, fft.size);
+                log::warn!("SCF {}: {} nas últimas {} iterações; {}", iteration, kind, detector.window, action);
+                mixing_actions.push((iteration, action));
+            }
+        }
+        rho_in = mixer.mix_preconditioned(&rho_in, &rho_out, fft)?;
+
+        if params.cancel.as_ref().is_some_and(|token| token.load(Ordering::Relaxed)) {
+            log::warn!("SCF cancelado após a iteração {}", iteration);
+            if let Some(checkpoint) = &params.checkpoint {
+                write_cancel_checkpoint(checkpoint, lattice, &rho_in, v_eff.take());
+            }
+            return Ok(ScfOutcome { rho: rho_out, history, converged: false, cancelled: true, fermi_energy, occupations, mixing_actions });
+        }
+    }
+
+    log::warn!("SCF não convergiu em {} iterações ({} correções de mixing)", params.max_iterations, mixing_actions.len());
+    Ok(ScfOutcome { rho: rho_out, history, converged: false, cancelled: false, fermi_energy, occupations, mixing_actions })
+}
+
+/// Grava ρ e V_eff de um SCF cancelado. Falhas só vão para o log: o resultado do ciclo
+/// ainda é devolvido ao chamador.
+fn write_cancel_checkpoint(checkpoint: &ScfCheckpoint, lattice: &Lattice, rho: &Array3<f64>, v_eff: Option<Array3<f64>>) {
+    let Some(v_eff) = v_eff else {
+        log::error!("Checkpoint não gravado: o passo do SCF não informou V_eff");
+        return;
+    };
+    let result = Checkpoint::new(
+        checkpoint.ecut,
+        checkpoint.ecut_rho,
+        DensityField::new(lattice.clone(), rho.clone()),
+        PotentialField::new(lattice.clone(), v_eff),
+    )
+    .and_then(|ckpt| ckpt.write(&checkpoint.path));
+    match result {
+        Ok(()) => log::info!("Checkpoint escrito em {}", checkpoint.path.display()),
+        Err(e) => log::error!("Checkpoint não gravado em {}: {}", checkpoint.path.display(), e),
+    }
+}
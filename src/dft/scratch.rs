@@ -0,0 +1,328 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use ndarray::Array1;
+use num_complex::Complex64;
+use serde::Deserialize;
+
+use crate::dft::wavefunctions::{Precision, Wavefunctions};
+
+/// Modo fora do núcleo: só `resident_bands` bandas por ponto K ficam em RAM (mais um bloco
+/// de pré-leitura); as demais vivem em arquivos de rascunho em `scratch_dir`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OutOfCore {
+    pub scratch_dir: PathBuf,
+    pub resident_bands: usize,
+}
+
+impl OutOfCore {
+    /// Arquivo de rascunho das bandas do ponto K `ik`.
+    pub fn scratch_file(&self, ik: usize) -> PathBuf {
+        self.scratch_dir.join(format!("bravie_wfc_k{:05}.tmp", ik))
+    }
+}
+
+/// Bandas de um ponto K guardadas fora do solver: em RAM na precisão pedida ou, no modo
+/// fora do núcleo, no arquivo de rascunho do ponto K.
+pub enum StoredBands {
+    Memory(Wavefunctions),
+    Scratch(ScratchWavefunctions),
+}
+
+impl StoredBands {
+    /// Guarda as bandas do ponto K `ik`; com `out_of_core` elas vão para o disco e só
+    /// voltam à RAM em blocos de `resident_bands`.
+    pub fn store(
+        bands: Vec<Array1<Complex64>>,
+        precision: Precision,
+        out_of_core: Option<&OutOfCore>,
+        ik: usize,
+    ) -> io::Result<Self> {
+        match out_of_core {
+            Some(ooc) => {
+                fs::create_dir_all(&ooc.scratch_dir)?;
+                let scratch = ScratchWavefunctions::from_bands(ooc.scratch_file(ik), &bands, precision, ooc.resident_bands)?;
+                Ok(StoredBands::Scratch(scratch))
+            }
+            None => Ok(StoredBands::Memory(Wavefunctions::new(bands, precision))),
+        }
+    }
+
+    pub fn n_bands(&self) -> usize {
+        match self {
+            StoredBands::Memory(w) => w.n_bands(),
+            StoredBands::Scratch(s) => s.n_bands(),
+        }
+    }
+
+    /// Banda `n` em Complex64.
+    pub fn band(&mut self, n: usize) -> io::Result<Array1<Complex64>> {
+        match self {
+            StoredBands::Memory(w) if n < w.n_bands() => Ok(w.band(n)),
+            StoredBands::Memory(w) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("banda {} inexistente ({} bandas)", n, w.n_bands()),
+            )),
+            StoredBands::Scratch(s) => s.band(n).cloned(),
+        }
+    }
+
+    /// Todas as bandas em Complex64.
+    pub fn to_double(&mut self) -> io::Result<Vec<Array1<Complex64>>> {
+        match self {
+            StoredBands::Memory(w) => Ok(w.to_double()),
+            StoredBands::Scratch(s) => s.to_double(),
+        }
+    }
+}
+
+/// Próximo bloco sendo lido em segundo plano.
+struct Prefetch {
+    block: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    handle: std::thread::JoinHandle<io::Result<Vec<Array1<Complex64>>>>,
+    #[cfg(target_arch = "wasm32")]
+    bands: io::Result<Vec<Array1<Complex64>>>,
+}
+
+/// Bandas de um ponto K num arquivo de rascunho, com um bloco ativo de `block_size`
+/// bandas em RAM (Complex64). Ao ativar o bloco b, o bloco b + 1 é lido numa thread
+/// auxiliar, então varreduras sequenciais (Davidson, densidade) quase não esperam disco.
+///
+/// Leituras e escritas são posicionais (seek + read/write) sobre um arquivo contínuo
+/// banda a banda, o que o cache de páginas do SO trata como um mapeamento: sistemas
+/// maiores que a RAM ficam lentos em vez de impossíveis. O arquivo é removido no `Drop`.
+pub struct ScratchWavefunctions {
+    path: PathBuf,
+    file: File,
+    npw: usize,
+    n_bands: usize,
+    precision: Precision,
+    block_size: usize,
+    active: Option<usize>,
+    cache: Vec<Array1<Complex64>>,
+    dirty: bool,
+    prefetch: Option<Prefetch>,
+}
+
+impl ScratchWavefunctions {
+    /// Cria o arquivo `path` com `n_bands` bandas nulas de `npw` coeficientes.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        npw: usize,
+        n_bands: usize,
+        precision: Precision,
+        block_size: usize,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path)?;
+        file.set_len((n_bands * npw * precision.complex_bytes()) as u64)?;
+        Ok(Self {
+            path,
+            file,
+            npw,
+            n_bands,
+            precision,
+            block_size: block_size.max(1),
+            active: None,
+            cache: Vec::new(),
+            dirty: false,
+            prefetch: None,
+        })
+    }
+
+    /// Cria o arquivo com as bandas dadas.
+    pub fn from_bands<P: AsRef<Path>>(
+        path: P,
+        bands: &[Array1<Complex64>],
+        precision: Precision,
+        block_size: usize,
+    ) -> io::Result<Self> {
+        let npw = bands.first().map_or(0, |b| b.len());
+        let mut scratch = Self::create(path, npw, bands.len(), precision, block_size)?;
+        for (n, psi) in bands.iter().enumerate() {
+            write_band(&mut scratch.file, band_offset(n, npw, precision), psi, precision)?;
+        }
+        Ok(scratch)
+    }
+
+    pub fn n_bands(&self) -> usize {
+        self.n_bands
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bytes em RAM (bloco ativo + bloco em pré-leitura).
+    pub fn resident_bytes(&self) -> usize {
+        2 * self.block_size.min(self.n_bands) * self.npw * std::mem::size_of::<Complex64>()
+    }
+
+    /// Banda `n`, ativando o bloco que a contém se preciso.
+    pub fn band(&mut self, n: usize) -> io::Result<&Array1<Complex64>> {
+        let block = self.activate(n)?;
+        Ok(&self.cache[n - block.start])
+    }
+
+    /// Substitui a banda `n` (gravada em disco quando o bloco sai da RAM ou em `flush`).
+    pub fn set_band(&mut self, n: usize, psi: &Array1<Complex64>) -> io::Result<()> {
+        if psi.len() != self.npw {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("banda com {} coeficientes, esperado {}", psi.len(), self.npw),
+            ));
+        }
+        let block = self.activate(n)?;
+        self.cache[n - block.start].assign(psi);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Grava o bloco ativo, se modificado.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let (Some(b), true) = (self.active, self.dirty) {
+            let start = self.block_range(b).start;
+            for (i, psi) in self.cache.iter().enumerate() {
+                write_band(&mut self.file, band_offset(start + i, self.npw, self.precision), psi, self.precision)?;
+            }
+            self.file.flush()?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Todas as bandas em RAM (ex: para a diagonalização no subespaço).
+    pub fn to_double(&mut self) -> io::Result<Vec<Array1<Complex64>>> {
+        self.flush()?;
+        read_bands(&mut self.file, 0..self.n_bands, self.npw, self.precision)
+    }
+
+    fn block_range(&self, block: usize) -> Range<usize> {
+        let start = block * self.block_size;
+        start..(start + self.block_size).min(self.n_bands)
+    }
+
+    fn activate(&mut self, n: usize) -> io::Result<Range<usize>> {
+        if n >= self.n_bands {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("banda {} inexistente ({} bandas)", n, self.n_bands),
+            ));
+        }
+        let block = n / self.block_size;
+        if self.active == Some(block) {
+            return Ok(self.block_range(block));
+        }
+
+        self.flush()?;
+        let range = self.block_range(block);
+        self.cache = match self.prefetch.take() {
+            Some(p) if p.block == block => p.join()?,
+            other => {
+                // Pré-leitura de outro bloco: descartada
+                if let Some(p) = other {
+                    let _ = p.join();
+                }
+                read_bands(&mut self.file, range.clone(), self.npw, self.precision)?
+            }
+        };
+        self.active = Some(block);
+
+        let next = block + 1;
+        if next * self.block_size < self.n_bands {
+            self.prefetch = Some(self.start_prefetch(next)?);
+        }
+        Ok(range)
+    }
+
+    fn start_prefetch(&self, block: usize) -> io::Result<Prefetch> {
+        let range = self.block_range(block);
+        let (npw, precision) = (self.npw, self.precision);
+        // Handle próprio: `try_clone` compartilharia a posição do cursor com as escritas
+        let mut file = File::open(&self.path)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let handle = std::thread::spawn(move || read_bands(&mut file, range, npw, precision));
+            Ok(Prefetch { block, handle })
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Ok(Prefetch { block, bands: read_bands(&mut file, range, npw, precision) })
+        }
+    }
+}
+
+impl Prefetch {
+    fn join(self) -> io::Result<Vec<Array1<Complex64>>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.handle.join().map_err(|_| io::Error::other("thread de pré-leitura abortou"))?
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.bands
+        }
+    }
+}
+
+impl Drop for ScratchWavefunctions {
+    fn drop(&mut self) {
+        if let Some(p) = self.prefetch.take() {
+            let _ = p.join();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn band_offset(n: usize, npw: usize, precision: Precision) -> u64 {
+    (n * npw * precision.complex_bytes()) as u64
+}
+
+fn write_band(file: &mut File, offset: u64, psi: &Array1<Complex64>, precision: Precision) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(psi.len() * precision.complex_bytes());
+    for c in psi {
+        match precision {
+            Precision::Double => {
+                bytes.extend_from_slice(&c.re.to_le_bytes());
+                bytes.extend_from_slice(&c.im.to_le_bytes());
+            }
+            Precision::Single => {
+                bytes.extend_from_slice(&(c.re as f32).to_le_bytes());
+                bytes.extend_from_slice(&(c.im as f32).to_le_bytes());
+            }
+        }
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(&bytes)
+}
+
+/// Lê as bandas `range` numa única leitura contínua.
+fn read_bands(file: &mut File, range: Range<usize>, npw: usize, precision: Precision) -> io::Result<Vec<Array1<Complex64>>> {
+    let stride = npw * precision.complex_bytes();
+    if stride == 0 {
+        return Ok(vec![Array1::zeros(0); range.len()]);
+    }
+    let mut bytes = vec![0u8; range.len() * stride];
+    file.seek(SeekFrom::Start(band_offset(range.start, npw, precision)))?;
+    file.read_exact(&mut bytes)?;
+
+    let bands = bytes.chunks_exact(stride)
+        .map(|band| match precision {
+            Precision::Double => band.chunks_exact(16)
+                .map(|c| Complex64::new(
+                    f64::from_le_bytes(c[..8].try_into().unwrap()),
+                    f64::from_le_bytes(c[8..].try_into().unwrap()),
+                ))
+                .collect(),
+            Precision::Single => band.chunks_exact(8)
+                .map(|c| Complex64::new(
+                    f32::from_le_bytes(c[..4].try_into().unwrap()) as f64,
+                    f32::from_le_bytes(c[4..].try_into().unwrap()) as f64,
+                ))
+                .collect(),
+        })
+        .collect();
+    Ok(bands)
+}
@@ -0,0 +1,257 @@
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::fft::FftGrid;
+use crate::dft::error::DftError;
+use crate::dft::hamiltonian::{apply_local_potential_add, check_buffers, HamiltonianOperator};
+use crate::dft::pcg::{solve_bands_pcg, PcgParameters};
+use crate::utils::timer;
+
+/// Limite de ondas planas para a diagonalização densa (matriz NPW x NPW complexa).
+/// 3000 PWs ~ 144 MB e alguns segundos; acima disso use o solver iterativo.
+pub const EXACT_DIAG_MAX_PW: usize = 3000;
+
+#[derive(Error, Debug)]
+pub enum SolverError {
+    #[error("Base grande demais para diagonalização exata: {0} ondas planas (máximo {1}).")]
+    BasisTooLarge(usize, usize),
+
+    #[error("Número de bandas inválido: {0} pedidas, base com {1} ondas planas.")]
+    InvalidBandCount(usize, usize),
+
+    #[error("Potencial com dimensões {0:?} diferentes do grid FFT {1:?}.")]
+    GridMismatch([usize; 3], [usize; 3]),
+
+    #[error("Matriz de overlap S não é positiva definida (Cholesky falhou).")]
+    OverlapNotPositiveDefinite,
+
+    #[error("Erro na FFT: {0}")]
+    Fft(#[from] DftError),
+
+    #[error("Erro ao gravar as bandas fora do núcleo: {0}")]
+    Scratch(#[from] std::io::Error),
+}
+
+/// Operador de overlap S do problema generalizado Hψ = εSψ (ultrasoft/PAW):
+/// S = 1 + Σ_ij q_ij |β_i⟩⟨β_j|. Para norma-conservantes S = 1 (passe `None`).
+pub trait Overlap {
+    /// S|ψ⟩ nos coeficientes de onda plana da base.
+    fn apply(&self, basis: &PlaneWaveBasis, psi: &Array1<Complex64>) -> Array1<Complex64>;
+}
+
+/// Resultado da diagonalização para um ponto K.
+#[derive(Debug, Clone)]
+pub struct BandSolverResult {
+    pub eigenvalues: Vec<f64>,               // Autovalores ordenados (Ry)
+    pub eigenvectors: Vec<Array1<Complex64>>, // Coeficientes c_G de cada banda (normalizados)
+    pub residual_norms: Vec<f64>,            // ||Hψ - εSψ|| de cada banda (Ry)
+    pub iterations: usize,                   // Iterações do solver (1 na diagonalização exata)
+}
+
+/// Tolerância padrão de `BandSolverResult::is_reliable` para o desvio de ortonormalidade.
+pub const ORTHONORMALITY_TOLERANCE: f64 = 1e-8;
+
+impl BandSolverResult {
+    /// Maior resíduo entre as bandas (0 se não houver bandas).
+    pub fn max_residual(&self) -> f64 {
+        self.residual_norms.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Maior desvio de ⟨ψ_n|S|ψ_m⟩ em relação a δ_nm (S = 1 se `overlap` for `None`).
+    /// Fora da diagonal mede a perda de ortogonalidade; na diagonal, a de normalização.
+    pub fn verify_orthonormality(&self, basis: &PlaneWaveBasis, overlap: Option<&dyn Overlap>) -> f64 {
+        let s_psi: Vec<Array1<Complex64>> = self.eigenvectors.iter()
+            .map(|psi| match overlap {
+                Some(op) => op.apply(basis, psi),
+                None => psi.clone(),
+            })
+            .collect();
+
+        let mut max_dev: f64 = 0.0;
+        for (n, psi) in self.eigenvectors.iter().enumerate() {
+            for (m, s_phi) in s_psi.iter().enumerate().skip(n) {
+                let o: Complex64 = psi.iter().zip(s_phi).map(|(a, b)| a.conj() * b).sum();
+                let expected = if n == m { 1.0 } else { 0.0 };
+                max_dev = max_dev.max((o - expected).norm());
+            }
+        }
+        max_dev
+    }
+
+    /// Diagonalização confiável: resíduos abaixo de `residual_tol` e bandas ortonormais.
+    /// Um SCF deve repetir a diagonalização (tolerância mais apertada) se for `false`.
+    pub fn is_reliable(&self, basis: &PlaneWaveBasis, overlap: Option<&dyn Overlap>, residual_tol: f64) -> bool {
+        self.max_residual() <= residual_tol
+            && self.verify_orthonormality(basis, overlap) <= ORTHONORMALITY_TOLERANCE
+    }
+}
+
+/// Aplica o Hamiltoniano local a uma banda sem montar a matriz:
+///
+/// (Hψ)_G = |k+G|^2 c_G + FFT[V_eff(r) · IFFT[c](r)]_G
+///
+/// Custo O(N log N) por banda. Para compor outros termos (não-local, SOC, +U) use
+/// `dft::hamiltonian::Hamiltonian`.
+pub fn apply_hamiltonian_local(
+    basis: &PlaneWaveBasis,
+    fft_grid: &mut FftGrid,
+    v_eff: &Array3<f64>,
+    psi: &Array1<Complex64>,
+) -> Result<Array1<Complex64>, SolverError> {
+    let mut h_psi = Array1::<Complex64>::zeros(psi.len());
+    let mut work = Array1::<Complex64>::zeros(psi.len());
+    apply_hamiltonian_local_into(basis, fft_grid, v_eff, psi, &mut h_psi, &mut work)?;
+    Ok(h_psi)
+}
+
+/// Como `apply_hamiltonian_local`, escrevendo em `out` com o rascunho `work`
+/// (ambos do chamador, tamanho da base): nenhuma alocação por chamada.
+pub fn apply_hamiltonian_local_into(
+    basis: &PlaneWaveBasis,
+    fft_grid: &mut FftGrid,
+    v_eff: &Array3<f64>,
+    psi: &Array1<Complex64>,
+    out: &mut Array1<Complex64>,
+    work: &mut Array1<Complex64>,
+) -> Result<(), SolverError> {
+    let _t = timer::scope("h_psi");
+    check_buffers(psi, out, work)?;
+    for ((o, c), &g2) in out.iter_mut().zip(psi).zip(&basis.g_norm_sq) {
+        *o = c * g2;
+    }
+    apply_local_potential_add(basis, fft_grid, v_eff, psi, out, work)
+}
+
+/// Rayleigh–Ritz num subespaço: resolve H c = ε S c (S = 1 se `None`) e devolve os
+/// `n` menores autopares, com autovetores S-ortonormais (colunas).
+/// Caso generalizado: S = L L† (Cholesky), H' = L⁻¹ H L⁻†, c = L⁻† y, tudo por
+/// substituição triangular (sem inverter L).
+pub fn rayleigh_ritz(
+    h: DMatrix<Complex64>,
+    s: Option<DMatrix<Complex64>>,
+    n: usize,
+) -> Result<(Vec<f64>, DMatrix<Complex64>), SolverError> {
+    let dim = h.nrows();
+    let (h_reduced, l) = match s {
+        Some(s) => {
+            let l = s.cholesky().ok_or(SolverError::OverlapNotPositiveDefinite)?.unpack();
+            // H Hermitiana: L⁻¹ H L⁻† = L⁻¹ (L⁻¹ H)†
+            let x = l.solve_lower_triangular(&h).ok_or(SolverError::OverlapNotPositiveDefinite)?;
+            let h_reduced = l.solve_lower_triangular(&x.adjoint()).ok_or(SolverError::OverlapNotPositiveDefinite)?;
+            (h_reduced, Some(l))
+        }
+        None => (h, None),
+    };
+
+    let eigen = SymmetricEigen::new(h_reduced);
+    let mut order: Vec<usize> = (0..dim).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+    let order = &order[..n.min(dim)];
+
+    let eigenvalues = order.iter().map(|&i| eigen.eigenvalues[i]).collect();
+    let mut vectors = DMatrix::from_fn(dim, order.len(), |r, c| eigen.eigenvectors[(r, order[c])]);
+    if let Some(l) = l {
+        // c = L⁻† y  <=>  L† c = y
+        vectors = l.adjoint().solve_upper_triangular(&vectors).ok_or(SolverError::OverlapNotPositiveDefinite)?;
+    }
+    Ok((eigenvalues, vectors))
+}
+
+/// Eigensolver das bandas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Diagonalizer {
+    /// `solve_bands_exact_generalized`: matriz densa, referência para bases de até
+    /// `EXACT_DIAG_MAX_PW` ondas planas.
+    #[default]
+    Exact,
+    /// `pcg::solve_bands_pcg` com `PcgParameters::default()`, a partir das bandas iniciais.
+    Pcg,
+}
+
+impl Diagonalizer {
+    /// `n_bands` autobandas de `h` (Hψ = εSψ com `overlap`). `initial` só é chamado pelos
+    /// solvers iterativos e deve devolver `n_bands` bandas da base de `h`.
+    pub fn solve(
+        self,
+        h: &mut dyn HamiltonianOperator,
+        n_bands: usize,
+        overlap: Option<&dyn Overlap>,
+        initial: impl FnOnce() -> Vec<Array1<Complex64>>,
+    ) -> Result<BandSolverResult, SolverError> {
+        match self {
+            Diagonalizer::Exact => solve_bands_exact_generalized(h, n_bands, overlap),
+            Diagonalizer::Pcg => solve_bands_pcg(h, initial(), &PcgParameters::default(), overlap),
+        }
+    }
+}
+
+/// Diagonalização exata (densa) de um Hamiltoniano na base de ondas planas.
+///
+/// A matriz H_GG' = ⟨G|H|G'⟩ é montada aplicando `h` a cada onda plana (todos os termos:
+/// cinético com qualquer `HamiltonianModel`, V_eff, não-local, ...) e diagonalizada,
+/// servindo como referência para validar os solvers iterativos em sistemas pequenos.
+pub fn solve_bands_exact(h: &mut dyn HamiltonianOperator, n_bands: usize) -> Result<BandSolverResult, SolverError> {
+    solve_bands_exact_generalized(h, n_bands, None)
+}
+
+/// Como `solve_bands_exact`, para o problema generalizado Hψ = εSψ.
+/// A matriz S é montada aplicando o operador a cada onda plana; os autovetores
+/// saem S-ortonormais (⟨ψ_n|S|ψ_m⟩ = δ_nm).
+pub fn solve_bands_exact_generalized(
+    h: &mut dyn HamiltonianOperator,
+    n_bands: usize,
+    overlap: Option<&dyn Overlap>,
+) -> Result<BandSolverResult, SolverError> {
+    let _t = timer::scope("exact_diag");
+    let npw = h.basis().g_vectors.len();
+    if npw > EXACT_DIAG_MAX_PW {
+        return Err(SolverError::BasisTooLarge(npw, EXACT_DIAG_MAX_PW));
+    }
+    if n_bands == 0 || n_bands > npw {
+        return Err(SolverError::InvalidBandCount(n_bands, npw));
+    }
+
+    // 1. Matrizes coluna a coluna: H e_G (e S e_G)
+    let mut h_mat = DMatrix::<Complex64>::zeros(npw, npw);
+    let mut s_mat = overlap.map(|_| DMatrix::<Complex64>::zeros(npw, npw));
+    let mut unit = Array1::<Complex64>::zeros(npw);
+    let mut column = Array1::<Complex64>::zeros(npw);
+    let mut work = Array1::<Complex64>::zeros(npw);
+    for b in 0..npw {
+        unit[b] = Complex64::new(1.0, 0.0);
+        h.apply_into(&unit, &mut column, &mut work)?;
+        h_mat.column_mut(b).iter_mut().zip(&column).for_each(|(m, v)| *m = *v);
+        if let (Some(op), Some(s)) = (overlap, s_mat.as_mut()) {
+            s.column_mut(b).iter_mut().zip(&op.apply(h.basis(), &unit)).for_each(|(m, v)| *m = *v);
+        }
+        unit[b] = Complex64::new(0.0, 0.0);
+    }
+    // Remove a assimetria de arredondamento das FFTs
+    let h_mat = (&h_mat + h_mat.adjoint()).map(|z| z * 0.5);
+
+    // 2. Diagonalização e ordenação dos autopares
+    let (eigenvalues, vectors) = rayleigh_ritz(h_mat, s_mat, n_bands)?;
+    let eigenvectors: Vec<Array1<Complex64>> = vectors.column_iter()
+        .map(|c| c.iter().copied().collect())
+        .collect();
+
+    // 3. Diagnóstico: resíduos ||Hψ - εSψ|| aplicando H pela FFT
+    let mut residual_norms = Vec::with_capacity(eigenvectors.len());
+    let mut h_psi = Array1::<Complex64>::zeros(npw);
+    for (e, psi) in eigenvalues.iter().zip(&eigenvectors) {
+        let s_psi = match overlap {
+            Some(op) => op.apply(h.basis(), psi),
+            None => psi.clone(),
+        };
+        h.apply_into(psi, &mut h_psi, &mut work)?;
+        let r: f64 = h_psi.iter().zip(&s_psi).map(|(h, s)| (h - s * *e).norm_sqr()).sum();
+        residual_norms.push(r.sqrt());
+    }
+
+    Ok(BandSolverResult { eigenvalues, eigenvectors, residual_norms, iterations: 1 })
+}
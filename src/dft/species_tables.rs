@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::dft::local_potential::LocalFormFactor;
+use crate::io::upf::Pseudopotential;
+
+/// Espaçamento da tabela real de ρ_atom (Bohr). Com interpolação linear, o erro relativo
+/// é ~(dr/r_core)² — bem abaixo do erro de amostragem do grid FFT.
+pub const DEFAULT_DR: f64 = 1e-3;
+
+/// ρ_atom(r) tabelado em malha uniforme: a consulta é um índice direto, sem a busca
+/// binária da spline na malha logarítmica do UPF.
+#[derive(Debug, Clone)]
+pub struct RealSpaceTable {
+    pub dr: f64,
+    pub values: Vec<f64>,
+}
+
+impl RealSpaceTable {
+    /// Amostra a spline ρ_atom do pseudo em r = 0, dr, ..., r_max da malha.
+    pub fn rho_atom(pseudo: &Pseudopotential, dr: f64) -> Self {
+        let spline = &pseudo.splines.rho_atom;
+        let n = (spline.x_max() / dr).floor() as usize + 1;
+        let values = (0..n).map(|i| spline.eval(i as f64 * dr)).collect();
+        Self { dr, values }
+    }
+
+    pub fn r_max(&self) -> f64 {
+        self.dr * self.values.len().saturating_sub(1) as f64
+    }
+
+    /// Interpolação linear; zero além de `r_max`.
+    pub fn eval(&self, r: f64) -> f64 {
+        let t = r / self.dr;
+        let i = t as usize;
+        match (self.values.get(i), self.values.get(i + 1)) {
+            (Some(a), Some(b)) => {
+                let w = t - i as f64;
+                a + w * (b - a)
+            }
+            (Some(a), None) if t - i as f64 <= 1e-12 => *a,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Tabelas radiais por espécie (chave: `species_id`), guardadas na `Simulation` para que
+/// átomos da mesma espécie, e chamadas repetidas (relaxação, SCF), não refaçam as
+/// transformadas e interpolações.
+#[derive(Default)]
+pub struct SpeciesTables {
+    q_max: f64,
+    local: HashMap<usize, LocalFormFactor>,
+    rho_atom: HashMap<usize, RealSpaceTable>,
+}
+
+impl SpeciesTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Descarta tudo (ex: pseudos trocados).
+    pub fn clear(&mut self) {
+        self.q_max = 0.0;
+        self.local.clear();
+        self.rho_atom.clear();
+    }
+
+    /// Fatores de forma do potencial local cobrindo |G| <= `q_max`. Um `q_max` maior que o
+    /// das tabelas atuais (célula deformada, grid maior) refaz as tabelas.
+    pub fn local_form_factors(
+        &mut self,
+        structure: &Structure,
+        pseudos: &HashMap<usize, Pseudopotential>,
+        q_max: f64,
+    ) -> Result<&HashMap<usize, LocalFormFactor>, DftError> {
+        if q_max > self.q_max {
+            self.local.clear();
+            self.q_max = q_max;
+        }
+        for atom in &structure.atoms {
+            if !self.local.contains_key(&atom.species_id) {
+                let pseudo = pseudos.get(&atom.species_id).ok_or(DftError::MissingPseudo(atom.species_id))?;
+                self.local.insert(atom.species_id, LocalFormFactor::new(pseudo, self.q_max));
+            }
+        }
+        Ok(&self.local)
+    }
+
+    /// Tabelas reais de ρ_atom das espécies presentes em `structure`.
+    pub fn rho_atom(
+        &mut self,
+        structure: &Structure,
+        pseudos: &HashMap<usize, Pseudopotential>,
+    ) -> Result<&HashMap<usize, RealSpaceTable>, DftError> {
+        for atom in &structure.atoms {
+            if !self.rho_atom.contains_key(&atom.species_id) {
+                let pseudo = pseudos.get(&atom.species_id).ok_or(DftError::MissingPseudo(atom.species_id))?;
+                self.rho_atom.insert(atom.species_id, RealSpaceTable::rho_atom(pseudo, DEFAULT_DR));
+            }
+        }
+        Ok(&self.rho_atom)
+    }
+}
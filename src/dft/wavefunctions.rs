@@ -0,0 +1,120 @@
+use ndarray::Array1;
+use num_complex::{Complex32, Complex64};
+use serde::Deserialize;
+
+/// Precisão de armazenamento dos coeficientes de onda plana.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    /// Complex64 (padrão).
+    #[default]
+    Double,
+    /// Complex32: metade da memória; erro relativo ~1e-7 nos coeficientes,
+    /// suficiente para ~1e-6 Ry nas energias. Reduções e energias seguem em f64.
+    Single,
+}
+
+impl Precision {
+    /// Bytes por coeficiente complexo.
+    pub fn complex_bytes(&self) -> usize {
+        match self {
+            Precision::Double => std::mem::size_of::<Complex64>(),
+            Precision::Single => std::mem::size_of::<Complex32>(),
+        }
+    }
+}
+
+/// Bandas de um ponto K guardadas na precisão escolhida. Os kernels (FFT, H|ψ⟩)
+/// continuam em f64: cada banda é promovida ao ser lida e arredondada ao ser gravada,
+/// e produtos internos acumulam sempre em f64.
+#[derive(Debug, Clone)]
+pub enum Wavefunctions {
+    Double(Vec<Array1<Complex64>>),
+    Single(Vec<Array1<Complex32>>),
+}
+
+impl Wavefunctions {
+    /// Guarda `bands` na precisão pedida, sem copiar no modo `Double`.
+    pub fn new(bands: Vec<Array1<Complex64>>, precision: Precision) -> Self {
+        match precision {
+            Precision::Double => Wavefunctions::Double(bands),
+            Precision::Single => Wavefunctions::Single(bands.iter().map(to_single).collect()),
+        }
+    }
+
+    pub fn from_bands(bands: &[Array1<Complex64>], precision: Precision) -> Self {
+        match precision {
+            Precision::Double => Wavefunctions::Double(bands.to_vec()),
+            Precision::Single => Wavefunctions::Single(bands.iter().map(to_single).collect()),
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        match self {
+            Wavefunctions::Double(_) => Precision::Double,
+            Wavefunctions::Single(_) => Precision::Single,
+        }
+    }
+
+    pub fn n_bands(&self) -> usize {
+        match self {
+            Wavefunctions::Double(b) => b.len(),
+            Wavefunctions::Single(b) => b.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_bands() == 0
+    }
+
+    /// Banda `n` em Complex64.
+    pub fn band(&self, n: usize) -> Array1<Complex64> {
+        match self {
+            Wavefunctions::Double(b) => b[n].clone(),
+            Wavefunctions::Single(b) => b[n].mapv(|c| Complex64::new(c.re as f64, c.im as f64)),
+        }
+    }
+
+    /// Grava a banda `n` (arredondada para f32 no modo simples).
+    pub fn set_band(&mut self, n: usize, psi: &Array1<Complex64>) {
+        match self {
+            Wavefunctions::Double(b) => b[n].assign(psi),
+            Wavefunctions::Single(b) => b[n] = to_single(psi),
+        }
+    }
+
+    /// Todas as bandas em Complex64.
+    pub fn to_double(&self) -> Vec<Array1<Complex64>> {
+        (0..self.n_bands()).map(|n| self.band(n)).collect()
+    }
+
+    /// ⟨ψ_m|ψ_n⟩ com acumulação em f64.
+    pub fn overlap(&self, m: usize, n: usize) -> Complex64 {
+        match self {
+            Wavefunctions::Double(b) => b[m].iter().zip(&b[n]).map(|(a, c)| a.conj() * c).sum(),
+            Wavefunctions::Single(b) => b[m].iter().zip(&b[n])
+                .map(|(a, c)| {
+                    let (a, c) = (promote(*a), promote(*c));
+                    a.conj() * c
+                })
+                .sum(),
+        }
+    }
+
+    /// Bytes ocupados pelos coeficientes.
+    pub fn memory_bytes(&self) -> usize {
+        let n_coeffs: usize = match self {
+            Wavefunctions::Double(b) => b.iter().map(|psi| psi.len()).sum(),
+            Wavefunctions::Single(b) => b.iter().map(|psi| psi.len()).sum(),
+        };
+        n_coeffs * self.precision().complex_bytes()
+    }
+}
+
+fn promote(c: Complex32) -> Complex64 {
+    Complex64::new(c.re as f64, c.im as f64)
+}
+
+fn to_single(psi: &Array1<Complex64>) -> Array1<Complex32> {
+    psi.mapv(|c| Complex32::new(c.re as f32, c.im as f32))
+}
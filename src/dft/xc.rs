@@ -0,0 +1,131 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum XcError {
+    #[error("Funcional XC desconhecido '{0}'. Disponíveis: {1}")]
+    Unknown(String, String),
+}
+
+/// Funcionais de troca-correlação conhecidos pelo Bravie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum XcFunctional {
+    /// LDA, correlação de Perdew-Zunger.
+    LdaPz,
+    /// LDA, correlação de Perdew-Wang.
+    LdaPw,
+    Pbe,
+    PbeSol,
+    RevPbe,
+    Blyp,
+}
+
+/// Registro nome -> funcional. O primeiro nome de cada funcional é o canônico; os demais
+/// são apelidos aceitos no input.
+pub const REGISTRY: &[(&str, XcFunctional)] = &[
+    ("lda-pz", XcFunctional::LdaPz),
+    ("lda", XcFunctional::LdaPz),
+    ("pz", XcFunctional::LdaPz),
+    ("lda-pw", XcFunctional::LdaPw),
+    ("pw", XcFunctional::LdaPw),
+    ("pbe", XcFunctional::Pbe),
+    ("pbesol", XcFunctional::PbeSol),
+    ("revpbe", XcFunctional::RevPbe),
+    ("blyp", XcFunctional::Blyp),
+];
+
+impl XcFunctional {
+    pub const ALL: [XcFunctional; 6] = [
+        XcFunctional::LdaPz,
+        XcFunctional::LdaPw,
+        XcFunctional::Pbe,
+        XcFunctional::PbeSol,
+        XcFunctional::RevPbe,
+        XcFunctional::Blyp,
+    ];
+
+    /// Nome canônico no registro.
+    pub fn name(&self) -> &'static str {
+        REGISTRY.iter()
+            .find(|(_, f)| f == self)
+            .map(|(name, _)| *name)
+            .expect("todo funcional tem um nome no registro")
+    }
+
+    /// Notação curta do QE no PP_HEADER (troca local, correlação local, gradiente de
+    /// troca, gradiente de correlação).
+    pub fn upf_notation(&self) -> &'static str {
+        match self {
+            XcFunctional::LdaPz => "SLA PZ NOGX NOGC",
+            XcFunctional::LdaPw => "SLA PW NOGX NOGC",
+            XcFunctional::Pbe => "SLA PW PBX PBC",
+            XcFunctional::PbeSol => "SLA PW PSX PSC",
+            XcFunctional::RevPbe => "SLA PW RPB PBC",
+            XcFunctional::Blyp => "SLA LYP B88 BLYP",
+        }
+    }
+
+    /// Funcional com correção de gradiente?
+    pub fn is_gga(&self) -> bool {
+        !matches!(self, XcFunctional::LdaPz | XcFunctional::LdaPw)
+    }
+
+    /// Identifica o funcional do PP_HEADER, seja pela notação do QE (ex: "SLA PW PBX PBC",
+    /// espaços ignorados) ou por um nome do registro (ex: "PBE").
+    pub fn from_upf(header_functional: &str) -> Option<Self> {
+        let normalize = |s: &str| s.split_whitespace().collect::<String>().to_ascii_uppercase();
+        let header = normalize(header_functional);
+        Self::ALL.into_iter()
+            .find(|f| normalize(f.upf_notation()) == header)
+            .or_else(|| header_functional.trim().parse().ok())
+    }
+
+    /// O pseudo com o funcional `header_functional` foi gerado com este funcional?
+    pub fn matches_upf(&self, header_functional: &str) -> bool {
+        Self::from_upf(header_functional) == Some(*self)
+    }
+}
+
+impl FromStr for XcFunctional {
+    type Err = XcError;
+
+    /// Nome do registro, sem distinção de maiúsculas; '_' e ' ' valem como '-'.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_ascii_lowercase().replace(['_', ' '], "-");
+        REGISTRY.iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, f)| *f)
+            .ok_or_else(|| {
+                let names: Vec<&str> = XcFunctional::ALL.iter().map(|f| f.name()).collect();
+                XcError::Unknown(s.to_string(), names.join(", "))
+            })
+    }
+}
+
+impl TryFrom<String> for XcFunctional {
+    type Error = XcError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for XcFunctional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// O que fazer quando o funcional pedido difere do funcional de um pseudo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum XcPolicy {
+    /// Aborta o build.
+    #[default]
+    Error,
+    /// Só avisa (ex: testar um pseudo LDA num cálculo PBE).
+    Warn,
+}
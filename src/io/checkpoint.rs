@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use nalgebra::Matrix3;
+use ndarray::Array3;
+use thiserror::Error;
+
+use crate::core::field::{DensityField, PotentialField};
+use crate::core::structure::Lattice;
+use crate::dft::error::DftError;
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("Erro de leitura/escrita do checkpoint: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Arquivo não é um checkpoint do Bravie (ou versão {0} não suportada)")]
+    BadFormat(u32),
+    #[error("Checkpoint no grid {0:?}, simulação no grid {1:?}")]
+    GridMismatch([usize; 3], [usize; 3]),
+    #[error("Célula do checkpoint difere da simulação")]
+    LatticeMismatch,
+    #[error("ρ e V_eff em grids diferentes: {0:?} e {1:?}")]
+    FieldMismatch([usize; 3], [usize; 3]),
+    #[error("Erro ao aplicar o V_eff do checkpoint: {0}")]
+    Dft(#[from] DftError),
+}
+
+const MAGIC: &[u8; 8] = b"BRAVIECK";
+const VERSION: u32 = 1;
+/// Tolerância (Bohr) na comparação da célula.
+const LATTICE_TOLERANCE: f64 = 1e-8;
+
+/// Estado necessário para retomar a partir de um SCF: ρ e V_eff no grid denso,
+/// com cortes e célula para conferir a compatibilidade com a nova simulação.
+///
+/// Formato binário little-endian: "BRAVIECK", versão (u32), ecut, ecut_rho (f64),
+/// grid (3 x u64), célula (9 x f64, por colunas), ρ e V_eff (N x f64 cada, ordem C).
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub ecut: f64,
+    pub ecut_rho: f64,
+    pub rho: DensityField,
+    pub v_eff: PotentialField,
+}
+
+impl Checkpoint {
+    pub fn new(ecut: f64, ecut_rho: f64, rho: DensityField, v_eff: PotentialField) -> Result<Self, CheckpointError> {
+        if rho.dims() != v_eff.dims() {
+            return Err(CheckpointError::FieldMismatch(rho.dims(), v_eff.dims()));
+        }
+        Ok(Self { ecut, ecut_rho, rho, v_eff })
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+        let mut w = BufWriter::new(File::create(path)?);
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        write_f64s(&mut w, [self.ecut, self.ecut_rho].iter())?;
+        for n in self.rho.dims() {
+            w.write_all(&(n as u64).to_le_bytes())?;
+        }
+        write_f64s(&mut w, self.rho.lattice.vectors.iter())?;
+        write_f64s(&mut w, self.rho.data.iter())?;
+        write_f64s(&mut w, self.v_eff.data.iter())?;
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, CheckpointError> {
+        let mut r = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        let mut version = [0u8; 4];
+        r.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if &magic != MAGIC || version != VERSION {
+            return Err(CheckpointError::BadFormat(version));
+        }
+
+        let cuts = read_f64s(&mut r, 2)?;
+        let mut dims = [0usize; 3];
+        for n in dims.iter_mut() {
+            let mut buf = [0u8; 8];
+            r.read_exact(&mut buf)?;
+            *n = u64::from_le_bytes(buf) as usize;
+        }
+        let lattice = Lattice { vectors: Matrix3::from_column_slice(&read_f64s(&mut r, 9)?) };
+
+        let n = dims.iter().product();
+        let shape = (dims[0], dims[1], dims[2]);
+        let as_grid = |v: Vec<f64>| Array3::from_shape_vec(shape, v).map_err(|_| CheckpointError::BadFormat(version));
+        let rho = as_grid(read_f64s(&mut r, n)?)?;
+        let v_eff = as_grid(read_f64s(&mut r, n)?)?;
+
+        Ok(Self {
+            ecut: cuts[0],
+            ecut_rho: cuts[1],
+            rho: DensityField::new(lattice.clone(), rho),
+            v_eff: PotentialField::new(lattice, v_eff),
+        })
+    }
+
+    /// Confere grid e célula contra os de uma simulação.
+    pub fn check_compatible(&self, lattice: &Lattice, fft_grid: [usize; 3]) -> Result<(), CheckpointError> {
+        if self.rho.dims() != fft_grid {
+            return Err(CheckpointError::GridMismatch(self.rho.dims(), fft_grid));
+        }
+        if (self.rho.lattice.vectors - lattice.vectors).abs().max() > LATTICE_TOLERANCE {
+            return Err(CheckpointError::LatticeMismatch);
+        }
+        Ok(())
+    }
+}
+
+fn write_f64s<'a, W: Write>(w: &mut W, values: impl Iterator<Item = &'a f64>) -> Result<(), CheckpointError> {
+    for v in values {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_f64s<R: Read>(r: &mut R, n: usize) -> Result<Vec<f64>, CheckpointError> {
+    let mut buf = vec![0u8; 8 * n];
+    r.read_exact(&mut buf)?;
+    Ok(buf.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect())
+}
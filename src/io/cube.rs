@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use ndarray::Array3;
+
+use crate::core::structure::Structure;
+
+/// Escreve um campo escalar no grid FFT em formato Gaussian cube (Bohr).
+/// Ordem dos dados: x mais lento, z mais rápido, 6 valores por linha.
+pub fn write_cube<P: AsRef<Path>>(
+    path: P,
+    structure: &Structure,
+    data: &Array3<f64>,
+    comment: &str,
+) -> std::io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    let (nx, ny, nz) = data.dim();
+    let dims = [nx, ny, nz];
+
+    writeln!(w, "Bravie cube")?;
+    writeln!(w, "{}", comment)?;
+    writeln!(w, "{:5} {:12.6} {:12.6} {:12.6}", structure.atoms.len(), 0.0, 0.0, 0.0)?;
+
+    // Vetores de voxel: a_i / N_i
+    for (i, &n) in dims.iter().enumerate() {
+        let a = structure.lattice.vectors.column(i);
+        let n_f = n as f64;
+        writeln!(w, "{:5} {:12.6} {:12.6} {:12.6}", n, a[0] / n_f, a[1] / n_f, a[2] / n_f)?;
+    }
+
+    for atom in &structure.atoms {
+        let z = structure
+            .species
+            .iter()
+            .find(|s| s.id == atom.species_id)
+            .map(|s| s.atomic_number)
+            .unwrap_or(0);
+        let r = &atom.position; // Cartesiano (Bohr)
+        writeln!(w, "{:5} {:12.6} {:12.6} {:12.6} {:12.6}", z, z as f64, r.x, r.y, r.z)?;
+    }
+
+    for i in 0..nx {
+        for j in 0..ny {
+            for k in 0..nz {
+                write!(w, " {:13.5E}", data[[i, j, k]])?;
+                if k % 6 == 5 {
+                    writeln!(w)?;
+                }
+            }
+            if nz % 6 != 0 {
+                writeln!(w)?;
+            }
+        }
+    }
+    w.flush()
+}
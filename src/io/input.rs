@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::kpoints::{KGrid, MeshCentering};
+use crate::core::simulation::{Simulation, SimulationBuilder};
+use crate::core::structure::{Species, Structure, StructureError};
+use crate::dft::occupations::{BandPolicy, Smearing};
+use crate::dft::xc::{XcFunctional, XcPolicy};
+use crate::dft::dispersion::D2Parameters;
+use crate::dft::scratch::OutOfCore;
+use crate::dft::solver::Diagonalizer;
+use crate::dft::wavefunctions::Precision;
+
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("Erro de Leitura do Input: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Erro de Sintaxe TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Erro na estrutura: {0}")]
+    Structure(#[from] StructureError),
+    #[error("Átomo {0} referencia espécie inexistente (id {1})")]
+    UnknownSpecies(usize, usize),
+    #[error("kpoints requer `grid` ou `spacing`")]
+    MissingKGrid,
+}
+
+/// Arquivo de entrada TOML do Bravie. Unidades: Bohr e Rydberg.
+///
+/// ```toml
+/// [structure]
+/// lattice = [[0.0, 5.13, 5.13], [5.13, 0.0, 5.13], [5.13, 5.13, 0.0]]
+///
+/// [[structure.species]]
+/// id = 0
+/// element = "Si"
+/// pseudo = "pp/Si.pbe-n-rrkjus_psl.1.0.0.UPF"
+///
+/// [[structure.atoms]]
+/// species = 0
+/// position = [0.0, 0.0, 0.0]
+/// fixed = [true, true, true] # opcional, congela x/y/z na relaxação
+///
+/// [calculation]
+/// ecut = 30.0
+/// ecut_rho = 240.0 # opcional, padrão 4 x ecut
+/// fft_grid = [45, 45, 45] # opcional, no lugar do grid automático
+/// xc = "pbe" # opcional, confere o funcional dos pseudos (ver `dft::xc::REGISTRY`)
+/// xc_policy = "warn" # opcional, padrão "error"
+/// kpoints = { grid = [4, 4, 4], shift = [0.0, 0.0, 0.0] }
+/// # ou: kpoints = { spacing = 0.2, centering = "gamma", time_reversal = true } (Å⁻¹)
+/// precision = "single" # opcional, funções de onda em f32
+/// scissor = 0.04 # opcional
+/// smearing = { kind = "gaussian", width = 0.01 } # opcional (Ry); ou "fermi-dirac"
+/// band_policy = { extra-fraction = 0.2 } # opcional; ou { extra = 8 }, { fixed = 24 }, { metallic = { min_extra = 8 } }
+/// out_of_core = { scratch_dir = "/tmp", resident_bands = 64 } # opcional
+/// diagonalizer = "pcg" # opcional, padrão "exact"
+///
+/// [bands] # opcional, para `bravie bands`
+/// path = [["Γ", [0.0, 0.0, 0.0]], ["X", [0.5, 0.0, 0.5]], ["L", [0.5, 0.5, 0.5]]]
+/// points_per_segment = 20
+///
+/// [dispersion] # opcional, DFT-D2 (ver `dft::dispersion::D2Parameters`)
+/// s6 = 0.75 # opcional, padrão do funcional
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct InputFile {
+    pub structure: StructureInput,
+    pub calculation: CalculationInput,
+    #[serde(default)]
+    pub bands: Option<BandsInput>,
+    #[serde(default)]
+    pub dispersion: Option<D2Parameters>,
+}
+
+/// Caminho para `bravie bands`: nós rotulados em coordenadas fracionárias da recíproca.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BandsInput {
+    pub path: Vec<(String, [f64; 3])>,
+    #[serde(default = "default_points_per_segment")]
+    pub points_per_segment: usize,
+}
+
+fn default_points_per_segment() -> usize {
+    20
+}
+
+impl BandsInput {
+    pub fn to_kgrid(&self, structure: &Structure) -> KGrid {
+        let nodes: Vec<(&str, [f64; 3])> = self.path.iter().map(|(l, k)| (l.as_str(), *k)).collect();
+        KGrid::band_path(&nodes, self.points_per_segment, structure)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StructureInput {
+    pub lattice: [[f64; 3]; 3], // Linhas = a1, a2, a3 (Bohr)
+    pub species: Vec<SpeciesInput>,
+    pub atoms: Vec<AtomInput>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeciesInput {
+    pub id: usize,
+    pub element: String,
+    #[serde(default)]
+    pub atomic_number: u8,
+    #[serde(default)]
+    pub mass: f64,
+    #[serde(default)]
+    pub pseudo: String, // Vazio: resolvido em BRAVIE_PSEUDO_DIR
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtomInput {
+    pub species: usize,
+    pub position: [f64; 3], // Cartesiano (Bohr)
+    #[serde(default)]
+    pub fixed: [bool; 3], // Componentes congeladas na relaxação
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalculationInput {
+    pub ecut: f64,
+    #[serde(default)]
+    pub ecut_rho: Option<f64>, // Corte da densidade (Ry); padrão 4 x ecut
+    #[serde(default)]
+    pub fft_grid: Option<[usize; 3]>, // Dimensões fixas do grid FFT
+    #[serde(default)]
+    pub fft_padding: usize, // Pontos extras por direção no grid FFT
+    #[serde(default)]
+    pub xc: Option<XcFunctional>, // Funcional pedido (ex: "pbe"); None aceita o dos pseudos
+    #[serde(default)]
+    pub xc_policy: XcPolicy, // "error" (padrão) ou "warn" quando o pseudo difere
+    #[serde(default)]
+    pub kpoints: Option<KPointsInput>,
+    #[serde(default)]
+    pub precision: Precision, // "double" (padrão) ou "single"
+    #[serde(default)]
+    pub scissor: Option<f64>, // Deslocamento rígido das bandas vazias (Ry)
+    #[serde(default)]
+    pub smearing: Smearing, // Padrão: ocupações inteiras
+    #[serde(default)]
+    pub band_policy: BandPolicy, // Padrão: n_occ + 4
+    #[serde(default)]
+    pub out_of_core: Option<OutOfCore>, // Funções de onda em arquivos de rascunho
+    #[serde(default)]
+    pub diagonalizer: Diagonalizer, // "exact" (padrão) ou "pcg"
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KPointsInput {
+    #[serde(default)]
+    pub grid: Option<[usize; 3]>,
+    #[serde(default)]
+    pub spacing: Option<f64>, // Espaçamento máximo (Å⁻¹); usado se `grid` ausente
+    #[serde(default)]
+    pub shift: [f64; 3],
+    #[serde(default)]
+    pub centering: Option<MeshCentering>, // Substitui `shift` quando presente
+    #[serde(default)]
+    pub time_reversal: bool, // Junta k e -k
+}
+
+impl InputFile {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, InputError> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, InputError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Constrói a `Structure` descrita no input.
+    pub fn to_structure(&self) -> Result<Structure, InputError> {
+        let [a1, a2, a3] = self.structure.lattice;
+        let mut builder = Structure::builder().lattice(a1, a2, a3);
+
+        for sp in &self.structure.species {
+            builder = builder.add_species(Species {
+                id: sp.id,
+                element: sp.element.clone(),
+                atomic_number: sp.atomic_number,
+                mass: sp.mass,
+                pseudo_path: sp.pseudo.clone(),
+            });
+        }
+
+        for (i, atom) in self.structure.atoms.iter().enumerate() {
+            if !self.structure.species.iter().any(|s| s.id == atom.species) {
+                return Err(InputError::UnknownSpecies(i + 1, atom.species));
+            }
+            builder = builder.add_fixed_atom(atom.position, atom.species, atom.fixed);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Prepara o `SimulationBuilder` (estrutura, cortes, k-points e funcional) a partir do input.
+    pub fn to_builder(&self) -> Result<SimulationBuilder, InputError> {
+        let structure = self.to_structure()?;
+        let mut builder = Simulation::builder().ecut(self.calculation.ecut);
+
+        if let Some(kp) = &self.calculation.kpoints {
+            let shift = kp.centering.map_or(kp.shift, |c| c.shift());
+            let mut k_grid = match (kp.grid, kp.spacing) {
+                (Some(grid), _) => KGrid::monkhorst_pack(grid, shift),
+                (None, Some(dk)) => KGrid::monkhorst_pack(KGrid::grid_dims_for_spacing(&structure, dk), shift),
+                (None, None) => return Err(InputError::MissingKGrid),
+            };
+            if kp.time_reversal {
+                k_grid = k_grid.reduce_time_reversal();
+            }
+            builder = builder.k_grid(k_grid);
+        }
+        builder = builder.structure(structure)
+            .precision(self.calculation.precision)
+            .smearing(self.calculation.smearing)
+            .band_policy(self.calculation.band_policy)
+            .diagonalizer(self.calculation.diagonalizer);
+        if let Some(ooc) = &self.calculation.out_of_core {
+            builder = builder.out_of_core(ooc.scratch_dir.clone(), ooc.resident_bands);
+        }
+        if let Some(ecut_rho) = self.calculation.ecut_rho {
+            builder = builder.ecut_rho(ecut_rho);
+        }
+        if let Some(grid) = self.calculation.fft_grid {
+            builder = builder.fft_grid(grid);
+        }
+        if self.calculation.fft_padding > 0 {
+            builder = builder.fft_padding(self.calculation.fft_padding);
+        }
+        if let Some(xc) = self.calculation.xc {
+            builder = builder.xc(xc).xc_policy(self.calculation.xc_policy);
+        }
+        if let Some(d2) = &self.dispersion {
+            builder = builder.dispersion(d2.clone());
+        }
+
+        Ok(builder)
+    }
+}
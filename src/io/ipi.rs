@@ -0,0 +1,171 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use nalgebra::{Matrix3, Vector3};
+use thiserror::Error;
+
+use crate::core::structure::{Atom, Lattice, Structure};
+use crate::dft::dispersion::D2;
+use crate::utils::constants::RY_TO_HA;
+
+#[derive(Error, Debug)]
+pub enum IpiError {
+    #[error("Erro de comunicação no socket i-PI: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Mensagem i-PI inesperada: '{0}'")]
+    UnexpectedMessage(String),
+    #[error("Servidor enviou {0} átomos, a estrutura tem {1}")]
+    AtomCountMismatch(usize, usize),
+    #[error("GETFORCE recebido sem posições calculadas")]
+    NoData,
+    #[error("Erro no cálculo: {0}")]
+    Calculator(String),
+}
+
+/// Tamanho fixo dos cabeçalhos do protocolo (ASCII, completado com espaços).
+const HEADER_LEN: usize = 12;
+
+/// Energia, forças e virial de uma configuração (unidades do Bravie: Ry, Bohr).
+#[derive(Debug, Clone)]
+pub struct ForceResult {
+    pub energy: f64,                // Ry
+    pub forces: Vec<Vector3<f64>>,  // Ry/Bohr, na ordem de `structure.atoms`
+    pub virial: Matrix3<f64>,       // Ry, -∂E/∂ε (= σΩ com σ na convenção do pw.x)
+}
+
+/// Backend que calcula energia e forças para o cliente i-PI.
+pub trait ForceCalculator {
+    fn compute(&mut self, structure: &Structure) -> Result<ForceResult, String>;
+}
+
+/// Só a dispersão DFT-D2, sem DFT (ex: conferir o acoplamento com o ASE ou pré-relaxar
+/// cristais moleculares).
+impl ForceCalculator for D2 {
+    fn compute(&mut self, structure: &Structure) -> Result<ForceResult, String> {
+        let d2 = D2::compute(self, structure).map_err(|e| e.to_string())?;
+        Ok(ForceResult {
+            energy: d2.energy,
+            forces: d2.forces,
+            virial: d2.stress * structure.lattice.volume(),
+        })
+    }
+}
+
+/// Cliente do protocolo de socket do i-PI, o mesmo do `SocketIOCalculator` do ASE:
+/// o servidor (ASE/i-PI) envia célula e posições, o Bravie devolve energia, forças e
+/// virial. No fio as unidades são Hartree e Bohr; a conversão de Ry é feita aqui.
+///
+/// `template` fornece espécies e ordem dos átomos; célula e posições vêm do servidor.
+/// Retorna o número de configurações calculadas quando o servidor envia EXIT.
+pub fn run_client<S: Read + Write, C: ForceCalculator>(
+    mut stream: S,
+    template: &Structure,
+    calculator: &mut C,
+) -> Result<usize, IpiError> {
+    let mut result: Option<ForceResult> = None;
+    let mut n_steps = 0;
+
+    loop {
+        let header = read_header(&mut stream)?;
+        match header.as_str() {
+            "STATUS" => {
+                let status = if result.is_some() { "HAVEDATA" } else { "READY" };
+                write_header(&mut stream, status)?;
+            }
+            "INIT" => {
+                let _bead = read_i32(&mut stream)?;
+                let len = read_i32(&mut stream)?.max(0) as usize;
+                let mut init = vec![0u8; len];
+                stream.read_exact(&mut init)?;
+            }
+            "POSDATA" => {
+                let cell = read_matrix(&mut stream)?;
+                let _inverse = read_matrix(&mut stream)?;
+                let n_atoms = read_i32(&mut stream)?.max(0) as usize;
+                if n_atoms != template.atoms.len() {
+                    return Err(IpiError::AtomCountMismatch(n_atoms, template.atoms.len()));
+                }
+                let positions = read_f64s(&mut stream, 3 * n_atoms)?;
+
+                let structure = Structure {
+                    lattice: Lattice { vectors: cell },
+                    species: template.species.clone(),
+                    atoms: template.atoms.iter().enumerate()
+                        .map(|(i, atom)| Atom {
+                            position: Vector3::new(positions[3 * i], positions[3 * i + 1], positions[3 * i + 2]),
+                            ..atom.clone()
+                        })
+                        .collect(),
+                };
+                result = Some(calculator.compute(&structure).map_err(IpiError::Calculator)?);
+                n_steps += 1;
+            }
+            "GETFORCE" => {
+                let r = result.take().ok_or(IpiError::NoData)?;
+                write_header(&mut stream, "FORCEREADY")?;
+                write_f64s(&mut stream, &[r.energy * RY_TO_HA])?;
+                stream.write_all(&(r.forces.len() as i32).to_le_bytes())?;
+                let forces: Vec<f64> = r.forces.iter().flat_map(|f| [f.x, f.y, f.z]).map(|f| f * RY_TO_HA).collect();
+                write_f64s(&mut stream, &forces)?;
+                // Virial^T em ordem C = ordem por colunas do nalgebra
+                let virial: Vec<f64> = r.virial.iter().map(|v| v * RY_TO_HA).collect();
+                write_f64s(&mut stream, &virial)?;
+                stream.write_all(&0i32.to_le_bytes())?; // Sem string extra
+                stream.flush()?;
+            }
+            "EXIT" => {
+                log::info!("i-PI: servidor encerrou após {} configurações", n_steps);
+                return Ok(n_steps);
+            }
+            other => return Err(IpiError::UnexpectedMessage(other.to_string())),
+        }
+    }
+}
+
+/// Conecta em `host:port` (modo inet do ASE/i-PI).
+pub fn connect_inet(host: &str, port: u16) -> Result<TcpStream, IpiError> {
+    let stream = TcpStream::connect((host, port))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// Conecta ao socket Unix `/tmp/ipi_<name>` (modo unixsocket do ASE/i-PI).
+#[cfg(unix)]
+pub fn connect_unix(name: &str) -> Result<std::os::unix::net::UnixStream, IpiError> {
+    Ok(std::os::unix::net::UnixStream::connect(format!("/tmp/ipi_{}", name))?)
+}
+
+fn read_header<R: Read>(r: &mut R) -> Result<String, IpiError> {
+    let mut buf = [0u8; HEADER_LEN];
+    r.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).trim().to_string())
+}
+
+fn write_header<W: Write>(w: &mut W, msg: &str) -> Result<(), IpiError> {
+    w.write_all(format!("{:<width$}", msg, width = HEADER_LEN).as_bytes())?;
+    w.flush()?;
+    Ok(())
+}
+
+fn read_i32<R: Read>(r: &mut R) -> Result<i32, IpiError> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_f64s<R: Read>(r: &mut R, n: usize) -> Result<Vec<f64>, IpiError> {
+    let mut buf = vec![0u8; 8 * n];
+    r.read_exact(&mut buf)?;
+    Ok(buf.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect())
+}
+
+fn write_f64s<W: Write>(w: &mut W, values: &[f64]) -> Result<(), IpiError> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    w.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Matriz 3x3 enviada como h^T em ordem C: a linha i do fio é a componente i dos
+/// três vetores, então as colunas da matriz lida são a1, a2, a3 (Bohr).
+fn read_matrix<R: Read>(r: &mut R) -> Result<Matrix3<f64>, IpiError> {
+    Ok(Matrix3::from_row_slice(&read_f64s(r, 9)?))
+}
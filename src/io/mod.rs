@@ -1 +1,12 @@
-pub mod upf;
\ No newline at end of file
+pub mod upf;
+pub mod results;
+pub mod input;
+pub mod cube;
+pub mod qe_density;
+pub mod pymatgen;
+pub mod ipi;
+pub mod report;
+pub mod checkpoint;
+pub mod pseudo_library;
+#[cfg(feature = "network")]
+pub mod pseudo_fetch;
\ No newline at end of file
@@ -0,0 +1,138 @@
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Diretório de cache (padrão: `$HOME/.cache/bravie/pseudos`).
+pub const PSEUDO_CACHE_ENV: &str = "BRAVIE_PSEUDO_CACHE";
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+    #[error("Erro de E/S no cache de pseudos: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Erro no download de {0}: {1}")]
+    Http(String, String),
+    #[error("Manifesto inválido: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("Elemento '{0}' não consta na tabela '{1}'")]
+    UnknownElement(String, String),
+    #[error("Checksum inválido para {0}: esperado {1}, obtido {2}")]
+    ChecksumMismatch(String, String, String),
+    #[error("Nome '{0}' do manifesto não é um nome de arquivo simples (sem '..', separadores ou caminho absoluto)")]
+    UnsafeName(String),
+}
+
+/// Tabela de pseudopotenciais (ex: SSSP efficiency, PseudoDojo) descrita em JSON:
+///
+/// ```json
+/// { "name": "sssp-efficiency-1.3", "base_url": "https://...",
+///   "entries": [ { "element": "Si", "file": "Si.pbe-n-rrkjus_psl.1.0.0.UPF", "sha256": "..." } ] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct PseudoManifest {
+    pub name: String,
+    pub base_url: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestEntry {
+    pub element: String,
+    pub file: String,
+    pub sha256: String,
+}
+
+impl PseudoManifest {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FetchError> {
+        let manifest: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Confere que `name` e os `file` das entradas são nomes simples: vêm de um JSON
+    /// remoto e viram caminhos dentro do cache.
+    pub fn validate(&self) -> Result<(), FetchError> {
+        plain_file_name(&self.name)?;
+        self.entries.iter().try_for_each(|e| plain_file_name(&e.file).map(|_| ()))
+    }
+
+    pub fn entry(&self, element: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|e| e.element.eq_ignore_ascii_case(element))
+    }
+}
+
+/// Baixa UPFs de uma tabela para o cache local, validando o SHA-256.
+/// O diretório da tabela pode ser somado a uma `PseudoLibrary` com `add_dir`.
+#[derive(Debug, Clone)]
+pub struct PseudoFetcher {
+    pub cache_dir: PathBuf,
+}
+
+impl PseudoFetcher {
+    pub fn new<P: AsRef<Path>>(cache_dir: P) -> Self {
+        Self { cache_dir: cache_dir.as_ref().to_path_buf() }
+    }
+
+    /// Cache em `BRAVIE_PSEUDO_CACHE` ou `$HOME/.cache/bravie/pseudos`.
+    pub fn from_env() -> Self {
+        let dir = env::var_os(PSEUDO_CACHE_ENV)
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache/bravie/pseudos")))
+            .unwrap_or_else(|| PathBuf::from(".bravie-pseudos"));
+        Self::new(dir)
+    }
+
+    pub fn table_dir(&self, manifest: &PseudoManifest) -> Result<PathBuf, FetchError> {
+        Ok(self.cache_dir.join(plain_file_name(&manifest.name)?))
+    }
+
+    /// Caminho local do UPF de `element`, baixando-o se ausente ou corrompido.
+    pub fn fetch(&self, manifest: &PseudoManifest, element: &str) -> Result<PathBuf, FetchError> {
+        let entry = manifest.entry(element)
+            .ok_or_else(|| FetchError::UnknownElement(element.to_string(), manifest.name.clone()))?;
+        let dir = self.table_dir(manifest)?;
+        let path = dir.join(plain_file_name(&entry.file)?);
+
+        if let Ok(bytes) = fs::read(&path) {
+            if sha256_hex(&bytes).eq_ignore_ascii_case(&entry.sha256) {
+                return Ok(path);
+            }
+            log::warn!("Checksum de {} não confere, baixando novamente", path.display());
+        }
+
+        let url = format!("{}/{}", manifest.base_url.trim_end_matches('/'), entry.file);
+        log::info!("Baixando {}", url);
+        let bytes = ureq::get(&url)
+            .call()
+            .and_then(|mut r| r.body_mut().read_to_vec())
+            .map_err(|e| FetchError::Http(url.clone(), e.to_string()))?;
+
+        let digest = sha256_hex(&bytes);
+        if !digest.eq_ignore_ascii_case(&entry.sha256) {
+            return Err(FetchError::ChecksumMismatch(entry.file.clone(), entry.sha256.clone(), digest));
+        }
+
+        fs::create_dir_all(&dir)?;
+        fs::write(&path, &bytes)?;
+        Ok(path)
+    }
+
+    /// Baixa os UPFs de todos os `elements`.
+    pub fn fetch_all(&self, manifest: &PseudoManifest, elements: &[&str]) -> Result<Vec<PathBuf>, FetchError> {
+        elements.iter().map(|el| self.fetch(manifest, el)).collect()
+    }
+}
+
+/// `name` se for um único componente de caminho normal (rejeita "", ".", "..", "a/b", "/x", "C:\\x").
+fn plain_file_name(name: &str) -> Result<&str, FetchError> {
+    let mut components = Path::new(name).components();
+    let simple = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+        && !name.contains(['/', '\\']);
+    if simple { Ok(name) } else { Err(FetchError::UnsafeName(name.to_string())) }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,81 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dft::xc::XcFunctional;
+use crate::io::upf::Header;
+
+/// Variável de ambiente com os diretórios de pseudopotenciais (separados por ':').
+pub const PSEUDO_DIR_ENV: &str = "BRAVIE_PSEUDO_DIR";
+
+/// Resolve espécie -> arquivo UPF procurando em diretórios configurados,
+/// comparando o elemento e (opcionalmente) o funcional do PP_HEADER.
+#[derive(Debug, Clone, Default)]
+pub struct PseudoLibrary {
+    pub dirs: Vec<PathBuf>,
+    pub functional: Option<XcFunctional>, // None aceita qualquer um
+}
+
+impl PseudoLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Biblioteca com os diretórios de `BRAVIE_PSEUDO_DIR`.
+    pub fn from_env() -> Self {
+        let dirs = env::var_os(PSEUDO_DIR_ENV)
+            .map(|v| env::split_paths(&v).collect())
+            .unwrap_or_default();
+        Self { dirs, functional: None }
+    }
+
+    pub fn add_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn functional(mut self, functional: XcFunctional) -> Self {
+        self.functional = Some(functional);
+        self
+    }
+
+    /// Primeiro UPF compatível com `element`. Arquivos cujo nome começa pelo símbolo
+    /// (ex: `Si.pbe-n-rrkjus_psl.1.0.0.UPF`) são inspecionados antes dos demais.
+    pub fn resolve(&self, element: &str) -> Option<PathBuf> {
+        for dir in &self.dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                log::debug!("Diretório de pseudos inacessível: {}", dir.display());
+                continue;
+            };
+            let mut files: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("upf")))
+                .collect();
+            files.sort_by_key(|p| (!name_starts_with_element(p, element), p.clone()));
+
+            for path in files {
+                match Header::from_file(&path) {
+                    Ok(header) if self.matches(&header, element) => return Some(path),
+                    Ok(_) => {}
+                    Err(e) => log::debug!("Ignorando {}: {}", path.display(), e),
+                }
+            }
+        }
+        None
+    }
+
+    fn matches(&self, header: &Header, element: &str) -> bool {
+        header.element.eq_ignore_ascii_case(element)
+            && self.functional.is_none_or(|f| f.matches_upf(&header.functional))
+    }
+}
+
+fn name_starts_with_element(path: &Path, element: &str) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let n = element.len();
+    name.len() > n
+        && name[..n].eq_ignore_ascii_case(element)
+        && !name.as_bytes()[n].is_ascii_alphabetic()
+}
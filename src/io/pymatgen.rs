@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::Path;
+use nalgebra::{Matrix3, Vector3};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::structure::{Species, Structure, StructureError};
+use crate::utils::constants::{ANGSTROM_TO_BOHR, BOHR_TO_ANGSTROM};
+
+#[derive(Error, Debug)]
+pub enum PymatgenError {
+    #[error("Erro de leitura/escrita do JSON pymatgen: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON pymatgen inválido: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Sítio {0} com ocupação parcial ou desordem ({1}); só estruturas ordenadas são suportadas")]
+    Disordered(usize, String),
+    #[error("Erro na estrutura: {0}")]
+    Structure(#[from] StructureError),
+}
+
+/// Estrutura no esquema de `pymatgen.core.Structure.as_dict()` (Å, frações em `abc`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PymatgenStructure {
+    #[serde(rename = "@module", default = "default_module")]
+    pub module: String,
+    #[serde(rename = "@class", default = "default_class")]
+    pub class: String,
+    #[serde(default)]
+    pub charge: f64,
+    pub lattice: PymatgenLattice,
+    #[serde(default)]
+    pub properties: serde_json::Value,
+    pub sites: Vec<PymatgenSite>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PymatgenLattice {
+    pub matrix: [[f64; 3]; 3], // Linhas = a1, a2, a3 (Å)
+    #[serde(default = "default_pbc")]
+    pub pbc: [bool; 3],
+    // Redundantes no pymatgen; escritos para compatibilidade, ignorados na leitura
+    #[serde(default)]
+    pub a: f64,
+    #[serde(default)]
+    pub b: f64,
+    #[serde(default)]
+    pub c: f64,
+    #[serde(default)]
+    pub alpha: f64,
+    #[serde(default)]
+    pub beta: f64,
+    #[serde(default)]
+    pub gamma: f64,
+    #[serde(default)]
+    pub volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PymatgenSite {
+    pub species: Vec<PymatgenSpecie>,
+    pub abc: [f64; 3],
+    #[serde(default)]
+    pub xyz: [f64; 3], // Å
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub properties: serde_json::Value,
+}
+
+impl PymatgenSite {
+    /// Componentes congeladas a partir de `selective_dynamics` (true = livre, convenção do VASP).
+    pub fn fixed(&self) -> [bool; 3] {
+        let flag = |i: usize| self.properties.get("selective_dynamics")
+            .and_then(|sd| sd.get(i))
+            .and_then(|v| v.as_bool())
+            .is_some_and(|free| !free);
+        [flag(0), flag(1), flag(2)]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PymatgenSpecie {
+    pub element: String,
+    #[serde(default = "default_occu")]
+    pub occu: f64,
+}
+
+fn default_module() -> String {
+    "pymatgen.core.structure".to_string()
+}
+
+fn default_class() -> String {
+    "Structure".to_string()
+}
+
+fn default_pbc() -> [bool; 3] {
+    [true; 3]
+}
+
+fn default_occu() -> f64 {
+    1.0
+}
+
+impl PymatgenStructure {
+    pub fn from_json(content: &str) -> Result<Self, PymatgenError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, PymatgenError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, PymatgenError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), PymatgenError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Converte para Bohr. Uma espécie por elemento, na ordem de aparição; Z, massa e
+    /// pseudo ficam vazios (resolvidos pela biblioteca de pseudos, como no input TOML).
+    pub fn to_structure(&self) -> Result<Structure, PymatgenError> {
+        let row = |i: usize| self.lattice.matrix[i].map(|x| x * ANGSTROM_TO_BOHR);
+        let mut builder = Structure::builder().lattice(row(0), row(1), row(2));
+        let vectors = Matrix3::from_columns(&[
+            Vector3::from(row(0)),
+            Vector3::from(row(1)),
+            Vector3::from(row(2)),
+        ]);
+
+        let mut elements: Vec<String> = Vec::new();
+        for (i, site) in self.sites.iter().enumerate() {
+            let element = match site.species.as_slice() {
+                [sp] if (sp.occu - 1.0).abs() < 1e-8 => base_element(&sp.element),
+                other => {
+                    let desc = other.iter().map(|s| format!("{}:{}", s.element, s.occu)).collect::<Vec<_>>().join(", ");
+                    return Err(PymatgenError::Disordered(i, desc));
+                }
+            };
+            let id = match elements.iter().position(|e| *e == element) {
+                Some(id) => id,
+                None => {
+                    elements.push(element.clone());
+                    builder = builder.add_species(Species {
+                        id: elements.len() - 1,
+                        element,
+                        atomic_number: 0,
+                        mass: 0.0,
+                        pseudo_path: String::new(),
+                    });
+                    elements.len() - 1
+                }
+            };
+            let pos = vectors * Vector3::from(site.abc);
+            builder = builder.add_fixed_atom([pos.x, pos.y, pos.z], id, site.fixed());
+        }
+        Ok(builder.build()?)
+    }
+
+    pub fn from_structure(structure: &Structure) -> Self {
+        let v = &structure.lattice.vectors;
+        let a_i = |i: usize| v.column(i).into_owned() * BOHR_TO_ANGSTROM;
+        let (a1, a2, a3) = (a_i(0), a_i(1), a_i(2));
+        let angle = |x: &Vector3<f64>, y: &Vector3<f64>| x.angle(y).to_degrees();
+        let inv = v.try_inverse();
+
+        let sites = structure.atoms.iter()
+            .map(|atom| {
+                let element = structure.species.iter()
+                    .find(|s| s.id == atom.species_id)
+                    .map(|s| s.element.clone())
+                    .unwrap_or_else(|| "X".to_string());
+                let abc = inv.map_or(Vector3::zeros(), |m| m * atom.position);
+                let xyz = atom.position * BOHR_TO_ANGSTROM;
+                PymatgenSite {
+                    species: vec![PymatgenSpecie { element: element.clone(), occu: 1.0 }],
+                    abc: [abc.x, abc.y, abc.z],
+                    xyz: [xyz.x, xyz.y, xyz.z],
+                    label: Some(element),
+                    properties: if atom.is_fixed() {
+                        let free = atom.fixed.map(|f| !f);
+                        serde_json::json!({ "selective_dynamics": free })
+                    } else {
+                        serde_json::json!({})
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            module: default_module(),
+            class: default_class(),
+            charge: 0.0,
+            lattice: PymatgenLattice {
+                matrix: [[a1.x, a1.y, a1.z], [a2.x, a2.y, a2.z], [a3.x, a3.y, a3.z]],
+                pbc: default_pbc(),
+                a: a1.norm(),
+                b: a2.norm(),
+                c: a3.norm(),
+                alpha: angle(&a2, &a3),
+                beta: angle(&a1, &a3),
+                gamma: angle(&a1, &a2),
+                volume: structure.lattice.volume() * BOHR_TO_ANGSTROM.powi(3),
+            },
+            properties: serde_json::json!({}),
+            sites,
+        }
+    }
+}
+
+/// Símbolo do elemento sem estado de oxidação ("Fe2+" -> "Fe").
+fn base_element(symbol: &str) -> String {
+    symbol.chars().take_while(|c| c.is_ascii_alphabetic()).collect()
+}
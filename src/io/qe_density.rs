@@ -0,0 +1,218 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use ndarray::Array3;
+use nalgebra::{Matrix3, Vector3};
+use num_complex::Complex64;
+use thiserror::Error;
+
+use crate::core::fft::FftGrid;
+use crate::core::field::DensityField;
+use crate::core::structure::Structure;
+use crate::core::structure_factors::fft_frequency;
+use crate::dft::error::DftError;
+
+#[derive(Error, Debug)]
+pub enum QeDensityError {
+    #[error("Erro de leitura/escrita da densidade QE: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Arquivo de densidade QE inválido: {0}")]
+    Format(String),
+    #[error("Vetores recíprocos do arquivo não correspondem à célula da simulação.")]
+    LatticeMismatch,
+    #[error("Vetor G {0:?} fora do grid FFT {1:?}.")]
+    OutsideGrid([i32; 3], [usize; 3]),
+    #[error("Erro na FFT: {0}")]
+    Fft(#[from] DftError),
+}
+
+/// Tolerância relativa na comparação dos vetores recíprocos.
+const LATTICE_TOLERANCE: f64 = 1e-5;
+
+/// Densidade no formato `charge-density.dat` do pw.x (sem HDF5): registros Fortran
+/// sequenciais little-endian, como em `io_base::write_rhog`:
+///
+/// 1. gamma_only (logical), ngm_g, nspin
+/// 2. b1, b2, b3 (unidades de 2π/alat)
+/// 3. índices de Miller (3 x ngm_g)
+/// 4. ρ(G) por componente de spin (total e, com nspin = 2, magnetização)
+///
+/// ρ(G) são os coeficientes de ρ(r) = Σ_G ρ(G) e^{iG·r} (e/Bohr^3).
+/// Com gamma_only só metade dos G é guardada (ρ(-G) = ρ(G)*).
+#[derive(Debug, Clone)]
+pub struct QeChargeDensity {
+    pub gamma_only: bool,
+    pub b: [[f64; 3]; 3], // 2π/alat
+    pub millers: Vec<[i32; 3]>,
+    pub rho_g: Vec<Vec<Complex64>>, // [spin][G]
+}
+
+impl QeChargeDensity {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, QeDensityError> {
+        let mut r = BufReader::new(File::open(path)?);
+
+        let header = read_record(&mut r)?;
+        if header.len() != 12 {
+            return Err(QeDensityError::Format(format!("cabeçalho com {} bytes, esperado 12", header.len())));
+        }
+        let gamma_only = i32_at(&header, 0) != 0;
+        let ngm = i32_at(&header, 4).max(0) as usize;
+        let nspin = i32_at(&header, 8).max(0) as usize;
+
+        let b_rec = read_record(&mut r)?;
+        if b_rec.len() != 72 {
+            return Err(QeDensityError::Format(format!("registro dos vetores b com {} bytes", b_rec.len())));
+        }
+        let mut b = [[0.0; 3]; 3];
+        for (i, v) in b.iter_mut().flatten().enumerate() {
+            *v = f64_at(&b_rec, 8 * i);
+        }
+
+        let m_rec = read_record(&mut r)?;
+        if m_rec.len() != 12 * ngm {
+            return Err(QeDensityError::Format(format!("{} índices de Miller, esperado {}", m_rec.len() / 12, ngm)));
+        }
+        let millers = (0..ngm)
+            .map(|g| [i32_at(&m_rec, 12 * g), i32_at(&m_rec, 12 * g + 4), i32_at(&m_rec, 12 * g + 8)])
+            .collect();
+
+        let mut rho_g = Vec::with_capacity(nspin);
+        for _ in 0..nspin {
+            let rec = read_record(&mut r)?;
+            if rec.len() != 16 * ngm {
+                return Err(QeDensityError::Format(format!("ρ(G) com {} bytes, esperado {}", rec.len(), 16 * ngm)));
+            }
+            rho_g.push((0..ngm).map(|g| Complex64::new(f64_at(&rec, 16 * g), f64_at(&rec, 16 * g + 8))).collect());
+        }
+
+        Ok(Self { gamma_only, b, millers, rho_g })
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), QeDensityError> {
+        let mut w = BufWriter::new(File::create(path)?);
+        let ngm = self.millers.len() as i32;
+
+        let mut header = Vec::with_capacity(12);
+        header.extend_from_slice(&(self.gamma_only as i32).to_le_bytes());
+        header.extend_from_slice(&ngm.to_le_bytes());
+        header.extend_from_slice(&(self.rho_g.len() as i32).to_le_bytes());
+        write_record(&mut w, &header)?;
+
+        let b: Vec<u8> = self.b.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        write_record(&mut w, &b)?;
+
+        let m: Vec<u8> = self.millers.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        write_record(&mut w, &m)?;
+
+        for spin in &self.rho_g {
+            let rec: Vec<u8> = spin.iter().flat_map(|c| [c.re.to_le_bytes(), c.im.to_le_bytes()]).flatten().collect();
+            write_record(&mut w, &rec)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Coeficientes ρ(G) de `rho` dentro da esfera |G|² <= `ecut_rho` (Ry), com alat = |a1|.
+    pub fn from_density(rho: &DensityField, fft: &mut FftGrid, ecut_rho: f64) -> Result<Self, QeDensityError> {
+        let rho_g = rho.to_gspace(fft)?;
+        let recip = rho.lattice.reciprocal();
+        let alat = rho.lattice.vectors.column(0).norm();
+        let [nx, ny, nz] = rho.dims();
+
+        let mut millers = Vec::new();
+        let mut coeffs = Vec::new();
+        for ((i, j, k), c) in rho_g.indexed_iter() {
+            let m = [fft_frequency(i, nx), fft_frequency(j, ny), fft_frequency(k, nz)];
+            let g = recip * Vector3::new(m[0] as f64, m[1] as f64, m[2] as f64);
+            if g.norm_squared() <= ecut_rho {
+                millers.push(m);
+                coeffs.push(*c);
+            }
+        }
+
+        let scale = alat / (2.0 * std::f64::consts::PI);
+        let col = |i: usize| [recip[(0, i)] * scale, recip[(1, i)] * scale, recip[(2, i)] * scale];
+        Ok(Self { gamma_only: false, b: [col(0), col(1), col(2)], millers, rho_g: vec![coeffs] })
+    }
+
+    /// ρ(r) total no grid `dims` de `structure`. Confere que os vetores b do arquivo
+    /// são os da célula (a menos do fator alat, desconhecido aqui).
+    pub fn to_density(&self, structure: &Structure, fft: &mut FftGrid) -> Result<DensityField, QeDensityError> {
+        self.check_lattice(&structure.lattice.reciprocal())?;
+        let total = self.rho_g.first().ok_or_else(|| QeDensityError::Format("sem componentes de ρ(G)".to_string()))?;
+        let size = fft.size;
+        let [nx, ny, nz] = size;
+
+        let wrap = |m: i32, n: usize| -> Option<usize> {
+            let half = (n / 2) as i32;
+            (m.abs() <= half).then(|| m.rem_euclid(n as i32) as usize)
+        };
+        let idx = |m: [i32; 3]| -> Result<(usize, usize, usize), QeDensityError> {
+            match (wrap(m[0], nx), wrap(m[1], ny), wrap(m[2], nz)) {
+                (Some(i), Some(j), Some(k)) => Ok((i, j, k)),
+                _ => Err(QeDensityError::OutsideGrid(m, size)),
+            }
+        };
+
+        fft.buffer.fill(Complex64::new(0.0, 0.0));
+        for (m, c) in self.millers.iter().zip(total) {
+            let pos = idx(*m)?;
+            fft.buffer[pos] = *c;
+            if self.gamma_only {
+                let neg = idx([-m[0], -m[1], -m[2]])?;
+                fft.buffer[neg] = c.conj();
+            }
+        }
+
+        // ifft normaliza por 1/N
+        fft.inverse_in_place()?;
+        let scale = (nx * ny * nz) as f64;
+        let data: Array3<f64> = fft.buffer.mapv(|c| c.re * scale);
+        Ok(DensityField::new(structure.lattice.clone(), data))
+    }
+
+    /// Compara as direções e razões de módulo dos b_i com a recíproca da célula.
+    fn check_lattice(&self, recip: &Matrix3<f64>) -> Result<(), QeDensityError> {
+        let file = Matrix3::from_columns(&[
+            Vector3::from(self.b[0]),
+            Vector3::from(self.b[1]),
+            Vector3::from(self.b[2]),
+        ]);
+        let scale = recip.column(0).norm() / file.column(0).norm();
+        if !scale.is_finite() || ((file * scale) - recip).norm() > LATTICE_TOLERANCE * recip.norm() {
+            return Err(QeDensityError::LatticeMismatch);
+        }
+        Ok(())
+    }
+}
+
+fn read_record<R: Read>(r: &mut R) -> Result<Vec<u8>, QeDensityError> {
+    let mut marker = [0u8; 4];
+    r.read_exact(&mut marker)?;
+    let len = u32::from_le_bytes(marker) as usize;
+    let mut data = vec![0u8; len];
+    r.read_exact(&mut data)?;
+    r.read_exact(&mut marker)?;
+    if u32::from_le_bytes(marker) as usize != len {
+        return Err(QeDensityError::Format("marcadores de registro Fortran inconsistentes".to_string()));
+    }
+    Ok(data)
+}
+
+fn write_record<W: Write>(w: &mut W, data: &[u8]) -> Result<(), QeDensityError> {
+    let len = u32::try_from(data.len())
+        .map_err(|_| QeDensityError::Format("registro maior que 4 GB".to_string()))?
+        .to_le_bytes();
+    w.write_all(&len)?;
+    w.write_all(data)?;
+    w.write_all(&len)?;
+    Ok(())
+}
+
+fn i32_at(buf: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn f64_at(buf: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
@@ -0,0 +1,257 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use nalgebra::Vector3;
+
+use crate::core::simulation::Simulation;
+use crate::dft::occupations::Occupations;
+use crate::dft::scf::ScfHistory;
+use crate::dft::solver::BandSolverResult;
+use crate::postproc::band_gap::BandEdges;
+use crate::tools::energy_check::EnergyTerms;
+use crate::utils::constants::{HA_TO_EV, RY_TO_HA};
+use crate::utils::timer;
+
+/// Relatório de texto de uma execução (no espírito do .out do pw.x): entradas,
+/// tabela SCF, autovalores por ponto K, decomposição da energia, forças e tempos.
+///
+/// A saída é determinística (ordem e formato fixos) para ser comparada com `diff` em
+/// testes de regressão; os tempos variam entre execuções e só entram com `with_timings`.
+pub struct RunReport<'a> {
+    sim: &'a Simulation,
+    scf: Option<&'a ScfHistory>,
+    bands: Vec<(usize, Vec<f64>)>,
+    energies: Option<EnergyTerms>,
+    total_energy: Option<f64>,
+    forces: Vec<Vector3<f64>>,
+    timings: bool,
+}
+
+impl<'a> RunReport<'a> {
+    pub fn new(sim: &'a Simulation) -> Self {
+        Self {
+            sim,
+            scf: None,
+            bands: Vec::new(),
+            energies: None,
+            total_energy: None,
+            forces: Vec::new(),
+            timings: false,
+        }
+    }
+
+    /// Tabela SCF a partir do histórico do ciclo (os tempos por iteração ficam de fora).
+    pub fn scf_history(mut self, history: &'a ScfHistory) -> Self {
+        self.scf = Some(history);
+        self
+    }
+
+    /// Autovalores do ponto K `k_index`.
+    pub fn add_bands(&mut self, k_index: usize, result: &BandSolverResult) {
+        self.bands.push((k_index, result.eigenvalues.clone()));
+    }
+
+    pub fn energies(mut self, terms: EnergyTerms, total: Option<f64>) -> Self {
+        self.energies = Some(terms);
+        self.total_energy = total;
+        self
+    }
+
+    /// Forças por átomo (Ry/Bohr), na ordem de `structure.atoms`.
+    pub fn forces(mut self, forces: Vec<Vector3<f64>>) -> Self {
+        self.forces = forces;
+        self
+    }
+
+    /// Inclui a tabela de `utils::timer` (torna o arquivo não determinístico).
+    pub fn with_timings(mut self, enabled: bool) -> Self {
+        self.timings = enabled;
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.write_header(&mut out);
+        self.write_scf(&mut out);
+        self.write_bands(&mut out);
+        self.write_band_edges(&mut out);
+        self.write_energies(&mut out);
+        self.write_forces(&mut out);
+        if self.timings {
+            let _ = writeln!(out);
+            out.push_str(&timer::report());
+        }
+        out
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        fs::write(path, self.render())
+    }
+
+    fn write_header(&self, out: &mut String) {
+        let sim = self.sim;
+        let s = &sim.structure;
+        let _ = writeln!(out, "Bravie v{}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(out, "Unidades: Bohr, Rydberg");
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "--- Estrutura ---");
+        let _ = writeln!(out, "Volume da célula   : {:>16.6} Bohr^3", s.lattice.volume());
+        for i in 0..3 {
+            let a = s.lattice.vectors.column(i);
+            let _ = writeln!(out, "a{}                 : {:>14.8} {:>14.8} {:>14.8}", i + 1, a[0], a[1], a[2]);
+        }
+        let _ = writeln!(out, "Átomos             : {}", s.atoms.len());
+        for (ia, atom) in s.atoms.iter().enumerate() {
+            let element = s.species.iter()
+                .find(|sp| sp.id == atom.species_id)
+                .map_or("X", |sp| sp.element.as_str());
+            let r = &atom.position;
+            let _ = writeln!(out, "{:>5} {:<3} {:>14.8} {:>14.8} {:>14.8}", ia + 1, element, r.x, r.y, r.z);
+        }
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "--- Pseudopotenciais ---");
+        let mut ids: Vec<&usize> = sim.pseudos.keys().collect();
+        ids.sort();
+        for id in ids {
+            let h = &sim.pseudos[id].header;
+            let _ = writeln!(
+                out,
+                "Espécie {:>3}: {:<3} Z_val = {:>6.2}  tipo {:<4} funcional {}",
+                id, h.element, h.z_valence, h.pseudo_type, h.functional
+            );
+        }
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "--- Parâmetros ---");
+        let _ = writeln!(out, "Ecut (funções)     : {:>12.4} Ry", sim.ecut);
+        let _ = writeln!(out, "Ecut (densidade)   : {:>12.4} Ry", sim.ecut_rho);
+        let [nx, ny, nz] = sim.fft_grid.size;
+        let _ = writeln!(out, "Grid FFT           : {} x {} x {}", nx, ny, nz);
+        if sim.smooth_fft.size != sim.fft_grid.size {
+            let [sx, sy, sz] = sim.smooth_fft.size;
+            let _ = writeln!(out, "Grid suave (ψ)     : {} x {} x {}", sx, sy, sz);
+        }
+        let _ = writeln!(out, "Bandas             : {}", sim.n_bands);
+        let _ = writeln!(out, "Funcional XC       : {}", sim.xc.map_or("desconhecido".to_string(), |f| f.to_string()));
+        let _ = writeln!(out, "Precisão de ψ      : {:?}", sim.precision);
+        let _ = writeln!(out, "Coulomb            : {}", sim.coulomb);
+        let _ = writeln!(out, "Pontos K           : {}", sim.k_grid.k_points.len());
+        for (ik, (kp, basis)) in sim.k_grid.k_points.iter().zip(&sim.bases).enumerate() {
+            let _ = writeln!(
+                out,
+                "{:>5} {:>12.8} {:>12.8} {:>12.8}  peso {:>10.8}  NPW {:>7}",
+                ik + 1, kp.coord[0], kp.coord[1], kp.coord[2], kp.weight, basis.g_vectors.len()
+            );
+        }
+    }
+
+    fn write_scf(&self, out: &mut String) {
+        let Some(history) = self.scf.filter(|h| !h.iterations.is_empty()) else {
+            return;
+        };
+        let _ = writeln!(out);
+        let _ = writeln!(out, "--- Ciclo SCF ---");
+        let _ = writeln!(out, "{:>5} {:>20} {:>14} {:>14} {:>12}", "iter", "E (Ry)", "ΔE (Ry)", "|Δρ| (e)", "tol. solver");
+        for r in &history.iterations {
+            let _ = writeln!(
+                out,
+                "{:>5} {:>20.10} {:>14.4e} {:>14.4e} {:>12.2e}",
+                r.iteration, r.energy, r.delta_energy, r.density_residual, r.solver_tolerance
+            );
+        }
+    }
+
+    fn write_bands(&self, out: &mut String) {
+        if self.bands.is_empty() {
+            return;
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "--- Autovalores (eV) e ocupações ---");
+        let mut bands: Vec<&(usize, Vec<f64>)> = self.bands.iter().collect();
+        bands.sort_by_key(|(ik, _)| *ik);
+        let occupations = self.occupations();
+        let k_indices: Vec<usize> = bands.iter().map(|(ik, _)| *ik).collect();
+        for ((ik, eigenvalues), occ) in bands.into_iter().zip(&occupations.values) {
+            let _ = match self.sim.k_grid.k_points.get(*ik) {
+                Some(kp) => writeln!(out, "k {:>4} = {:>10.6} {:>10.6} {:>10.6}", ik + 1, kp.coord[0], kp.coord[1], kp.coord[2]),
+                None => writeln!(out, "k {:>4}", ik + 1),
+            };
+            for row in eigenvalues.chunks(8) {
+                let line: Vec<String> = row.iter().map(|e| format!("{:>10.4}", e * RY_TO_HA * HA_TO_EV)).collect();
+                let _ = writeln!(out, "{}", line.join(""));
+            }
+            for row in occ.chunks(8) {
+                let line: Vec<String> = row.iter().map(|f| format!("{:>10.4}", f)).collect();
+                let _ = writeln!(out, "{}", line.join(""));
+            }
+        }
+
+        // Ex: "isolante" com estado degenerado semi-ocupado em Γ
+        let fractional = occupations.fractional(1e-4);
+        if !fractional.is_empty() {
+            let _ = writeln!(out, "Ocupações fracionárias:");
+            for (i, n, f) in fractional {
+                let _ = writeln!(out, "  k {:>4}  banda {:>4}  f = {:.6}", k_indices[i] + 1, n + 1, f);
+            }
+        }
+    }
+
+    /// Ocupações das bandas anexadas (ordenadas por ponto K) com o alargamento da simulação.
+    fn occupations(&self) -> Occupations {
+        let mut bands: Vec<&(usize, Vec<f64>)> = self.bands.iter().collect();
+        bands.sort_by_key(|(ik, _)| *ik);
+        let eigenvalues: Vec<Vec<f64>> = bands.iter().map(|(_, e)| e.clone()).collect();
+        let weights: Vec<f64> = bands.iter()
+            .map(|(ik, _)| self.sim.k_grid.k_points.get(*ik).map_or(0.0, |k| k.weight))
+            .collect();
+        Occupations::compute(&eigenvalues, &weights, self.sim.n_electrons(), self.sim.smearing)
+    }
+
+    fn write_band_edges(&self, out: &mut String) {
+        if self.bands.is_empty() {
+            return;
+        }
+        let mut bands: Vec<&(usize, Vec<f64>)> = self.bands.iter().collect();
+        bands.sort_by_key(|(ik, _)| *ik);
+        let eigenvalues: Vec<Vec<f64>> = bands.iter().map(|(_, e)| e.clone()).collect();
+        let weights: Vec<f64> = bands.iter()
+            .map(|(ik, _)| self.sim.k_grid.k_points.get(*ik).map_or(0.0, |k| k.weight))
+            .collect();
+        let edges = BandEdges::from_eigenvalues(&eigenvalues, &weights, self.sim.n_electrons(), self.sim.smearing);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "--- Bordas de banda ---");
+        out.push_str(&edges.to_string());
+    }
+
+    fn write_energies(&self, out: &mut String) {
+        let Some(terms) = &self.energies else {
+            return;
+        };
+        let _ = writeln!(out);
+        let _ = writeln!(out, "--- Energia (Ry) ---");
+        for (name, value) in terms.iter() {
+            let _ = match value {
+                Some(v) => writeln!(out, "{:<18} : {:>20.10}", name, v),
+                None => writeln!(out, "{:<18} : {:>20}", name, "não calculado"),
+            };
+        }
+        if let Some(total) = self.total_energy {
+            let _ = writeln!(out, "{:<18} : {:>20.10}", "total", total);
+        }
+    }
+
+    fn write_forces(&self, out: &mut String) {
+        if self.forces.is_empty() {
+            return;
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "--- Forças (Ry/Bohr) ---");
+        for (ia, f) in self.forces.iter().enumerate() {
+            let _ = writeln!(out, "{:>5} {:>14.8} {:>14.8} {:>14.8}", ia + 1, f.x, f.y, f.z);
+        }
+        let max = self.forces.iter().map(|f| f.norm()).fold(0.0, f64::max);
+        let _ = writeln!(out, "Força máxima       : {:>14.8}", max);
+    }
+}
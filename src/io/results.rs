@@ -0,0 +1,285 @@
+use std::fs;
+use std::path::Path;
+use nalgebra::{Matrix3, Vector3};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::core::kpoints::PathLabel;
+use crate::core::field::DensityField;
+use crate::core::simulation::Simulation;
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::dft::occupations::{Occupations, Smearing, TOP_BAND_TOLERANCE};
+use crate::dft::scf::ScfHistory;
+use crate::dft::solver::BandSolverResult;
+use crate::postproc::band_gap::BandEdges;
+use crate::postproc::magnetization::{magnetization_summary, spin_density, MagnetizationSummary, DEFAULT_SPHERE_RADIUS};
+use crate::utils::timer;
+
+#[derive(Error, Debug)]
+pub enum ResultsError {
+    #[error("Erro de escrita do arquivo de resultados: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Erro de serialização JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "yaml")]
+    #[error("Erro de serialização YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// Resultados de uma execução em formato serializável (JSON/YAML),
+/// pensado para scripts de pós-processamento em Python.
+/// Unidades internas do Bravie: Bohr e Rydberg.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunResults {
+    pub version: String,
+    pub structure: StructureRecord,
+    pub ecut: f64,
+    pub ecut_rho: f64,
+    pub fft_grid: [usize; 3],
+    pub k_points: Vec<KPointRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path_labels: Vec<PathLabel>, // Ticks do eixo x em caminhos de bandas
+    pub total_charge: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dispersion_energy: Option<f64>, // DFT-D2 (Ry)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dispersion_forces: Vec<[f64; 3]>, // DFT-D2 por átomo (Ry/Bohr)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dispersion_stress: Option<[[f64; 3]; 3]>, // DFT-D2 (Ry/Bohr³)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub forces: Vec<[f64; 3]>, // Forças totais por átomo (Ry/Bohr), ver `set_forces`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stress: Option<[[f64; 3]; 3]>, // Tensor de tensão total (Ry/Bohr³), ver `set_stress`
+    pub bands: Vec<BandsRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fermi_energy: Option<f64>, // Ry, das ocupações (`compute_occupations`/`set_occupations`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub band_edges: Option<BandEdges>, // E_F, VBM/CBM e gaps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scf_history: Option<ScfHistory>, // Convergência por iteração (também em CSV)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnetization: Option<MagnetizationSummary>, // Só com spin polarizado
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<TimingRecord>, // Tempos por fase (`utils::timer`), ver `record_timings`
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureRecord {
+    pub volume: f64,
+    pub lattice: [[f64; 3]; 3], // Linhas = a1, a2, a3
+    pub atoms: Vec<AtomRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AtomRecord {
+    pub element: String,
+    pub position: [f64; 3], // Cartesiano (Bohr)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPointRecord {
+    pub coord: [f64; 3], // Fracionário
+    pub weight: f64,
+    pub npw: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>, // Distância acumulada no caminho de bandas (Bohr⁻¹)
+}
+
+/// Tempo acumulado de uma fase do cálculo.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingRecord {
+    pub name: String,
+    pub calls: usize,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BandsRecord {
+    pub k_index: usize,
+    pub eigenvalues: Vec<f64>, // Ry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub occupations: Vec<f64>, // 0 a 2 por banda (fracionárias com alargamento)
+}
+
+impl RunResults {
+    /// Coleta o estado atual da simulação (estrutura, cutoffs, grid, k-points e carga).
+    pub fn from_simulation(sim: &Simulation) -> Self {
+        let lattice = &sim.structure.lattice.vectors;
+        let column = |i: usize| [lattice[(0, i)], lattice[(1, i)], lattice[(2, i)]];
+
+        let atoms = sim.structure.atoms.iter()
+            .map(|atom| AtomRecord {
+                element: sim.structure.species.iter()
+                    .find(|s| s.id == atom.species_id)
+                    .map(|s| s.element.clone())
+                    .unwrap_or_else(|| "X".to_string()),
+                position: [atom.position.x, atom.position.y, atom.position.z],
+            })
+            .collect();
+
+        let distances = sim.k_grid.distances();
+        let k_points = sim.k_grid.k_points.iter()
+            .zip(&sim.bases)
+            .enumerate()
+            .map(|(ik, (kp, basis))| KPointRecord {
+                coord: kp.coord,
+                weight: kp.weight,
+                npw: basis.g_vectors.len(),
+                distance: distances.map(|d| d[ik]),
+            })
+            .collect();
+
+        let volume = sim.structure.lattice.volume();
+        let dispersion = sim.dispersion_correction().and_then(|r| match r {
+            Ok(d2) => Some(d2),
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        });
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            structure: StructureRecord {
+                volume,
+                lattice: [column(0), column(1), column(2)],
+                atoms,
+            },
+            ecut: sim.ecut,
+            ecut_rho: sim.ecut_rho,
+            fft_grid: sim.fft_grid.size,
+            k_points,
+            path_labels: sim.k_grid.labels().to_vec(),
+            total_charge: sim.rho.total_charge(),
+            dispersion_energy: dispersion.as_ref().map(|d2| d2.energy),
+            dispersion_stress: dispersion.as_ref().map(|d2| matrix_rows(&d2.stress)),
+            dispersion_forces: dispersion.map_or_else(Vec::new, |d2| vector_rows(&d2.forces)),
+            forces: Vec::new(),
+            stress: None,
+            bands: Vec::new(),
+            fermi_energy: None,
+            band_edges: None,
+            scf_history: None,
+            magnetization: None,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Forças totais por átomo (Ry/Bohr), na ordem de `structure.atoms`.
+    pub fn set_forces(&mut self, forces: &[Vector3<f64>]) {
+        self.forces = vector_rows(forces);
+    }
+
+    /// Tensor de tensão total (Ry/Bohr³), com a convenção de `DispersionResult::stress`.
+    pub fn set_stress(&mut self, stress: &Matrix3<f64>) {
+        self.stress = Some(matrix_rows(stress));
+    }
+
+    /// Copia os tempos acumulados em `utils::timer` até agora (chame antes de escrever).
+    pub fn record_timings(&mut self) {
+        self.timings = timer::snapshot().into_iter()
+            .map(|e| TimingRecord { name: e.name.to_string(), calls: e.calls, seconds: e.total.as_secs_f64() })
+            .collect();
+    }
+
+    /// Anexa os autovalores de um ponto K.
+    pub fn add_bands(&mut self, k_index: usize, result: &BandSolverResult) {
+        self.bands.push(BandsRecord {
+            k_index,
+            eigenvalues: result.eigenvalues.clone(),
+            occupations: Vec::new(),
+        });
+    }
+
+    /// Preenche as ocupações de cada banda anexada e devolve E_F (Ry).
+    pub fn compute_occupations(&mut self, n_electrons: f64, smearing: Smearing) -> Option<f64> {
+        if self.bands.is_empty() {
+            return None;
+        }
+        self.bands.sort_by_key(|r| r.k_index);
+        let eigenvalues: Vec<Vec<f64>> = self.bands.iter().map(|r| r.eigenvalues.clone()).collect();
+        let weights: Vec<f64> = self.bands.iter()
+            .map(|r| self.k_points.get(r.k_index).map_or(0.0, |k| k.weight))
+            .collect();
+        let occupations = Occupations::compute(&eigenvalues, &weights, n_electrons, smearing);
+        occupations.warn_if_too_few_bands(TOP_BAND_TOLERANCE);
+        for (record, values) in self.bands.iter_mut().zip(occupations.values) {
+            record.occupations = values;
+        }
+        self.fermi_energy = Some(occupations.fermi_energy);
+        self.fermi_energy
+    }
+
+    /// Copia ocupações já calculadas (ex: `ScfOutcome::occupations`) para as bandas
+    /// anexadas, com `values[k]` na ordem de `k_index`, e guarda E_F.
+    pub fn set_occupations(&mut self, occupations: &Occupations) {
+        self.bands.sort_by_key(|r| r.k_index);
+        for record in &mut self.bands {
+            if let Some(values) = occupations.values.get(record.k_index) {
+                record.occupations = values.clone();
+            }
+        }
+        self.fermi_energy = Some(occupations.fermi_energy);
+    }
+
+    /// Calcula E_F, bordas de banda e gaps sobre as bandas anexadas (chame depois de
+    /// `add_bands` e de uma eventual tesoura).
+    pub fn compute_band_edges(&mut self, n_electrons: f64, smearing: Smearing) -> Option<&BandEdges> {
+        if self.bands.is_empty() {
+            return None;
+        }
+        let mut records: Vec<&BandsRecord> = self.bands.iter().collect();
+        records.sort_by_key(|r| r.k_index);
+        let eigenvalues: Vec<Vec<f64>> = records.iter().map(|r| r.eigenvalues.clone()).collect();
+        let weights: Vec<f64> = records.iter()
+            .map(|r| self.k_points.get(r.k_index).map_or(0.0, |k| k.weight))
+            .collect();
+        self.band_edges = Some(BandEdges::from_eigenvalues(&eigenvalues, &weights, n_electrons, smearing));
+        self.band_edges.as_ref()
+    }
+
+    /// Com densidades de spin [ρ↑, ρ↓] (ex: `DensityBuilder::spin_densities`), calcula a
+    /// magnetização total e absoluta e os momentos atômicos (esferas de
+    /// `DEFAULT_SPHERE_RADIUS`) e os escreve no resumo do log. Com um só canal não faz nada.
+    pub fn add_magnetization(&mut self, structure: &Structure, spin_densities: &[DensityField])
+        -> Result<Option<&MagnetizationSummary>, DftError>
+    {
+        let [up, down] = spin_densities else {
+            return Ok(None);
+        };
+        let m = spin_density(&up.data, &down.data);
+        let summary = magnetization_summary(structure, &m, |_| DEFAULT_SPHERE_RADIUS)?;
+        summary.log(structure);
+        self.magnetization = Some(summary);
+        Ok(self.magnetization.as_ref())
+    }
+
+    pub fn to_json(&self) -> Result<String, ResultsError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<(), ResultsError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> Result<String, ResultsError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn write_yaml<P: AsRef<Path>>(&self, path: P) -> Result<(), ResultsError> {
+        fs::write(path, self.to_yaml()?)?;
+        Ok(())
+    }
+}
+
+fn vector_rows(vectors: &[Vector3<f64>]) -> Vec<[f64; 3]> {
+    vectors.iter().map(|v| [v.x, v.y, v.z]).collect()
+}
+
+fn matrix_rows(m: &Matrix3<f64>) -> [[f64; 3]; 3] {
+    [0, 1, 2].map(|i| [m[(i, 0)], m[(i, 1)], m[(i, 2)]])
+}
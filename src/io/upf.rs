@@ -1,8 +1,13 @@
+use std::f64::consts::PI;
+use std::fmt;
+use ndarray::Array2;
 use roxmltree::Document;
 use std::fs;
 use std::path::Path;
 use thiserror::Error;
 
+use crate::utils::spline::CubicSpline;
+
 #[derive(Error, Debug)]
 pub enum UpfError {
     #[error("Erro de Leitura de Arquivo: {0}")]
@@ -13,16 +18,142 @@ pub enum UpfError {
     MissingField(String),
     #[error("Erro ao converter string para número")]
     ParseNumber,
+    #[error("UPF inválido (modo estrito): {0}")]
+    Invalid(UpfWarning),
+}
+
+/// Problemas encontrados no UPF. No modo tolerante viram avisos; no estrito, `UpfError::Invalid`.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum UpfWarning {
+    #[error("atributo {0} ausente ou ilegível, usando padrão '{1}'")]
+    DefaultedAttribute(String, String),
+    #[error("bloco {0} ausente")]
+    MissingBlock(String),
+    #[error("{0} com {1} pontos, esperado {2} (malha radial)")]
+    LengthMismatch(String, usize, usize),
+    #[error("{0} projetores encontrados, header declara {1}")]
+    ProjectorCount(usize, usize),
+    #[error("{0} orbitais PP_CHI encontrados, header declara {1}")]
+    WavefunctionCount(usize, usize),
+    #[error("PP_DIJ com {0} elementos, esperado {1} (n_beta²)")]
+    DijSize(usize, usize),
+    #[error("D_ij não é simétrica (elemento {0},{1})")]
+    DijNotSymmetric(usize, usize),
+    #[error("malha radial não é crescente no índice {0}")]
+    NonMonotonicMesh(usize),
+    #[error("∫ρ_atom = {0:.4}, difere de z_valence = {1:.4}")]
+    ChargeIntegral(f64, f64),
 }
 
+/// Tolerância relativa na integral de ρ_atom frente a z_valence.
+const CHARGE_TOLERANCE: f64 = 1e-2;
+
+// Malha logarítmica e parâmetros de `Pseudopotential::mock` (Bohr)
+const MOCK_R_MIN: f64 = 1e-4;
+const MOCK_R_MAX: f64 = 50.0;
+const MOCK_DX: f64 = 0.0125;
+const MOCK_CORE_RADIUS: f64 = 0.5;
+const MOCK_DENSITY_ALPHA: f64 = 1.0; // Bohr⁻²
+
 #[derive(Debug, Clone)]
 pub struct Pseudopotential {
     pub header: Header,
     pub mesh: RadialMesh,
     pub local: Vec<f64>,        // Potencial Local V_loc(r)
     pub nonlocal: Vec<BetaFunction>, // Projetores Não-Locais Beta(r)
+    pub pswfc: Vec<AtomicWavefunction>, // Orbitais pseudo-atômicos χ(r) (LCAO, PDOS, DFT+U)
     pub rho_atom: Vec<f64>,     // Densidade Atômica (para chute inicial)
-    pub dij: Vec<f64>,          // Matriz de coeficientes D_ij (Opcional)
+    pub dij: Array2<f64>,       // D_ij (Ry), n_beta x n_beta na ordem de `nonlocal`
+    pub rho_core: Option<Vec<f64>>, // Carga de caroço parcial (NLCC), sem fator 4πr²
+    pub augmentation: Option<Augmentation>, // Ultrasoft/PAW (PP_AUGMENTATION)
+    pub paw: Option<PawData>,        // Dados específicos de PAW (PP_PAW, PP_FULL_WFC)
+    pub spin_orb: Option<SpinOrbit>, // Dados j-dependentes (apenas UPF totalmente relativístico)
+    pub warnings: Vec<UpfWarning>,   // Padrões aplicados durante a leitura tolerante
+    pub units: UnitReport,           // Unidades detectadas no arquivo (dados já normalizados)
+    pub splines: RadialSplines,      // Interpolantes pré-calculados na leitura
+}
+
+/// Splines cúbicas das grandezas radiais usadas em espaço real.
+#[derive(Debug, Clone)]
+pub struct RadialSplines {
+    pub local: CubicSpline,    // V_loc(r) (Ry)
+    pub rho_atom: CubicSpline, // ρ(r) volumétrico (sem 4πr²), apenas r > 0
+}
+
+impl RadialSplines {
+    fn new(mesh: &RadialMesh, local: &[f64], rho_atom: &[f64]) -> Self {
+        let (r_pos, rho): (Vec<f64>, Vec<f64>) = mesh.r.iter().zip(rho_atom)
+            .filter(|(r, _)| **r > 1e-10)
+            .map(|(&r, &p)| (r, p / (4.0 * PI * r * r)))
+            .unzip();
+        Self {
+            local: CubicSpline::new(&mesh.r, local),
+            rho_atom: CubicSpline::new(&r_pos, &rho),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyUnit {
+    Rydberg,
+    Hartree,
+}
+
+/// Origem da unidade de energia em `UnitReport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSource {
+    Header,    // Declarada no arquivo (linha com "unit" no PP_INFO)
+    Heuristic, // Inferida da cauda coulombiana de V_loc
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DensityConvention {
+    Radial,     // 4πr²ρ(r) (padrão UPF)
+    Volumetric, // ρ(r)
+}
+
+/// Convenções detectadas no arquivo. Após a leitura, V_loc está em Ry e ρ_atom inclui
+/// o fator 4πr², independentemente do gerador. D_ij só é convertido quando a unidade
+/// vem do arquivo (`unit_source == Header`): a cauda de V_loc não diz nada sobre ele.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitReport {
+    pub local_unit: EnergyUnit,
+    pub unit_source: UnitSource,
+    pub local_tail: f64, // -r·V_loc(r)/Z no fim da malha (2 em Ry, 1 em Ha)
+    pub dij_converted: bool,
+    pub density: DensityConvention,
+    pub density_charge: f64, // ∫ρ_atom dr após a normalização
+}
+
+impl UnitReport {
+    /// Algum dado precisou ser convertido (ou D_ij ficou com unidade incerta)?
+    pub fn converted(&self) -> bool {
+        self.local_unit != EnergyUnit::Rydberg || self.density != DensityConvention::Radial
+    }
+}
+
+impl fmt::Display for UnitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.local_unit {
+            EnergyUnit::Rydberg => "Ry",
+            EnergyUnit::Hartree => "Ha (convertido para Ry)",
+        };
+        let source = match self.unit_source {
+            UnitSource::Header => "declarada no PP_INFO",
+            UnitSource::Heuristic => "inferida da cauda",
+        };
+        let dij = match (self.local_unit, self.dij_converted) {
+            (EnergyUnit::Rydberg, _) => "",
+            (EnergyUnit::Hartree, true) => ", D_ij convertido para Ry",
+            (EnergyUnit::Hartree, false) => ", D_ij mantido (unidade não declarada)",
+        };
+        let density = match self.density {
+            DensityConvention::Radial => "4πr²ρ",
+            DensityConvention::Volumetric => "ρ (multiplicado por 4πr²)",
+        };
+        write!(f, "V_loc em {} ({}, -r·V/Z = {:.3}){}, ρ_atom como {} (∫ = {:.4})",
+            unit, source, self.local_tail, dij, density, self.density_charge)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +163,14 @@ pub struct Header {
     pub mesh_size: usize,
     pub functional: String,
     pub number_of_proj: usize,
+    pub number_of_wfc: usize,
+    pub pseudo_type: String, // "NC", "SL", "US", "PAW"
+    pub is_ultrasoft: bool,
+    pub is_paw: bool,
+    pub has_so: bool, // Pseudo totalmente relativístico (contém PP_SPIN_ORB)
+    pub core_correction: bool, // Correção não-linear de caroço (contém PP_NLCC)
+    pub wfc_cutoff: f64, // Ecut sugerido pelo autor do pseudo (Ry); 0 se ausente
+    pub rho_cutoff: f64, // Ecut da densidade sugerido (Ry); 0 se ausente
 }
 
 #[derive(Debug, Clone)]
@@ -48,29 +187,146 @@ pub struct BetaFunction {
     pub data: Vec<f64>,             // O projetor em si
 }
 
+/// Funções de aumento Q_ij(r) de ultrasoft/PAW (bloco PP_AUGMENTATION).
+/// Índices de projetor em base 0, na ordem de `nonlocal`.
+#[derive(Debug, Clone)]
+pub struct Augmentation {
+    pub q_with_l: bool,          // Q_ij dependente de l (PP_QIJL) ou único (PP_QIJ)
+    pub q: Array2<f64>,          // q_ij = ∫ Q_ij(r) dr (carga de aumento, define S)
+    pub multipoles: Vec<f64>,    // Multipolos das cargas de compensação (apenas PAW)
+    pub functions: Vec<AugmentationFunction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AugmentationFunction {
+    pub i: usize,
+    pub j: usize,
+    pub l: Option<usize>, // None se q_with_l = F
+    pub data: Vec<f64>,   // Q_ij(r) (inclui r²)
+}
+
+/// Dados PAW: ondas parciais e quantidades all-electron.
+#[derive(Debug, Clone)]
+pub struct PawData {
+    pub core_energy: f64,
+    pub occupations: Vec<f64>,  // Ocupação de cada projetor no átomo de referência
+    pub ae_nlcc: Vec<f64>,      // Carga de caroço all-electron
+    pub ae_vloc: Vec<f64>,      // Potencial local all-electron (Ry)
+    pub ae_wfc: Vec<Vec<f64>>,  // Ondas parciais all-electron φ_i (r·φ)
+    pub ps_wfc: Vec<Vec<f64>>,  // Ondas parciais pseudo φ̃_i (r·φ̃)
+}
+
+impl Header {
+    /// Pseudo com carga de aumento (ultrasoft ou PAW): problema generalizado Hψ = εSψ.
+    pub fn is_augmented(&self) -> bool {
+        self.is_ultrasoft || self.is_paw || matches!(self.pseudo_type.as_str(), "US" | "USPP" | "PAW")
+    }
+}
+
+/// Orbital pseudo-atômico (bloco PP_CHI). `data` = r·χ(r) na malha radial.
+#[derive(Debug, Clone)]
+pub struct AtomicWavefunction {
+    pub index: usize,     // Base 0, na ordem do arquivo
+    pub label: String,    // Ex: "3S", "3P"
+    pub l: usize,
+    pub occupation: f64,
+    pub data: Vec<f64>,
+}
+
+/// Informação de spin-órbita de um UPF totalmente relativístico (bloco `PP_SPIN_ORB`).
+/// Cada projetor beta e cada função de onda atômica ganham o momento angular total j = l ± 1/2.
+/// Só é lido: nenhum Hamiltoniano de spinores usa os termos dependentes de j (sem spin-órbita).
+#[derive(Debug, Clone)]
+pub struct SpinOrbit {
+    pub relbeta: Vec<RelBeta>, // Um por projetor, na mesma ordem de `nonlocal`
+    pub relwfc: Vec<RelWfc>,   // Um por função de onda atômica (PP_CHI)
+}
+
+#[derive(Debug, Clone)]
+pub struct RelBeta {
+    pub index: usize,
+    pub l: i32,
+    pub j: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelWfc {
+    pub index: usize,
+    pub l: i32,
+    pub j: f64,
+    pub nn: usize, // Número quântico principal
+}
+
+impl SpinOrbit {
+    /// Retorna o j do projetor beta com o índice dado (base 0, como em `BetaFunction::index`).
+    pub fn beta_j(&self, index: usize) -> Option<f64> {
+        self.relbeta.get(index).map(|b| b.j)
+    }
+}
+
+impl Header {
+    fn from_node(node: roxmltree::Node, warnings: &mut Vec<UpfWarning>) -> Self {
+        Header {
+            element: attr_or(node, "element", "X".to_string(), warnings),
+            z_valence: attr_or(node, "z_valence", 0.0, warnings),
+            mesh_size: attr_or(node, "mesh_size", 0, warnings),
+            functional: attr_or(node, "functional", "unknown".to_string(), warnings),
+            number_of_proj: attr_or(node, "number_of_proj", 0, warnings),
+            number_of_wfc: attr_or(node, "number_of_wfc", 0, warnings),
+            pseudo_type: node.attribute("pseudo_type").unwrap_or("NC").trim().to_string(),
+            is_ultrasoft: parse_bool(node.attribute("is_ultrasoft").unwrap_or("F")),
+            is_paw: parse_bool(node.attribute("is_paw").unwrap_or("F")),
+            has_so: parse_bool(node.attribute("has_so").unwrap_or("F")),
+            core_correction: parse_bool(node.attribute("core_correction").unwrap_or("F")),
+            wfc_cutoff: optional_attr(node, "wfc_cutoff").unwrap_or(0.0),
+            rho_cutoff: optional_attr(node, "rho_cutoff").unwrap_or(0.0),
+        }
+    }
+
+    /// Lê apenas o PP_HEADER (sem converter as malhas), para inspecionar bibliotecas de pseudos.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, UpfError> {
+        let content = fs::read_to_string(path)?;
+        let doc = Document::parse(&content)?;
+        doc.root_element().children()
+            .find(|n| n.has_tag_name("PP_HEADER"))
+            .map(|n| Header::from_node(n, &mut Vec::new()))
+            .ok_or(UpfError::MissingField("PP_HEADER".into()))
+    }
+}
+
 impl Pseudopotential {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, UpfError> {
         let content = fs::read_to_string(path)?;
         Self::from_str(&content)
     }
 
+    /// Leitura estrita: qualquer atributo ausente ou inconsistência de `validate()` é erro.
+    pub fn from_file_strict<P: AsRef<Path>>(path: P) -> Result<Self, UpfError> {
+        let content = fs::read_to_string(path)?;
+        Self::from_str_strict(&content)
+    }
+
+    pub fn from_str_strict(xml_content: &str) -> Result<Self, UpfError> {
+        let pp = Self::from_str(xml_content)?;
+        match pp.warnings.iter().chain(pp.validate().iter()).next() {
+            Some(w) => Err(UpfError::Invalid(w.clone())),
+            None => Ok(pp),
+        }
+    }
+
+    /// Leitura tolerante: atributos ausentes recebem valores padrão, registrados em `warnings`.
     pub fn from_str(xml_content: &str) -> Result<Self, UpfError> {
         // Parsear o XML
         let doc = Document::parse(xml_content)?;
         let root = doc.root_element();
+        let mut warnings = Vec::new();
 
         // 1. HEADER
         let header_node = root.children()
             .find(|n| n.has_tag_name("PP_HEADER"))
             .ok_or(UpfError::MissingField("PP_HEADER".into()))?;
 
-        let header = Header {
-            element: header_node.attribute("element").unwrap_or("X").to_string(),
-            z_valence: header_node.attribute("z_valence").unwrap_or("0.0").parse().unwrap_or(0.0),
-            mesh_size: header_node.attribute("mesh_size").unwrap_or("0").parse().unwrap_or(0),
-            functional: header_node.attribute("functional").unwrap_or("unknown").to_string(),
-            number_of_proj: header_node.attribute("number_of_proj").unwrap_or("0").parse().unwrap_or(0),
-        };
+        let header = Header::from_node(header_node, &mut warnings);
 
         // 2. MESH (Grid Radial)
         let mesh_node = root.children()
@@ -98,11 +354,9 @@ impl Pseudopotential {
         if let Some(nl_node) = root.children().find(|n| n.has_tag_name("PP_NONLOCAL")) {
             for child in nl_node.children() {
                 if child.tag_name().name().starts_with("PP_BETA") {
-                    let l = child.attribute("angular_momentum")
-                        .unwrap_or("0").parse().unwrap_or(0);
+                    let l = attr_or(child, "angular_momentum", 0, &mut warnings);
                     
-                    let cutoff = child.attribute("cutoff_radius_index")
-                        .unwrap_or("0").parse().unwrap_or(0); // Útil para otimização
+                    let cutoff = attr_or(child, "cutoff_radius_index", 0, &mut warnings); // Útil para otimização
 
                     let data = parse_numbers(child.text().unwrap_or(""))?;
 
@@ -116,35 +370,450 @@ impl Pseudopotential {
             }
         }
 
+        // 4b. ORBITAIS PSEUDO-ATÔMICOS (PP_PSWFC/PP_CHI.n)
+        let mut pswfc = Vec::new();
+        if let Some(wfc_node) = root.children().find(|n| n.has_tag_name("PP_PSWFC")) {
+            for child in wfc_node.children().filter(|n| n.tag_name().name().starts_with("PP_CHI")) {
+                pswfc.push(AtomicWavefunction {
+                    index: pswfc.len(),
+                    label: child.attribute("label").unwrap_or("").trim().to_string(),
+                    l: attr_or(child, "l", 0, &mut warnings),
+                    occupation: attr_or(child, "occupation", 0.0, &mut warnings),
+                    data: parse_numbers(child.text().unwrap_or(""))?,
+                });
+            }
+        }
+
         // 5. RHO ATOM (Densidade de Carga Inicial)
         // Essencial para o primeiro passo do SCF
         let rho_atom = if let Some(rho_node) = root.children().find(|n| n.has_tag_name("PP_RHOATOM")) {
             parse_numbers(rho_node.text().unwrap_or(""))?
         } else {
             // Se não tiver, retorna zeros (arriscado, mas evita crash)
+            warnings.push(UpfWarning::MissingBlock("PP_RHOATOM".into()));
             vec![0.0; header.mesh_size]
         };
 
         // 6. DIJ (Coeficientes de Energia Não-Local)
-        // Alguns arquivos colocam isso explicito. 
-        // Se não existir, assumimos vazio (trataremos como identidade ou zeros depois)
-        let dij = if let Some(dij_node) = root.children().find(|n| n.has_tag_name("PP_DIJ")) {
-             parse_numbers(dij_node.text().unwrap_or(""))?
+        // Matriz n_beta x n_beta (incluindo blocos entre l diferentes), simétrica.
+        // Se não existir ou tiver tamanho errado, usamos zeros.
+        let n_beta = nonlocal.len();
+        let dij_flat = match root.children().find(|n| n.has_tag_name("PP_DIJ")) {
+            Some(dij_node) => parse_numbers(dij_node.text().unwrap_or(""))?,
+            None => Vec::new(),
+        };
+        let dij = if dij_flat.len() == n_beta * n_beta {
+            Array2::from_shape_vec((n_beta, n_beta), dij_flat).expect("Dimensão de D_ij verificada")
+        } else {
+            if n_beta > 0 {
+                warnings.push(UpfWarning::DijSize(dij_flat.len(), n_beta * n_beta));
+            }
+            Array2::zeros((n_beta, n_beta))
+        };
+
+        // 6b. NLCC (carga de caroço parcial)
+        let rho_core = match root.children().find(|n| n.has_tag_name("PP_NLCC")) {
+            Some(nlcc_node) => Some(parse_numbers(nlcc_node.text().unwrap_or(""))?),
+            None => None,
+        };
+        if header.core_correction && rho_core.is_none() {
+            return Err(UpfError::MissingField("PP_NLCC".into()));
+        }
+
+        // 6c. AUMENTO (ultrasoft/PAW)
+        let augmentation = match nl_find(root, "PP_AUGMENTATION") {
+            Some(aug_node) => Some(parse_augmentation(aug_node, n_beta, &mut warnings)?),
+            None => None,
+        };
+        if header.is_augmented() && augmentation.is_none() {
+            return Err(UpfError::MissingField("PP_AUGMENTATION".into()));
+        }
+
+        // 6d. PAW
+        let paw = if header.is_paw || header.pseudo_type == "PAW" {
+            let paw_node = root.children().find(|n| n.has_tag_name("PP_PAW"))
+                .ok_or(UpfError::MissingField("PP_PAW".into()))?;
+            let block = |name: &str| -> Result<Vec<f64>, UpfError> {
+                match paw_node.children().find(|n| n.has_tag_name(name)) {
+                    Some(n) => parse_numbers(n.text().unwrap_or("")),
+                    None => Ok(Vec::new()),
+                }
+            };
+            let full_wfc = |prefix: &str| -> Result<Vec<Vec<f64>>, UpfError> {
+                root.children()
+                    .filter(|n| n.has_tag_name("PP_FULL_WFC"))
+                    .flat_map(|n| n.children())
+                    .filter(|n| n.tag_name().name().starts_with(prefix))
+                    .map(|n| parse_numbers(n.text().unwrap_or("")))
+                    .collect()
+            };
+            Some(PawData {
+                core_energy: attr_or(paw_node, "core_energy", 0.0, &mut warnings),
+                occupations: block("PP_OCCUPATIONS")?,
+                ae_nlcc: block("PP_AE_NLCC")?,
+                ae_vloc: block("PP_AE_VLOC")?,
+                ae_wfc: full_wfc("PP_AEWFC")?,
+                ps_wfc: full_wfc("PP_PSWFC")?,
+            })
+        } else {
+            None
+        };
+
+        // 7. SPIN-ÓRBITA (somente UPF totalmente relativístico)
+        // Os índices no arquivo começam em 1; guardamos em base 0 como os betas.
+        let spin_orb = if let Some(so_node) = root.children().find(|n| n.has_tag_name("PP_SPIN_ORB")) {
+            let mut relbeta = Vec::new();
+            let mut relwfc = Vec::new();
+            for child in so_node.children().filter(|n| n.is_element()) {
+                let name = child.tag_name().name();
+                let index = child.attribute("index")
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .map(|i| i.saturating_sub(1));
+
+                if name.starts_with("PP_RELBETA") {
+                    relbeta.push(RelBeta {
+                        index: index.unwrap_or(relbeta.len()),
+                        l: parse_attr(child, "lll")?,
+                        j: parse_attr(child, "jjj")?,
+                    });
+                } else if name.starts_with("PP_RELWFC") {
+                    relwfc.push(RelWfc {
+                        index: index.unwrap_or(relwfc.len()),
+                        l: parse_attr(child, "lchi")?,
+                        j: parse_attr(child, "jchi")?,
+                        nn: child.attribute("nn").unwrap_or("0").trim().parse().unwrap_or(0),
+                    });
+                }
+            }
+            relbeta.sort_by_key(|b| b.index);
+            relwfc.sort_by_key(|w| w.index);
+            Some(SpinOrbit { relbeta, relwfc })
         } else {
-             Vec::new()
+            None
         };
 
+        if header.has_so && spin_orb.is_none() {
+            return Err(UpfError::MissingField("PP_SPIN_ORB".into()));
+        }
+
+        // 8. UNIDADES: normaliza para Ry e 4πr²ρ
+        let info: String = root.children()
+            .filter(|n| n.has_tag_name("PP_INFO"))
+            .flat_map(|n| n.descendants())
+            .filter_map(|n| if n.is_text() { n.text() } else { None })
+            .collect();
+        let declared = declared_energy_unit(&info);
+        let (units, local, dij, rho_atom) = normalize_units(&header, &mesh, declared, local, dij, rho_atom);
+        let splines = RadialSplines::new(&mesh, &local, &rho_atom);
+
         Ok(Pseudopotential {
             header,
             mesh,
             local,
             nonlocal,
+            pswfc,
+            rho_atom,
+            dij,
+            rho_core,
+            augmentation,
+            paw,
+            spin_orb,
+            warnings,
+            units,
+            splines,
+        })
+    }
+
+    /// Pseudo analítico apenas local, sem arquivo: Coulomb suavizado
+    /// V_loc(r) = -2Z/√(r² + r_c²) (Ry) e ρ_atom gaussiana com carga Z.
+    /// Para testes e exemplos que não podem depender de UPFs no disco; não é transferível.
+    pub fn mock(element: &str, z_valence: f64) -> Self {
+        let n = ((MOCK_R_MAX / MOCK_R_MIN).ln() / MOCK_DX).ceil() as usize + 1;
+        let r: Vec<f64> = (0..n).map(|i| MOCK_R_MIN * (i as f64 * MOCK_DX).exp()).collect();
+        let rab: Vec<f64> = r.iter().map(|ri| ri * MOCK_DX).collect();
+        let mesh = RadialMesh { r, rab };
+
+        let rc2 = MOCK_CORE_RADIUS * MOCK_CORE_RADIUS;
+        let local = mesh.r.iter().map(|ri| -2.0 * z_valence / (ri * ri + rc2).sqrt()).collect();
+        // 4πr² ρ(r), ρ(r) = Z (α/π)^{3/2} e^{-αr²}
+        let alpha = MOCK_DENSITY_ALPHA;
+        let norm = z_valence * (alpha / PI).powf(1.5);
+        let rho_atom = mesh.r.iter().map(|ri| 4.0 * PI * ri * ri * norm * (-alpha * ri * ri).exp()).collect();
+
+        let header = Header {
+            element: element.to_string(),
+            z_valence,
+            mesh_size: n,
+            functional: "SLA PW PBX PBC".to_string(),
+            number_of_proj: 0,
+            number_of_wfc: 0,
+            pseudo_type: "NC".to_string(),
+            is_ultrasoft: false,
+            is_paw: false,
+            has_so: false,
+            core_correction: false,
+            wfc_cutoff: 0.0,
+            rho_cutoff: 0.0,
+        };
+        let (units, local, dij, rho_atom) = normalize_units(&header, &mesh, None, local, Array2::zeros((0, 0)), rho_atom);
+        let splines = RadialSplines::new(&mesh, &local, &rho_atom);
+
+        Pseudopotential {
+            header,
+            mesh,
+            local,
+            nonlocal: Vec::new(),
+            pswfc: Vec::new(),
             rho_atom,
             dij,
+            rho_core: None,
+            augmentation: None,
+            paw: None,
+            spin_orb: None,
+            warnings: Vec::new(),
+            units,
+            splines,
+        }
+    }
+
+    /// D_ij entre os projetores i e j (índices de `nonlocal`).
+    pub fn d(&self, i: usize, j: usize) -> f64 {
+        self.dij[[i, j]]
+    }
+
+    /// Projetores com momento angular `l`.
+    pub fn projectors_with_l(&self, l: i32) -> impl Iterator<Item = &BetaFunction> {
+        self.nonlocal.iter().filter(move |b| b.angular_momentum == l)
+    }
+
+    /// Maior momento angular entre os projetores.
+    pub fn lmax(&self) -> Option<i32> {
+        self.nonlocal.iter().map(|b| b.angular_momentum).max()
+    }
+
+    /// Número de canais β_lm por átomo: Σ_i (2 l_i + 1).
+    pub fn n_beta_lm(&self) -> usize {
+        self.nonlocal.iter().map(|b| (2 * b.angular_momentum + 1) as usize).sum()
+    }
+
+    /// Checagens de consistência: tamanhos dos blocos frente à malha, número de projetores,
+    /// dimensão de D_ij, malha crescente e ∫ρ_atom dr = z_valence (ρ_atom inclui 4πr²).
+    pub fn validate(&self) -> Vec<UpfWarning> {
+        let mut issues = Vec::new();
+        let n = self.mesh.r.len();
+
+        if self.header.mesh_size != 0 && self.header.mesh_size != n {
+            issues.push(UpfWarning::LengthMismatch("PP_R".into(), n, self.header.mesh_size));
+        }
+        let blocks = [("PP_RAB", self.mesh.rab.len()), ("PP_LOCAL", self.local.len()), ("PP_RHOATOM", self.rho_atom.len())];
+        for (name, len) in blocks {
+            if len != n {
+                issues.push(UpfWarning::LengthMismatch(name.into(), len, n));
+            }
+        }
+        for beta in &self.nonlocal {
+            if beta.data.len() != n {
+                issues.push(UpfWarning::LengthMismatch(format!("PP_BETA.{}", beta.index + 1), beta.data.len(), n));
+            }
+        }
+
+        for chi in &self.pswfc {
+            if chi.data.len() != n {
+                issues.push(UpfWarning::LengthMismatch(format!("PP_CHI.{}", chi.index + 1), chi.data.len(), n));
+            }
+        }
+        if self.pswfc.len() != self.header.number_of_wfc {
+            issues.push(UpfWarning::WavefunctionCount(self.pswfc.len(), self.header.number_of_wfc));
+        }
+
+        let n_proj = self.header.number_of_proj;
+        if self.nonlocal.len() != n_proj {
+            issues.push(UpfWarning::ProjectorCount(self.nonlocal.len(), n_proj));
+        }
+        if let Some((i, j)) = (0..self.dij.nrows())
+            .flat_map(|i| (0..i).map(move |j| (i, j)))
+            .find(|&(i, j)| (self.dij[[i, j]] - self.dij[[j, i]]).abs() > 1e-8)
+        {
+            issues.push(UpfWarning::DijNotSymmetric(i, j));
+        }
+        if let Some(core) = &self.rho_core
+            && core.len() != n
+        {
+            issues.push(UpfWarning::LengthMismatch("PP_NLCC".into(), core.len(), n));
+        }
+
+        if let Some(i) = self.mesh.r.windows(2).position(|w| w[1] <= w[0]) {
+            issues.push(UpfWarning::NonMonotonicMesh(i + 1));
+        }
+
+        if self.rho_atom.len() == self.mesh.rab.len() {
+            let charge: f64 = self.rho_atom.iter().zip(&self.mesh.rab).map(|(rho, rab)| rho * rab).sum();
+            let z = self.header.z_valence;
+            if (charge - z).abs() > CHARGE_TOLERANCE * z.abs().max(1.0) {
+                issues.push(UpfWarning::ChargeIntegral(charge, z));
+            }
+        }
+        issues
+    }
+}
+
+/// PP_AUGMENTATION fica dentro de PP_NONLOCAL no UPF v2.
+fn nl_find<'a, 'input>(root: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    root.children()
+        .find(|n| n.has_tag_name("PP_NONLOCAL"))
+        .and_then(|nl| nl.children().find(|n| n.has_tag_name(name)))
+}
+
+/// Lê PP_Q, PP_MULTIPOLES e as funções PP_QIJ.i.j / PP_QIJL.i.j.l.
+fn parse_augmentation(
+    node: roxmltree::Node,
+    n_beta: usize,
+    warnings: &mut Vec<UpfWarning>,
+) -> Result<Augmentation, UpfError> {
+    let q_with_l = parse_bool(node.attribute("q_with_l").unwrap_or("F"));
+    let block = |name: &str| -> Result<Vec<f64>, UpfError> {
+        match node.children().find(|n| n.has_tag_name(name)) {
+            Some(n) => parse_numbers(n.text().unwrap_or("")),
+            None => Ok(Vec::new()),
+        }
+    };
+
+    let q_flat = block("PP_Q")?;
+    let q = if q_flat.len() == n_beta * n_beta {
+        Array2::from_shape_vec((n_beta, n_beta), q_flat).expect("Dimensão de q_ij verificada")
+    } else {
+        warnings.push(UpfWarning::LengthMismatch("PP_Q".into(), q_flat.len(), n_beta * n_beta));
+        Array2::zeros((n_beta, n_beta))
+    };
+
+    let prefix = if q_with_l { "PP_QIJL" } else { "PP_QIJ." };
+    let mut functions = Vec::new();
+    for child in node.children().filter(|n| n.tag_name().name().starts_with(prefix)) {
+        let i: usize = parse_attr(child, "first_index")?;
+        let j: usize = parse_attr(child, "second_index")?;
+        let l = if q_with_l { Some(parse_attr(child, "angular_momentum")?) } else { None };
+        functions.push(AugmentationFunction {
+            i: i.saturating_sub(1),
+            j: j.saturating_sub(1),
+            l,
+            data: parse_numbers(child.text().unwrap_or(""))?,
+        });
+    }
+
+    Ok(Augmentation { q_with_l, q, multipoles: block("PP_MULTIPOLES")?, functions })
+}
+
+/// Unidade de energia declarada no texto do PP_INFO: a primeira linha que fala em
+/// "unit" e cita só Hartree (Ha) ou só Rydberg (Ry).
+fn declared_energy_unit(info: &str) -> Option<EnergyUnit> {
+    info.lines()
+        .map(str::to_lowercase)
+        .filter(|line| line.contains("unit"))
+        .find_map(|line| {
+            let words: Vec<&str> = line.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+            let has = |names: &[&str]| words.iter().any(|w| names.contains(w));
+            match (has(&["hartree", "ha"]), has(&["rydberg", "ry"])) {
+                (true, false) => Some(EnergyUnit::Hartree),
+                (false, true) => Some(EnergyUnit::Rydberg),
+                _ => None,
+            }
         })
+}
+
+/// Detecta as convenções do gerador e converte para as internas:
+/// - V_loc: usa a unidade declarada no arquivo (`declared`); sem ela, a cauda
+///   coulombiana decide (-2Z/r em Ry, -Z/r em Ha);
+/// - D_ij: convertido apenas com unidade declarada em Ha;
+/// - ρ_atom: escolhe a convenção cuja integral radial fica mais próxima de z_valence.
+///
+/// Casos ambíguos (Z = 0, cauda fora do esperado) mantêm o padrão UPF.
+fn normalize_units(
+    header: &Header,
+    mesh: &RadialMesh,
+    declared: Option<EnergyUnit>,
+    mut local: Vec<f64>,
+    mut dij: Array2<f64>,
+    mut rho_atom: Vec<f64>,
+) -> (UnitReport, Vec<f64>, Array2<f64>, Vec<f64>) {
+    let z = header.z_valence;
+    let n = mesh.r.len().min(local.len());
+
+    let local_tail = if z > 0.0 && n > 0 {
+        -mesh.r[n - 1] * local[n - 1] / z
+    } else {
+        2.0
+    };
+    let (local_unit, unit_source) = match declared {
+        Some(unit) => (unit, UnitSource::Header),
+        None if (0.5..1.5).contains(&local_tail) => (EnergyUnit::Hartree, UnitSource::Heuristic),
+        None => (EnergyUnit::Rydberg, UnitSource::Heuristic),
+    };
+    if local_unit == EnergyUnit::Hartree {
+        local.iter_mut().for_each(|v| *v *= 2.0);
+    }
+    let dij_converted = declared == Some(EnergyUnit::Hartree);
+    if dij_converted {
+        dij.mapv_inplace(|d| d * 2.0);
+    }
+
+    let radial_charge = |rho: &[f64]| -> f64 { rho.iter().zip(&mesh.rab).map(|(p, rab)| p * rab).sum() };
+    let q_radial = radial_charge(&rho_atom);
+    let as_radial: Vec<f64> = rho_atom.iter().zip(&mesh.r).map(|(p, r)| 4.0 * PI * r * r * p).collect();
+    let q_volumetric = radial_charge(&as_radial);
+
+    let density = if z > 0.0 && (q_volumetric - z).abs() < (q_radial - z).abs() {
+        DensityConvention::Volumetric
+    } else {
+        DensityConvention::Radial
+    };
+    let density_charge = match density {
+        DensityConvention::Radial => q_radial,
+        DensityConvention::Volumetric => {
+            rho_atom = as_radial;
+            q_volumetric
+        }
+    };
+
+    let report = UnitReport { local_unit, unit_source, local_tail, dij_converted, density, density_charge };
+    (report, local, dij, rho_atom)
+}
+
+/// Helper: Lê um atributo numérico obrigatório de um nó do UPF.
+fn parse_attr<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Result<T, UpfError> {
+    node.attribute(name)
+        .ok_or_else(|| UpfError::MissingField(format!("{}@{}", node.tag_name().name(), name)))?
+        .trim()
+        .parse()
+        .map_err(|_| UpfError::ParseNumber)
+}
+
+/// Helper: Lê um atributo que pode faltar sem que isso seja um problema (sem aviso).
+fn optional_attr<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Option<T> {
+    node.attribute(name).and_then(|v| v.trim().parse::<T>().ok())
+}
+
+/// Helper: Lê um atributo opcional; se ausente ou ilegível, usa `default` e registra o aviso.
+fn attr_or<T>(node: roxmltree::Node, name: &str, default: T, warnings: &mut Vec<UpfWarning>) -> T
+where
+    T: std::str::FromStr + ToString,
+{
+    match node.attribute(name).map(|v| v.trim().parse::<T>()) {
+        Some(Ok(value)) => value,
+        _ => {
+            warnings.push(UpfWarning::DefaultedAttribute(
+                format!("{}@{}", node.tag_name().name(), name),
+                default.to_string(),
+            ));
+            default
+        }
     }
 }
 
+/// Helper: Interpreta os booleanos do Fortran ("T", ".true.", ...) usados no header do UPF.
+fn parse_bool(text: &str) -> bool {
+    matches!(text.trim().trim_matches('.').to_ascii_uppercase().as_str(), "T" | "TRUE")
+}
+
 /// Helper: Converte string gigante de números separada por espaços/novas linhas em Vec<f64>
 fn parse_numbers(text: &str) -> Result<Vec<f64>, UpfError> {
     text.split_whitespace()
@@ -1,7 +1,17 @@
+// Linkagem das bibliotecas BLAS/LAPACK escolhidas pela feature
+#[cfg(any(feature = "openblas", feature = "netlib"))]
+extern crate blas_src;
+#[cfg(any(feature = "openblas", feature = "netlib"))]
+extern crate lapack_src;
+
 pub mod io;
 pub mod core;
 pub mod dft;
 pub mod utils;
+pub mod tools;
+pub mod postproc;
+pub mod optim;
+pub mod testkit;
 
 pub use io::upf::Pseudopotential;
 pub use core::simulation::Simulation;
\ No newline at end of file
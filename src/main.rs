@@ -1,4 +1,263 @@
+// src/main.rs
+use std::path::{Path, PathBuf};
+use std::process;
+use clap::{Parser, Subcommand};
+
+use bravie::dft::nscf::run_nscf_for;
+use bravie::io::input::InputFile;
+use bravie::io::results::RunResults;
+use bravie::io::upf::Pseudopotential;
+use bravie::postproc::scissor::Scissor;
+use bravie::tools::units_audit;
+use bravie::utils::logger::{self, Verbosity};
+use bravie::utils::parallel::{self, ParallelConfig};
+use bravie::utils::timer;
+
+#[derive(Parser)]
+#[command(
+    name = "bravie",
+    version,
+    about = "Bravie: A (work in progress) Rust DFT Software",
+    after_help = "Desenvolvido em Rust por Gustavo Verneck.\nRepositório: https://github.com/gustavoverneck/bravie"
+)]
+struct Cli {
+    /// Ativa logs detalhados (debug mode)
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Mostra apenas avisos e erros
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Define número de threads (Rayon)
+    #[arg(short, long, global = true, value_name = "N")]
+    parallel: Option<usize>,
+
+    /// Modo determinístico: resultados reprodutíveis bit a bit (RNG semeado, reduções ordenadas)
+    #[arg(long, global = true)]
+    deterministic: bool,
+
+    /// Semente do RNG no modo determinístico
+    #[arg(long, global = true, value_name = "SEED", requires = "deterministic")]
+    seed: Option<u64>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Executa uma simulação completa (leitura -> scf -> output)
+    Run {
+        /// Arquivo de entrada (.toml)
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+        /// Arquivo de saída com os resultados (.json)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Executa apenas o ciclo de Autoconsistência (Self-Consistent Field)
+    Scf {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+        /// Checkpoint com ρ e V_eff para etapas seguintes (ex: `bands`)
+        #[arg(short, long, value_name = "FILE", default_value = "bravie.ckpt")]
+        checkpoint: PathBuf,
+    },
+    /// Calcula a estrutura de bandas (requer densidade convergida)
+    Bands {
+        /// Arquivo de entrada com a seção [bands]
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+        /// Checkpoint com ρ e V_eff (`Simulation::save_checkpoint`)
+        #[arg(short, long, value_name = "FILE", default_value = "bravie.ckpt")]
+        checkpoint: PathBuf,
+        /// Arquivo de saída com as bandas (.json)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Calcula a densidade de estados
+    Dos {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+    },
+    /// Otimiza a geometria do sistema (minimiza forças)
+    Relax {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+    },
+    /// Verifica se o arquivo de input e pseudopotenciais são válidos
+    Check {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+    },
+    /// Mostra informações de um pseudopotencial UPF
+    PpInfo {
+        /// Arquivo .upf
+        file: PathBuf,
+    },
+}
+
+fn run(input: &Path, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let input_file = InputFile::from_file(input)?;
+    let mut sim = input_file.to_builder()?.build()?;
+    sim.run();
+    sim.initialize_density()?;
+
+    if let Some(path) = output {
+        let mut results = RunResults::from_simulation(&sim);
+        if let Some(shift) = input_file.calculation.scissor {
+            Scissor::for_simulation(&sim, shift).apply_to_results(&mut results);
+        }
+        results.record_timings();
+        results.write_json(path)?;
+        log::info!("Resultados escritos em {}", path.display());
+    }
+
+    log::info!("{}", timer::report().trim_end());
+    Ok(())
+}
+
+fn bands(input: &Path, checkpoint: &Path, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let input_file = InputFile::from_file(input)?;
+    let path = input_file.bands.as_ref().ok_or("Input sem seção [bands] com o caminho de pontos K")?;
+    let mut sim = input_file.to_builder()?.build()?;
+    let v_eff = sim.load_checkpoint(checkpoint)?;
+
+    let k_path = path.to_kgrid(&sim.structure);
+    let nscf = run_nscf_for(&sim, &v_eff, &k_path)?;
+    sim.k_grid = nscf.k_grid;
+    sim.bases = nscf.bases;
+
+    let mut results = RunResults::from_simulation(&sim);
+    for (ik, b) in nscf.bands.iter().enumerate() {
+        results.add_bands(ik, b);
+    }
+    if let Some(shift) = input_file.calculation.scissor {
+        Scissor::for_simulation(&sim, shift).apply_to_results(&mut results);
+    }
+    results.compute_occupations(sim.n_electrons(), sim.smearing);
+    if let Some(edges) = results.compute_band_edges(sim.n_electrons(), sim.smearing) {
+        log::info!("{}", edges.to_string().trim_end());
+    }
+    if let Some(path) = output {
+        results.record_timings();
+        results.write_json(path)?;
+        log::info!("Bandas escritas em {}", path.display());
+    }
+    log::info!("{}", timer::report().trim_end());
+    Ok(())
+}
+
+fn check(input: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // O build carrega todos os pseudopotenciais e monta bases/grids
+    let sim = InputFile::from_file(input)?.to_builder()?.build()?;
+    println!("Input válido: {} átomos, {} espécies, {} k-points.",
+        sim.structure.atoms.len(), sim.pseudos.len(), sim.k_grid.k_points.len());
+
+    // Auditoria de unidades: elétron livre analítico e bases da simulação
+    units_audit::audit_free_electron(10.0, 20.0)?;
+    for basis in &sim.bases {
+        let audit = units_audit::audit_basis(&sim.structure, basis)?;
+        log::debug!("NPW = {} (estimativa {:.0})", audit.npw, audit.npw_estimate);
+    }
+    println!("Convenções de unidades conferidas (|k+G|² em Ry, B com 2π).");
+    Ok(())
+}
+
+fn pp_info(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let pseudo = Pseudopotential::from_file(path)?;
+
+    println!("--- Análise do Pseudopotencial: {} ---", pseudo.header.element);
+    println!("Carga de Valência (Z_valence): {:.4}", pseudo.header.z_valence);
+    println!("Tamanho da Malha Radial: {}", pseudo.header.mesh_size);
+    println!("Unidades: {}", pseudo.units);
+    if pseudo.header.wfc_cutoff > 0.0 {
+        println!("Cutoffs sugeridos: WFC={:.1} Ry, Rho={:.1} Ry", pseudo.header.wfc_cutoff, pseudo.header.rho_cutoff);
+    }
+    println!("Projetores: {} (D_ij {}x{})", pseudo.nonlocal.len(), pseudo.dij.nrows(), pseudo.dij.ncols());
+    println!("Correção de caroço (NLCC): {}", if pseudo.rho_core.is_some() { "sim" } else { "não" });
+    if !pseudo.pswfc.is_empty() {
+        println!("Orbitais pseudo-atômicos: {}", pseudo.pswfc.len());
+        for chi in &pseudo.pswfc {
+            println!("  {:<4} l = {}, ocupação = {:.2}", chi.label, chi.l, chi.occupation);
+        }
+    }
+    if let Some(so) = &pseudo.spin_orb {
+        println!("Spin-Órbita: sim ({} projetores com j definido; não usado no cálculo)", so.relbeta.len());
+        for beta in &so.relbeta {
+            println!("  beta {}: l = {}, j = {:.1}", beta.index + 1, beta.l, beta.j);
+        }
+    }
+
+    // Integração Numérica Radial
+    // UPF define: int f(r) dr = sum_i f(r_i) * rab(i)
+    let n_points = pseudo.mesh.r.len().min(pseudo.rho_atom.len()).min(pseudo.mesh.rab.len());
+    let integral_charge: f64 = (0..n_points)
+        .map(|i| pseudo.rho_atom[i].abs() * pseudo.mesh.rab[i])
+        .sum();
+
+    println!("Integral Radial Calculada (Soma direta): {:.4}", integral_charge);
+
+    let diff = (integral_charge - pseudo.header.z_valence).abs();
+    println!("Erro Absoluto: {:.4}", diff);
+
+    if diff < 0.1 {
+        println!("A integral radial do UPF está consistente.");
+    } else {
+        println!("AVISO: A integral radial difere significativamente de Z_valence.");
+        println!("Isso sugere que 'rho_atom' no UPF pode ter uma definição diferente");
+        println!("(ex: densidade de core incluída ou normalização diferente).");
+    }
+
+    let issues: Vec<_> = pseudo.warnings.iter().chain(pseudo.validate().iter()).cloned().collect();
+    if !issues.is_empty() {
+        println!("Problemas encontrados ({}):", issues.len());
+        for issue in &issues {
+            println!("  - {}", issue);
+        }
+    }
+    Ok(())
+}
+
+fn not_implemented(command: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err(format!("O comando '{}' ainda não está implementado.", command).into())
+}
+
+fn dispatch(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let verbosity = if cli.quiet {
+        Verbosity::Quiet
+    } else if cli.verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+    logger::init(verbosity);
+
+    parallel::configure(&ParallelConfig {
+        threads: cli.parallel,
+        deterministic: cli.deterministic,
+        seed: cli.seed.unwrap_or(parallel::DEFAULT_SEED),
+    })?;
+
+    let result = match cli.command {
+        Command::Run { input, output } => run(&input, output.as_deref()),
+        Command::Check { input } => check(&input),
+        Command::PpInfo { file } => pp_info(&file),
+        Command::Scf { .. } => not_implemented("scf"),
+        Command::Bands { input, checkpoint, output } => bands(&input, &checkpoint, output.as_deref()),
+        Command::Dos { .. } => not_implemented("dos"),
+        Command::Relax { .. } => not_implemented("relax"),
+    };
+    // Wisdom dos planos criados na execução, gravada uma vez só
+    #[cfg(feature = "fftw")]
+    bravie::core::fft::export_wisdom();
+    result
+}
 
 fn main() {
-    println!("Hello World!")
-}
\ No newline at end of file
+    if let Err(e) = dispatch(Cli::parse()) {
+        eprintln!("Erro: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1 @@
+pub mod neb;
\ No newline at end of file
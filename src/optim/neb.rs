@@ -0,0 +1,284 @@
+use nalgebra::Vector3;
+use thiserror::Error;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+
+#[derive(Error, Debug)]
+pub enum NebError {
+    #[error("As estruturas inicial e final têm números de átomos diferentes ({0} vs {1}).")]
+    AtomCountMismatch(usize, usize),
+
+    #[error("O cálculo de forças retornou {0} vetores, esperado {1} (um por átomo).")]
+    ForceCount(usize, usize),
+
+    #[error("São necessárias pelo menos 1 imagem intermediária.")]
+    NoImages,
+
+    #[error("{0}")]
+    Dft(#[from] DftError),
+}
+
+/// Parâmetros do Nudged Elastic Band.
+#[derive(Debug, Clone)]
+pub struct NebParameters {
+    pub n_images: usize, // Imagens intermediárias (sem contar os extremos)
+    pub spring_k: f64,   // Constante de mola (Ry/Bohr^2)
+    pub climbing: bool,  // Climbing image (ativada após `climb_after` iterações)
+    pub climb_after: usize,
+    pub fmax: f64,       // Critério de convergência: força NEB máxima (Ry/Bohr)
+    pub max_iter: usize,
+    pub dt: f64,         // Passo inicial do FIRE
+}
+
+impl Default for NebParameters {
+    fn default() -> Self {
+        Self {
+            n_images: 5,
+            spring_k: 0.1,
+            climbing: true,
+            climb_after: 10,
+            fmax: 1e-3,
+            max_iter: 500,
+            dt: 0.1,
+        }
+    }
+}
+
+/// Caminho de mínima energia encontrado pelo NEB.
+#[derive(Debug, Clone)]
+pub struct NebResult {
+    pub images: Vec<Structure>, // Inclui os extremos
+    pub energies: Vec<f64>,     // Ry, uma por imagem
+    pub path_length: Vec<f64>,  // Coordenada de reação acumulada (Bohr)
+    pub barrier_forward: f64,   // E_max - E_inicial (Ry)
+    pub barrier_reverse: f64,   // E_max - E_final (Ry)
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+type Config = Vec<Vector3<f64>>;
+
+fn positions(s: &Structure) -> Config {
+    s.atoms.iter().map(|a| a.position).collect()
+}
+
+fn dot(a: &Config, b: &Config) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x.dot(y)).sum()
+}
+
+fn sub(a: &Config, b: &Config) -> Config {
+    a.iter().zip(b).map(|(x, y)| x - y).collect()
+}
+
+fn norm(a: &Config) -> f64 {
+    dot(a, a).sqrt()
+}
+
+fn scale(a: &Config, s: f64) -> Config {
+    a.iter().map(|x| x * s).collect()
+}
+
+/// Interpola linearmente `n_images` imagens entre duas estruturas (mesma rede).
+/// O deslocamento de cada átomo usa a convenção de imagem mínima.
+pub fn interpolate_images(initial: &Structure, final_: &Structure, n_images: usize) -> Result<Vec<Structure>, NebError> {
+    if initial.atoms.len() != final_.atoms.len() {
+        return Err(NebError::AtomCountMismatch(initial.atoms.len(), final_.atoms.len()));
+    }
+
+    let lattice = &initial.lattice.vectors;
+    let lattice_inv = lattice.try_inverse().ok_or(DftError::SingularLattice)?;
+
+    let delta: Config = initial.atoms.iter().zip(&final_.atoms)
+        .map(|(a, b)| {
+            let mut d = lattice_inv * (b.position - a.position);
+            d.iter_mut().for_each(|x| *x -= x.round());
+            lattice * d
+        })
+        .collect();
+
+    let mut images = Vec::with_capacity(n_images + 2);
+    for k in 0..=(n_images + 1) {
+        let t = k as f64 / (n_images + 1) as f64;
+        let mut s = initial.clone();
+        for (atom, d) in s.atoms.iter_mut().zip(&delta) {
+            atom.position += d * t;
+        }
+        images.push(s);
+    }
+    Ok(images)
+}
+
+/// Tangente melhorada (Henkelman & Jónsson, 2000): aponta para o vizinho de maior energia,
+/// com mistura ponderada nos extremos locais de energia.
+fn improved_tangent(prev: &Config, cur: &Config, next: &Config, e_prev: f64, e_cur: f64, e_next: f64) -> Config {
+    let t_plus = sub(next, cur);
+    let t_minus = sub(cur, prev);
+
+    let tau = if e_next > e_cur && e_cur > e_prev {
+        t_plus
+    } else if e_next < e_cur && e_cur < e_prev {
+        t_minus
+    } else {
+        let dv_max = (e_next - e_cur).abs().max((e_prev - e_cur).abs());
+        let dv_min = (e_next - e_cur).abs().min((e_prev - e_cur).abs());
+        if e_next > e_prev {
+            t_plus.iter().zip(&t_minus).map(|(p, m)| p * dv_max + m * dv_min).collect()
+        } else {
+            t_plus.iter().zip(&t_minus).map(|(p, m)| p * dv_min + m * dv_max).collect()
+        }
+    };
+
+    let n = norm(&tau).max(1e-300);
+    scale(&tau, 1.0 / n)
+}
+
+/// Busca do estado de transição por Nudged Elastic Band (com climbing image opcional).
+///
+/// `energy_forces(structure)` devolve (energia em Ry, forças em Ry/Bohr). Os extremos são
+/// avaliados uma vez e mantidos fixos; as imagens intermediárias são otimizadas com FIRE.
+/// *Ref: Henkelman, G., Uberuaga, B. P., & Jónsson, H. (2000). J. Chem. Phys. 113, 9901.*
+pub fn run_neb<F, E>(
+    initial: &Structure,
+    final_: &Structure,
+    params: &NebParameters,
+    mut energy_forces: F,
+) -> Result<NebResult, E>
+where
+    F: FnMut(&Structure) -> Result<(f64, Vec<Vector3<f64>>), E>,
+    E: From<NebError>,
+{
+    if params.n_images == 0 {
+        return Err(NebError::NoImages.into());
+    }
+
+    let mut images = interpolate_images(initial, final_, params.n_images)?;
+    let n_total = images.len();
+    let natoms = initial.atoms.len();
+
+    let mut evaluate = |s: &Structure| -> Result<(f64, Config), E> {
+        let (e, mut f) = energy_forces(s)?;
+        if f.len() != natoms {
+            return Err(NebError::ForceCount(f.len(), natoms).into());
+        }
+        s.constrain_forces(&mut f);
+        Ok((e, f))
+    };
+
+    let mut energies = vec![0.0; n_total];
+    let mut forces: Vec<Config> = vec![vec![Vector3::zeros(); natoms]; n_total];
+    for k in 0..n_total {
+        let (e, f) = evaluate(&images[k])?;
+        energies[k] = e;
+        forces[k] = f;
+    }
+
+    // Estado do FIRE
+    let (f_inc, f_dec, alpha_start, f_alpha, n_min) = (1.1, 0.5, 0.1, 0.99, 5);
+    let dt_max = 10.0 * params.dt;
+    let mut dt = params.dt;
+    let mut alpha = alpha_start;
+    let mut steps_positive = 0;
+    let mut velocities: Vec<Config> = vec![vec![Vector3::zeros(); natoms]; n_total];
+
+    let mut converged = false;
+    let mut iterations = 0;
+
+    for iter in 0..params.max_iter {
+        iterations = iter + 1;
+        let pos: Vec<Config> = images.iter().map(positions).collect();
+        let climbing_index = if params.climbing && iter >= params.climb_after {
+            (1..n_total - 1).max_by(|&a, &b| energies[a].total_cmp(&energies[b]))
+        } else {
+            None
+        };
+
+        // Forças NEB nas imagens intermediárias
+        let mut neb_forces: Vec<Config> = vec![vec![Vector3::zeros(); natoms]; n_total];
+        let mut f_max: f64 = 0.0;
+        for k in 1..n_total - 1 {
+            let tau = improved_tangent(&pos[k - 1], &pos[k], &pos[k + 1], energies[k - 1], energies[k], energies[k + 1]);
+            let f_par = dot(&forces[k], &tau);
+
+            let mut f = if Some(k) == climbing_index {
+                // Climbing image: sobe ao longo da tangente, sem molas
+                forces[k].iter().zip(&tau).map(|(f, t)| f - t * (2.0 * f_par)).collect::<Config>()
+            } else {
+                let spring = params.spring_k * (norm(&sub(&pos[k + 1], &pos[k])) - norm(&sub(&pos[k], &pos[k - 1])));
+                forces[k].iter().zip(&tau).map(|(f, t)| f - t * f_par + t * spring).collect::<Config>()
+            };
+
+            // A tangente pode ter componentes nos graus de liberdade congelados
+            images[k].constrain_forces(&mut f);
+            f_max = f.iter().map(|v| v.amax()).fold(f_max, f64::max);
+            neb_forces[k] = f;
+        }
+
+        log::debug!("NEB iter {:>4}: F_max = {:.3e} Ry/Bohr, E_max = {:.6} Ry",
+            iterations, f_max, energies.iter().cloned().fold(f64::NEG_INFINITY, f64::max));
+
+        // Só declara convergência com a climbing image ativa (se pedida)
+        if f_max < params.fmax && (!params.climbing || climbing_index.is_some()) {
+            converged = true;
+            break;
+        }
+
+        // Passo FIRE
+        let power: f64 = (1..n_total - 1).map(|k| dot(&neb_forces[k], &velocities[k])).sum();
+        if power > 0.0 {
+            let v_norm = (1..n_total - 1).map(|k| dot(&velocities[k], &velocities[k])).sum::<f64>().sqrt();
+            let f_norm = (1..n_total - 1).map(|k| dot(&neb_forces[k], &neb_forces[k])).sum::<f64>().sqrt().max(1e-300);
+            for k in 1..n_total - 1 {
+                for (v, f) in velocities[k].iter_mut().zip(&neb_forces[k]) {
+                    *v = *v * (1.0 - alpha) + f * (alpha * v_norm / f_norm);
+                }
+            }
+            steps_positive += 1;
+            if steps_positive > n_min {
+                dt = (dt * f_inc).min(dt_max);
+                alpha *= f_alpha;
+            }
+        } else {
+            velocities.iter_mut().for_each(|v| v.iter_mut().for_each(|x| *x = Vector3::zeros()));
+            steps_positive = 0;
+            dt *= f_dec;
+            alpha = alpha_start;
+        }
+
+        for k in 1..n_total - 1 {
+            for ((atom, v), f) in images[k].atoms.iter_mut().zip(velocities[k].iter_mut()).zip(&neb_forces[k]) {
+                *v += f * dt;
+                atom.position += *v * dt;
+            }
+            let (e, f) = evaluate(&images[k])?;
+            energies[k] = e;
+            forces[k] = f;
+        }
+    }
+
+    let pos: Vec<Config> = images.iter().map(positions).collect();
+    let mut path_length = vec![0.0; n_total];
+    for k in 1..n_total {
+        path_length[k] = path_length[k - 1] + norm(&sub(&pos[k], &pos[k - 1]));
+    }
+
+    let e_max = energies.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let result = NebResult {
+        barrier_forward: e_max - energies[0],
+        barrier_reverse: e_max - energies[n_total - 1],
+        images,
+        energies,
+        path_length,
+        iterations,
+        converged,
+    };
+
+    if result.converged {
+        log::info!("NEB convergido em {} iterações. Barreira: {:.6} Ry (direta), {:.6} Ry (reversa)",
+            result.iterations, result.barrier_forward, result.barrier_reverse);
+    } else {
+        log::warn!("NEB não convergiu em {} iterações.", result.iterations);
+    }
+
+    Ok(result)
+}
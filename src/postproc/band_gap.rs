@@ -0,0 +1,122 @@
+use std::fmt;
+use serde::Serialize;
+
+use crate::core::kpoints::KGrid;
+use crate::dft::occupations::{fermi_level, Smearing};
+use crate::dft::solver::BandSolverResult;
+use crate::utils::constants::{HA_TO_EV, RY_TO_HA};
+
+/// Extremo de banda: energia (Ry), ponto K e banda onde ocorre.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BandExtremum {
+    pub energy: f64,
+    pub k_index: usize,
+    pub band: usize,
+}
+
+/// Nível de Fermi, bordas de banda e gaps procurados em todos os pontos K calculados.
+///
+/// Estados abaixo de E_F são de valência e acima, de condução. Se alguma banda cruza E_F
+/// (tem estados dos dois lados) o sistema é metálico e os gaps ficam `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BandEdges {
+    pub fermi_energy: f64,                  // Ry
+    pub vbm: Option<BandExtremum>,          // Máximo da banda de valência
+    pub cbm: Option<BandExtremum>,          // Mínimo da banda de condução
+    pub metallic: bool,
+    pub gap: Option<f64>,                   // CBM - VBM (Ry)
+    pub direct_gap: Option<(f64, usize)>,   // Menor gap vertical (Ry) e seu ponto K
+}
+
+impl BandEdges {
+    /// Analisa autovalores `eigenvalues[k][n]` (Ry) com pesos de ponto K.
+    pub fn from_eigenvalues(eigenvalues: &[Vec<f64>], weights: &[f64], n_electrons: f64, smearing: Smearing) -> Self {
+        let fermi_energy = fermi_level(eigenvalues, weights, n_electrons, smearing);
+
+        let mut vbm: Option<BandExtremum> = None;
+        let mut cbm: Option<BandExtremum> = None;
+        let mut direct_gap: Option<(f64, usize)> = None;
+        for (k_index, bands) in eigenvalues.iter().enumerate() {
+            let mut top: Option<f64> = None;
+            let mut bottom: Option<f64> = None;
+            for (band, &energy) in bands.iter().enumerate() {
+                let extremum = BandExtremum { energy, k_index, band };
+                if energy <= fermi_energy {
+                    top = Some(top.map_or(energy, |t| t.max(energy)));
+                    if vbm.is_none_or(|v| energy > v.energy) {
+                        vbm = Some(extremum);
+                    }
+                } else {
+                    bottom = Some(bottom.map_or(energy, |b| b.min(energy)));
+                    if cbm.is_none_or(|c| energy < c.energy) {
+                        cbm = Some(extremum);
+                    }
+                }
+            }
+            if let (Some(t), Some(b)) = (top, bottom) {
+                if direct_gap.is_none_or(|(g, _)| b - t < g) {
+                    direct_gap = Some((b - t, k_index));
+                }
+            }
+        }
+
+        let n_bands = eigenvalues.iter().map(Vec::len).min().unwrap_or(0);
+        let metallic = (0..n_bands).any(|n| {
+            let below = eigenvalues.iter().any(|e| e[n] <= fermi_energy);
+            let above = eigenvalues.iter().any(|e| e[n] > fermi_energy);
+            below && above
+        });
+
+        let gap = match (vbm, cbm) {
+            (Some(v), Some(c)) if !metallic => Some(c.energy - v.energy),
+            _ => None,
+        };
+        Self {
+            fermi_energy,
+            vbm,
+            cbm,
+            metallic,
+            gap,
+            direct_gap: if metallic { None } else { direct_gap },
+        }
+    }
+
+    /// Bordas a partir dos resultados do eigensolver (um por ponto K de `k_grid`).
+    pub fn from_bands(bands: &[BandSolverResult], k_grid: &KGrid, n_electrons: f64, smearing: Smearing) -> Self {
+        let eigenvalues: Vec<Vec<f64>> = bands.iter().map(|b| b.eigenvalues.clone()).collect();
+        let weights: Vec<f64> = k_grid.k_points.iter().map(|kp| kp.weight).collect();
+        Self::from_eigenvalues(&eigenvalues, &weights, n_electrons, smearing)
+    }
+
+    /// Gap indireto: VBM e CBM em pontos K diferentes.
+    pub fn is_indirect(&self) -> bool {
+        match (self.gap, self.vbm, self.cbm) {
+            (Some(_), Some(v), Some(c)) => v.k_index != c.k_index,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for BandEdges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ev = |e: f64| e * RY_TO_HA * HA_TO_EV;
+        writeln!(f, "Nível de Fermi     : {:>12.4} eV", ev(self.fermi_energy))?;
+        if let Some(v) = self.vbm {
+            writeln!(f, "VBM                : {:>12.4} eV  (k {}, banda {})", ev(v.energy), v.k_index + 1, v.band + 1)?;
+        }
+        if let Some(c) = self.cbm {
+            writeln!(f, "CBM                : {:>12.4} eV  (k {}, banda {})", ev(c.energy), c.k_index + 1, c.band + 1)?;
+        }
+        if self.metallic {
+            return writeln!(f, "Gap                : metálico (bandas cruzam E_F)");
+        }
+        match self.gap {
+            Some(g) => writeln!(f, "Gap                : {:>12.4} eV  ({})", ev(g), if self.is_indirect() { "indireto" } else { "direto" })?,
+            None => writeln!(f, "Gap                : sem bandas de condução")?,
+        }
+        if let Some((g, ik)) = self.direct_gap {
+            writeln!(f, "Gap direto mínimo  : {:>12.4} eV  (k {})", ev(g), ik + 1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::utils::constants::{HA_TO_EV, RY_TO_HA};
+
+const RY_TO_EV: f64 = RY_TO_HA * HA_TO_EV;
+
+#[derive(Error, Debug)]
+pub enum DosError {
+    #[error("Largura de alargamento inválida: σ = {0} Ry (deve ser positiva e finita).")]
+    InvalidSigma(f64),
+
+    #[error("{0} distâncias no caminho, mas o canal de spin {1} tem {2} pontos K.")]
+    KPointCount(usize, usize, usize),
+
+    #[error("Erro de escrita: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Autovalores por canal de spin: `eigenvalues[spin][k][n]` (Ry). Um canal só é o caso sem
+/// spin (cada banda comporta 2 elétrons); dois canais são ↑ e ↓ da LSDA (1 elétron cada).
+#[derive(Debug, Clone)]
+pub struct SpinBands {
+    pub eigenvalues: Vec<Vec<Vec<f64>>>,
+    pub weights: Vec<f64>, // Pesos dos pontos K (normalizados aqui)
+}
+
+impl SpinBands {
+    pub fn unpolarized(eigenvalues: Vec<Vec<f64>>, weights: Vec<f64>) -> Self {
+        Self { eigenvalues: vec![eigenvalues], weights }
+    }
+
+    pub fn polarized(up: Vec<Vec<f64>>, down: Vec<Vec<f64>>, weights: Vec<f64>) -> Self {
+        Self { eigenvalues: vec![up, down], weights }
+    }
+
+    pub fn n_spin(&self) -> usize {
+        self.eigenvalues.len()
+    }
+
+    /// Elétrons por estado: 2 sem spin, 1 por canal com spin.
+    pub fn degeneracy(&self) -> f64 {
+        if self.n_spin() == 1 { 2.0 } else { 1.0 }
+    }
+
+    fn energy_range(&self) -> (f64, f64) {
+        self.eigenvalues.iter().flatten().flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &e| (lo.min(e), hi.max(e)))
+    }
+}
+
+/// Densidade de estados por canal de spin (estados/Ry/célula).
+#[derive(Debug, Clone)]
+pub struct Dos {
+    pub energies: Vec<f64>,      // Ry
+    pub channels: Vec<Vec<f64>>, // channels[spin][i]
+}
+
+impl Dos {
+    /// Alargamento gaussiano de largura `sigma` (Ry) em `n_points` energias, cobrindo as
+    /// bandas com folga de 5σ. Erro se σ não for positivo.
+    pub fn gaussian(bands: &SpinBands, sigma: f64, n_points: usize) -> Result<Self, DosError> {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return Err(DosError::InvalidSigma(sigma));
+        }
+        let (lo, hi) = bands.energy_range();
+        let (e_min, e_max) = (lo - 5.0 * sigma, hi + 5.0 * sigma);
+        let step = (e_max - e_min) / n_points.saturating_sub(1).max(1) as f64;
+        let energies: Vec<f64> = (0..n_points).map(|i| e_min + i as f64 * step).collect();
+
+        let total: f64 = bands.weights.iter().sum();
+        let norm = bands.degeneracy() / (sigma * PI.sqrt());
+        let channels = bands.eigenvalues.iter()
+            .map(|channel| {
+                let mut dos = vec![0.0; n_points];
+                for (levels, &w) in channel.iter().zip(&bands.weights) {
+                    let w = if total > 0.0 { w / total } else { 1.0 / channel.len() as f64 };
+                    for &e_n in levels {
+                        for (d, &e) in dos.iter_mut().zip(&energies) {
+                            let x = (e - e_n) / sigma;
+                            if x.abs() < 6.0 {
+                                *d += w * norm * (-x * x).exp();
+                            }
+                        }
+                    }
+                }
+                dos
+            })
+            .collect();
+        Ok(Self { energies, channels })
+    }
+
+    /// Texto para gnuplot: `E - E_F (eV)` e uma coluna por canal (estados/eV/célula);
+    /// com spin, colunas `up down total`. Para o gráfico espelhado: `using 1:(-$3)`.
+    pub fn to_gnuplot(&self, fermi_energy: f64) -> String {
+        let mut out = String::from("# E-E_F(eV)");
+        match self.channels.len() {
+            1 => out.push_str("  dos\n"),
+            _ => out.push_str("  dos_up  dos_down  dos_total\n"),
+        }
+        for (i, &e) in self.energies.iter().enumerate() {
+            let _ = write!(out, "{:>12.6}", (e - fermi_energy) * RY_TO_EV);
+            let values: Vec<f64> = self.channels.iter().map(|c| c[i] / RY_TO_EV).collect();
+            for v in &values {
+                let _ = write!(out, " {:>14.8}", v);
+            }
+            if values.len() > 1 {
+                let _ = write!(out, " {:>14.8}", values.iter().sum::<f64>());
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn write_gnuplot<P: AsRef<Path>>(&self, path: P, fermi_energy: f64) -> io::Result<()> {
+        fs::write(path, self.to_gnuplot(fermi_energy))
+    }
+}
+
+/// Bandas para gnuplot: um bloco por banda (separados por linha em branco), colunas
+/// `distância  E↑ - E_F  [E↓ - E_F]` em eV. `distances[k]` vem de `KGrid::distances` e
+/// deve ter um valor por ponto K de cada canal; bandas além da menor contagem são omitidas.
+pub fn bands_to_gnuplot(bands: &SpinBands, distances: &[f64], fermi_energy: f64) -> Result<String, DosError> {
+    if let Some((spin, channel)) = bands.eigenvalues.iter().enumerate().find(|(_, c)| c.len() != distances.len()) {
+        return Err(DosError::KPointCount(distances.len(), spin, channel.len()));
+    }
+    let n_bands = bands.eigenvalues.iter().flatten().map(Vec::len).min().unwrap_or(0);
+    let mut out = String::from("# k(1/Bohr)");
+    match bands.n_spin() {
+        1 => out.push_str("  E-E_F(eV)\n"),
+        _ => out.push_str("  E_up-E_F(eV)  E_down-E_F(eV)\n"),
+    }
+    for n in 0..n_bands {
+        let _ = writeln!(out, "# banda {}", n + 1);
+        for (ik, &d) in distances.iter().enumerate() {
+            let _ = write!(out, "{:>12.6}", d);
+            for channel in &bands.eigenvalues {
+                let _ = write!(out, " {:>12.6}", (channel[ik][n] - fermi_energy) * RY_TO_EV);
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+pub fn write_bands_gnuplot<P: AsRef<Path>>(
+    path: P,
+    bands: &SpinBands,
+    distances: &[f64],
+    fermi_energy: f64,
+) -> Result<(), DosError> {
+    fs::write(path, bands_to_gnuplot(bands, distances, fermi_energy)?)?;
+    Ok(())
+}
+
+/// Desdobramento de troca Δ = ε↓ - ε↑ de uma banda num ponto K (Ry).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpinSplitting {
+    pub k_index: usize,
+    pub band: usize,
+    pub splitting: f64,
+}
+
+/// Desdobramentos de troca de todas as bandas nos pontos `k_indices` (ex: Γ e os rótulos
+/// do caminho). Vazio sem spin.
+pub fn exchange_splitting(bands: &SpinBands, k_indices: &[usize]) -> Vec<SpinSplitting> {
+    let [up, down] = bands.eigenvalues.as_slice() else {
+        return Vec::new();
+    };
+    k_indices.iter()
+        .filter(|&&ik| ik < up.len() && ik < down.len())
+        .flat_map(|&ik| {
+            up[ik].iter().zip(&down[ik]).enumerate()
+                .map(move |(band, (e_up, e_down))| SpinSplitting { k_index: ik, band, splitting: e_down - e_up })
+        })
+        .collect()
+}
+
+/// Resumo em texto (meV) dos desdobramentos, um ponto K por bloco.
+pub fn splitting_summary(splittings: &[SpinSplitting]) -> String {
+    let mut out = String::from("Desdobramento de troca (ε↓ - ε↑):\n");
+    let mut current = None;
+    for s in splittings {
+        if current != Some(s.k_index) {
+            let _ = writeln!(out, "  Ponto K {}:", s.k_index + 1);
+            current = Some(s.k_index);
+        }
+        let _ = writeln!(out, "    banda {:>3}: {:>10.2} meV", s.band + 1, s.splitting * RY_TO_EV * 1e3);
+    }
+    out
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use nalgebra::{Matrix3, SymmetricEigen, Vector3};
+use thiserror::Error;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+
+/// Passo padrão em k (Bohr⁻¹, cartesiano) para as diferenças finitas.
+pub const DEFAULT_DK: f64 = 0.01;
+
+#[derive(Error, Debug)]
+pub enum EffectiveMassError {
+    #[error("Banda {0} pedida, mas o cálculo de autovalores retornou {1} bandas.")]
+    BandOutOfRange(usize, usize),
+
+    #[error("{0}")]
+    Dft(#[from] DftError),
+}
+
+/// Tensor de massa efetiva de uma banda em torno de um ponto k0.
+#[derive(Debug, Clone)]
+pub struct EffectiveMass {
+    pub k0: [f64; 3],               // Fracionário
+    pub band: usize,
+    pub hessian: Matrix3<f64>,      // ∂²E/∂k_a∂k_b (Ry·Bohr²)
+    pub inverse_mass: Matrix3<f64>, // (1/m*)_ab em unidades de 1/m_e
+    pub principal_masses: [f64; 3], // m*/m_e ao longo das direções principais
+    pub principal_axes: Matrix3<f64>, // Colunas = direções principais (cartesianas)
+}
+
+impl fmt::Display for EffectiveMass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Massa efetiva (banda {}, k0 = [{:.4}, {:.4}, {:.4}]):",
+            self.band, self.k0[0], self.k0[1], self.k0[2])?;
+        for i in 0..3 {
+            let axis = self.principal_axes.column(i);
+            writeln!(f, "  m*_{} = {:>9.4} m_e  ao longo de [{:.3}, {:.3}, {:.3}]",
+                i + 1, self.principal_masses[i], axis[0], axis[1], axis[2])?;
+        }
+        Ok(())
+    }
+}
+
+/// Tensor de massa efetiva por diferenças finitas centrais de E(k):
+///
+/// (1/m*)_ab = (1/ħ²) ∂²E/∂k_a∂k_b  →  em Rydberg (ħ = 1, m_e = 1/2): (1/m*)_ab [1/m_e] = ½ ∂²E_Ry/∂k_a∂k_b
+///
+/// `energies(k_frac)` devolve os autovalores (Ry) no ponto k (coordenadas fracionárias),
+/// ex: diagonalização não auto-consistente com V_eff fixo. São 19 avaliações.
+/// Massas negativas indicam máximos (buracos, topo da banda de valência).
+/// Erro se `band` não estiver entre os autovalores devolvidos ou se a rede for singular.
+pub fn effective_mass_tensor<F, E>(
+    structure: &Structure,
+    k0: [f64; 3],
+    band: usize,
+    dk: f64,
+    mut energies: F,
+) -> Result<EffectiveMass, E>
+where
+    F: FnMut([f64; 3]) -> Result<Vec<f64>, E>,
+    E: From<EffectiveMassError>,
+{
+    let recip = structure.lattice.reciprocal();
+    let recip_inv = recip.try_inverse().ok_or(EffectiveMassError::Dft(DftError::SingularLattice))?;
+    let k0_vec = Vector3::from(k0);
+
+    // Deslocamento cartesiano -> fracionário
+    let mut energy_at = |d_cart: Vector3<f64>| -> Result<f64, E> {
+        let k = k0_vec + recip_inv * d_cart;
+        let e = energies([k.x, k.y, k.z])?;
+        e.get(band).copied().ok_or_else(|| EffectiveMassError::BandOutOfRange(band, e.len()).into())
+    };
+
+    let axis = |a: usize| {
+        let mut v = Vector3::zeros();
+        v[a] = dk;
+        v
+    };
+
+    let e0 = energy_at(Vector3::zeros())?;
+    let mut hessian = Matrix3::zeros();
+
+    for a in 0..3 {
+        let ep = energy_at(axis(a))?;
+        let em = energy_at(-axis(a))?;
+        hessian[(a, a)] = (ep - 2.0 * e0 + em) / (dk * dk);
+    }
+    for a in 0..3 {
+        for b in (a + 1)..3 {
+            let epp = energy_at(axis(a) + axis(b))?;
+            let epm = energy_at(axis(a) - axis(b))?;
+            let emp = energy_at(-axis(a) + axis(b))?;
+            let emm = energy_at(-axis(a) - axis(b))?;
+            let h = (epp - epm - emp + emm) / (4.0 * dk * dk);
+            hessian[(a, b)] = h;
+            hessian[(b, a)] = h;
+        }
+    }
+
+    let inverse_mass = hessian * 0.5;
+    let eigen = SymmetricEigen::new(inverse_mass);
+    let principal_masses = [
+        1.0 / eigen.eigenvalues[0],
+        1.0 / eigen.eigenvalues[1],
+        1.0 / eigen.eigenvalues[2],
+    ];
+
+    Ok(EffectiveMass {
+        k0,
+        band,
+        hessian,
+        inverse_mass,
+        principal_masses,
+        principal_axes: eigen.eigenvectors,
+    })
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+use ndarray::Array3;
+use nalgebra::{Matrix3, Vector3};
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::utils::constants::{AU_EFG_TO_V_PER_M2, BARN_TO_M2, ELEMENTARY_CHARGE_SI, PLANCK_SI};
+use crate::utils::radial::erf;
+
+/// Tensor gradiente de campo elétrico num sítio, em unidades atômicas (Ha/(e·Bohr²)).
+#[derive(Debug, Clone, Copy)]
+pub struct EfgTensor {
+    pub tensor: Matrix3<f64>,   // V_ij cartesiano, sem traço
+    pub principal: [f64; 3],    // V_xx, V_yy, V_zz com |V_zz| >= |V_yy| >= |V_xx|
+    pub eta: f64,               // Assimetria (V_xx - V_yy) / V_zz, em [0, 1]
+    pub cq: Option<f64>,        // Constante de acoplamento quadrupolar e·Q·V_zz/h (MHz)
+}
+
+impl EfgTensor {
+    /// Diagonaliza V (após remover o traço) e calcula η e, com o momento de quadrupolo
+    /// nuclear `q_barn`, C_Q.
+    pub fn from_tensor(v: Matrix3<f64>, q_barn: Option<f64>) -> Self {
+        let tensor = v - Matrix3::from_diagonal_element(v.trace() / 3.0);
+        let eig = tensor.symmetric_eigen();
+        let mut principal = [eig.eigenvalues[0], eig.eigenvalues[1], eig.eigenvalues[2]];
+        principal.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+        let [vxx, vyy, vzz] = principal;
+        let eta = if vzz.abs() > 1e-12 { ((vxx - vyy) / vzz).clamp(0.0, 1.0) } else { 0.0 };
+        let cq = q_barn.map(|q| ELEMENTARY_CHARGE_SI * q * BARN_TO_M2 * vzz * AU_EFG_TO_V_PER_M2 / PLANCK_SI * 1e-6);
+        Self { tensor, principal, eta, cq }
+    }
+
+    /// V_zz em V/Å².
+    pub fn vzz_v_per_angstrom2(&self) -> f64 {
+        self.principal[2] * AU_EFG_TO_V_PER_M2 * 1e-20
+    }
+}
+
+impl fmt::Display for EfgTensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "V_zz = {:>10.5} a.u. ({:>9.3} V/Å²)  η = {:.4}", self.principal[2], self.vzz_v_per_angstrom2(), self.eta)?;
+        if let Some(cq) = self.cq {
+            write!(f, "  C_Q = {:.4} MHz", cq)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tensores EFG nas posições atômicas a partir da densidade eletrônica `rho` (e/Bohr³) e
+/// das cargas iônicas `z_valence(species_id)`, por soma de Ewald:
+///
+/// - espaço recíproco: V_ij(τ) = -Σ_{G≠0} 4π G_iG_j/G² ρ_q(G) e^{iG·τ}, com
+///   ρ_q(G) = Σ_b Z_b e^{-G²/4α} e^{-iG·τ_b}/Ω - ρ(G) (íons gaussianos);
+/// - espaço real: ∂_i∂_j Z_b erfc(√α r)/r sobre as imagens dos outros íons, que troca
+///   as gaussianas por cargas pontuais.
+///
+/// A carga gaussiana do próprio sítio é esfericamente simétrica e some ao remover o
+/// traço. Com pseudopotenciais, ρ é só a densidade de valência suave (sem reconstrução
+/// PAW nem polarização do caroço): os valores servem para tendências e comparações
+/// entre sítios, não para C_Q quantitativo. `quadrupole_moments` (barn, por elemento)
+/// é opcional.
+pub fn electric_field_gradients<Z>(
+    structure: &Structure,
+    fft: &mut FftGrid,
+    rho: &Array3<f64>,
+    z_valence: Z,
+    quadrupole_moments: &HashMap<String, f64>,
+) -> Result<Vec<EfgTensor>, DftError>
+where
+    Z: Fn(usize) -> f64,
+{
+    let [nx, ny, nz] = fft.size;
+    let (dx, dy, dz) = rho.dim();
+    if [dx, dy, dz] != fft.size {
+        return Err(DftError::GridMismatch([dx, dy, dz], fft.size));
+    }
+    let n_points = (nx * ny * nz) as f64;
+    let volume = structure.lattice.volume();
+    let lattice = &structure.lattice.vectors;
+    let recip = structure.lattice.reciprocal();
+    let charges: Vec<f64> = structure.atoms.iter().map(|a| z_valence(a.species_id)).collect();
+
+    // α: gaussianas resolvidas no grid, e^{-G_max²/4α} ~ 1e-10
+    let g_max = (0..3)
+        .map(|i| (fft.size[i] / 2) as f64 * recip.column(i).norm())
+        .fold(f64::INFINITY, f64::min);
+    let alpha = g_max * g_max / (4.0 * 23.0);
+    let sqrt_alpha = alpha.sqrt();
+
+    // ρ(G) = FFT[ρ(r)] / N
+    fft.buffer.zip_mut_with(rho, |c, &r| *c = Complex64::new(r, 0.0));
+    fft.forward_in_place()?;
+
+    let freq = |i: usize, n: usize| if i > n / 2 { i as i64 - n as i64 } else { i as i64 };
+    let mut tensors = vec![Matrix3::<f64>::zeros(); structure.atoms.len()];
+
+    for ((i, j, k), &rho_g) in fft.buffer.indexed_iter() {
+        let m = Vector3::new(freq(i, nx) as f64, freq(j, ny) as f64, freq(k, nz) as f64);
+        if m == Vector3::zeros() {
+            continue;
+        }
+        let g = recip * m;
+        let g2 = g.norm_squared();
+        let gaussian = (-g2 / (4.0 * alpha)).exp();
+        let ions = structure.atoms.iter().zip(&charges).fold(Complex64::new(0.0, 0.0), |acc, (b, &z)| {
+            acc + z * gaussian / volume * Complex64::from_polar(1.0, -g.dot(&b.position))
+        });
+        let rho_q = ions - rho_g / n_points;
+        let ggt = g * g.transpose() * (4.0 * PI / g2);
+
+        for (v, atom) in tensors.iter_mut().zip(&structure.atoms) {
+            let value = (rho_q * Complex64::from_polar(1.0, g.dot(&atom.position))).re;
+            *v -= ggt * value;
+        }
+    }
+
+    // Correção em espaço real: erfc(√α r) < 1e-10 além de r_c
+    let r_cut = 5.0 / sqrt_alpha;
+    let n_max: Vec<i32> = (0..3)
+        .map(|i| (r_cut * recip.column(i).norm() / (2.0 * PI)).ceil() as i32)
+        .collect();
+    let two_over_sqrt_pi = 2.0 / PI.sqrt();
+    for (a, atom) in structure.atoms.iter().enumerate() {
+        for (b, other) in structure.atoms.iter().enumerate() {
+            let base = atom.position - other.position;
+            for n1 in -n_max[0]..=n_max[0] {
+                for n2 in -n_max[1]..=n_max[1] {
+                    for n3 in -n_max[2]..=n_max[2] {
+                        if a == b && n1 == 0 && n2 == 0 && n3 == 0 {
+                            continue;
+                        }
+                        let x = base - lattice * Vector3::new(n1 as f64, n2 as f64, n3 as f64);
+                        let r = x.norm();
+                        if r > r_cut {
+                            continue;
+                        }
+                        // f(r) = Z erfc(√α r)/r: ∂_i∂_j f = f'' x̂_i x̂_j + f'/r (δ_ij - x̂_i x̂_j)
+                        let erfc = 1.0 - erf(sqrt_alpha * r);
+                        let gauss = two_over_sqrt_pi * sqrt_alpha * (-alpha * r * r).exp();
+                        let d1 = -gauss / r - erfc / (r * r);
+                        let d2 = 2.0 * erfc / (r * r * r) + 2.0 * gauss / (r * r) + 2.0 * alpha * gauss;
+                        let xx = x * x.transpose() / (r * r);
+                        tensors[a] += charges[b] * (xx * d2 + (Matrix3::identity() - xx) * (d1 / r));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(structure.atoms.iter().zip(tensors)
+        .map(|(atom, v)| {
+            let q = structure.species.iter()
+                .find(|s| s.id == atom.species_id)
+                .and_then(|s| quadrupole_moments.get(&s.element).copied());
+            EfgTensor::from_tensor(v, q)
+        })
+        .collect())
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use nalgebra::{DMatrix, DVector, Matrix3};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::structure::{Atom, Lattice, Structure};
+use crate::tools::scan::{ScanError, ScanParameter, ScanPoint, ScanRunner};
+use crate::utils::constants::{AU_PRESSURE_TO_GPA, RY_TO_HA};
+
+#[derive(Error, Debug)]
+pub enum ElasticError {
+    #[error("Deformação {0:?}: pontos insuficientes para o ajuste ({1}, mínimo 3).")]
+    NotEnoughPoints(CubicStrain, usize),
+
+    #[error("Deformação {0:?}: ajuste quadrático de E(δ) falhou.")]
+    FitFailed(CubicStrain),
+
+    #[error("Rede mecanicamente instável: {0}")]
+    Unstable(String),
+}
+
+/// Deformações adaptadas à simetria cúbica. Para cada uma, ΔE/V0 = k·δ² com k
+/// combinação de C11, C12 e C44 (notação de Voigt, γ = 2ε para cisalhamento).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CubicStrain {
+    /// ε = diag(δ, δ, δ): k = 3/2 (C11 + 2 C12).
+    Hydrostatic,
+    /// ε = diag(δ, -δ, 0): k = C11 - C12.
+    Orthorhombic,
+    /// ε_xy = ε_yx = δ/2: k = C44 / 2.
+    Shear,
+}
+
+impl CubicStrain {
+    pub const ALL: [CubicStrain; 3] = [CubicStrain::Hydrostatic, CubicStrain::Orthorhombic, CubicStrain::Shear];
+
+    /// Tensor de deformação ε para a amplitude δ.
+    pub fn tensor(&self, delta: f64) -> Matrix3<f64> {
+        match self {
+            CubicStrain::Hydrostatic => Matrix3::from_diagonal_element(delta),
+            CubicStrain::Orthorhombic => Matrix3::new(delta, 0.0, 0.0, 0.0, -delta, 0.0, 0.0, 0.0, 0.0),
+            CubicStrain::Shear => Matrix3::new(0.0, delta / 2.0, 0.0, delta / 2.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Aplica a deformação homogênea a' = (I + ε) a à célula; os átomos mantêm as coordenadas
+/// fracionárias (sem relaxação interna, exata para redes de Bravais e sítios de alta simetria).
+pub fn apply_strain(structure: &Structure, strain: &Matrix3<f64>) -> Structure {
+    let deformation = Matrix3::identity() + strain;
+    Structure {
+        lattice: Lattice { vectors: deformation * structure.lattice.vectors },
+        species: structure.species.clone(),
+        atoms: structure.atoms.iter()
+            .map(|atom| Atom { position: deformation * atom.position, ..atom.clone() })
+            .collect(),
+    }
+}
+
+/// Constantes elásticas de um cristal cúbico (Ry/Bohr³).
+#[derive(Debug, Clone, Copy)]
+pub struct CubicElasticConstants {
+    pub c11: f64,
+    pub c12: f64,
+    pub c44: f64,
+    pub v0: f64, // Volume da célula de referência (Bohr³)
+}
+
+impl CubicElasticConstants {
+    /// Módulo volumétrico B = (C11 + 2 C12) / 3.
+    pub fn bulk_modulus(&self) -> f64 {
+        (self.c11 + 2.0 * self.c12) / 3.0
+    }
+
+    /// (C11, C12, C44) em GPa.
+    pub fn to_gpa(&self) -> [f64; 3] {
+        [self.c11, self.c12, self.c44].map(|c| c * RY_TO_HA * AU_PRESSURE_TO_GPA)
+    }
+
+    /// Critérios de Born para a rede cúbica: C11 - C12 > 0, C11 + 2 C12 > 0, C44 > 0.
+    pub fn check_stability(&self) -> Result<(), ElasticError> {
+        if self.c11 - self.c12 <= 0.0 {
+            return Err(ElasticError::Unstable("C11 - C12 <= 0".into()));
+        }
+        if self.c11 + 2.0 * self.c12 <= 0.0 {
+            return Err(ElasticError::Unstable("C11 + 2 C12 <= 0".into()));
+        }
+        if self.c44 <= 0.0 {
+            return Err(ElasticError::Unstable("C44 <= 0".into()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CubicElasticConstants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [c11, c12, c44] = self.to_gpa();
+        writeln!(f, "Constantes elásticas (cúbica):")?;
+        writeln!(f, "  C11 = {:.2} GPa", c11)?;
+        writeln!(f, "  C12 = {:.2} GPa", c12)?;
+        writeln!(f, "  C44 = {:.2} GPa", c44)?;
+        write!(f, "  B   = {:.2} GPa", self.bulk_modulus() * RY_TO_HA * AU_PRESSURE_TO_GPA)
+    }
+}
+
+/// Curvatura c2 do ajuste E(δ) = c0 + c1 δ + c2 δ² (mínimos quadrados). O termo linear
+/// absorve uma tensão residual da célula de referência.
+fn curvature(kind: CubicStrain, deltas: &[f64], energies: &[f64]) -> Result<f64, ElasticError> {
+    let n = deltas.len().min(energies.len());
+    if n < 3 {
+        return Err(ElasticError::NotEnoughPoints(kind, n));
+    }
+    let a = DMatrix::from_fn(n, 3, |i, j| deltas[i].powi(j as i32));
+    let b = DVector::from_column_slice(&energies[..n]);
+    let c = a.svd(true, true).solve(&b, 1e-14).map_err(|_| ElasticError::FitFailed(kind))?;
+    Ok(c[2])
+}
+
+/// C11, C12 e C44 a partir das curvas E(δ) (Ry) das três deformações, na ordem de
+/// `CubicStrain::ALL`, para a célula de volume `v0`.
+pub fn fit_cubic(v0: f64, curves: [(&[f64], &[f64]); 3]) -> Result<CubicElasticConstants, ElasticError> {
+    let [k_hydro, k_ortho, k_shear] = [0, 1, 2]
+        .map(|i| curvature(CubicStrain::ALL[i], curves[i].0, curves[i].1).map(|c| c / v0));
+    let sum = k_hydro? * 2.0 / 3.0; // C11 + 2 C12
+    let diff = k_ortho?;            // C11 - C12
+    let c44 = 2.0 * k_shear?;
+
+    Ok(CubicElasticConstants {
+        c11: (sum + 2.0 * diff) / 3.0,
+        c12: (sum - diff) / 3.0,
+        c44,
+        v0,
+    })
+}
+
+/// Varredura de deformações para uma célula cúbica: para cada deformação de
+/// `CubicStrain::ALL` e cada amplitude em `deltas` (ex: ±0.005, ±0.01), chama
+/// `energy(estrutura deformada)` via `ScanRunner` (pontos em cache são reaproveitados) e
+/// ajusta C11, C12 e C44. O ponto δ = 0 é incluído uma única vez, se presente em `deltas`.
+pub fn cubic_elastic_scan<F, E>(
+    runner: &mut ScanRunner,
+    structure: &Structure,
+    deltas: &[f64],
+    mut energy: F,
+) -> Result<CubicElasticConstants, E>
+where
+    F: FnMut(&Structure) -> Result<f64, E>,
+    E: From<ScanError> + From<ElasticError>,
+{
+    let mut curves: Vec<(Vec<f64>, Vec<f64>)> = Vec::with_capacity(3);
+    for kind in CubicStrain::ALL {
+        log::info!("Deformação {:?}", kind);
+        // δ = 0 é a mesma célula para as três deformações: um único cálculo
+        let parameters = deltas.iter().map(|&d| {
+            if d == 0.0 { ScanParameter::Strain(CubicStrain::Hydrostatic, 0.0) } else { ScanParameter::Strain(kind, d) }
+        });
+        let points: Vec<ScanPoint> = runner.run(parameters, |p| match *p {
+            ScanParameter::Strain(kind, delta) => energy(&apply_strain(structure, &kind.tensor(delta))),
+            _ => unreachable!("varredura elástica só gera deformações"),
+        })?;
+        curves.push((deltas.to_vec(), points.iter().map(|p| p.energy).collect()));
+    }
+
+    let v0 = structure.lattice.volume();
+    let constants = fit_cubic(v0, [
+        (&curves[0].0, &curves[0].1),
+        (&curves[1].0, &curves[1].1),
+        (&curves[2].0, &curves[2].1),
+    ])?;
+    log::info!("{}", constants);
+    Ok(constants)
+}
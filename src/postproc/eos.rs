@@ -0,0 +1,236 @@
+use std::fmt;
+use nalgebra::{DMatrix, DVector};
+use thiserror::Error;
+
+use crate::utils::constants::{AU_PRESSURE_TO_GPA, RY_TO_HA};
+
+#[derive(Error, Debug)]
+pub enum EosError {
+    #[error("Volumes e energias com tamanhos diferentes ({0} vs {1}).")]
+    LengthMismatch(usize, usize),
+
+    #[error("Pontos insuficientes para o ajuste: {0} (mínimo {1}).")]
+    NotEnoughPoints(usize, usize),
+
+    #[error("O ajuste não possui mínimo físico (curva E(V) sem concavidade positiva).")]
+    NoMinimum,
+
+    #[error("O ajuste não-linear não convergiu.")]
+    FitFailed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EosKind {
+    BirchMurnaghan,
+    Vinet,
+}
+
+/// Parâmetros de uma equação de estado ajustada. Unidades: Ry e Bohr.
+#[derive(Debug, Clone, Copy)]
+pub struct EosFit {
+    pub kind: EosKind,
+    pub e0: f64,       // Energia no mínimo (Ry)
+    pub v0: f64,       // Volume de equilíbrio (Bohr^3)
+    pub b0: f64,       // Módulo volumétrico (Ry/Bohr^3)
+    pub b0_prime: f64, // dB/dP (adimensional)
+    pub rms: f64,      // Resíduo RMS do ajuste (Ry)
+}
+
+impl EosFit {
+    /// Módulo volumétrico em GPa.
+    pub fn b0_gpa(&self) -> f64 {
+        self.b0 * RY_TO_HA * AU_PRESSURE_TO_GPA
+    }
+
+    /// Parâmetro de rede a partir de V0 = fator * a^3
+    /// (fator = 1 cúbica simples, 1/4 FCC, 1/2 BCC, considerando a célula primitiva).
+    pub fn lattice_constant(&self, volume_factor: f64) -> f64 {
+        (self.v0 / volume_factor).cbrt()
+    }
+
+    /// Energia prevista pela equação ajustada em um volume V.
+    pub fn energy(&self, v: f64) -> f64 {
+        match self.kind {
+            EosKind::BirchMurnaghan => birch_murnaghan(v, self.e0, self.v0, self.b0, self.b0_prime),
+            EosKind::Vinet => vinet(v, self.e0, self.v0, self.b0, self.b0_prime),
+        }
+    }
+}
+
+impl fmt::Display for EosFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "EOS ({:?}):", self.kind)?;
+        writeln!(f, "  E0  = {:.8} Ry", self.e0)?;
+        writeln!(f, "  V0  = {:.4} Bohr^3", self.v0)?;
+        writeln!(f, "  B0  = {:.2} GPa", self.b0_gpa())?;
+        writeln!(f, "  B0' = {:.3}", self.b0_prime)?;
+        write!(f, "  RMS = {:.3e} Ry", self.rms)
+    }
+}
+
+/// E(V) de Birch-Murnaghan de 3ª ordem.
+pub fn birch_murnaghan(v: f64, e0: f64, v0: f64, b0: f64, bp: f64) -> f64 {
+    let eta = (v0 / v).powf(2.0 / 3.0);
+    e0 + 9.0 * v0 * b0 / 16.0 * ((eta - 1.0).powi(3) * bp + (eta - 1.0).powi(2) * (6.0 - 4.0 * eta))
+}
+
+/// E(V) de Vinet.
+pub fn vinet(v: f64, e0: f64, v0: f64, b0: f64, bp: f64) -> f64 {
+    let eta = (v / v0).cbrt();
+    let a = 1.5 * (bp - 1.0);
+    e0 + 2.0 * b0 * v0 / (bp - 1.0).powi(2)
+        * (2.0 - (5.0 + 3.0 * bp * (eta - 1.0) - 3.0 * eta) * (-a * (eta - 1.0)).exp())
+}
+
+fn check_input(volumes: &[f64], energies: &[f64], min_points: usize) -> Result<(), EosError> {
+    if volumes.len() != energies.len() {
+        return Err(EosError::LengthMismatch(volumes.len(), energies.len()));
+    }
+    if volumes.len() < min_points {
+        return Err(EosError::NotEnoughPoints(volumes.len(), min_points));
+    }
+    Ok(())
+}
+
+fn rms(volumes: &[f64], energies: &[f64], model: impl Fn(f64) -> f64) -> f64 {
+    let sum: f64 = volumes.iter().zip(energies).map(|(&v, &e)| (model(v) - e).powi(2)).sum();
+    (sum / volumes.len() as f64).sqrt()
+}
+
+/// Ajuste de Birch-Murnaghan de 3ª ordem.
+///
+/// A BM3 é um polinômio cúbico em x = V^(-2/3): E = c0 + c1 x + c2 x^2 + c3 x^3,
+/// então o ajuste é linear (mínimos quadrados). V0, B0 = V E''(V) e B0' saem das
+/// derivadas analíticas do polinômio no mínimo.
+pub fn fit_birch_murnaghan(volumes: &[f64], energies: &[f64]) -> Result<EosFit, EosError> {
+    check_input(volumes, energies, 4)?;
+
+    let n = volumes.len();
+    let a = DMatrix::from_fn(n, 4, |i, j| volumes[i].powf(-2.0 / 3.0).powi(j as i32));
+    let b = DVector::from_column_slice(energies);
+    let c = a.svd(true, true).solve(&b, 1e-14).map_err(|_| EosError::FitFailed)?;
+    let (c1, c2, c3) = (c[1], c[2], c[3]);
+
+    // dE/dx = c1 + 2 c2 x + 3 c3 x^2 = 0, com E''(x) > 0
+    let x_min = volumes.iter().cloned().fold(f64::INFINITY, f64::min).powf(-2.0 / 3.0);
+    let x_max = volumes.iter().cloned().fold(0.0, f64::max).powf(-2.0 / 3.0);
+    let x_mid = 0.5 * (x_min + x_max);
+
+    let roots: Vec<f64> = if c3.abs() < 1e-300 {
+        vec![-c1 / (2.0 * c2)]
+    } else {
+        let disc = 4.0 * c2 * c2 - 12.0 * c3 * c1;
+        if disc < 0.0 {
+            return Err(EosError::NoMinimum);
+        }
+        vec![(-2.0 * c2 + disc.sqrt()) / (6.0 * c3), (-2.0 * c2 - disc.sqrt()) / (6.0 * c3)]
+    };
+
+    let x0 = roots.into_iter()
+        .filter(|&x| x > 0.0 && 2.0 * c2 + 6.0 * c3 * x > 0.0)
+        .min_by(|a, b| (a - x_mid).abs().total_cmp(&(b - x_mid).abs()))
+        .ok_or(EosError::NoMinimum)?;
+
+    let v0 = x0.powf(-1.5);
+    let poly = |x: f64| c[0] + c1 * x + c2 * x * x + c3 * x * x * x;
+
+    // Regra da cadeia x(V) = V^(-2/3); no mínimo E'(x) = 0
+    let e_xx = 2.0 * c2 + 6.0 * c3 * x0;
+    let e_xxx = 6.0 * c3;
+    let dx = -2.0 / 3.0 * v0.powf(-5.0 / 3.0);
+    let d2x = 10.0 / 9.0 * v0.powf(-8.0 / 3.0);
+    let e_vv = e_xx * dx * dx;
+    let e_vvv = e_xxx * dx.powi(3) + 3.0 * e_xx * dx * d2x;
+
+    let b0 = v0 * e_vv;
+    // B' = dB/dP = -(V/B) dB/dV, com dB/dV = E'' + V E'''
+    let b0_prime = -1.0 - v0 * v0 * e_vvv / b0;
+
+    let mut fit = EosFit {
+        kind: EosKind::BirchMurnaghan,
+        e0: poly(x0),
+        v0,
+        b0,
+        b0_prime,
+        rms: 0.0,
+    };
+    fit.rms = rms(volumes, energies, |v| fit.energy(v));
+    Ok(fit)
+}
+
+/// Ajuste da equação de Vinet por Levenberg-Marquardt, partindo do ajuste BM3.
+pub fn fit_vinet(volumes: &[f64], energies: &[f64]) -> Result<EosFit, EosError> {
+    let guess = fit_birch_murnaghan(volumes, energies)?;
+    let p = levenberg_marquardt(
+        [guess.e0, guess.v0, guess.b0, guess.b0_prime],
+        |p: &[f64; 4]| volumes.iter().zip(energies).map(|(&v, &e)| vinet(v, p[0], p[1], p[2], p[3]) - e).collect(),
+    )?;
+
+    if p[1] <= 0.0 || p[2] <= 0.0 {
+        return Err(EosError::NoMinimum);
+    }
+
+    let mut fit = EosFit { kind: EosKind::Vinet, e0: p[0], v0: p[1], b0: p[2], b0_prime: p[3], rms: 0.0 };
+    fit.rms = rms(volumes, energies, |v| fit.energy(v));
+    Ok(fit)
+}
+
+/// Levenberg-Marquardt mínimo para 4 parâmetros, com Jacobiano por diferenças finitas
+/// e escala de Marquardt (diag(J^T J)) para lidar com parâmetros de ordens muito diferentes.
+fn levenberg_marquardt<F>(start: [f64; 4], residuals: F) -> Result<[f64; 4], EosError>
+where
+    F: Fn(&[f64; 4]) -> Vec<f64>,
+{
+    let cost = |r: &[f64]| r.iter().map(|x| x * x).sum::<f64>();
+    let mut p = start;
+    let mut r = residuals(&p);
+    let mut lambda = 1e-3;
+
+    for _ in 0..500 {
+        let n = r.len();
+        let mut jac = DMatrix::<f64>::zeros(n, 4);
+        for j in 0..4 {
+            let h = 1e-7 * p[j].abs().max(1e-10);
+            let mut pp = p;
+            pp[j] += h;
+            let rp = residuals(&pp);
+            for i in 0..n {
+                jac[(i, j)] = (rp[i] - r[i]) / h;
+            }
+        }
+
+        let jtj = jac.transpose() * &jac;
+        let jtr = jac.transpose() * DVector::from_column_slice(&r);
+
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut a = jtj.clone();
+            for j in 0..4 {
+                a[(j, j)] += lambda * jtj[(j, j)].max(1e-300);
+            }
+            let Some(step) = a.lu().solve(&(-&jtr)) else { break; };
+
+            let trial = [p[0] + step[0], p[1] + step[1], p[2] + step[2], p[3] + step[3]];
+            let rt = residuals(&trial);
+            if rt.iter().all(|x| x.is_finite()) && cost(&rt) < cost(&r) {
+                let rel: f64 = (0..4).map(|j| (step[j] / p[j].abs().max(1e-300)).abs()).fold(0.0, f64::max);
+                p = trial;
+                r = rt;
+                lambda = (lambda * 0.1).max(1e-12);
+                improved = true;
+                if rel < 1e-12 {
+                    return Ok(p);
+                }
+                break;
+            }
+            lambda *= 10.0;
+        }
+
+        if !improved {
+            // Sem passo que reduza o custo: estamos no mínimo (dentro da precisão numérica)
+            return if p.iter().all(|x| x.is_finite()) { Ok(p) } else { Err(EosError::FitFailed) };
+        }
+    }
+
+    Ok(p)
+}
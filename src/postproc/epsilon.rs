@@ -0,0 +1,138 @@
+use std::f64::consts::PI;
+use nalgebra::Vector3;
+use num_complex::Complex64;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::Structure;
+use crate::dft::solver::BandSolverResult;
+use crate::utils::constants::RY_TO_HA;
+
+/// Estados de um ponto K usados no cálculo óptico.
+pub struct OpticalKPoint<'a> {
+    pub basis: &'a PlaneWaveBasis,
+    pub bands: &'a BandSolverResult,
+    pub weight: f64, // Peso na ZB (soma 1)
+}
+
+/// Função dielétrica de partículas independentes (componentes diagonais xx, yy, zz).
+#[derive(Debug, Clone)]
+pub struct DielectricFunction {
+    pub omega: Vec<f64>,      // Energia do fóton (Ry)
+    pub eps2: Vec<[f64; 3]>,  // Parte imaginária
+    pub eps1: Vec<[f64; 3]>,  // Parte real (Kramers-Kronig)
+}
+
+impl DielectricFunction {
+    /// Média isotrópica (traço/3) de ε₂.
+    pub fn eps2_average(&self) -> Vec<f64> {
+        self.eps2.iter().map(|e| (e[0] + e[1] + e[2]) / 3.0).collect()
+    }
+
+    /// Média isotrópica (traço/3) de ε₁.
+    pub fn eps1_average(&self) -> Vec<f64> {
+        self.eps1.iter().map(|e| (e[0] + e[1] + e[2]) / 3.0).collect()
+    }
+}
+
+/// Elementos de matriz do momento p = -i∇ entre duas bandas: Σ_G c_a*(G) c_b(G) (k+G).
+pub fn momentum_matrix_element(
+    basis: &PlaneWaveBasis,
+    recip: &nalgebra::Matrix3<f64>,
+    psi_a: &ndarray::Array1<Complex64>,
+    psi_b: &ndarray::Array1<Complex64>,
+) -> Vector3<Complex64> {
+    let mut p = Vector3::<Complex64>::zeros();
+    for (ig, &(i, j, k)) in basis.g_vectors.iter().enumerate() {
+        let kg = recip * (basis.k_point + Vector3::new(i as f64, j as f64, k as f64));
+        let c = psi_a[ig].conj() * psi_b[ig];
+        p += Vector3::new(c * kg.x, c * kg.y, c * kg.z);
+    }
+    p
+}
+
+/// ε₂(ω) em aproximação de partículas independentes (unidades atômicas de Hartree):
+///
+/// ε₂_αα(ω) = (8π² / Ω ω²) Σ_k w_k Σ_{v,c} |⟨c|p_α|v⟩|² δ(ε_c - ε_v - ω)
+///
+/// O fator 2 de spin está incluído; a delta é alargada por uma gaussiana de largura `sigma` (Ry).
+/// Considera-se apenas o termo local do momento (o comutador [V_NL, r] é desprezado).
+/// As primeiras `n_occupied` bandas de cada k são ocupadas, as demais vazias.
+pub fn dielectric_function(
+    structure: &Structure,
+    kpoints: &[OpticalKPoint],
+    n_occupied: usize,
+    omega_max: f64,
+    n_omega: usize,
+    sigma: f64,
+) -> DielectricFunction {
+    let recip = structure.lattice.reciprocal();
+    let volume = structure.lattice.volume();
+    let d_omega = omega_max / n_omega.max(1) as f64;
+    let omega: Vec<f64> = (0..=n_omega).map(|i| i as f64 * d_omega).collect();
+
+    // Tudo em Hartree internamente
+    let sigma_ha = sigma * RY_TO_HA;
+    let mut eps2 = vec![[0.0; 3]; omega.len()];
+
+    for kp in kpoints {
+        let nb = kp.bands.eigenvalues.len();
+        for v in 0..n_occupied.min(nb) {
+            for c in n_occupied..nb {
+                let de = (kp.bands.eigenvalues[c] - kp.bands.eigenvalues[v]) * RY_TO_HA;
+                if de <= 0.0 {
+                    continue;
+                }
+                let p = momentum_matrix_element(kp.basis, &recip, &kp.bands.eigenvectors[c], &kp.bands.eigenvectors[v]);
+                let p2 = [p.x.norm_sqr(), p.y.norm_sqr(), p.z.norm_sqr()];
+
+                for (iw, &w) in omega.iter().enumerate() {
+                    let w_ha = w * RY_TO_HA;
+                    if w_ha <= 0.0 {
+                        continue;
+                    }
+                    let x = (de - w_ha) / sigma_ha;
+                    if x.abs() > 6.0 {
+                        continue;
+                    }
+                    let delta = (-0.5 * x * x).exp() / (sigma_ha * (2.0 * PI).sqrt());
+                    let pref = 8.0 * PI * PI / (volume * w_ha * w_ha) * kp.weight * delta;
+                    for a in 0..3 {
+                        eps2[iw][a] += pref * p2[a];
+                    }
+                }
+            }
+        }
+    }
+
+    let eps1 = kramers_kronig(&omega, &eps2);
+    DielectricFunction { omega, eps2, eps1 }
+}
+
+/// ε₁(ω) = 1 + (2/π) P∫ ω' ε₂(ω') / (ω'² - ω²) dω'
+/// em uma malha uniforme; o valor principal é tratado omitindo o ponto singular ω' = ω.
+pub fn kramers_kronig(omega: &[f64], eps2: &[[f64; 3]]) -> Vec<[f64; 3]> {
+    let n = omega.len();
+    if n < 2 {
+        return vec![[1.0; 3]; n];
+    }
+    let dw = omega[1] - omega[0];
+
+    (0..n)
+        .map(|i| {
+            let mut e1 = [1.0; 3];
+            for j in 0..n {
+                if j == i {
+                    continue;
+                }
+                let wj = omega[j];
+                // Regra do trapézio: pesos 1/2 nos extremos
+                let trap = if j == 0 || j == n - 1 { 0.5 } else { 1.0 };
+                let kernel = 2.0 / PI * trap * dw * wj / (wj * wj - omega[i] * omega[i]);
+                for a in 0..3 {
+                    e1[a] += kernel * eps2[j][a];
+                }
+            }
+            e1
+        })
+        .collect()
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+use ndarray::Array3;
+use nalgebra::Vector3;
+use serde::Serialize;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::io::cube::write_cube;
+
+/// Raio padrão das esferas de integração (Bohr).
+pub const DEFAULT_SPHERE_RADIUS: f64 = 2.0;
+
+/// Resumo magnético de um cálculo com spin polarizado.
+#[derive(Debug, Clone, Serialize)]
+pub struct MagnetizationSummary {
+    pub total: f64,            // ∫(ρ↑ - ρ↓) dr (μ_B)
+    pub absolute: f64,         // ∫|ρ↑ - ρ↓| dr (μ_B)
+    pub atomic_moments: Vec<f64>, // Momento integrado na esfera de cada átomo (μ_B)
+}
+
+impl MagnetizationSummary {
+    /// Linhas para o resumo final do SCF.
+    pub fn log(&self, structure: &Structure) {
+        log::info!("Magnetização total    = {:>10.4} μ_B/célula", self.total);
+        log::info!("Magnetização absoluta = {:>10.4} μ_B/célula", self.absolute);
+        for (i, (atom, m)) in structure.atoms.iter().zip(&self.atomic_moments).enumerate() {
+            let element = structure.species.iter()
+                .find(|s| s.id == atom.species_id)
+                .map(|s| s.element.as_str())
+                .unwrap_or("?");
+            log::info!("  átomo {:>3} ({:<2}): m = {:>8.4} μ_B", i + 1, element, m);
+        }
+    }
+}
+
+/// Densidade de spin m(r) = ρ↑(r) - ρ↓(r).
+pub fn spin_density(rho_up: &Array3<f64>, rho_down: &Array3<f64>) -> Array3<f64> {
+    rho_up - rho_down
+}
+
+/// Momentos atômicos por integração em esferas centradas em cada átomo.
+/// `radius(species_id)` devolve o raio (Bohr); use raios que não se sobreponham.
+/// Distâncias pela convenção de imagem mínima.
+pub fn atomic_moments<F>(structure: &Structure, m: &Array3<f64>, radius: F) -> Result<Vec<f64>, DftError>
+where
+    F: Fn(usize) -> f64,
+{
+    let (nx, ny, nz) = m.dim();
+    let lattice = &structure.lattice.vectors;
+    let lattice_inv = lattice.try_inverse().ok_or(DftError::SingularLattice)?;
+    let dvol = structure.lattice.dvol(m.len());
+
+    let mut moments = vec![0.0; structure.atoms.len()];
+    for ((i, j, k), &val) in m.indexed_iter() {
+        let frac = Vector3::new(i as f64 / nx as f64, j as f64 / ny as f64, k as f64 / nz as f64);
+        for (ia, atom) in structure.atoms.iter().enumerate() {
+            let mut d = frac - lattice_inv * atom.position;
+            d.apply(|x| *x -= x.round());
+            if (lattice * d).norm() <= radius(atom.species_id) {
+                moments[ia] += val * dvol;
+            }
+        }
+    }
+    Ok(moments)
+}
+
+/// Magnetização total, absoluta e momentos atômicos.
+pub fn magnetization_summary<F>(structure: &Structure, m: &Array3<f64>, radius: F) -> Result<MagnetizationSummary, DftError>
+where
+    F: Fn(usize) -> f64,
+{
+    let dvol = structure.lattice.dvol(m.len());
+    Ok(MagnetizationSummary {
+        total: m.sum() * dvol,
+        absolute: m.iter().map(|x| x.abs()).sum::<f64>() * dvol,
+        atomic_moments: atomic_moments(structure, m, radius)?,
+    })
+}
+
+/// Escreve ρ↑ - ρ↓ em formato cube.
+pub fn write_spin_density<P: AsRef<Path>>(
+    path: P,
+    structure: &Structure,
+    rho_up: &Array3<f64>,
+    rho_down: &Array3<f64>,
+) -> std::io::Result<()> {
+    write_cube(path, structure, &spin_density(rho_up, rho_down), "Densidade de spin (rho_up - rho_down)")
+}
@@ -0,0 +1,13 @@
+pub mod eos;
+pub mod phonon;
+pub mod epsilon;
+pub mod effective_mass;
+pub mod partial_density;
+pub mod magnetization;
+pub mod site_potential;
+pub mod scissor;
+pub mod band_gap;
+pub mod elastic;
+pub mod thermal;
+pub mod dos;
+pub mod efg;
\ No newline at end of file
@@ -0,0 +1,69 @@
+use ndarray::Array3;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::fft::FftGrid;
+use crate::core::structure::Structure;
+use crate::dft::solver::BandSolverResult;
+use crate::dft::density::DensityBuilder;
+use crate::dft::error::DftError;
+
+/// Estados de um ponto K para a densidade parcial.
+pub struct KPointStates<'a> {
+    pub basis: &'a PlaneWaveBasis,
+    pub bands: &'a BandSolverResult,
+    pub weight: f64, // Peso na ZB (soma 1)
+}
+
+/// Critério de seleção dos estados |ψ_nk|².
+#[derive(Debug, Clone, Copy)]
+pub enum BandSelection {
+    /// Índices de banda [first, last] (inclusivo, base 0)
+    Range(usize, usize),
+    /// Autovalores dentro de [e_min, e_max] (Ry)
+    EnergyWindow(f64, f64),
+}
+
+impl BandSelection {
+    fn contains(&self, band: usize, energy: f64) -> bool {
+        match *self {
+            BandSelection::Range(first, last) => band >= first && band <= last,
+            BandSelection::EnergyWindow(e_min, e_max) => energy >= e_min && energy <= e_max,
+        }
+    }
+}
+
+/// ρ_parcial(r) = 2 Σ_k w_k Σ_{n ∈ seleção} |ψ_nk(r)|²  (elétrons/Bohr³, spin degenerado).
+///
+/// `k_index` restringe a soma a um único ponto K (ex: estado de defeito em Γ).
+/// `fft` é o grid denso das bases (ex: `Simulation::fft_grid`).
+/// Útil para visualizar estados tipo HOMO/LUMO; exporte com `io::cube::write_cube`.
+pub fn partial_density(
+    structure: &Structure,
+    fft: &mut FftGrid,
+    kpoints: &[KPointStates],
+    selection: BandSelection,
+    k_index: Option<usize>,
+) -> Result<Array3<f64>, DftError> {
+    if kpoints.is_empty() {
+        return Ok(Array3::zeros((0, 0, 0)));
+    }
+    let mut builder = DensityBuilder::new(structure.lattice.clone(), fft, 1);
+
+    for (ik, kp) in kpoints.iter().enumerate() {
+        if k_index.is_some_and(|sel| sel != ik) {
+            continue;
+        }
+
+        for (n, (&e, psi)) in kp.bands.eigenvalues.iter().zip(&kp.bands.eigenvectors).enumerate() {
+            if selection.contains(n, e) {
+                builder.add_state(kp.basis, psi, kp.weight, 2.0, 0)?;
+            }
+        }
+    }
+    Ok(builder.build().0.data)
+}
+
+/// Carga total de um campo no grid: ∫ρ dr = Ω/N Σ ρ(r).
+pub fn integrate(structure: &Structure, rho: &Array3<f64>) -> f64 {
+    rho.sum() * structure.lattice.dvol(rho.len())
+}
@@ -0,0 +1,331 @@
+use nalgebra::{DMatrix, SymmetricEigen, Vector3};
+use thiserror::Error;
+
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+use crate::utils::constants::{AMU_TO_AU, HA_TO_THZ, HA_TO_WAVENUMBER, RY_TO_HA};
+
+/// Deslocamento padrão para as diferenças finitas (Bohr), como no phonopy (~0.01 Å).
+pub const DEFAULT_DISPLACEMENT: f64 = 0.02;
+
+#[derive(Error, Debug)]
+pub enum PhononError {
+    #[error("Espécie {0} sem massa definida (Species.mass = 0).")]
+    MissingMass(usize),
+
+    #[error("O cálculo de forças retornou {0} vetores, esperado {1} (um por átomo).")]
+    ForceCount(usize, usize),
+}
+
+/// Fônons em Γ: frequências e modos normais.
+#[derive(Debug, Clone)]
+pub struct GammaPhonons {
+    /// Frequências (cm⁻¹) em ordem crescente; modos instáveis (imaginários) são negativos.
+    pub frequencies_cm: Vec<f64>,
+    /// Autovetores da matriz dinâmica (colunas, 3N x 3N), mesma ordem das frequências.
+    pub eigenvectors: DMatrix<f64>,
+    /// Constantes de força Φ_{iα,jβ} (Ry/Bohr^2).
+    pub force_constants: DMatrix<f64>,
+}
+
+impl GammaPhonons {
+    /// Frequências em THz.
+    pub fn frequencies_thz(&self) -> Vec<f64> {
+        self.frequencies_cm.iter().map(|w| w / HA_TO_WAVENUMBER * HA_TO_THZ).collect()
+    }
+}
+
+/// Massa em unidades de Rydberg (m_e = 1/2) a partir da massa atômica (u).
+pub fn amu_to_ry_mass(mass_amu: f64) -> f64 {
+    mass_amu * AMU_TO_AU * 0.5
+}
+
+/// Massas (unidades Ry) de cada átomo, na ordem de `structure.atoms`.
+pub fn atomic_masses(structure: &Structure) -> Result<Vec<f64>, PhononError> {
+    structure.atoms.iter()
+        .map(|atom| {
+            structure.species.iter()
+                .find(|s| s.id == atom.species_id)
+                .map(|s| s.mass)
+                .filter(|&m| m > 0.0)
+                .map(amu_to_ry_mass)
+                .ok_or(PhononError::MissingMass(atom.species_id))
+        })
+        .collect()
+}
+
+/// Autovalor da matriz dinâmica (Ry^2, unidades de Rydberg) -> frequência em cm⁻¹.
+/// Autovalores negativos viram frequências negativas (modos imaginários).
+pub fn eigenvalue_to_cm(lambda: f64) -> f64 {
+    lambda.signum() * lambda.abs().sqrt() * RY_TO_HA * HA_TO_WAVENUMBER
+}
+
+/// Constantes de força por diferenças finitas centrais:
+/// Φ_{iα,jβ} = -[F_jβ(+u_iα) - F_jβ(-u_iα)] / (2u)
+///
+/// `forces(structure)` devolve a força (Ry/Bohr) em cada átomo. São 6N cálculos de força.
+pub fn force_constants<F, E>(structure: &Structure, displacement: f64, mut forces: F) -> Result<DMatrix<f64>, E>
+where
+    F: FnMut(&Structure) -> Result<Vec<Vector3<f64>>, E>,
+    E: From<PhononError>,
+{
+    let natoms = structure.atoms.len();
+    let mut phi = DMatrix::<f64>::zeros(3 * natoms, 3 * natoms);
+
+    for i in 0..natoms {
+        for alpha in 0..3 {
+            let mut displaced = |sign: f64| -> Result<Vec<Vector3<f64>>, E> {
+                let mut s = structure.clone();
+                s.atoms[i].position[alpha] += sign * displacement;
+                let f = forces(&s)?;
+                if f.len() != natoms {
+                    return Err(PhononError::ForceCount(f.len(), natoms).into());
+                }
+                Ok(f)
+            };
+            let f_plus = displaced(1.0)?;
+            let f_minus = displaced(-1.0)?;
+
+            for j in 0..natoms {
+                for beta in 0..3 {
+                    phi[(3 * i + alpha, 3 * j + beta)] =
+                        -(f_plus[j][beta] - f_minus[j][beta]) / (2.0 * displacement);
+                }
+            }
+        }
+    }
+
+    // Simetrização (remove ruído numérico das forças)
+    let phi_t = phi.transpose();
+    Ok((phi + phi_t) * 0.5)
+}
+
+/// Impõe a regra de soma acústica: Σ_j Φ_{iα,jβ} = 0 (translação rígida não custa energia).
+/// Corrige os blocos diagonais Φ_{iα,iβ} = -Σ_{j≠i} Φ_{iα,jβ}.
+pub fn apply_acoustic_sum_rule(phi: &mut DMatrix<f64>) {
+    let natoms = phi.nrows() / 3;
+    for i in 0..natoms {
+        for alpha in 0..3 {
+            for beta in 0..3 {
+                let off: f64 = (0..natoms)
+                    .filter(|&j| j != i)
+                    .map(|j| phi[(3 * i + alpha, 3 * j + beta)])
+                    .sum();
+                phi[(3 * i + alpha, 3 * i + beta)] = -off;
+            }
+        }
+    }
+    let phi_t = phi.transpose();
+    *phi = (&*phi + phi_t) * 0.5;
+}
+
+/// Diagonaliza a matriz dinâmica D = Φ / sqrt(M_i M_j) e devolve frequências (cm⁻¹) e modos.
+pub fn diagonalize_dynamical_matrix(phi: &DMatrix<f64>, masses: &[f64]) -> (Vec<f64>, DMatrix<f64>) {
+    let n = phi.nrows();
+    let dyn_mat = DMatrix::from_fn(n, n, |a, b| phi[(a, b)] / (masses[a / 3] * masses[b / 3]).sqrt());
+
+    let eigen = SymmetricEigen::new(dyn_mat);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+
+    let frequencies = order.iter().map(|&k| eigenvalue_to_cm(eigen.eigenvalues[k])).collect();
+    let eigenvectors = DMatrix::from_fn(n, n, |a, b| eigen.eigenvectors[(a, order[b])]);
+    (frequencies, eigenvectors)
+}
+
+/// Fônons em Γ por deslocamentos congelados (frozen phonon).
+///
+/// Cada átomo é deslocado em ±x, ±y, ±z; as forças resultantes formam as constantes de força,
+/// que (com a regra de soma acústica) dão a matriz dinâmica em Γ.
+pub fn gamma_phonons<F, E>(structure: &Structure, displacement: f64, forces: F) -> Result<GammaPhonons, E>
+where
+    F: FnMut(&Structure) -> Result<Vec<Vector3<f64>>, E>,
+    E: From<PhononError>,
+{
+    let masses = atomic_masses(structure)?;
+
+    log::info!("Fônons em Γ: {} deslocamentos de {:.4} Bohr", 6 * structure.atoms.len(), displacement);
+    let mut phi = force_constants(structure, displacement, forces)?;
+    apply_acoustic_sum_rule(&mut phi);
+
+    let (frequencies_cm, eigenvectors) = diagonalize_dynamical_matrix(&phi, &masses);
+    for (k, w) in frequencies_cm.iter().enumerate() {
+        log::info!("  Modo {:>3}: {:>10.2} cm^-1", k + 1, w);
+    }
+
+    Ok(GammaPhonons { frequencies_cm, eigenvectors, force_constants: phi })
+}
+
+/// Constantes de força de uma supercélula, prontas para interpolação de Fourier.
+#[derive(Debug, Clone)]
+pub struct SupercellForceConstants {
+    pub primitive: Structure,
+    pub supercell_dims: [usize; 3],
+    /// Φ_{iα, sβ}: linhas = átomo primitivo i (célula t = 0) x α; colunas = átomo s da supercélula x β.
+    pub phi: DMatrix<f64>,
+    masses: Vec<f64>,
+}
+
+/// Dispersão de fônons ao longo de um caminho de vetores q.
+#[derive(Debug, Clone)]
+pub struct PhononBands {
+    pub q_points: Vec<[f64; 3]>,       // Fracionário (rede recíproca primitiva)
+    pub frequencies_cm: Vec<Vec<f64>>, // [q][modo], cm⁻¹
+}
+
+/// Constantes de força interatômicas por deslocamentos finitos numa supercélula.
+///
+/// Por simetria de translação, basta deslocar os átomos da célula primitiva de origem
+/// (3N_prim x 2 cálculos de força na supercélula). `forces` recebe a supercélula deslocada.
+pub fn supercell_force_constants<F, E>(
+    primitive: &Structure,
+    supercell_dims: [usize; 3],
+    displacement: f64,
+    mut forces: F,
+) -> Result<SupercellForceConstants, E>
+where
+    F: FnMut(&Structure) -> Result<Vec<Vector3<f64>>, E>,
+    E: From<PhononError>,
+{
+    let masses = atomic_masses(primitive)?;
+    let supercell = primitive.supercell(supercell_dims);
+    let n_prim = primitive.atoms.len();
+    let n_super = supercell.atoms.len();
+
+    log::info!(
+        "Fônons em supercélula {}x{}x{} ({} átomos): {} deslocamentos",
+        supercell_dims[0], supercell_dims[1], supercell_dims[2], n_super, 6 * n_prim
+    );
+
+    let mut phi = DMatrix::<f64>::zeros(3 * n_prim, 3 * n_super);
+    for i in 0..n_prim {
+        for alpha in 0..3 {
+            let mut displaced = |sign: f64| -> Result<Vec<Vector3<f64>>, E> {
+                let mut s = supercell.clone();
+                s.atoms[i].position[alpha] += sign * displacement;
+                let f = forces(&s)?;
+                if f.len() != n_super {
+                    return Err(PhononError::ForceCount(f.len(), n_super).into());
+                }
+                Ok(f)
+            };
+            let f_plus = displaced(1.0)?;
+            let f_minus = displaced(-1.0)?;
+
+            for s in 0..n_super {
+                for beta in 0..3 {
+                    phi[(3 * i + alpha, 3 * s + beta)] =
+                        -(f_plus[s][beta] - f_minus[s][beta]) / (2.0 * displacement);
+                }
+            }
+        }
+    }
+
+    // Regra de soma acústica: corrige o termo próprio de cada átomo primitivo
+    for i in 0..n_prim {
+        for alpha in 0..3 {
+            for beta in 0..3 {
+                let off: f64 = (0..n_super)
+                    .filter(|&s| s != i)
+                    .map(|s| phi[(3 * i + alpha, 3 * s + beta)])
+                    .sum();
+                phi[(3 * i + alpha, 3 * i + beta)] = -off;
+            }
+        }
+    }
+
+    Ok(SupercellForceConstants {
+        primitive: primitive.clone(),
+        supercell_dims,
+        phi,
+        masses,
+    })
+}
+
+impl SupercellForceConstants {
+    /// Matriz dinâmica em q (fracionário na rede recíproca primitiva):
+    /// D_{iα,jβ}(q) = Σ_s Φ_{iα,sβ} Σ_T w_T e^{2πi q·(R_s + T)} / sqrt(M_i M_j)
+    ///
+    /// Cada par (i, s) usa as imagens de mínima distância na supercélula (peso 1/n_imagens),
+    /// como no phonopy, para que a interpolação seja exata nos q comensuráveis.
+    pub fn dynamical_matrix(&self, q: [f64; 3]) -> Result<DMatrix<num_complex::Complex64>, DftError> {
+        use num_complex::Complex64;
+
+        let n_prim = self.primitive.atoms.len();
+        let lattice = &self.primitive.lattice.vectors;
+        let lattice_inv = lattice.try_inverse().ok_or(DftError::SingularLattice)?;
+        let [n1, n2, n3] = self.supercell_dims;
+        let q = Vector3::from(q);
+
+        let frac: Vec<Vector3<f64>> = self.primitive.atoms.iter().map(|a| lattice_inv * a.position).collect();
+        let mut dmat = DMatrix::<Complex64>::zeros(3 * n_prim, 3 * n_prim);
+
+        let mut s = 0;
+        for t1 in 0..n1 {
+            for t2 in 0..n2 {
+                for t3 in 0..n3 {
+                    let t = Vector3::new(t1 as f64, t2 as f64, t3 as f64);
+                    for j in 0..n_prim {
+                        for i in 0..n_prim {
+                            // Imagens periódicas da supercélula com distância mínima
+                            let mut images: Vec<(f64, Vector3<f64>)> = Vec::with_capacity(27);
+                            for m1 in -2..=2 {
+                                for m2 in -2..=2 {
+                                    for m3 in -2..=2 {
+                                        let shift = Vector3::new(
+                                            (m1 * n1 as i32) as f64,
+                                            (m2 * n2 as i32) as f64,
+                                            (m3 * n3 as i32) as f64,
+                                        );
+                                        let r = t + shift;
+                                        let d = lattice * (frac[j] + r - frac[i]);
+                                        images.push((d.norm(), r));
+                                    }
+                                }
+                            }
+                            let d_min = images.iter().map(|x| x.0).fold(f64::INFINITY, f64::min);
+                            let nearest: Vec<Vector3<f64>> = images.into_iter()
+                                .filter(|x| x.0 < d_min + 1e-5)
+                                .map(|x| x.1)
+                                .collect();
+                            let weight = 1.0 / nearest.len() as f64;
+
+                            let phase: Complex64 = nearest.iter()
+                                .map(|r| Complex64::from_polar(weight, 2.0 * std::f64::consts::PI * q.dot(r)))
+                                .sum();
+
+                            let mass = (self.masses[i] * self.masses[j]).sqrt();
+                            for alpha in 0..3 {
+                                for beta in 0..3 {
+                                    let phi = self.phi[(3 * i + alpha, 3 * (s + j) + beta)];
+                                    dmat[(3 * i + alpha, 3 * j + beta)] += phase * (phi / mass);
+                                }
+                            }
+                        }
+                    }
+                    s += n_prim;
+                }
+            }
+        }
+
+        // Hermitização (remove assimetrias numéricas)
+        let dmat_h = dmat.adjoint();
+        Ok((dmat + dmat_h) * Complex64::new(0.5, 0.0))
+    }
+
+    /// Frequências (cm⁻¹, ordem crescente) em um vetor q.
+    pub fn frequencies(&self, q: [f64; 3]) -> Result<Vec<f64>, DftError> {
+        let eigen = SymmetricEigen::new(self.dynamical_matrix(q)?);
+        let mut w: Vec<f64> = eigen.eigenvalues.iter().map(|&l| eigenvalue_to_cm(l)).collect();
+        w.sort_by(|a, b| a.total_cmp(b));
+        Ok(w)
+    }
+
+    /// Dispersão ao longo de uma lista de vetores q (ex: `KGrid::band_path`).
+    pub fn band_structure(&self, q_points: &[[f64; 3]]) -> Result<PhononBands, DftError> {
+        Ok(PhononBands {
+            q_points: q_points.to_vec(),
+            frequencies_cm: q_points.iter().map(|&q| self.frequencies(q)).collect::<Result<_, _>>()?,
+        })
+    }
+}
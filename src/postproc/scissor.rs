@@ -0,0 +1,40 @@
+use crate::core::simulation::Simulation;
+use crate::dft::solver::BandSolverResult;
+use crate::io::results::RunResults;
+
+/// Operador tesoura: deslocamento rígido das bandas desocupadas.
+/// Corrige o gap subestimado por LDA/GGA (ex: Δ = E_g^exp - E_g^DFT) antes de
+/// estrutura de bandas, DOS e função dielétrica.
+#[derive(Debug, Clone, Copy)]
+pub struct Scissor {
+    pub shift: f64,        // Ry
+    pub n_occupied: usize, // Bandas abaixo do gap (não deslocadas)
+}
+
+impl Scissor {
+    pub fn new(shift: f64, n_occupied: usize) -> Self {
+        Self { shift, n_occupied }
+    }
+
+    /// Tesoura para a simulação, com o número de bandas ocupadas deduzido dos pseudos.
+    pub fn for_simulation(sim: &Simulation, shift: f64) -> Self {
+        Self::new(shift, sim.n_occupied())
+    }
+
+    /// ε_n += Δ para n >= n_occupied.
+    pub fn apply(&self, eigenvalues: &mut [f64]) {
+        for e in eigenvalues.iter_mut().skip(self.n_occupied) {
+            *e += self.shift;
+        }
+    }
+
+    pub fn apply_to_bands(&self, bands: &mut BandSolverResult) {
+        self.apply(&mut bands.eigenvalues);
+    }
+
+    pub fn apply_to_results(&self, results: &mut RunResults) {
+        for record in &mut results.bands {
+            self.apply(&mut record.eigenvalues);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::f64::consts::PI;
+use ndarray::Array3;
+use nalgebra::Vector3;
+use num_complex::Complex64;
+
+use crate::core::fft::FftGrid;
+use crate::core::structure::Structure;
+use crate::dft::error::DftError;
+
+/// Potencial eletrostático em um sítio atômico (Ry).
+#[derive(Debug, Clone, Copy)]
+pub struct SitePotential {
+    pub hartree: f64,
+    pub local: f64,
+}
+
+impl SitePotential {
+    pub fn total(&self) -> f64 {
+        self.hartree + self.local
+    }
+}
+
+/// Avalia V_H + V_loc exatamente nas posições atômicas por soma em espaço recíproco
+/// (sem interpolação no grid):
+///
+/// V_H(τ) = Σ_{G≠0} 8π ρ(G)/G² e^{iG·τ},   V_loc(τ) = Σ_{G≠0} Σ_b v_b(|G|) e^{iG·(τ - τ_b)}
+///
+/// `local_form_factor(species_id, |G|)` devolve v_s(G) = (1/Ω)∫V_s(r)e^{-iG·r}dr (Ry),
+/// ou `None` para considerar apenas o termo de Hartree.
+/// O termo G = 0 é omitido: os valores são definidos a menos de uma constante,
+/// o que basta para comparar sítios inequivalentes (deslocamentos de nível de caroço).
+pub fn site_potentials<F>(
+    structure: &Structure,
+    fft: &mut FftGrid,
+    rho: &Array3<f64>,
+    local_form_factor: Option<F>,
+) -> Result<Vec<SitePotential>, DftError>
+where
+    F: Fn(usize, f64) -> f64,
+{
+    let [nx, ny, nz] = fft.size;
+    let (dx, dy, dz) = rho.dim();
+    if [dx, dy, dz] != fft.size {
+        return Err(DftError::GridMismatch([dx, dy, dz], fft.size));
+    }
+    let n_points = (nx * ny * nz) as f64;
+    let recip = structure.lattice.reciprocal();
+
+    // ρ(G) = FFT[ρ(r)] / N
+    fft.buffer.zip_mut_with(rho, |c, &r| *c = Complex64::new(r, 0.0));
+    fft.forward_in_place()?;
+
+    let freq = |i: usize, n: usize| if i > n / 2 { i as i64 - n as i64 } else { i as i64 };
+    let mut sites = vec![SitePotential { hartree: 0.0, local: 0.0 }; structure.atoms.len()];
+
+    for ((i, j, k), &rho_g) in fft.buffer.indexed_iter() {
+        let m = Vector3::new(freq(i, nx) as f64, freq(j, ny) as f64, freq(k, nz) as f64);
+        if m == Vector3::zeros() {
+            continue;
+        }
+        let g = recip * m;
+        let g2 = g.norm_squared();
+        let v_h = 8.0 * PI * rho_g / (n_points * g2);
+
+        // Potencial local total em G: Σ_b v_b(G) e^{-iG·τ_b}
+        let v_loc = local_form_factor.as_ref().map(|ff| {
+            let g_norm = g2.sqrt();
+            structure.atoms.iter().fold(Complex64::new(0.0, 0.0), |acc, b| {
+                acc + ff(b.species_id, g_norm) * Complex64::from_polar(1.0, -g.dot(&b.position))
+            })
+        });
+
+        for (site, atom) in sites.iter_mut().zip(&structure.atoms) {
+            let phase = Complex64::from_polar(1.0, g.dot(&atom.position));
+            site.hartree += (v_h * phase).re;
+            if let Some(v) = v_loc {
+                site.local += (v * phase).re;
+            }
+        }
+    }
+    Ok(sites)
+}
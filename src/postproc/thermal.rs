@@ -0,0 +1,194 @@
+use thiserror::Error;
+
+use crate::core::structure::Structure;
+use crate::postproc::eos::{fit_birch_murnaghan, EosError};
+use crate::tools::scan::{ScanError, ScanParameter, ScanRunner};
+use crate::utils::constants::{HA_TO_RY, HA_TO_WAVENUMBER, KELVIN_TO_HA};
+
+/// Frequências abaixo deste valor (cm⁻¹) são ignoradas: modos acústicos em Γ e
+/// modos instáveis (negativos) não têm contribuição harmônica definida.
+pub const MIN_FREQUENCY_CM: f64 = 1e-3;
+
+#[derive(Error, Debug)]
+pub enum ThermalError {
+    #[error("Frequências de {0} pontos q, mas {1} pesos.")]
+    WeightCount(usize, usize),
+
+    #[error("Volumes ({0}), energias ({1}) e conjuntos de frequências ({2}) com tamanhos diferentes.")]
+    LengthMismatch(usize, usize, usize),
+
+    #[error("Ajuste EOS do quase-harmônico falhou: {0}")]
+    Eos(#[from] EosError),
+}
+
+/// Propriedades vibracionais harmônicas por célula a uma temperatura. Unidades: Ry e K.
+#[derive(Debug, Clone, Copy)]
+pub struct ThermalPoint {
+    pub temperature: f64,     // K
+    pub free_energy: f64,     // F_vib (Ry), inclui o ponto zero
+    pub internal_energy: f64, // E_vib (Ry)
+    pub entropy: f64,         // S (Ry/K)
+    pub heat_capacity: f64,   // C_v (Ry/K)
+}
+
+/// Soma sobre modos da função de partição harmônica, com ħω em Ry e x = ħω/k_BT:
+///
+/// F = Σ [ħω/2 + k_BT ln(1 - e^{-x})]
+/// S = k_B Σ [x/(e^x - 1) - ln(1 - e^{-x})]
+/// C_v = k_B Σ x² e^x / (e^x - 1)²
+///
+/// `frequencies_cm[q][modo]` com pesos de ponto q (normalizados aqui); uma lista só de Γ
+/// (ex: `GammaPhonons`) usa `weights = [1.0]`.
+pub fn harmonic_properties(
+    frequencies_cm: &[Vec<f64>],
+    weights: &[f64],
+    temperatures: &[f64],
+) -> Result<Vec<ThermalPoint>, ThermalError> {
+    if frequencies_cm.len() != weights.len() {
+        return Err(ThermalError::WeightCount(frequencies_cm.len(), weights.len()));
+    }
+    let total: f64 = weights.iter().sum();
+    let k_b = KELVIN_TO_HA * HA_TO_RY;
+
+    // (ħω em Ry, peso) de cada modo estável
+    let modes: Vec<(f64, f64)> = frequencies_cm.iter().zip(weights)
+        .flat_map(|(freqs, &w)| freqs.iter().map(move |&f| (f, w / total)))
+        .filter(|&(f, _)| f > MIN_FREQUENCY_CM)
+        .map(|(f, w)| (f / HA_TO_WAVENUMBER * HA_TO_RY, w))
+        .collect();
+    let unstable = frequencies_cm.iter().flatten().filter(|&&f| f < -MIN_FREQUENCY_CM).count();
+    if unstable > 0 {
+        log::warn!("{} modos imaginários ignorados nas propriedades térmicas", unstable);
+    }
+
+    let zero_point: f64 = modes.iter().map(|(hw, w)| 0.5 * w * hw).sum();
+    let points = temperatures.iter()
+        .map(|&t| {
+            let kt = k_b * t;
+            let mut point = ThermalPoint {
+                temperature: t,
+                free_energy: zero_point,
+                internal_energy: zero_point,
+                entropy: 0.0,
+                heat_capacity: 0.0,
+            };
+            if kt <= 0.0 {
+                return point;
+            }
+            for &(hw, w) in &modes {
+                let x = hw / kt;
+                // e^{-x} → 0: o modo está congelado
+                if x > 500.0 {
+                    continue;
+                }
+                let n_bose = 1.0 / x.exp_m1();
+                let log_term = (-(-x).exp()).ln_1p();
+                point.free_energy += w * kt * log_term;
+                point.internal_energy += w * hw * n_bose;
+                point.entropy += w * k_b * (x * n_bose - log_term);
+                point.heat_capacity += w * k_b * x * x * n_bose * (n_bose + 1.0);
+            }
+            point
+        })
+        .collect();
+    Ok(points)
+}
+
+/// Resultado quase-harmônico a uma temperatura: mínimo de G(V) = E(V) + F_vib(V, T)
+/// (pressão nula).
+#[derive(Debug, Clone, Copy)]
+pub struct QhaPoint {
+    pub temperature: f64,        // K
+    pub volume: f64,             // V(T) (Bohr³)
+    pub free_energy: f64,        // G(V(T)) (Ry)
+    pub bulk_modulus: f64,       // B(T) (Ry/Bohr³)
+    pub thermal_expansion: f64,  // α_V = (1/V) dV/dT (1/K)
+}
+
+/// Aproximação quase-harmônica: para cada temperatura, ajusta Birch-Murnaghan a
+/// E_estática(V) + F_vib(V, T) e toma o mínimo. α_V sai de diferenças finitas de V(T)
+/// entre temperaturas vizinhas (centrais no interior da lista, laterais nos extremos).
+///
+/// `frequencies_cm[v][q][modo]` são os fônons em cada volume de `volumes`.
+pub fn quasi_harmonic(
+    volumes: &[f64],
+    static_energies: &[f64],
+    frequencies_cm: &[Vec<Vec<f64>>],
+    weights: &[f64],
+    temperatures: &[f64],
+) -> Result<Vec<QhaPoint>, ThermalError> {
+    if volumes.len() != static_energies.len() || volumes.len() != frequencies_cm.len() {
+        return Err(ThermalError::LengthMismatch(volumes.len(), static_energies.len(), frequencies_cm.len()));
+    }
+
+    // F_vib[v][t]
+    let f_vib: Vec<Vec<f64>> = frequencies_cm.iter()
+        .map(|freqs| {
+            harmonic_properties(freqs, weights, temperatures)
+                .map(|pts| pts.iter().map(|p| p.free_energy).collect())
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut points = Vec::with_capacity(temperatures.len());
+    for (it, &t) in temperatures.iter().enumerate() {
+        let g: Vec<f64> = static_energies.iter().zip(&f_vib).map(|(e, f)| e + f[it]).collect();
+        let fit = fit_birch_murnaghan(volumes, &g)?;
+        points.push(QhaPoint {
+            temperature: t,
+            volume: fit.v0,
+            free_energy: fit.e0,
+            bulk_modulus: fit.b0,
+            thermal_expansion: 0.0,
+        });
+    }
+
+    let n = points.len();
+    for i in 0..n {
+        let (lo, hi) = (i.saturating_sub(1), (i + 1).min(n - 1));
+        let dt = points[hi].temperature - points[lo].temperature;
+        if dt.abs() > 0.0 {
+            points[i].thermal_expansion = (points[hi].volume - points[lo].volume) / dt / points[i].volume;
+        }
+    }
+    Ok(points)
+}
+
+/// Driver quase-harmônico: varre `lattice_constants` com `ScanRunner` (energias estáticas
+/// em cache), calcula os fônons de cada volume com `phonons` e chama `quasi_harmonic`.
+/// `structure_at(a)` monta a célula para o parâmetro de rede `a` (Bohr); `phonons`
+/// devolve `frequencies[q][modo]` (cm⁻¹) com os pesos `weights`.
+pub fn quasi_harmonic_scan<S, F, P, E>(
+    runner: &mut ScanRunner,
+    lattice_constants: &[f64],
+    mut structure_at: S,
+    mut energy: F,
+    mut phonons: P,
+    weights: &[f64],
+    temperatures: &[f64],
+) -> Result<Vec<QhaPoint>, E>
+where
+    S: FnMut(f64) -> Structure,
+    F: FnMut(&Structure) -> Result<f64, E>,
+    P: FnMut(&Structure) -> Result<Vec<Vec<f64>>, E>,
+    E: From<ScanError> + From<ThermalError>,
+{
+    let scan = runner.run(
+        lattice_constants.iter().map(|&a| ScanParameter::LatticeConstant(a)),
+        |p| match *p {
+            ScanParameter::LatticeConstant(a) => energy(&structure_at(a)),
+            _ => unreachable!("varredura EOS só gera parâmetros de rede"),
+        },
+    )?;
+
+    let mut volumes = Vec::with_capacity(lattice_constants.len());
+    let mut frequencies = Vec::with_capacity(lattice_constants.len());
+    for &a in lattice_constants {
+        let structure = structure_at(a);
+        log::info!("Fônons em a = {:.4} Bohr", a);
+        volumes.push(structure.lattice.volume());
+        frequencies.push(phonons(&structure)?);
+    }
+    let energies: Vec<f64> = scan.iter().map(|p| p.energy).collect();
+
+    Ok(quasi_harmonic(&volumes, &energies, &frequencies, weights, temperatures)?)
+}
@@ -0,0 +1,55 @@
+use std::f64::consts::PI;
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::Array3;
+
+use crate::core::structure::Structure;
+use crate::testkit::potential_on_grid;
+
+/// Ondas planas por sentido na cadeia 1D usada como referência (converge muito além de 1e-12).
+const CHAIN_HALF_WIDTH: i32 = 40;
+
+/// V(r) = 2 V0 cos(2π x / a) na caixa cúbica de lado `a`: acopla só G e G ± b1 com V0.
+pub fn cosine_potential(structure: &Structure, grid: [usize; 3], a: f64, v0: f64) -> Array3<f64> {
+    potential_on_grid(structure, grid, |r| 2.0 * v0 * (2.0 * PI * r.x / a).cos())
+}
+
+/// Níveis 1D de -d²/dx² + 2 V0 cos(2πx/a) em k = 0 (equação de Mathieu): diagonalização
+/// da matriz tridiagonal (2πn/a)^2 δ_nm + V0 δ_{n,m±1}, convergida no número de termos.
+pub fn mathieu_levels(a: f64, v0: f64, n: usize) -> Vec<f64> {
+    let b = 2.0 * PI / a;
+    let dim = (2 * CHAIN_HALF_WIDTH + 1) as usize;
+    let h = DMatrix::from_fn(dim, dim, |p, q| {
+        if p == q {
+            let m = p as i32 - CHAIN_HALF_WIDTH;
+            (b * m as f64).powi(2)
+        } else if p.abs_diff(q) == 1 {
+            v0
+        } else {
+            0.0
+        }
+    });
+    let mut levels: Vec<f64> = SymmetricEigen::new(h).eigenvalues.iter().copied().collect();
+    levels.sort_by(f64::total_cmp);
+    levels.truncate(n);
+    levels
+}
+
+/// Espectro 3D exato em Γ: o potencial só depende de x, então
+/// E = E_Mathieu + (2π/a)^2 (m_y² + m_z²). Retorna os `n` menores níveis.
+pub fn cosine_levels(a: f64, v0: f64, n: usize) -> Vec<f64> {
+    let b2 = (2.0 * PI / a).powi(2);
+    let chain = mathieu_levels(a, v0, n);
+    let m_max = (n as f64).sqrt().ceil() as i32 + 1;
+
+    let mut levels = Vec::new();
+    for &e in &chain {
+        for my in -m_max..=m_max {
+            for mz in -m_max..=m_max {
+                levels.push(e + b2 * (my * my + mz * mz) as f64);
+            }
+        }
+    }
+    levels.sort_by(f64::total_cmp);
+    levels.truncate(n);
+    levels
+}
@@ -0,0 +1,10 @@
+use crate::core::basis::PlaneWaveBasis;
+
+/// Gás de elétrons livres (V = 0): os autovalores são exatamente |k+G|^2 (Ry),
+/// os `n` menores da base.
+pub fn free_electron_levels(basis: &PlaneWaveBasis, n: usize) -> Vec<f64> {
+    let mut levels = basis.g_norm_sq.clone();
+    levels.sort_by(f64::total_cmp);
+    levels.truncate(n);
+    levels
+}
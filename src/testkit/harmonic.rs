@@ -0,0 +1,33 @@
+use nalgebra::Vector3;
+use ndarray::Array3;
+
+use crate::core::structure::Structure;
+use crate::testkit::potential_on_grid;
+
+/// Confinamento harmônico V(r) = c |r - r0|^2 (Ry), centrado na célula, com distância
+/// pela imagem mínima. Com o oscilador bem menor que a caixa o resultado é o do espaço livre.
+pub fn harmonic_potential(structure: &Structure, grid: [usize; 3], c: f64) -> Array3<f64> {
+    let lattice = structure.lattice.vectors;
+    let lattice_inv = lattice.try_inverse().expect("Lattice matrix singular");
+    let center = lattice * Vector3::new(0.5, 0.5, 0.5);
+    potential_on_grid(structure, grid, |r| {
+        let mut d = lattice_inv * (r - center);
+        d.apply(|x| *x -= x.round());
+        c * (lattice * d).norm_squared()
+    })
+}
+
+/// Níveis exatos de -∇² + c r^2 (Ry): E = 2√c (n_x + n_y + n_z + 3/2),
+/// com a degenerescência (N+1)(N+2)/2 de cada camada N. Retorna os `n` menores.
+pub fn harmonic_levels(c: f64, n: usize) -> Vec<f64> {
+    let omega = 2.0 * c.sqrt();
+    let mut levels = Vec::with_capacity(n);
+    let mut shell = 0usize;
+    while levels.len() < n {
+        let degeneracy = (shell + 1) * (shell + 2) / 2;
+        let e = omega * (shell as f64 + 1.5);
+        levels.extend(std::iter::repeat_n(e, degeneracy.min(n - levels.len())));
+        shell += 1;
+    }
+    levels
+}
@@ -0,0 +1,33 @@
+pub mod free_electron;
+pub mod cosine;
+pub mod harmonic;
+
+use nalgebra::Vector3;
+use ndarray::Array3;
+
+use crate::core::structure::{Lattice, Structure};
+
+/// Caixa cúbica de lado `a` (Bohr) sem átomos: os modelos definem o potencial diretamente.
+pub fn empty_cubic_box(a: f64) -> Structure {
+    Structure {
+        lattice: Lattice::new(
+            Vector3::new(a, 0.0, 0.0),
+            Vector3::new(0.0, a, 0.0),
+            Vector3::new(0.0, 0.0, a),
+        ),
+        species: Vec::new(),
+        atoms: Vec::new(),
+    }
+}
+
+/// Amostra `f(r)` (r cartesiano, Bohr) nos pontos do grid FFT da célula.
+pub fn potential_on_grid<F>(structure: &Structure, grid: [usize; 3], f: F) -> Array3<f64>
+where
+    F: Fn(Vector3<f64>) -> f64,
+{
+    let [nx, ny, nz] = grid;
+    Array3::from_shape_fn((nx, ny, nz), |(i, j, k)| {
+        let frac = Vector3::new(i as f64 / nx as f64, j as f64 / ny as f64, k as f64 / nz as f64);
+        f(structure.lattice.vectors * frac)
+    })
+}
@@ -0,0 +1,136 @@
+use crate::core::kpoints::KGrid;
+use crate::core::structure::Structure;
+use crate::utils::constants::{EV_TO_HA, HA_TO_RY};
+
+/// Um ponto da varredura de convergência.
+#[derive(Debug, Clone)]
+pub struct ConvergencePoint<P> {
+    pub parameter: P,
+    pub energy: f64,        // Energia total (Ry)
+    pub delta_per_atom: f64, // |E_i - E_{i-1}| / N_atoms (Ry); NaN no primeiro ponto
+}
+
+/// Resultado de uma varredura: tabela completa e o parâmetro recomendado (se convergiu).
+#[derive(Debug, Clone)]
+pub struct ConvergenceResult<P> {
+    pub converged: Option<P>,
+    pub table: Vec<ConvergencePoint<P>>,
+}
+
+/// Converte meV/átomo para Ry/átomo.
+pub fn mev_to_ry(mev: f64) -> f64 {
+    mev * 1.0e-3 * EV_TO_HA * HA_TO_RY
+}
+
+/// Varredura genérica: avalia `energy(p)` para cada parâmetro em ordem e para no primeiro
+/// ponto cuja variação em relação ao anterior fica abaixo de `tol_ry_per_atom`.
+/// O parâmetro recomendado é o *anterior* desse par (já convergido em relação ao seguinte).
+pub fn converge_parameter<P, F, E>(
+    parameters: impl IntoIterator<Item = P>,
+    n_atoms: usize,
+    tol_ry_per_atom: f64,
+    mut energy: F,
+) -> Result<ConvergenceResult<P>, E>
+where
+    P: Clone + std::fmt::Debug,
+    F: FnMut(&P) -> Result<f64, E>,
+{
+    let n_atoms = n_atoms.max(1) as f64;
+    let mut table: Vec<ConvergencePoint<P>> = Vec::new();
+
+    for p in parameters {
+        let e = energy(&p)?;
+        let delta = table.last().map(|prev| (e - prev.energy).abs() / n_atoms).unwrap_or(f64::NAN);
+
+        log::info!("  {:?}: E = {:.8} Ry | dE/átomo = {:.3e} Ry", p, e, delta);
+        table.push(ConvergencePoint { parameter: p, energy: e, delta_per_atom: delta });
+
+        if delta < tol_ry_per_atom {
+            let converged = table[table.len() - 2].parameter.clone();
+            return Ok(ConvergenceResult { converged: Some(converged), table });
+        }
+    }
+
+    Ok(ConvergenceResult { converged: None, table })
+}
+
+/// Sequência de cutoffs [start, start+step, ..., <= max] (Ry).
+pub fn ecut_sequence(start: f64, step: f64, max: f64) -> Vec<f64> {
+    let n = ((max - start) / step).floor().max(0.0) as usize;
+    (0..=n).map(|i| start + i as f64 * step).collect()
+}
+
+/// Convergência da energia total em relação ao Ecut.
+///
+/// `energy(structure, ecut)` deve rodar o cálculo completo e devolver a energia total (Ry).
+/// Retorna o menor Ecut para o qual a variação até o próximo fica abaixo de
+/// `target_mev_per_atom`.
+pub fn converge_ecut<F, E>(
+    structure: &Structure,
+    target_mev_per_atom: f64,
+    ecuts: impl IntoIterator<Item = f64>,
+    mut energy: F,
+) -> Result<ConvergenceResult<f64>, E>
+where
+    F: FnMut(&Structure, f64) -> Result<f64, E>,
+{
+    log::info!("Convergência de Ecut (alvo: {:.2} meV/átomo)", target_mev_per_atom);
+    converge_parameter(
+        ecuts,
+        structure.atoms.len(),
+        mev_to_ry(target_mev_per_atom),
+        |&ecut| energy(structure, ecut),
+    )
+}
+
+/// Malhas uniformes n x n x n para n em `range` (ex: 2..=8).
+pub fn uniform_kgrids(range: std::ops::RangeInclusive<usize>) -> Vec<[usize; 3]> {
+    range.map(|n| [n, n, n]).collect()
+}
+
+/// Malhas derivadas de uma lista de espaçamentos (Å⁻¹), do mais grosso ao mais fino.
+/// Espaçamentos que levam à mesma malha são descartados.
+pub fn spacing_kgrids(structure: &Structure, spacings_inv_angstrom: &[f64]) -> Vec<[usize; 3]> {
+    let mut grids: Vec<[usize; 3]> = Vec::new();
+    for &dk in spacings_inv_angstrom {
+        let grid = KGrid::grid_dims_for_spacing(structure, dk);
+        if grids.last() != Some(&grid) {
+            grids.push(grid);
+        }
+    }
+    grids
+}
+
+/// Convergência da energia total em relação à malha de k-points.
+///
+/// `energy(structure, grid)` deve rodar o cálculo com uma malha Monkhorst-Pack `grid`
+/// e devolver a energia total (Ry). Ao final, a tabela energia/nº de k-points é impressa.
+pub fn converge_kgrid<F, E>(
+    structure: &Structure,
+    target_mev_per_atom: f64,
+    grids: impl IntoIterator<Item = [usize; 3]>,
+    mut energy: F,
+) -> Result<ConvergenceResult<[usize; 3]>, E>
+where
+    F: FnMut(&Structure, [usize; 3]) -> Result<f64, E>,
+{
+    log::info!("Convergência de k-points (alvo: {:.2} meV/átomo)", target_mev_per_atom);
+    let result = converge_parameter(
+        grids,
+        structure.atoms.len(),
+        mev_to_ry(target_mev_per_atom),
+        |&grid| energy(structure, grid),
+    )?;
+
+    log::info!("{:<12} {:>6} {:>16}", "Malha", "N_k", "E (Ry)");
+    for p in &result.table {
+        let [n1, n2, n3] = p.parameter;
+        log::info!("{:<12} {:>6} {:>16.8}", format!("{}x{}x{}", n1, n2, n3), n1 * n2 * n3, p.energy);
+    }
+    match result.converged {
+        Some([n1, n2, n3]) => log::info!("Malha convergida: {}x{}x{}", n1, n2, n3),
+        None => log::warn!("Convergência de k-points não atingida na faixa testada."),
+    }
+
+    Ok(result)
+}
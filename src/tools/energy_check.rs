@@ -0,0 +1,113 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EnergyCheckError {
+    #[error("Erro de Leitura da referência: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Erro de Sintaxe TOML na referência: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+/// Componentes da energia total (Ry). `None` para termos que o Bravie ainda não calcula.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnergyTerms {
+    pub kinetic: Option<f64>,
+    pub hartree: Option<f64>,
+    pub xc: Option<f64>,
+    pub local: Option<f64>,
+    pub nonlocal: Option<f64>,
+    pub ewald: Option<f64>,
+}
+
+impl EnergyTerms {
+    /// (nome, valor) na ordem de impressão do relatório.
+    pub fn iter(&self) -> [(&'static str, Option<f64>); 6] {
+        [
+            ("cinética", self.kinetic),
+            ("hartree", self.hartree),
+            ("xc", self.xc),
+            ("local", self.local),
+            ("não-local", self.nonlocal),
+            ("ewald", self.ewald),
+        ]
+    }
+}
+
+/// Valores de referência (ex: saída do pw.x com `verbosity = 'high'`), em Ry.
+///
+/// ```toml
+/// # valores ilustrativos
+/// tolerance = 1e-4 # Ry, por termo
+/// kinetic = 6.0
+/// hartree = 1.1
+/// xc = -4.8
+/// local = -4.9
+/// nonlocal = 1.9
+/// ewald = -16.9
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnergyReference {
+    pub tolerance: f64,
+    pub kinetic: f64,
+    pub hartree: f64,
+    pub xc: f64,
+    pub local: f64,
+    pub nonlocal: f64,
+    pub ewald: f64,
+}
+
+impl EnergyReference {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, EnergyCheckError> {
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    fn values(&self) -> [f64; 6] {
+        [self.kinetic, self.hartree, self.xc, self.local, self.nonlocal, self.ewald]
+    }
+}
+
+/// Comparação de um termo com a referência.
+#[derive(Debug, Clone, Copy)]
+pub struct TermCheck {
+    pub name: &'static str,
+    pub computed: Option<f64>,
+    pub reference: f64,
+    pub tolerance: f64,
+}
+
+impl TermCheck {
+    /// `None` se o termo ainda não é calculado.
+    pub fn passed(&self) -> Option<bool> {
+        self.computed.map(|e| (e - self.reference).abs() <= self.tolerance)
+    }
+}
+
+impl fmt::Display for TermCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.computed {
+            Some(e) => write!(
+                f, "{:<10} {:>16.8} {:>16.8} {:>12.3e}  {}",
+                self.name, e, self.reference, e - self.reference,
+                if self.passed() == Some(true) { "OK" } else { "FALHOU" }
+            ),
+            None => write!(f, "{:<10} {:>16} {:>16.8} {:>12}  pendente", self.name, "-", self.reference, "-"),
+        }
+    }
+}
+
+/// Compara termo a termo; termos não calculados ficam como pendentes (não falham).
+pub fn compare(computed: &EnergyTerms, reference: &EnergyReference) -> Vec<TermCheck> {
+    computed.iter().iter().zip(reference.values())
+        .map(|(&(name, value), r)| TermCheck {
+            name,
+            computed: value,
+            reference: r,
+            tolerance: reference.tolerance,
+        })
+        .collect()
+}
@@ -0,0 +1,4 @@
+pub mod convergence;
+pub mod energy_check;
+pub mod scan;
+pub mod units_audit;
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::postproc::elastic::CubicStrain;
+
+#[derive(Error, Debug)]
+pub enum ScanError {
+    #[error("Erro de E/S no cache da varredura: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Cache da varredura corrompido: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Parâmetro variado em um ponto da varredura.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ScanParameter {
+    LatticeConstant(f64), // Bohr
+    Ecut(f64),            // Ry
+    KGrid([usize; 3]),
+    Strain(CubicStrain, f64), // Deformação e amplitude δ
+}
+
+impl fmt::Display for ScanParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanParameter::LatticeConstant(a) => write!(f, "a={}", a),
+            ScanParameter::Ecut(e) => write!(f, "ecut={}", e),
+            ScanParameter::KGrid([n1, n2, n3]) => write!(f, "k={}x{}x{}", n1, n2, n3),
+            ScanParameter::Strain(kind, d) => write!(f, "{:?} δ={}", kind, d),
+        }
+    }
+}
+
+/// Resultado de um ponto concluído.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanPoint {
+    pub parameter: ScanParameter,
+    pub energy: f64, // Energia total (Ry)
+}
+
+/// Executa varreduras de parâmetros (EOS, Ecut, k-points) guardando cada ponto concluído
+/// em um cache JSON. Ao reiniciar com o mesmo cache, pontos já calculados são pulados.
+pub struct ScanRunner {
+    cache_path: PathBuf,
+    points: Vec<ScanPoint>,
+}
+
+impl ScanRunner {
+    /// Abre (ou cria, na primeira escrita) o cache em `cache_path`.
+    pub fn new<P: AsRef<Path>>(cache_path: P) -> Result<Self, ScanError> {
+        let cache_path = cache_path.as_ref().to_path_buf();
+        let points = if cache_path.is_file() {
+            serde_json::from_str(&fs::read_to_string(&cache_path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self { cache_path, points })
+    }
+
+    /// Pontos concluídos (do cache e desta execução), na ordem em que terminaram.
+    pub fn points(&self) -> &[ScanPoint] {
+        &self.points
+    }
+
+    /// Roda `energy(p)` para cada parâmetro ainda não presente no cache e devolve os
+    /// pontos da varredura na ordem de `parameters`. O cache é reescrito após cada ponto,
+    /// então uma interrupção perde no máximo o cálculo em andamento.
+    pub fn run<F, E>(
+        &mut self,
+        parameters: impl IntoIterator<Item = ScanParameter>,
+        mut energy: F,
+    ) -> Result<Vec<ScanPoint>, E>
+    where
+        F: FnMut(&ScanParameter) -> Result<f64, E>,
+        E: From<ScanError>,
+    {
+        let mut sweep = Vec::new();
+        for p in parameters {
+            if let Some(done) = self.points.iter().find(|pt| pt.parameter == p) {
+                log::info!("  {}: E = {:.8} Ry (cache)", p, done.energy);
+                sweep.push(done.clone());
+                continue;
+            }
+            let e = energy(&p)?;
+            log::info!("  {}: E = {:.8} Ry", p, e);
+            let point = ScanPoint { parameter: p, energy: e };
+            self.points.push(point.clone());
+            self.save()?;
+            sweep.push(point);
+        }
+        Ok(sweep)
+    }
+
+    fn save(&self) -> Result<(), ScanError> {
+        // Escreve num temporário e renomeia: o cache nunca fica pela metade
+        let tmp = self.cache_path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.points)?)?;
+        fs::rename(&tmp, &self.cache_path)?;
+        Ok(())
+    }
+}
+
+/// Relatório CSV (`parameter,value,energy_ry`); k-grids como `n1xn2xn3`.
+pub fn to_csv(points: &[ScanPoint]) -> String {
+    let mut out = String::from("parameter,value,energy_ry\n");
+    for p in points {
+        let (name, value) = match p.parameter {
+            ScanParameter::LatticeConstant(a) => ("lattice_constant", a.to_string()),
+            ScanParameter::Ecut(e) => ("ecut", e.to_string()),
+            ScanParameter::KGrid([n1, n2, n3]) => ("kgrid", format!("{}x{}x{}", n1, n2, n3)),
+            ScanParameter::Strain(kind, d) => ("strain", format!("{:?}:{}", kind, d)),
+        };
+        out.push_str(&format!("{},{},{:.10}\n", name, value, p.energy));
+    }
+    out
+}
+
+pub fn write_csv<P: AsRef<Path>>(points: &[ScanPoint], path: P) -> Result<(), ScanError> {
+    fs::write(path, to_csv(points))?;
+    Ok(())
+}
+
+pub fn write_json<P: AsRef<Path>>(points: &[ScanPoint], path: P) -> Result<(), ScanError> {
+    fs::write(path, serde_json::to_string_pretty(points)?)?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::f64::consts::PI;
+use nalgebra::{Matrix3, Vector3};
+use thiserror::Error;
+
+use crate::core::basis::PlaneWaveBasis;
+use crate::core::structure::Structure;
+use crate::testkit::{empty_cubic_box, free_electron::free_electron_levels};
+
+/// Tolerância relativa das checagens de convenção.
+const TOLERANCE: f64 = 1e-8;
+
+#[derive(Error, Debug)]
+pub enum UnitsError {
+    #[error("Rede recíproca inconsistente: Aᵀ·B deveria ser 2π·I, desvio máximo {0:.3e} (falta ou sobra o fator 2π?).")]
+    ReciprocalLattice(f64),
+
+    #[error("|k+G|² do vetor {0:?}: base guarda {1:.10} Ry, recalculado {2:.10} Ry.")]
+    KineticMismatch((i32, i32, i32), f64, f64),
+
+    #[error("Vetor {0:?} com |k+G|² = {1:.6} Ry acima de Ecut = {2:.6} Ry.")]
+    OutsideCutoff((i32, i32, i32), f64, f64),
+
+    #[error("NPW = {0}, estimativa Ω·Ecut^(3/2)/(6π²) = {1:.0}: razão {2:.3} sugere erro de unidades (Ha vs Ry, fator 2π).")]
+    NpwMismatch(usize, f64, f64),
+
+    #[error("Teste do elétron livre: nível {0} = {1:.10} Ry, analítico {2:.10} Ry.")]
+    FreeElectronLevel(usize, f64, f64),
+
+    #[error("Teste do elétron livre: NPW = {0}, contagem exata {1}.")]
+    FreeElectronCount(usize, usize),
+}
+
+/// Resumo de uma auditoria bem-sucedida.
+#[derive(Debug, Clone, Copy)]
+pub struct UnitsAudit {
+    pub reciprocal_error: f64,
+    pub npw: usize,
+    pub npw_estimate: f64,
+}
+
+/// Aᵀ·B = 2π·I (colunas de A: a_i; colunas de B: b_j). Devolve o desvio máximo relativo.
+pub fn check_reciprocal(structure: &Structure) -> Result<f64, UnitsError> {
+    let a = structure.lattice.vectors;
+    let b = structure.lattice.reciprocal();
+    let product = a.transpose() * b / (2.0 * PI);
+    let error = (product - Matrix3::identity()).amax();
+    if error > TOLERANCE {
+        return Err(UnitsError::ReciprocalLattice(error));
+    }
+    Ok(error)
+}
+
+/// Confere a base contra a convenção de energia cinética do Bravie: |k+G|² em Ry
+/// (ħ²/2m = 1), com k e G fracionários convertidos por B (que já contém o 2π).
+pub fn audit_basis(structure: &Structure, basis: &PlaneWaveBasis) -> Result<UnitsAudit, UnitsError> {
+    let reciprocal_error = check_reciprocal(structure)?;
+    let b = structure.lattice.reciprocal();
+
+    for (&g, &stored) in basis.g_vectors.iter().zip(&basis.g_norm_sq) {
+        let kg = basis.k_point + Vector3::new(g.0 as f64, g.1 as f64, g.2 as f64);
+        let g2 = (b * kg).norm_squared();
+        if (g2 - stored).abs() > TOLERANCE * g2.max(1.0) {
+            return Err(UnitsError::KineticMismatch(g, stored, g2));
+        }
+        if g2 > basis.ecut * (1.0 + TOLERANCE) {
+            return Err(UnitsError::OutsideCutoff(g, g2, basis.ecut));
+        }
+    }
+
+    // Esfera |q|² <= Ecut: NPW ≈ Ω/(2π)³ · 4π/3 · Ecut^(3/2). Erros de unidade mudam a
+    // contagem por fatores grandes (2^(3/2) para Ha/Ry, (2π)³ para o 2π), bem acima do
+    // erro de superfície da esfera discreta.
+    let npw = basis.g_vectors.len();
+    let npw_estimate = structure.lattice.volume() * basis.ecut.powf(1.5) / (6.0 * PI * PI);
+    if npw_estimate >= 100.0 {
+        let ratio = npw as f64 / npw_estimate;
+        if !(0.75..=1.33).contains(&ratio) {
+            return Err(UnitsError::NpwMismatch(npw, npw_estimate, ratio));
+        }
+    }
+    Ok(UnitsAudit { reciprocal_error, npw, npw_estimate })
+}
+
+/// Teste analítico: caixa cúbica de lado `a` sem potencial, em Γ. Os níveis são
+/// (2π/a)²·|n|² (Ry) para n inteiro, e a base deve conter exatamente os n com
+/// (2π/a)²·|n|² <= Ecut.
+pub fn audit_free_electron(a: f64, ecut: f64) -> Result<(), UnitsError> {
+    let structure = empty_cubic_box(a);
+    check_reciprocal(&structure)?;
+    let basis = PlaneWaveBasis::new(&structure, ecut, None);
+
+    let unit = (2.0 * PI / a).powi(2);
+    let n_max = (ecut / unit).sqrt().floor() as i32;
+    let mut exact: Vec<f64> = Vec::new();
+    for i in -n_max..=n_max {
+        for j in -n_max..=n_max {
+            for k in -n_max..=n_max {
+                let e = unit * (i * i + j * j + k * k) as f64;
+                if e <= ecut {
+                    exact.push(e);
+                }
+            }
+        }
+    }
+    exact.sort_by(f64::total_cmp);
+
+    if basis.g_vectors.len() != exact.len() {
+        return Err(UnitsError::FreeElectronCount(basis.g_vectors.len(), exact.len()));
+    }
+    let levels = free_electron_levels(&basis, exact.len());
+    for (n, (&got, &want)) in levels.iter().zip(&exact).enumerate() {
+        if (got - want).abs() > TOLERANCE * want.max(1.0) {
+            return Err(UnitsError::FreeElectronLevel(n, got, want));
+        }
+    }
+    Ok(())
+}
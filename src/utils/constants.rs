@@ -37,6 +37,9 @@ pub const ERG_TO_HA: f64 = 1.0 / HA_TO_ERG;
 pub const HA_TO_WAVENUMBER: f64 = 219474.63136320; // cm^-1
 pub const HA_TO_THZ: f64 = 6579.683920502; // Terahertz
 
+// Temperatura
+pub const KELVIN_TO_HA: f64 = 3.166811563e-6; // k_B (Ha/K)
+
 // MASSA (Base: Massa do Elétron / me)
 pub const ELECTRON_MASS_SI: f64 = 9.10938356e-31; // kg
 pub const PROTON_MASS_AU: f64 = 1836.15267343;    // me
@@ -68,4 +71,9 @@ pub const GPA_TO_AU_PRESSURE: f64 = 1.0 / AU_PRESSURE_TO_GPA;
 pub const AU_PRESSURE_TO_BAR: f64 = AU_PRESSURE_TO_PASCAL * 1.0e-5;
 
 // Constante de estrutura fina
-pub const FINE_STRUCTURE_CONST: f64 = 7.2973525693e-3;
\ No newline at end of file
+pub const FINE_STRUCTURE_CONST: f64 = 7.2973525693e-3;
+
+// GRADIENTE DE CAMPO ELÉTRICO (Base: Hartree / (e Bohr^2))
+pub const AU_EFG_TO_V_PER_M2: f64 = HA_TO_JOULE / (ELEMENTARY_CHARGE_SI * BOHR_TO_METER * BOHR_TO_METER);
+pub const PLANCK_SI: f64 = 6.62607015e-34; // J s
+pub const BARN_TO_M2: f64 = 1.0e-28;
\ No newline at end of file
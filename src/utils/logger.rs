@@ -0,0 +1,114 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Target usado pelos registros estruturados (uma linha `chave=valor` por evento).
+pub const RECORD_TARGET: &str = "bravie::record";
+
+/// Nível de verbosidade do Bravie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,   // Somente avisos e erros
+    Normal,  // Progresso padrão
+    Verbose, // Detalhes de inicialização e diagnósticos
+    Debug,   // Tudo
+}
+
+impl Verbosity {
+    fn level_filter(self) -> LevelFilter {
+        match self {
+            Verbosity::Quiet => LevelFilter::Warn,
+            Verbosity::Normal => LevelFilter::Info,
+            Verbosity::Verbose => LevelFilter::Debug,
+            Verbosity::Debug => LevelFilter::Trace,
+        }
+    }
+
+    fn from_u8(v: u8) -> Self {
+        match v {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            2 => Verbosity::Verbose,
+            _ => Verbosity::Debug,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+static RECORDS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+struct BravieLogger;
+
+static LOGGER: BravieLogger = BravieLogger;
+
+impl Log for BravieLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.target() == RECORD_TARGET {
+            return RECORDS_ENABLED.load(Ordering::Relaxed);
+        }
+        metadata.level() <= Verbosity::from_u8(VERBOSITY.load(Ordering::Relaxed)).level_filter()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // Avisos e erros vão para stderr; progresso para stdout
+        match record.level() {
+            Level::Error => eprintln!("ERRO: {}", record.args()),
+            Level::Warn => eprintln!("AVISO: {}", record.args()),
+            Level::Info => println!("{}", record.args()),
+            Level::Debug | Level::Trace => println!("[debug] {}", record.args()),
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// Instala o logger do Bravie. Sem chamar `init`, a biblioteca fica silenciosa
+/// (comportamento padrão da fachada `log`), o que é útil quando embutida em outros programas.
+/// Chamadas repetidas apenas ajustam a verbosidade.
+pub fn init(verbosity: Verbosity) {
+    let _ = log::set_logger(&LOGGER);
+    set_verbosity(verbosity);
+}
+
+/// Altera a verbosidade em tempo de execução.
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    update_max_level();
+}
+
+/// Liga/desliga a emissão de registros estruturados (`record`), independente da verbosidade.
+pub fn enable_records(enabled: bool) {
+    RECORDS_ENABLED.store(enabled, Ordering::Relaxed);
+    update_max_level();
+}
+
+// O filtro global da fachada precisa deixar passar Info quando os registros estão ligados,
+// mesmo em modo silencioso; o filtro fino por target fica em `BravieLogger::enabled`.
+fn update_max_level() {
+    let filter = Verbosity::from_u8(VERBOSITY.load(Ordering::Relaxed)).level_filter();
+    if RECORDS_ENABLED.load(Ordering::Relaxed) {
+        log::set_max_level(filter.max(LevelFilter::Info));
+    } else {
+        log::set_max_level(filter);
+    }
+}
+
+/// Emite um registro legível por máquina no formato logfmt:
+/// `event=basis_init ecut=30 npw=1234`
+pub fn record(event: &str, fields: &[(&str, String)]) {
+    if !RECORDS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut line = format!("event={}", event);
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    log::info!(target: RECORD_TARGET, "{}", line);
+}
@@ -1,2 +1,9 @@
 pub mod welcome;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod logger;
+pub mod timer;
+pub mod radial;
+pub mod spline;
+pub mod ylm;
+pub mod rng;
+pub mod parallel;
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::core::gridops;
+use crate::dft::error::DftError;
+
+#[derive(Error, Debug)]
+pub enum ParallelError {
+    #[error("Não foi possível criar o pool de threads: {0}")]
+    ThreadPool(String),
+}
+
+/// Semente padrão do modo determinístico.
+pub const DEFAULT_SEED: u64 = 20250101;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+static SEED: AtomicU64 = AtomicU64::new(DEFAULT_SEED);
+
+/// Paralelismo e reprodutibilidade de uma execução.
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelConfig {
+    pub threads: Option<usize>, // None: um thread por núcleo (padrão do Rayon)
+    pub deterministic: bool,    // Reduções em ordem fixa e RNG semeado: resultados bit a bit iguais
+    pub seed: u64,              // Semente do RNG (chutes iniciais) no modo determinístico
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self { threads: None, deterministic: false, seed: DEFAULT_SEED }
+    }
+}
+
+/// Aplica a configuração. O pool global do Rayon só pode ser criado uma vez por processo,
+/// então `threads` deve ser definido antes de qualquer operação paralela.
+/// Sem a feature `parallel`, `threads` é ignorado.
+pub fn configure(config: &ParallelConfig) -> Result<(), ParallelError> {
+    DETERMINISTIC.store(config.deterministic, Ordering::Relaxed);
+    SEED.store(config.seed, Ordering::Relaxed);
+    #[cfg(feature = "parallel")]
+    if let Some(n) = config.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build_global()
+            .map_err(|e| ParallelError::ThreadPool(e.to_string()))?;
+    }
+    log::debug!(
+        "Paralelismo: {} threads, modo determinístico {}",
+        num_threads(),
+        if config.deterministic { "ativo" } else { "inativo" }
+    );
+    Ok(())
+}
+
+/// Threads usados pelos kernels (1 sem a feature `parallel`).
+#[cfg(feature = "parallel")]
+pub fn num_threads() -> usize {
+    rayon::current_num_threads()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn num_threads() -> usize {
+    1
+}
+
+/// Aplica `f(índice, bloco)` a blocos consecutivos de `chunk` elementos de `data`,
+/// em paralelo com a feature `parallel` (ex: fatias i = const de um grid 3D).
+pub fn for_each_chunk_mut<T, F>(data: &mut [T], chunk: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    data.par_chunks_mut(chunk).enumerate().for_each(|(i, c)| f(i, c));
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(chunk).enumerate().for_each(|(i, c)| f(i, c));
+}
+
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// Semente para chutes aleatórios: a configurada no modo determinístico, senão do relógio.
+pub fn starting_seed() -> u64 {
+    if is_deterministic() {
+        SEED.load(Ordering::Relaxed)
+    } else {
+        clock_seed()
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn clock_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(DEFAULT_SEED)
+}
+
+// wasm32-unknown-unknown não tem relógio do sistema (SystemTime::now entra em pânico)
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn clock_seed() -> u64 {
+    DEFAULT_SEED
+}
+
+/// Parcelas por bloco das reduções: fixo, para que os blocos (e a ordem da soma no modo
+/// determinístico) não dependam do número de threads.
+const REDUCTION_CHUNK: usize = 4096;
+
+/// Σ x[i] de um grid/vetor (ex: carga ∫ρ, energias ∫ρV), somando blocos de
+/// `REDUCTION_CHUNK` com `gridops::sum`. Em paralelo os blocos são combinados na ordem do
+/// escalonamento (o resultado varia no último bit); no modo determinístico, na ordem dos
+/// blocos, com resultado independente do número de threads.
+pub fn sum(values: &[f64]) -> f64 {
+    #[cfg(feature = "parallel")]
+    {
+        let blocks = values.par_chunks(REDUCTION_CHUNK).map(gridops::sum);
+        if !is_deterministic() {
+            return blocks.sum();
+        }
+        blocks.collect::<Vec<f64>>().iter().sum()
+    }
+    #[cfg(not(feature = "parallel"))]
+    values.chunks(REDUCTION_CHUNK).map(gridops::sum).sum()
+}
+
+/// Σ x[i] y[i], com os mesmos blocos e ordem de `sum`.
+pub fn dot(x: &[f64], y: &[f64]) -> Result<f64, DftError> {
+    if x.len() != y.len() {
+        return Err(DftError::SizeMismatch("produto escalar", y.len(), x.len()));
+    }
+    #[cfg(feature = "parallel")]
+    {
+        let blocks = x.par_chunks(REDUCTION_CHUNK).zip(y.par_chunks(REDUCTION_CHUNK)).map(|(a, b)| gridops::dot(a, b));
+        if !is_deterministic() {
+            return blocks.sum();
+        }
+        Ok(blocks.collect::<Result<Vec<f64>, _>>()?.iter().sum())
+    }
+    #[cfg(not(feature = "parallel"))]
+    x.chunks(REDUCTION_CHUNK).zip(y.chunks(REDUCTION_CHUNK)).map(|(a, b)| gridops::dot(a, b)).sum()
+}
@@ -0,0 +1,177 @@
+use std::f64::consts::PI;
+
+use crate::io::upf::Pseudopotential;
+use crate::utils::spline::CubicSpline;
+
+/// Integração de Simpson numa malha radial arbitrária: ∫f(r)dr = Σ_i w_i f(r_i) rab_i.
+/// Para número par de pontos o último intervalo é integrado por trapézio.
+pub fn simpson(f: &[f64], rab: &[f64]) -> f64 {
+    let n = f.len().min(rab.len());
+    if n < 3 {
+        return f.iter().zip(rab).map(|(a, b)| a * b).sum();
+    }
+    let m = if n % 2 == 1 { n } else { n - 1 };
+    let mut sum = f[0] * rab[0] + f[m - 1] * rab[m - 1];
+    for i in 1..m - 1 {
+        let w = if i % 2 == 1 { 4.0 } else { 2.0 };
+        sum += w * f[i] * rab[i];
+    }
+    sum /= 3.0;
+    if m < n {
+        sum += 0.5 * (f[n - 2] * rab[n - 2] + f[n - 1] * rab[n - 1]);
+    }
+    sum
+}
+
+/// Função de Bessel esférica j_l(x). Série de potências para x < l + 1, onde a
+/// recorrência ascendente perde precisão; fórmulas fechadas + recorrência acima.
+pub fn spherical_bessel(l: usize, x: f64) -> f64 {
+    if x.abs() < (l + 1) as f64 {
+        // j_l(x) = x^l/(2l+1)!! Σ_k (-x²/2)^k / (k! (2l+3)(2l+5)...(2l+2k+1))
+        let double_fact: f64 = (1..=2 * l + 1).step_by(2).map(|k| k as f64).product();
+        let mut term = 1.0;
+        let mut sum = 1.0;
+        for k in 1..60 {
+            term *= -x * x / (2.0 * k as f64 * (2 * l + 2 * k + 1) as f64);
+            sum += term;
+            if term.abs() < 1e-17 * sum.abs() {
+                break;
+            }
+        }
+        return x.powi(l as i32) / double_fact * sum;
+    }
+    let (s, c) = x.sin_cos();
+    let j0 = s / x;
+    if l == 0 {
+        return j0;
+    }
+    let j1 = s / (x * x) - c / x;
+    if l == 1 {
+        return j1;
+    }
+    // Recorrência ascendente j_{l+1} = (2l+1)/x j_l - j_{l-1} (estável para x > l)
+    let (mut jm, mut j) = (j0, j1);
+    for k in 1..l {
+        let next = (2 * k + 1) as f64 / x * j - jm;
+        jm = j;
+        j = next;
+    }
+    j
+}
+
+/// Função erro (precisão ~1e-15): série de Taylor para |x| < 3, fração contínua de erfc acima.
+pub fn erf(x: f64) -> f64 {
+    let ax = x.abs();
+    let value = if ax < 3.0 {
+        let mut term = ax;
+        let mut sum = ax;
+        let x2 = ax * ax;
+        for n in 1..200 {
+            term *= -x2 / n as f64;
+            let add = term / (2 * n + 1) as f64;
+            sum += add;
+            if add.abs() < 1e-17 * sum.abs() {
+                break;
+            }
+        }
+        2.0 / PI.sqrt() * sum
+    } else {
+        // erfc(x) = e^{-x²}/√π · 1/(x + 1/2/(x + 1/(x + 3/2/(x + ...))))
+        let mut cf = 0.0;
+        for k in (1..60).rev() {
+            cf = (k as f64 / 2.0) / (ax + cf);
+        }
+        1.0 - (-ax * ax).exp() / PI.sqrt() / (ax + cf)
+    };
+    value.copysign(x)
+}
+
+/// Transformada de Bessel ∫ f(r) j_l(qr) dr na malha radial (o chamador inclui os fatores de r).
+pub fn bessel_transform(l: usize, r: &[f64], rab: &[f64], f: &[f64], q: f64) -> f64 {
+    let integrand: Vec<f64> = f.iter().zip(r).map(|(v, &ri)| v * spherical_bessel(l, q * ri)).collect();
+    simpson(&integrand, rab)
+}
+
+/// Tabela uniforme em q de uma grandeza radial transformada, para interpolação barata
+/// (spline cúbica) na montagem em espaço recíproco (potencial local, SAD, projetores).
+#[derive(Debug, Clone)]
+pub struct RadialTable {
+    pub dq: f64,
+    pub values: Vec<f64>,
+    spline: CubicSpline,
+}
+
+impl RadialTable {
+    /// Amostra `f(q)` em q = 0, dq, ..., q_max.
+    pub fn new<F: Fn(f64) -> f64>(q_max: f64, dq: f64, f: F) -> Self {
+        let n = (q_max / dq).ceil() as usize + 1;
+        let q: Vec<f64> = (0..n).map(|i| i as f64 * dq).collect();
+        let values: Vec<f64> = q.iter().map(|&qi| f(qi)).collect();
+        let spline = CubicSpline::new(&q, &values);
+        Self { dq, values, spline }
+    }
+
+    pub fn q_max(&self) -> f64 {
+        self.dq * (self.values.len().saturating_sub(1)) as f64
+    }
+
+    /// Valor interpolado; zero além de q_max.
+    pub fn interpolate(&self, q: f64) -> f64 {
+        if q > self.q_max() {
+            return 0.0;
+        }
+        self.spline.eval(q)
+    }
+}
+
+/// Espaçamento padrão das tabelas em q (Bohr⁻¹).
+pub const DEFAULT_DQ: f64 = 0.01;
+
+/// β_i(q) = 4π ∫ r β_i(r) j_l(qr) r dr  (o UPF guarda r·β). Falta o fator 1/√Ω.
+pub fn beta_table(pseudo: &Pseudopotential, index: usize, q_max: f64, dq: f64) -> RadialTable {
+    let beta = &pseudo.nonlocal[index];
+    let l = beta.angular_momentum.max(0) as usize;
+    let n = if beta.cutoff_radius_index > 0 { beta.cutoff_radius_index } else { beta.data.len() };
+    let n = n.min(beta.data.len()).min(pseudo.mesh.r.len());
+    let r = &pseudo.mesh.r[..n];
+    let f: Vec<f64> = beta.data[..n].iter().zip(r).map(|(b, ri)| b * ri).collect();
+    RadialTable::new(q_max, dq, |q| 4.0 * PI * bessel_transform(l, r, &pseudo.mesh.rab[..n], &f, q))
+}
+
+/// χ_i(q) = 4π ∫ r χ_i(r) j_l(qr) r dr  (o UPF guarda r·χ em PP_CHI). Falta o fator 1/√Ω.
+pub fn pswfc_table(pseudo: &Pseudopotential, index: usize, q_max: f64, dq: f64) -> RadialTable {
+    let chi = &pseudo.pswfc[index];
+    let n = chi.data.len().min(pseudo.mesh.r.len());
+    let r = &pseudo.mesh.r[..n];
+    let f: Vec<f64> = chi.data[..n].iter().zip(r).map(|(c, ri)| c * ri).collect();
+    RadialTable::new(q_max, dq, |q| 4.0 * PI * bessel_transform(chi.l, r, &pseudo.mesh.rab[..n], &f, q))
+}
+
+/// ρ_atom(q) = ∫ 4πr²ρ(r) j_0(qr) dr  (ρ(q=0) = z_valence). Falta o fator 1/Ω.
+pub fn rho_atom_table(pseudo: &Pseudopotential, q_max: f64, dq: f64) -> RadialTable {
+    let mesh = &pseudo.mesh;
+    RadialTable::new(q_max, dq, |q| bessel_transform(0, &mesh.r, &mesh.rab, &pseudo.rho_atom, q))
+}
+
+/// Parte de curto alcance do potencial local (Ry):
+/// V_sr(q) = 4π ∫ r² [V_loc(r) + 2Z erf(r)/r] j_0(qr) dr. Falta o fator 1/Ω.
+/// A parte de longo alcance é `local_long_range(z, q)`.
+pub fn local_short_range_table(pseudo: &Pseudopotential, q_max: f64, dq: f64) -> RadialTable {
+    let mesh = &pseudo.mesh;
+    let z = pseudo.header.z_valence;
+    let f: Vec<f64> = pseudo.local.iter().zip(&mesh.r)
+        .map(|(v, &r)| {
+            let coulomb = if r > 1e-10 { 2.0 * z * erf(r) / r } else { 4.0 * z / PI.sqrt() };
+            r * r * (v + coulomb)
+        })
+        .collect();
+    RadialTable::new(q_max, dq, |q| 4.0 * PI * bessel_transform(0, &mesh.r, &mesh.rab, &f, q))
+}
+
+/// Transformada analítica de -2Z erf(r)/r (Ry): -8πZ e^{-q²/4} / q² (q > 0). Falta o fator 1/Ω.
+pub fn local_long_range(z: f64, q: f64) -> f64 {
+    if q < 1e-10 {
+        return 0.0;
+    }
+    -8.0 * PI * z * (-q * q / 4.0).exp() / (q * q)
+}
@@ -0,0 +1,35 @@
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+/// Gerador pseudo-aleatório SplitMix64: pequeno, rápido e reprodutível entre plataformas.
+/// Suficiente para chutes iniciais e testes; não é criptográfico.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniforme em [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Complexo com módulo em [0, 1) e fase uniforme.
+    pub fn next_complex(&mut self) -> Complex64 {
+        let r = self.next_f64();
+        let phi = 2.0 * PI * self.next_f64();
+        Complex64::from_polar(r, phi)
+    }
+}
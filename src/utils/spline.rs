@@ -0,0 +1,71 @@
+/// Spline cúbica natural (segunda derivada nula nos extremos) em uma malha crescente
+/// arbitrária, como as malhas logarítmicas dos UPF.
+#[derive(Debug, Clone, Default)]
+pub struct CubicSpline {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    m: Vec<f64>, // Segundas derivadas nos nós
+}
+
+impl CubicSpline {
+    /// Resolve o sistema tridiagonal das segundas derivadas (algoritmo de Thomas).
+    pub fn new(x: &[f64], y: &[f64]) -> Self {
+        let n = x.len().min(y.len());
+        let (x, y) = (x[..n].to_vec(), y[..n].to_vec());
+        let mut m = vec![0.0; n];
+        if n < 3 {
+            return Self { x, y, m };
+        }
+
+        let mut c_prime = vec![0.0; n];
+        let mut d_prime = vec![0.0; n];
+        for i in 1..n - 1 {
+            let h0 = x[i] - x[i - 1];
+            let h1 = x[i + 1] - x[i];
+            let a = h0;
+            let b = 2.0 * (h0 + h1);
+            let c = h1;
+            let d = 6.0 * ((y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0);
+
+            let denom = b - a * c_prime[i - 1];
+            c_prime[i] = c / denom;
+            d_prime[i] = (d - a * d_prime[i - 1]) / denom;
+        }
+        for i in (1..n - 1).rev() {
+            m[i] = d_prime[i] - c_prime[i] * m[i + 1];
+        }
+        Self { x, y, m }
+    }
+
+    pub fn x_min(&self) -> f64 {
+        self.x.first().copied().unwrap_or(0.0)
+    }
+
+    pub fn x_max(&self) -> f64 {
+        self.x.last().copied().unwrap_or(0.0)
+    }
+
+    /// Valor interpolado; fora da malha devolve o valor do extremo mais próximo.
+    pub fn eval(&self, t: f64) -> f64 {
+        let n = self.x.len();
+        match n {
+            0 => return 0.0,
+            1 => return self.y[0],
+            _ => {}
+        }
+        if t <= self.x[0] {
+            return self.y[0];
+        }
+        if t >= self.x[n - 1] {
+            return self.y[n - 1];
+        }
+
+        // Intervalo [x_i, x_{i+1}] contendo t
+        let i = self.x.partition_point(|&xi| xi <= t).saturating_sub(1).min(n - 2);
+        let h = self.x[i + 1] - self.x[i];
+        let a = (self.x[i + 1] - t) / h;
+        let b = (t - self.x[i]) / h;
+        a * self.y[i] + b * self.y[i + 1]
+            + ((a * a * a - a) * self.m[i] + (b * b * b - b) * self.m[i + 1]) * h * h / 6.0
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Tempo acumulado de uma fase do cálculo.
+#[derive(Debug, Clone)]
+pub struct TimerEntry {
+    pub name: &'static str,
+    pub total: Duration,
+    pub calls: usize,
+}
+
+// Registro global: fase -> (tempo total, número de chamadas).
+// Global para que kernels profundos (FFT, H·psi) possam ser instrumentados sem
+// passar um objeto de contexto por toda a pilha de chamadas.
+fn registry() -> &'static Mutex<HashMap<&'static str, (Duration, usize)>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<&'static str, (Duration, usize)>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Guarda RAII: acumula o tempo decorrido na fase quando sai de escopo.
+pub struct TimerGuard {
+    name: &'static str,
+    start: Option<Instant>, // None onde não há relógio (wasm32-unknown-unknown)
+}
+
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        add(self.name, self.start.map_or(Duration::ZERO, |s| s.elapsed()));
+    }
+}
+
+/// Inicia a medição de uma fase. Ex: `let _t = timer::scope("fft");`
+pub fn scope(name: &'static str) -> TimerGuard {
+    TimerGuard { name, start: now() }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+// Instant::now entra em pânico em wasm32-unknown-unknown: só contamos chamadas
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn now() -> Option<Instant> {
+    None
+}
+
+/// Soma manualmente um intervalo de tempo a uma fase.
+pub fn add(name: &'static str, elapsed: Duration) {
+    if let Ok(mut reg) = registry().lock() {
+        let entry = reg.entry(name).or_insert((Duration::ZERO, 0));
+        entry.0 += elapsed;
+        entry.1 += 1;
+    }
+}
+
+/// Zera todos os contadores.
+pub fn reset() {
+    if let Ok(mut reg) = registry().lock() {
+        reg.clear();
+    }
+}
+
+/// Cópia dos contadores, ordenada por tempo total (decrescente).
+pub fn snapshot() -> Vec<TimerEntry> {
+    let mut entries: Vec<TimerEntry> = match registry().lock() {
+        Ok(reg) => reg.iter()
+            .map(|(&name, &(total, calls))| TimerEntry { name, total, calls })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    entries.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(b.name)));
+    entries
+}
+
+/// Tabela com o tempo de cada fase, no estilo do relatório final do Quantum ESPRESSO.
+/// As fases podem ser aninhadas, então as porcentagens não somam necessariamente 100%.
+pub fn report() -> String {
+    let entries = snapshot();
+    let wall: f64 = entries.iter().map(|e| e.total.as_secs_f64()).fold(0.0, f64::max);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- Tempos por Fase ---");
+    let _ = writeln!(out, "{:<20} {:>10} {:>12} {:>12} {:>7}", "Fase", "Chamadas", "Total (s)", "Média (ms)", "%");
+    for e in &entries {
+        let total = e.total.as_secs_f64();
+        let _ = writeln!(
+            out,
+            "{:<20} {:>10} {:>12.3} {:>12.3} {:>6.1}%",
+            e.name,
+            e.calls,
+            total,
+            1000.0 * total / e.calls.max(1) as f64,
+            if wall > 0.0 { 100.0 * total / wall } else { 0.0 }
+        );
+    }
+    out
+}
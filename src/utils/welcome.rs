@@ -10,5 +10,5 @@ Bravie: A (work in progress) Rust DFT Software
 Versão: 0.1.0 (Dev)
 ----------------------------------------------
     "#;
-    println!("{}", banner);
+    log::info!("{}", banner);
 }
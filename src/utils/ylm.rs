@@ -0,0 +1,44 @@
+use std::f64::consts::PI;
+use nalgebra::Vector3;
+
+/// Maior l suportado pelos harmônicos esféricos reais.
+pub const LMAX: usize = 3;
+
+/// Harmônicos esféricos reais normalizados Y_lm(r̂), m = 0..2l (ordem: m = 0, depois pares
+/// cos/sin para |m| = 1..l). Para r = 0 apenas Y_00 é não nulo. `None` se l > `LMAX`.
+pub fn real_ylm(l: usize, v: &Vector3<f64>) -> Option<Vec<f64>> {
+    if l > LMAX {
+        return None;
+    }
+    let norm = v.norm();
+    if norm < 1e-12 {
+        let mut y = vec![0.0; 2 * l + 1];
+        if l == 0 {
+            y[0] = 0.5 / PI.sqrt();
+        }
+        return Some(y);
+    }
+    let (x, y, z) = (v.x / norm, v.y / norm, v.z / norm);
+    let c = |k: f64| (k / PI).sqrt();
+
+    Some(match l {
+        0 => vec![0.5 / PI.sqrt()],
+        1 => vec![c(3.0 / 4.0) * z, c(3.0 / 4.0) * x, c(3.0 / 4.0) * y],
+        2 => vec![
+            c(5.0 / 16.0) * (3.0 * z * z - 1.0),
+            c(15.0 / 4.0) * x * z,
+            c(15.0 / 4.0) * y * z,
+            c(15.0 / 16.0) * (x * x - y * y),
+            c(15.0 / 4.0) * x * y,
+        ],
+        _ => vec![
+            c(7.0 / 16.0) * z * (5.0 * z * z - 3.0),
+            c(21.0 / 32.0) * x * (5.0 * z * z - 1.0),
+            c(21.0 / 32.0) * y * (5.0 * z * z - 1.0),
+            c(105.0 / 16.0) * z * (x * x - y * y),
+            c(105.0 / 4.0) * x * y * z,
+            c(35.0 / 32.0) * x * (x * x - 3.0 * y * y),
+            c(35.0 / 32.0) * y * (3.0 * x * x - y * y),
+        ],
+    })
+}
@@ -0,0 +1,40 @@
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use bravie::dft::scratch::{OutOfCore, StoredBands};
+use bravie::dft::wavefunctions::Precision;
+
+fn sample_bands(n_bands: usize, npw: usize) -> Vec<Array1<Complex64>> {
+    (0..n_bands)
+        .map(|n| Array1::from_shape_fn(npw, |g| Complex64::new((n * npw + g) as f64 * 0.1, -(g as f64))))
+        .collect()
+}
+
+#[test]
+fn out_of_core_bands_round_trip() {
+    let dir = std::env::temp_dir().join(format!("bravie_scratch_{}", std::process::id()));
+    let ooc = OutOfCore { scratch_dir: dir.clone(), resident_bands: 2 };
+    let bands = sample_bands(5, 7);
+
+    let mut stored = StoredBands::store(bands.clone(), Precision::Double, Some(&ooc), 3).unwrap();
+    assert!(matches!(stored, StoredBands::Scratch(_)));
+    assert!(ooc.scratch_file(3).is_file());
+    assert_eq!(stored.n_bands(), 5);
+    assert_eq!(stored.band(4).unwrap(), bands[4]);
+    assert_eq!(stored.to_double().unwrap(), bands);
+    assert!(stored.band(5).is_err());
+
+    drop(stored);
+    assert!(!ooc.scratch_file(3).exists());
+    std::fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn single_precision_bands_stay_close() {
+    let bands = sample_bands(3, 11);
+    let mut stored = StoredBands::store(bands.clone(), Precision::Single, None, 0).unwrap();
+    for (a, b) in stored.to_double().unwrap().iter().zip(&bands) {
+        let error = a.iter().zip(b).map(|(x, y)| (x - y).norm() / y.norm().max(1.0)).fold(0.0, f64::max);
+        assert!(error < 1e-6, "erro relativo {:.3e}", error);
+    }
+}
@@ -0,0 +1,79 @@
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::dispersion::{D2, D2Parameters, D2Species};
+use bravie::dft::xc::XcFunctional;
+use bravie::utils::constants::ANGSTROM_TO_BOHR;
+
+fn argon() -> Species {
+    Species {
+        id: 0,
+        element: "Ar".to_string(),
+        atomic_number: 18,
+        mass: 39.948,
+        pseudo_path: "inexistente.UPF".to_string(),
+    }
+}
+
+/// Dímero de Ar numa caixa cúbica de lado `box_size` (Bohr), a `r` Bohr de distância.
+fn argon_dimer(r: f64, box_size: f64) -> Structure {
+    Structure::builder()
+        .cubic(box_size)
+        .add_species(argon())
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([r, 0.0, 0.0], 0)
+        .build()
+        .unwrap()
+}
+
+fn pbe_d2(cutoff: f64) -> D2 {
+    D2::new(D2Parameters { cutoff, ..Default::default() }, Some(XcFunctional::Pbe)).unwrap()
+}
+
+#[test]
+fn argon_dimer_matches_grimme_parameters() {
+    // Ar: C6 = 4.61 J·nm⁶/mol, R0 = 1.595 Å; PBE: s6 = 0.75, d = 20. A 3.76 Å:
+    // E = -s6 C6 / r⁶ · 1/(1 + e^{-d(r/2R0 - 1)}) = -4.5332e-4 Ha
+    let d2 = pbe_d2(20.0);
+    assert_eq!(D2Species::reference("Ar"), Some(D2Species { c6: 4.61, r0: 1.595 }));
+    let result = d2.compute(&argon_dimer(3.76 * ANGSTROM_TO_BOHR, 60.0)).unwrap();
+    assert!((result.energy - (-9.06646e-4)).abs() < 1e-8, "E = {:.6e} Ry", result.energy);
+
+    // Atração: o primeiro átomo é puxado para +x, o segundo para -x
+    assert!(result.forces[0].x > 0.0);
+    assert!((result.forces[0] + result.forces[1]).norm() < 1e-14);
+}
+
+#[test]
+fn forces_match_energy_derivative() {
+    let d2 = pbe_d2(20.0);
+    let (r, h) = (7.0, 1e-4);
+    let energy = |r: f64| d2.compute(&argon_dimer(r, 60.0)).unwrap().energy;
+    // F_2 = -dE/dr
+    let numeric = -(energy(r + h) - energy(r - h)) / (2.0 * h);
+    let force = d2.compute(&argon_dimer(r, 60.0)).unwrap().forces[1].x;
+    assert!((force - numeric).abs() < 1e-8 * numeric.abs().max(1.0), "{} != {}", force, numeric);
+}
+
+#[test]
+fn stress_matches_strain_derivative() {
+    // Ar cúbico simples: sob deformação isotrópica ε, dE/dε = -Ω tr σ
+    let d2 = pbe_d2(20.0);
+    let (a, h) = (7.0, 1e-5);
+    let crystal = |a: f64| Structure::builder().cubic(a).add_species(argon()).add_atom([0.0; 3], 0).build().unwrap();
+    let energy = |a: f64| d2.compute(&crystal(a)).unwrap().energy;
+    let numeric = (energy(a * (1.0 + h)) - energy(a * (1.0 - h))) / (2.0 * h);
+
+    let stress = d2.compute(&crystal(a)).unwrap().stress;
+    let analytic = -a.powi(3) * stress.trace();
+    assert!((analytic - numeric).abs() < 1e-6 * numeric.abs(), "{} != {}", analytic, numeric);
+    // Simetria cúbica: diagonal, componentes iguais; dispersão atrai (tensão negativa)
+    assert!((stress[(0, 0)] - stress[(1, 1)]).abs() < 1e-12 && stress[(0, 1)].abs() < 1e-12);
+    assert!(stress[(0, 0)] < 0.0);
+}
+
+#[test]
+fn functional_without_d2_needs_explicit_s6() {
+    assert!(D2::new(D2Parameters::default(), Some(XcFunctional::LdaPz)).is_err());
+    let d2 = D2::new(D2Parameters { s6: Some(1.0), ..Default::default() }, Some(XcFunctional::LdaPz)).unwrap();
+    assert_eq!(d2.s6, 1.0);
+    assert!(d2.species_data("Og").is_err());
+}
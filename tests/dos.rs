@@ -0,0 +1,27 @@
+use bravie::postproc::dos::{bands_to_gnuplot, Dos, DosError, SpinBands};
+
+#[test]
+fn gaussian_dos_integrates_to_the_state_count() {
+    let bands = SpinBands::unpolarized(vec![vec![-0.5, 0.1], vec![-0.4, 0.2]], vec![1.0, 1.0]);
+    let dos = Dos::gaussian(&bands, 0.02, 2001).unwrap();
+    let step = dos.energies[1] - dos.energies[0];
+    let states: f64 = dos.channels[0].iter().sum::<f64>() * step;
+    // 2 bandas x 2 elétrons
+    assert!((states - 4.0).abs() < 1e-6, "∫DOS = {}", states);
+
+    for sigma in [0.0, -0.01, f64::NAN] {
+        assert!(matches!(Dos::gaussian(&bands, sigma, 10), Err(DosError::InvalidSigma(_))));
+    }
+}
+
+#[test]
+fn band_path_must_match_distances() {
+    let bands = SpinBands::polarized(vec![vec![0.0, 0.1]; 3], vec![vec![0.05, 0.15]; 2], vec![1.0; 3]);
+    let err = bands_to_gnuplot(&bands, &[0.0, 0.5, 1.0], 0.0).unwrap_err();
+    assert!(matches!(err, DosError::KPointCount(3, 1, 2)));
+
+    let bands = SpinBands::unpolarized(vec![vec![0.0, 0.1], vec![0.02]], vec![1.0; 2]);
+    let text = bands_to_gnuplot(&bands, &[0.0, 0.5], 0.0).unwrap();
+    // Só a banda comum a todos os pontos K
+    assert_eq!(text.matches("# banda").count(), 1);
+}
@@ -0,0 +1,35 @@
+use bravie::core::structure::{Species, Structure};
+use bravie::postproc::effective_mass::{effective_mass_tensor, EffectiveMassError, DEFAULT_DK};
+
+fn empty_box() -> Structure {
+    Structure::builder()
+        .cubic(8.0)
+        .add_species(Species {
+            id: 0,
+            element: "H".to_string(),
+            atomic_number: 1,
+            mass: 1.008,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([0.0; 3], 0)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn free_electron_has_unit_mass_and_missing_band_is_an_error() {
+    let structure = empty_box();
+    let recip = structure.lattice.reciprocal();
+    // Elétron livre: E = |k|² Ry, m* = m_e
+    let free = |k: [f64; 3]| -> Result<Vec<f64>, EffectiveMassError> {
+        Ok(vec![(recip * nalgebra::Vector3::from(k)).norm_squared()])
+    };
+
+    let mass = effective_mass_tensor(&structure, [0.1, 0.0, 0.0], 0, DEFAULT_DK, free).unwrap();
+    for m in mass.principal_masses {
+        assert!((m - 1.0).abs() < 1e-6, "m* = {}", m);
+    }
+
+    let err = effective_mass_tensor(&structure, [0.0; 3], 3, DEFAULT_DK, free).unwrap_err();
+    assert!(matches!(err, EffectiveMassError::BandOutOfRange(3, 1)));
+}
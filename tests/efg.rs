@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+use ndarray::Array3;
+
+use bravie::core::fft::FftGrid;
+use bravie::dft::error::DftError;
+use bravie::postproc::efg::electric_field_gradients;
+use bravie::testkit::empty_cubic_box;
+
+#[test]
+fn density_on_another_grid_is_an_error() {
+    let structure = empty_cubic_box(8.0);
+    let mut fft = FftGrid::with_size([12, 12, 12]).unwrap();
+    let rho = Array3::<f64>::zeros((12, 12, 10));
+    let err = electric_field_gradients(&structure, &mut fft, &rho, |_| 1.0, &HashMap::new()).unwrap_err();
+    assert!(matches!(err, DftError::GridMismatch([12, 12, 10], [12, 12, 12])));
+}
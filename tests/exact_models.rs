@@ -0,0 +1,92 @@
+use ndarray::Array3;
+
+use bravie::core::basis::PlaneWaveBasis;
+use bravie::core::fft::FftGrid;
+use bravie::core::field::PotentialField;
+use bravie::core::structure::Structure;
+use bravie::dft::hamiltonian::{EffectiveMass, Hamiltonian};
+use bravie::dft::solver::{apply_hamiltonian_local, solve_bands_exact, BandSolverResult};
+use bravie::testkit::cosine::{cosine_levels, cosine_potential};
+use bravie::testkit::empty_cubic_box;
+use bravie::testkit::free_electron::free_electron_levels;
+use bravie::testkit::harmonic::{harmonic_levels, harmonic_potential};
+
+/// Autoestados exatos de H = -∇² + `v`.
+fn exact_bands(structure: &Structure, basis: &PlaneWaveBasis, fft: &mut FftGrid, v: &Array3<f64>, n: usize) -> BandSolverResult {
+    let hamiltonian = Hamiltonian::new(PotentialField::new(structure.lattice.clone(), v.clone()));
+    solve_bands_exact(&mut hamiltonian.bind(basis, fft), n).unwrap()
+}
+
+fn assert_levels(computed: &[f64], exact: &[f64], tol: f64) {
+    assert_eq!(computed.len(), exact.len());
+    for (n, (e, e_ref)) in computed.iter().zip(exact).enumerate() {
+        assert!((e - e_ref).abs() < tol, "nível {}: {} != {} (tol {})", n, e, e_ref, tol);
+    }
+}
+
+#[test]
+fn free_electron_gas_matches_kinetic_energies() {
+    let structure = empty_cubic_box(7.0);
+    let basis = PlaneWaveBasis::new(&structure, 6.0, Some([0.1, 0.2, 0.3]));
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let [nx, ny, nz] = basis.fft_grid;
+    let v = Array3::<f64>::zeros((nx, ny, nz));
+
+    let result = exact_bands(&structure, &basis, &mut fft, &v, 10);
+    assert_levels(&result.eigenvalues, &free_electron_levels(&basis, 10), 1e-10);
+}
+
+#[test]
+fn cosine_potential_matches_mathieu_levels() {
+    let (a, v0) = (8.0, 0.2);
+    let structure = empty_cubic_box(a);
+    let basis = PlaneWaveBasis::new(&structure, 10.0, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v = cosine_potential(&structure, basis.fft_grid, a, v0);
+
+    let result = exact_bands(&structure, &basis, &mut fft, &v, 7);
+    assert_levels(&result.eigenvalues, &cosine_levels(a, v0, 7), 1e-5);
+}
+
+#[test]
+fn local_hamiltonian_reproduces_exact_eigenpairs() {
+    let (a, v0) = (8.0, 0.2);
+    let structure = empty_cubic_box(a);
+    let basis = PlaneWaveBasis::new(&structure, 10.0, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v = cosine_potential(&structure, basis.fft_grid, a, v0);
+
+    let result = exact_bands(&structure, &basis, &mut fft, &v, 4);
+    for (e, psi) in result.eigenvalues.iter().zip(&result.eigenvectors) {
+        let h_psi = apply_hamiltonian_local(&basis, &mut fft, &v, psi).unwrap();
+        let residual: f64 = h_psi.iter().zip(psi).map(|(h, c)| (h - c * *e).norm_sqr()).sum();
+        assert!(residual.sqrt() < 1e-8, "|Hψ - εψ| = {:.3e}", residual.sqrt());
+    }
+}
+
+#[test]
+fn harmonic_confinement_matches_oscillator_levels() {
+    let c = 1.0;
+    let structure = empty_cubic_box(10.0);
+    let basis = PlaneWaveBasis::new(&structure, 20.0, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v = harmonic_potential(&structure, basis.fft_grid, c);
+
+    let result = exact_bands(&structure, &basis, &mut fft, &v, 4);
+    assert_levels(&result.eigenvalues, &harmonic_levels(c, 4), 1e-3);
+}
+
+#[test]
+fn exact_solver_uses_the_hamiltonian_dispersion() {
+    // T = |k+G|²/m*: o gás livre com m* = 2 tem metade das energias
+    let structure = empty_cubic_box(7.0);
+    let basis = PlaneWaveBasis::new(&structure, 6.0, Some([0.1, 0.2, 0.3]));
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v_eff = PotentialField::zeros(structure.lattice.clone(), basis.fft_grid);
+    let hamiltonian = Hamiltonian::with_model(Box::new(EffectiveMass { m_star: 2.0 }), v_eff);
+
+    let result = solve_bands_exact(&mut hamiltonian.bind(&basis, &mut fft), 10).unwrap();
+    let expected: Vec<f64> = free_electron_levels(&basis, 10).iter().map(|e| e / 2.0).collect();
+    assert_levels(&result.eigenvalues, &expected, 1e-10);
+    assert!(result.max_residual() < 1e-10);
+}
@@ -0,0 +1,68 @@
+use std::f64::consts::PI;
+
+use nalgebra::Vector3;
+use ndarray::{Array1, Array3};
+use num_complex::Complex64;
+
+use bravie::core::basis::{KPhases, PlaneWaveBasis};
+use bravie::core::fft::FftGrid;
+use bravie::dft::error::DftError;
+use bravie::testkit::empty_cubic_box;
+
+/// Coeficientes arbitrários (não nulos em todos os G) para uma base.
+fn coefficients(n: usize) -> Array1<Complex64> {
+    Array1::from_shape_fn(n, |i| Complex64::new((0.3 * i as f64).cos(), (0.7 * i as f64).sin()))
+}
+
+#[test]
+fn coefficients_must_match_the_basis() {
+    let basis = PlaneWaveBasis::new(&empty_cubic_box(8.0), 6.0, Some([0.1, 0.0, 0.2]));
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let npw = basis.g_vectors.len();
+
+    let short = coefficients(npw - 1);
+    assert!(matches!(fft.to_real_space(&basis, &short), Err(DftError::SizeMismatch(_, n, m)) if n == npw - 1 && m == npw));
+    let long = coefficients(npw + 1);
+    assert!(fft.to_real_space(&basis, &long).is_err());
+
+    // Ida e volta com o tamanho certo
+    let c = coefficients(npw);
+    fft.to_real_space(&basis, &c).unwrap();
+    let mut back = Array1::zeros(npw);
+    fft.to_recip_space(&basis, &mut back).unwrap();
+    assert!(back.iter().zip(&c).all(|(a, b)| (a - b).norm() < 1e-12));
+}
+
+#[test]
+fn k_phases_round_trip() {
+    let size = [6, 5, 4];
+    let phases = KPhases::new(Vector3::new(0.25, -0.1, 0.5), size);
+    assert!(!phases.is_gamma());
+
+    let field = Array3::from_shape_fn((6, 5, 4), |(i, j, k)| Complex64::new(1.0 + i as f64, (j * k) as f64 - 0.5));
+    let mut shifted = field.clone();
+    phases.apply(&mut shifted, false);
+    assert!((shifted[[1, 0, 0]] - field[[1, 0, 0]] * Complex64::from_polar(1.0, 2.0 * PI * 0.25 / 6.0)).norm() < 1e-12);
+    phases.apply(&mut shifted, true);
+    assert!(shifted.iter().zip(&field).all(|(a, b)| (a - b).norm() < 1e-12));
+}
+
+#[test]
+fn bloch_transforms_round_trip_away_from_gamma() {
+    let basis = PlaneWaveBasis::new(&empty_cubic_box(8.0), 6.0, Some([0.25, 0.0, -0.125]));
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let c = coefficients(basis.g_vectors.len());
+
+    // ψ_k(r) = e^{ik·r}·u_k(r)
+    fft.to_real_space(&basis, &c).unwrap();
+    let periodic = fft.buffer.clone();
+    fft.bloch_to_real_space(&basis, &c).unwrap();
+    let phases = basis.phases_for(fft.size).unwrap();
+    for ((i, j, k), psi) in fft.buffer.indexed_iter() {
+        assert!((psi - phases.at(i, j, k) * periodic[[i, j, k]]).norm() < 1e-12);
+    }
+
+    let mut back = Array1::zeros(c.len());
+    fft.bloch_to_recip_space(&basis, &mut back).unwrap();
+    assert!(back.iter().zip(&c).all(|(a, b)| (a - b).norm() < 1e-12));
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "fftw")]
+
+use ndarray::Array3;
+use ndrustfft::{ndfft, ndifft, FftHandler};
+use num_complex::Complex64;
+
+use bravie::core::fft::FftGrid;
+
+/// Transformada 3D de referência com o ndrustfft (backend padrão), eixo a eixo.
+fn reference(input: &Array3<Complex64>, inverse: bool) -> Array3<Complex64> {
+    let (nx, ny, nz) = input.dim();
+    let handlers = [FftHandler::new(nx), FftHandler::new(ny), FftHandler::new(nz)];
+    let mut data = input.clone();
+    let mut out = Array3::zeros(input.dim());
+    for (axis, handler) in handlers.iter().enumerate() {
+        if inverse {
+            ndifft(&data, &mut out, handler, axis);
+        } else {
+            ndfft(&data, &mut out, handler, axis);
+        }
+        std::mem::swap(&mut data, &mut out);
+    }
+    data
+}
+
+fn max_diff(a: &Array3<Complex64>, b: &Array3<Complex64>) -> f64 {
+    a.iter().zip(b).fold(0.0, |m, (x, y)| m.max((x - y).norm()))
+}
+
+#[test]
+fn fftw_matches_ndrustfft() {
+    // Tamanhos não potência de 2, como os grids da base
+    let size = [6, 5, 9];
+    let input = Array3::from_shape_fn((6, 5, 9), |(i, j, k)| {
+        Complex64::new((0.7 * i as f64 + 0.3 * j as f64).sin(), (1.1 * k as f64 - 0.2 * j as f64).cos())
+    });
+    let mut fft = FftGrid::with_size(size).unwrap();
+
+    fft.buffer.assign(&input);
+    fft.forward_in_place().unwrap();
+    let diff = max_diff(&fft.buffer, &reference(&input, false));
+    assert!(diff < 1e-10, "forward: diferença {:.3e}", diff);
+
+    fft.buffer.assign(&input);
+    fft.inverse_in_place().unwrap();
+    let diff = max_diff(&fft.buffer, &reference(&input, true));
+    assert!(diff < 1e-12, "inversa: diferença {:.3e}", diff);
+}
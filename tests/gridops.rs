@@ -0,0 +1,64 @@
+use nalgebra::Vector3;
+use ndarray::Array3;
+use num_complex::Complex64;
+
+use bravie::core::field::PotentialField;
+use bravie::core::gridops;
+use bravie::core::structure::Lattice;
+use bravie::dft::error::DftError;
+
+/// sin(0.7 i + fase); com n = 37 há blocos de `LANES` e um resto.
+fn samples(n: usize, phase: f64) -> Vec<f64> {
+    (0..n).map(|i| (0.7 * i as f64 + phase).sin()).collect()
+}
+
+#[test]
+fn kernels_match_scalar_loops() {
+    let x = samples(37, 0.0);
+    let y = samples(37, 1.3);
+
+    let naive_dot: f64 = x.iter().zip(&y).map(|(a, b)| a * b).sum();
+    assert!((gridops::dot(&x, &y).unwrap() - naive_dot).abs() < 1e-12);
+    assert!((gridops::sum(&x) - x.iter().sum::<f64>()).abs() < 1e-12);
+
+    let mut z = y.clone();
+    gridops::axpy(-2.0, &x, &mut z).unwrap();
+    assert!(z.iter().zip(&x).zip(&y).all(|((z, x), y)| (z - (y - 2.0 * x)).abs() < 1e-15));
+
+    let psi: Vec<Complex64> = x.iter().zip(&y).map(|(&a, &b)| Complex64::new(a, b)).collect();
+    let mut rho = vec![1.0; 37];
+    gridops::axpy_norm_sqr(0.5, &psi, &mut rho).unwrap();
+    assert!(rho.iter().zip(&psi).all(|(r, p)| (r - (1.0 + 0.5 * p.norm_sqr())).abs() < 1e-15));
+
+    let mut buf = psi.clone();
+    gridops::mul_real(&mut buf, &y).unwrap();
+    assert!(buf.iter().zip(&psi).zip(&y).all(|((b, p), v)| (b - p * v).norm() < 1e-15));
+
+    gridops::scale(3.0, &mut z);
+    assert!(z.iter().zip(&x).zip(&y).all(|((z, x), y)| (z - 3.0 * (y - 2.0 * x)).abs() < 1e-14));
+}
+
+#[test]
+fn length_mismatch_is_an_error() {
+    let x = samples(16, 0.0);
+    let mut y = samples(15, 0.0);
+    let mut buf = vec![Complex64::new(1.0, 0.0); 15];
+    assert!(matches!(gridops::dot(&x, &y), Err(DftError::SizeMismatch(_, 15, 16))));
+    assert!(matches!(gridops::axpy(1.0, &x, &mut y), Err(DftError::SizeMismatch(_, 16, 15))));
+    assert!(matches!(gridops::mul_real(&mut buf, &x), Err(DftError::SizeMismatch(_, 16, 15))));
+    assert!(gridops::axpy_norm_sqr(1.0, &buf, &mut samples(16, 0.0)).is_err());
+}
+
+#[test]
+fn potentials_add_pointwise() {
+    let lattice = Lattice::new(Vector3::new(4.0, 0.0, 0.0), Vector3::new(0.0, 4.0, 0.0), Vector3::new(0.0, 0.0, 4.0));
+    let v_loc = PotentialField::new(lattice.clone(), Array3::from_shape_fn((4, 5, 6), |(i, j, k)| (i + 2 * j + 3 * k) as f64));
+    let v_h = PotentialField::new(lattice.clone(), Array3::from_elem((4, 5, 6), 0.25));
+
+    let mut v_eff = v_loc.clone();
+    v_eff.add_scaled(1.0, &v_h).unwrap();
+    assert!(v_eff.data.iter().zip(&v_loc.data).all(|(e, l)| (e - l - 0.25).abs() < 1e-15));
+
+    let other = PotentialField::zeros(lattice, [4, 5, 5]);
+    assert!(v_eff.add_scaled(1.0, &other).is_err());
+}
@@ -0,0 +1,106 @@
+use std::f64::consts::PI;
+use nalgebra::Vector3;
+use ndarray::Array3;
+
+use bravie::core::fft::FftGrid;
+use bravie::core::field::DensityField;
+use bravie::core::structure::{Lattice, Species, Structure};
+use bravie::dft::hartree::{ewald_energy, hartree_potential, ion_ion_energy, CoulombKernel};
+
+fn species() -> Species {
+    Species {
+        id: 0,
+        element: "H".to_string(),
+        atomic_number: 1,
+        mass: 1.008,
+        pseudo_path: "inexistente.UPF".to_string(),
+    }
+}
+
+/// Gaussiana normalizada de carga `q` e largura `sigma` no centro de uma caixa cúbica.
+fn gaussian_density(side: f64, n: usize, sigma: f64, q: f64) -> DensityField {
+    let lattice = Lattice::new(
+        Vector3::new(side, 0.0, 0.0),
+        Vector3::new(0.0, side, 0.0),
+        Vector3::new(0.0, 0.0, side),
+    );
+    let h = side / n as f64;
+    let norm = q / (2.0 * PI * sigma * sigma).powf(1.5);
+    let data = Array3::from_shape_fn((n, n, n), |(i, j, k)| {
+        let r2 = [i, j, k].iter().map(|&m| (m as f64 * h - side / 2.0).powi(2)).sum::<f64>();
+        norm * (-r2 / (2.0 * sigma * sigma)).exp()
+    });
+    DensityField::new(lattice, data)
+}
+
+#[test]
+fn truncated_kernel_gives_isolated_gaussian_self_energy() {
+    // E_H = q²/(√π σ) Ry para uma gaussiana isolada; R_c = 12 Bohr cobre a densidade
+    let rho = gaussian_density(24.0, 48, 1.0, 1.0);
+    let mut fft = FftGrid::with_size([48, 48, 48]).unwrap();
+    let expected = 1.0 / PI.sqrt();
+
+    let kernel = CoulombKernel::isolated(&rho.lattice);
+    assert!(matches!(kernel, CoulombKernel::Truncated { radius } if (radius - 12.0).abs() < 1e-12));
+    let truncated = hartree_potential(&rho, &mut fft, kernel).unwrap();
+    assert!((truncated.energy - expected).abs() < 1e-6, "{} != {}", truncated.energy, expected);
+
+    // O kernel periódico inclui as imagens e o fundo neutralizante (erro de ~0.12 Ry aqui)
+    let periodic = hartree_potential(&rho, &mut fft, CoulombKernel::Periodic).unwrap();
+    assert!(expected - periodic.energy > 0.1, "E_H periódico = {}", periodic.energy);
+    assert!(periodic.potential.integrate().abs() < 1e-8);
+}
+
+#[test]
+fn truncated_kernel_is_continuous_at_g0() {
+    let kernel = CoulombKernel::Truncated { radius: 10.0 };
+    let k0 = kernel.eval(0.0);
+    assert!((k0 - 400.0 * PI).abs() < 1e-10);
+    // 8π(1 - cos(GR))/G² = 4πR²(1 - (GR)²/12 + ...)
+    let g: f64 = 1e-3;
+    assert!((kernel.eval(g * g) - k0 * (1.0 - 1e-4 / 12.0)).abs() < 1e-8 * k0);
+}
+
+#[test]
+fn ewald_matches_simple_cubic_madelung_constant() {
+    // Rede cúbica simples de cargas unitárias em fundo neutralizante: E = -2.8372975/a Ry
+    let a = 5.0;
+    let structure = Structure::builder().cubic(a).add_species(species()).add_atom([0.0; 3], 0).build().unwrap();
+    let energy = ewald_energy(&structure, &[1.0]);
+    assert!((energy + 2.837297479 / a).abs() < 1e-8, "E = {}", energy);
+
+    // Supercélula 2x1x1: energia extensiva
+    let double = structure.supercell([2, 1, 1]);
+    assert!((ewald_energy(&double, &[1.0, 1.0]) - 2.0 * energy).abs() < 1e-8);
+}
+
+#[test]
+fn ewald_matches_silicon_diamond() {
+    // Si diamante, a = 10.20 Bohr, Z = 4: o termo "ewald contribution" do verify_energy
+    let a = 10.20;
+    let structure = Structure::builder()
+        .lattice([0.0, a / 2.0, a / 2.0], [a / 2.0, 0.0, a / 2.0], [a / 2.0, a / 2.0, 0.0])
+        .add_species(species())
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([a / 4.0, a / 4.0, a / 4.0], 0)
+        .build()
+        .unwrap();
+    let energy = ewald_energy(&structure, &[4.0, 4.0]);
+    assert!((energy + 16.89975857).abs() < 1e-7, "E = {}", energy);
+}
+
+#[test]
+fn isolated_ions_interact_by_direct_coulomb() {
+    let structure = Structure::builder()
+        .cubic(20.0)
+        .add_species(species())
+        .add_atom([8.0, 10.0, 10.0], 0)
+        .add_atom([10.0, 10.0, 10.0], 0)
+        .build()
+        .unwrap();
+    let kernel = CoulombKernel::isolated(&structure.lattice);
+    // 2 Z_1 Z_2 / r (Ry)
+    let energy = ion_ion_energy(&structure, &[1.0, 3.0], kernel).unwrap();
+    assert!((energy - 3.0).abs() < 1e-12);
+    assert!(ion_ion_energy(&structure, &[1.0], kernel).is_err());
+}
@@ -0,0 +1,179 @@
+use nalgebra::Vector3;
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use bravie::core::basis::PlaneWaveBasis;
+use bravie::core::kpoints::KGrid;
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::initial_guess::{random_wavefunctions, InitialGuess};
+use bravie::dft::solver::Diagonalizer;
+use bravie::io::upf::AtomicWavefunction;
+use bravie::testkit::empty_cubic_box;
+use bravie::utils::ylm::{real_ylm, LMAX};
+use bravie::{Pseudopotential, Simulation};
+
+fn max_orthonormality_error(bands: &[Array1<Complex64>]) -> f64 {
+    let mut error: f64 = 0.0;
+    for (m, a) in bands.iter().enumerate() {
+        for (n, b) in bands.iter().enumerate() {
+            let dot: Complex64 = a.iter().zip(b).map(|(x, y)| x.conj() * y).sum();
+            let expected = if m == n { 1.0 } else { 0.0 };
+            error = error.max((dot - expected).norm());
+        }
+    }
+    error
+}
+
+#[test]
+fn same_seed_gives_identical_orthonormal_bands() {
+    let basis = PlaneWaveBasis::new(&empty_cubic_box(8.0), 6.0, Some([0.1, 0.0, 0.2]));
+    let first = random_wavefunctions(&basis, 8, 42, None);
+    let second = random_wavefunctions(&basis, 8, 42, None);
+    let other = random_wavefunctions(&basis, 8, 43, None);
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    let error = max_orthonormality_error(&first);
+    assert!(error < 1e-10, "|⟨ψ_m|ψ_n⟩ - δ_mn| = {:.3e}", error);
+}
+
+#[test]
+fn shells_reproduce_per_vector_norms() {
+    let basis = PlaneWaveBasis::new(&empty_cubic_box(8.0), 6.0, Some([0.1, 0.0, 0.2]));
+    assert!(basis.shells.len() < basis.g_vectors.len());
+    let q = basis.shells.expand(&basis.shells.map(|q| q));
+    for (q, g2) in q.iter().zip(&basis.g_norm_sq) {
+        assert!((q - g2.sqrt()).abs() < 1e-7, "{} != {}", q, g2.sqrt());
+    }
+}
+
+/// Si diamante (a = 10.26 Bohr) com pseudo mock, só Γ.
+fn silicon(diagonalizer: Diagonalizer) -> Simulation {
+    let a: f64 = 10.26;
+    let structure = Structure::builder()
+        .lattice([0.0, a / 2.0, a / 2.0], [a / 2.0, 0.0, a / 2.0], [a / 2.0, a / 2.0, 0.0])
+        .add_species(Species {
+            id: 0,
+            element: "Si".to_string(),
+            atomic_number: 14,
+            mass: 28.085,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([a / 4.0, a / 4.0, a / 4.0], 0)
+        .build()
+        .unwrap();
+    Simulation::builder()
+        .structure(structure)
+        .ecut(8.0)
+        .k_grid(KGrid::gamma())
+        .pseudo(0, Pseudopotential::mock("Si", 4.0))
+        .diagonalizer(diagonalizer)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn simulation_starts_from_random_bands() {
+    let sim = silicon(Diagonalizer::default());
+    assert_eq!(sim.initial_guess, InitialGuess::Random);
+    let bands = sim.initial_wavefunctions(0);
+    assert_eq!(bands.len(), sim.n_bands);
+    let error = max_orthonormality_error(&bands);
+    assert!(error < 1e-10, "|⟨ψ_m|ψ_n⟩ - δ_mn| = {:.3e}", error);
+}
+
+#[test]
+fn iterative_bands_start_from_the_initial_guess() {
+    // V_eff ainda zerado: elétron livre, com a camada (111) degenerada cortada por n_bands
+    let mut sim = silicon(Diagonalizer::Pcg);
+    let pcg = sim.solve_bands(0).unwrap();
+    sim.diagonalizer = Diagonalizer::Exact;
+    let exact = sim.solve_bands(0).unwrap();
+
+    assert!(pcg.iterations > 0);
+    assert_eq!(pcg.eigenvalues.len(), sim.n_bands);
+    for (e, e_ref) in pcg.eigenvalues.iter().zip(&exact.eigenvalues) {
+        assert!((e - e_ref).abs() < 1e-8, "{} != {}", e, e_ref);
+    }
+}
+
+#[test]
+fn atomic_orbital_projects_onto_the_ground_state() {
+    // H com o V_loc mock (Coulomb suave, r_c = 0.5 Bohr): o estado fundamental radial tem
+    // ~99.5% de sobreposição com e^{-0.75 r}, dado como PP_CHI (r·χ)
+    let mut pp = Pseudopotential::mock("H", 1.0);
+    let data = pp.mesh.r.iter().map(|r| r * (-0.75 * r).exp()).collect();
+    pp.pswfc.push(AtomicWavefunction { index: 0, label: "1S".to_string(), l: 0, occupation: 1.0, data });
+    pp.header.number_of_wfc = 1;
+
+    let structure = Structure::builder()
+        .cubic(10.0)
+        .add_species(Species {
+            id: 0,
+            element: "H".to_string(),
+            atomic_number: 1,
+            mass: 1.008,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([5.0, 5.0, 5.0], 0)
+        .build()
+        .unwrap();
+    let mut sim = Simulation::builder()
+        .structure(structure)
+        .ecut(8.0)
+        .k_grid(KGrid::gamma())
+        .pseudo(0, pp)
+        .build()
+        .unwrap();
+    let v_loc = sim.local_potential().unwrap();
+    sim.set_effective_potential(&v_loc).unwrap();
+    let ground = sim.solve_bands(0).unwrap().eigenvectors.swap_remove(0);
+
+    let weight = |band: &Array1<Complex64>| band.iter().zip(&ground).map(|(a, b)| a.conj() * b).sum::<Complex64>().norm_sqr();
+    sim.initial_guess = InitialGuess::Atomic;
+    let atomic = sim.initial_wavefunctions(0);
+    sim.initial_guess = InitialGuess::Random;
+    let random = sim.initial_wavefunctions(0);
+
+    assert_eq!(atomic.len(), sim.n_bands);
+    assert!(max_orthonormality_error(&atomic) < 1e-10);
+    assert!(weight(&atomic[0]) > 0.95, "|⟨χ|ψ_0⟩|² = {}", weight(&atomic[0]));
+    assert!(weight(&atomic[0]) > weight(&random[0]));
+}
+
+#[test]
+fn orbitals_above_lmax_are_skipped() {
+    assert!(real_ylm(LMAX + 1, &Vector3::new(0.3, -0.2, 0.9)).is_none());
+    let y00 = real_ylm(0, &Vector3::zeros()).unwrap();
+    assert!((y00[0] - 0.5 / std::f64::consts::PI.sqrt()).abs() < 1e-15);
+
+    // Orbital g (l = 4) no pseudo: o chute atômico cai para bandas aleatórias sem pânico
+    let mut pp = Pseudopotential::mock("H", 1.0);
+    let data = pp.mesh.r.iter().map(|r| r.powi(5) * (-r).exp()).collect();
+    pp.pswfc.push(AtomicWavefunction { index: 0, label: "5G".to_string(), l: 4, occupation: 0.0, data });
+    pp.header.number_of_wfc = 1;
+    let structure = Structure::builder()
+        .cubic(8.0)
+        .add_species(Species {
+            id: 0,
+            element: "H".to_string(),
+            atomic_number: 1,
+            mass: 1.008,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([4.0, 4.0, 4.0], 0)
+        .build()
+        .unwrap();
+    let mut sim = Simulation::builder()
+        .structure(structure)
+        .ecut(6.0)
+        .k_grid(KGrid::gamma())
+        .pseudo(0, pp)
+        .build()
+        .unwrap();
+    sim.initial_guess = InitialGuess::Atomic;
+    let bands = sim.initial_wavefunctions(0);
+    assert_eq!(bands.len(), sim.n_bands);
+    assert!(max_orthonormality_error(&bands) < 1e-10);
+}
@@ -0,0 +1,100 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::dispersion::{D2, D2Parameters};
+use bravie::dft::xc::XcFunctional;
+use bravie::io::ipi::{connect_inet, run_client};
+
+/// Dímero de Ar numa caixa cúbica de 30 Bohr.
+fn argon_dimer() -> Structure {
+    Structure::builder()
+        .cubic(30.0)
+        .add_species(Species {
+            id: 0,
+            element: "Ar".to_string(),
+            atomic_number: 18,
+            mass: 39.948,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([10.0, 10.0, 10.0], 0)
+        .add_atom([17.0, 10.0, 10.0], 0)
+        .build()
+        .unwrap()
+}
+
+fn send_header(s: &mut TcpStream, msg: &str) {
+    s.write_all(format!("{:<12}", msg).as_bytes()).unwrap();
+}
+
+fn recv_header(s: &mut TcpStream) -> String {
+    let mut buf = [0u8; 12];
+    s.read_exact(&mut buf).unwrap();
+    String::from_utf8_lossy(&buf).trim().to_string()
+}
+
+fn send_f64s(s: &mut TcpStream, values: &[f64]) {
+    s.write_all(&values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>()).unwrap();
+}
+
+fn recv_f64s(s: &mut TcpStream, n: usize) -> Vec<f64> {
+    let mut buf = vec![0u8; 8 * n];
+    s.read_exact(&mut buf).unwrap();
+    buf.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect()
+}
+
+fn recv_i32(s: &mut TcpStream) -> i32 {
+    let mut buf = [0u8; 4];
+    s.read_exact(&mut buf).unwrap();
+    i32::from_le_bytes(buf)
+}
+
+#[test]
+fn d2_client_answers_a_socket_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let template = argon_dimer();
+    let mut d2 = D2::new(D2Parameters { cutoff: 20.0, ..Default::default() }, Some(XcFunctional::Pbe)).unwrap();
+    let expected = d2.compute(&template).unwrap();
+
+    // Servidor no papel do ASE: mesma célula, segundo átomo 1 Bohr mais perto
+    let server = thread::spawn(move || {
+        let (mut s, _) = listener.accept().unwrap();
+        send_header(&mut s, "STATUS");
+        assert_eq!(recv_header(&mut s), "READY");
+        send_header(&mut s, "POSDATA");
+        let cell = [30.0, 0.0, 0.0, 0.0, 30.0, 0.0, 0.0, 0.0, 30.0];
+        send_f64s(&mut s, &cell);
+        send_f64s(&mut s, &cell.map(|c| if c > 0.0 { 1.0 / c } else { 0.0 }));
+        s.write_all(&2i32.to_le_bytes()).unwrap();
+        send_f64s(&mut s, &[10.0, 10.0, 10.0, 16.0, 10.0, 10.0]);
+        send_header(&mut s, "STATUS");
+        assert_eq!(recv_header(&mut s), "HAVEDATA");
+        send_header(&mut s, "GETFORCE");
+        assert_eq!(recv_header(&mut s), "FORCEREADY");
+        let energy = recv_f64s(&mut s, 1)[0];
+        assert_eq!(recv_i32(&mut s), 2);
+        let forces = recv_f64s(&mut s, 6);
+        let virial = recv_f64s(&mut s, 9);
+        assert_eq!(recv_i32(&mut s), 0);
+        send_header(&mut s, "EXIT");
+        (energy, forces, virial)
+    });
+
+    let stream = connect_inet("127.0.0.1", port).unwrap();
+    let steps = run_client(stream, &template, &mut d2).unwrap();
+    let (energy, forces, virial) = server.join().unwrap();
+    assert_eq!(steps, 1);
+
+    // Hartree no fio; a geometria calculada é a do servidor, não a do template
+    let mut moved = template.clone();
+    moved.atoms[1].position.x = 16.0;
+    let reference = d2.compute(&moved).unwrap();
+    assert!((energy - 0.5 * reference.energy).abs() < 1e-14);
+    assert!(reference.energy < expected.energy);
+    assert!((forces[0] - 0.5 * reference.forces[0].x).abs() < 1e-14 && forces[0] > 0.0);
+    assert!((forces[3] + forces[0]).abs() < 1e-14);
+    // Virial -∂E/∂ε: atração puxa a célula para dentro (componente xx negativa)
+    assert!(virial[0] < 0.0);
+}
@@ -0,0 +1,41 @@
+use std::f64::consts::PI;
+
+use bravie::dft::local_potential::LocalFormFactor;
+use bravie::utils::radial::bessel_transform;
+use bravie::Pseudopotential;
+
+const Z: f64 = 4.0;
+const Q_MAX: f64 = 8.0;
+
+/// v(G) por quadratura radial direta, com a cauda -2Z/r separada em vez de erf(r)/r:
+/// v(G) = 4π ∫ r² [V_loc(r) + 2Z/r] j_0(Gr) dr - 8πZ/G²  (sem o termo divergente em G = 0).
+fn direct_form_factor(pp: &Pseudopotential, g: f64) -> f64 {
+    let f: Vec<f64> = pp.local.iter().zip(&pp.mesh.r).map(|(v, &r)| r * (r * v + 2.0 * Z)).collect();
+    let short = 4.0 * PI * bessel_transform(0, &pp.mesh.r, &pp.mesh.rab, &f, g);
+    if g < 1e-10 { short } else { short - 8.0 * PI * Z / (g * g) }
+}
+
+#[test]
+fn form_factor_matches_direct_quadrature() {
+    let pp = Pseudopotential::mock("Si", Z);
+    let factor = LocalFormFactor::new(&pp, Q_MAX);
+    for g in [0.0, 0.35, 0.7, 1.5, 3.0, 6.0] {
+        let (v, v_ref) = (factor.eval(g), direct_form_factor(&pp, g));
+        assert!((v - v_ref).abs() < 1e-5 * v_ref.abs().max(1.0), "|G| = {}: {} != {}", g, v, v_ref);
+    }
+}
+
+#[test]
+fn form_factor_is_continuous_at_g0() {
+    let pp = Pseudopotential::mock("Si", Z);
+    let factor = LocalFormFactor::new(&pp, Q_MAX);
+    // Sem o termo -8πZ/G², que é omitido em G = 0, v(G) → v(0)
+    let v0 = factor.eval(0.0);
+    let mut previous = f64::INFINITY;
+    for g in [1e-2, 1e-3, 1e-4] {
+        let gap = (factor.eval(g) + 8.0 * PI * Z / (g * g) - v0).abs();
+        assert!(gap < previous, "|G| = {:e}: salto {:.3e} não diminui", g, gap);
+        previous = gap;
+    }
+    assert!(previous < 1e-4 * v0.abs(), "salto {:.3e} em |G| = 1e-4 (v(0) = {})", previous, v0);
+}
@@ -0,0 +1,78 @@
+use bravie::core::kpoints::KGrid;
+use bravie::core::structure::{Species, Structure};
+use bravie::io::upf::{EnergyUnit, UnitSource};
+use bravie::{Pseudopotential, Simulation};
+
+#[test]
+fn mock_pseudo_is_consistent() {
+    let pp = Pseudopotential::mock("Si", 4.0);
+    assert!(pp.validate().is_empty(), "{:?}", pp.validate());
+    assert!(!pp.units.converted());
+}
+
+#[test]
+fn simulation_builds_without_upf_files() {
+    let a: f64 = 10.26;
+    let structure = Structure::builder()
+        .lattice([0.0, a / 2.0, a / 2.0], [a / 2.0, 0.0, a / 2.0], [a / 2.0, a / 2.0, 0.0])
+        .add_species(Species {
+            id: 0,
+            element: "Si".to_string(),
+            atomic_number: 14,
+            mass: 28.085,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([a / 4.0, a / 4.0, a / 4.0], 0)
+        .build()
+        .unwrap();
+
+    let mut sim = Simulation::builder()
+        .structure(structure)
+        .ecut(10.0)
+        .k_grid(KGrid::gamma())
+        .pseudo(0, Pseudopotential::mock("Si", 4.0))
+        .build()
+        .unwrap();
+    sim.initialize_density().unwrap();
+
+    assert!((sim.rho.total_charge() - 8.0).abs() < 1e-6);
+}
+
+/// UPF mínimo com V_loc = -Z/r (cauda em Hartree), um projetor e D_11 = 1.
+fn hartree_upf(info: &str) -> String {
+    let r: Vec<f64> = (1..=200).map(|i| 0.1 * i as f64).collect();
+    let join = |v: &[f64]| v.iter().map(|x| format!("{:.8e}", x)).collect::<Vec<_>>().join(" ");
+    let local: Vec<f64> = r.iter().map(|x| -1.0 / x).collect();
+    let beta: Vec<f64> = r.iter().map(|x| x * (-x * x).exp()).collect();
+    let rho: Vec<f64> = r.iter().map(|x| 4.0 * x * x * (-2.0 * x).exp()).collect();
+    format!(
+        r#"<UPF version="2.0.1">
+<PP_INFO>{info}</PP_INFO>
+<PP_HEADER element="H" z_valence="1.0" mesh_size="200" functional="PBE" number_of_proj="1" number_of_wfc="0" pseudo_type="NC"/>
+<PP_MESH><PP_R>{r}</PP_R><PP_RAB>{rab}</PP_RAB></PP_MESH>
+<PP_LOCAL>{local}</PP_LOCAL>
+<PP_NONLOCAL><PP_BETA.1 angular_momentum="0" cutoff_radius_index="200">{beta}</PP_BETA.1></PP_NONLOCAL>
+<PP_DIJ>1.0</PP_DIJ>
+<PP_RHOATOM>{rho}</PP_RHOATOM>
+</UPF>"#,
+        r = join(&r), rab = join(&vec![0.1; r.len()]), local = join(&local), beta = join(&beta), rho = join(&rho),
+    )
+}
+
+#[test]
+fn dij_converted_only_with_declared_unit() {
+    let guessed = Pseudopotential::from_str(&hartree_upf("Gerado para teste")).unwrap();
+    assert_eq!(guessed.units.local_unit, EnergyUnit::Hartree);
+    assert_eq!(guessed.units.unit_source, UnitSource::Heuristic);
+    assert!(!guessed.units.dij_converted);
+    assert_eq!(guessed.dij[[0, 0]], 1.0);
+
+    let declared = Pseudopotential::from_str(&hartree_upf("Energy units: Hartree")).unwrap();
+    assert_eq!(declared.units.unit_source, UnitSource::Header);
+    assert!(declared.units.dij_converted);
+    assert_eq!(declared.dij[[0, 0]], 2.0);
+    // V_loc vai para Ry nos dois casos: -2Z/r
+    assert!((guessed.local[0] + 20.0).abs() < 1e-6);
+    assert!((declared.local[0] - guessed.local[0]).abs() < 1e-12);
+}
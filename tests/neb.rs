@@ -0,0 +1,35 @@
+use nalgebra::Matrix3;
+
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::error::DftError;
+use bravie::optim::neb::{interpolate_images, NebError};
+
+fn dimer(x: f64) -> Structure {
+    Structure::builder()
+        .cubic(10.0)
+        .add_species(Species {
+            id: 0,
+            element: "H".to_string(),
+            atomic_number: 1,
+            mass: 1.008,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([0.0, 0.0, 0.0], 0)
+        .add_atom([x, 0.0, 0.0], 0)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn interpolation_uses_minimum_image_and_rejects_singular_lattice() {
+    // 1.0 -> 9.5 Bohr: pela imagem mínima o átomo anda -1.5 Bohr, não +8.5
+    let images = interpolate_images(&dimer(1.0), &dimer(9.5), 2).unwrap();
+    assert_eq!(images.len(), 4);
+    assert!((images[1].atoms[1].position.x - 0.5).abs() < 1e-12);
+    assert!((images[3].atoms[1].position.x + 0.5).abs() < 1e-12);
+
+    let mut flat = dimer(1.0);
+    flat.lattice.vectors = Matrix3::new(10.0, 0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 0.0);
+    let err = interpolate_images(&flat, &dimer(9.5), 2).unwrap_err();
+    assert!(matches!(err, NebError::Dft(DftError::SingularLattice)));
+}
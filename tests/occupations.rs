@@ -0,0 +1,30 @@
+use bravie::dft::occupations::{BandPolicy, OccupationError, Smearing};
+
+#[test]
+fn smearing_width_must_be_positive() {
+    let parsed: Smearing = serde_json::from_str(r#"{ "kind": "fermi-dirac", "width": 0.01 }"#).unwrap();
+    assert_eq!(parsed, Smearing::FermiDirac { width: 0.01 });
+    let fixed: Smearing = serde_json::from_str(r#"{ "kind": "fixed" }"#).unwrap();
+    assert_eq!(fixed, Smearing::Fixed);
+
+    for width in ["0.0", "-0.01"] {
+        let text = format!(r#"{{ "kind": "gaussian", "width": {} }}"#, width);
+        assert!(serde_json::from_str::<Smearing>(&text).is_err(), "σ = {}", width);
+    }
+    assert!(matches!(
+        Smearing::Gaussian { width: f64::NAN }.validate(),
+        Err(OccupationError::InvalidSmearingWidth(w)) if w.is_nan()
+    ));
+    assert!(Smearing::Fixed.validate().is_ok());
+}
+
+#[test]
+fn default_band_policy_is_n_occ_plus_four() {
+    // n_occ = 50: o padrão não cresce com o sistema; `Metallic` é opcional
+    assert_eq!(BandPolicy::default().n_bands(100.0), 54);
+    assert_eq!(BandPolicy::default().n_bands(7.0), 8);
+    assert_eq!(BandPolicy::Metallic { min_extra: 4 }.n_bands(100.0), 60);
+
+    let parsed: BandPolicy = serde_json::from_str(r#"{ "extra": 8 }"#).unwrap();
+    assert_eq!(parsed, BandPolicy::Extra(8));
+}
@@ -0,0 +1,19 @@
+use bravie::utils::parallel::{self, ParallelConfig};
+
+#[test]
+fn deterministic_reductions_ignore_thread_count() {
+    parallel::configure(&ParallelConfig { deterministic: true, ..Default::default() }).unwrap();
+    let x: Vec<f64> = (0..100_003).map(|i| 1e3 * (0.37 * i as f64).sin() / (1.0 + i as f64)).collect();
+    let reference: f64 = x.iter().sum();
+    let total = parallel::sum(&x);
+    assert!((total - reference).abs() < 1e-10 * reference.abs().max(1.0), "{} != {}", total, reference);
+
+    // Blocos de tamanho fixo somados em ordem: mesmos bits com qualquer número de threads
+    #[cfg(feature = "parallel")]
+    for threads in [1, 3, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        assert_eq!(pool.install(|| parallel::sum(&x)).to_bits(), total.to_bits());
+        assert_eq!(pool.install(|| parallel::dot(&x, &x).unwrap()).to_bits(), parallel::dot(&x, &x).unwrap().to_bits());
+    }
+    assert!(parallel::dot(&x, &x[1..]).is_err());
+}
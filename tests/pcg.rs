@@ -0,0 +1,67 @@
+use ndarray::Array1;
+use num_complex::Complex64;
+
+use bravie::core::basis::PlaneWaveBasis;
+use bravie::core::fft::FftGrid;
+use bravie::core::field::PotentialField;
+use bravie::dft::hamiltonian::Hamiltonian;
+use bravie::dft::initial_guess::random_wavefunctions;
+use bravie::dft::pcg::{solve_bands_pcg, PcgParameters};
+use bravie::dft::solver::{solve_bands_exact, Diagonalizer, SolverError};
+use bravie::testkit::cosine::cosine_potential;
+use bravie::testkit::empty_cubic_box;
+
+#[test]
+fn pcg_converges_to_exact_eigenvalues() {
+    let (a, v0) = (8.0, 0.2);
+    let structure = empty_cubic_box(a);
+    let basis = PlaneWaveBasis::new(&structure, 10.0, Some([0.1, 0.2, 0.3]));
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v = cosine_potential(&structure, basis.fft_grid, a, v0);
+    let hamiltonian = Hamiltonian::new(PotentialField::new(structure.lattice.clone(), v));
+
+    let exact = solve_bands_exact(&mut hamiltonian.bind(&basis, &mut fft), 6).unwrap();
+    let params = PcgParameters { tolerance: 1e-8, ..Default::default() };
+    let initial = random_wavefunctions(&basis, 6, 7, None);
+    let pcg = solve_bands_pcg(&mut hamiltonian.bind(&basis, &mut fft), initial, &params, None).unwrap();
+
+    assert!(pcg.iterations > 0 && pcg.iterations < params.max_sweeps, "{} varreduras", pcg.iterations);
+    assert!(pcg.is_reliable(&basis, None, 1e-7), "resíduo {:.3e}", pcg.max_residual());
+    for (n, (e, e_ref)) in pcg.eigenvalues.iter().zip(&exact.eigenvalues).enumerate() {
+        assert!((e - e_ref).abs() < 1e-10, "banda {}: {} != {}", n, e, e_ref);
+    }
+}
+
+#[test]
+fn pcg_rejects_bands_of_another_basis() {
+    let structure = empty_cubic_box(6.0);
+    let basis = PlaneWaveBasis::new(&structure, 5.0, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let hamiltonian = Hamiltonian::new(PotentialField::zeros(structure.lattice.clone(), basis.fft_grid));
+    let initial = vec![Array1::<Complex64>::zeros(basis.g_vectors.len() + 1)];
+
+    let result = solve_bands_pcg(&mut hamiltonian.bind(&basis, &mut fft), initial, &PcgParameters::default(), None);
+    assert!(matches!(result, Err(SolverError::Fft(_))));
+    let result = solve_bands_pcg(&mut hamiltonian.bind(&basis, &mut fft), Vec::new(), &PcgParameters::default(), None);
+    assert!(matches!(result, Err(SolverError::InvalidBandCount(0, _))));
+}
+
+#[test]
+fn diagonalizers_agree_and_exact_ignores_initial_bands() {
+    let (a, v0) = (8.0, 0.2);
+    let structure = empty_cubic_box(a);
+    let basis = PlaneWaveBasis::new(&structure, 8.0, None);
+    let mut fft = FftGrid::new(&basis).unwrap();
+    let v = cosine_potential(&structure, basis.fft_grid, a, v0);
+    let hamiltonian = Hamiltonian::new(PotentialField::new(structure.lattice.clone(), v));
+
+    let exact = Diagonalizer::Exact
+        .solve(&mut hamiltonian.bind(&basis, &mut fft), 4, None, || panic!("chute inicial na diagonalização exata"))
+        .unwrap();
+    let pcg = Diagonalizer::Pcg
+        .solve(&mut hamiltonian.bind(&basis, &mut fft), 4, None, || random_wavefunctions(&basis, 4, 3, None))
+        .unwrap();
+    for (e, e_ref) in pcg.eigenvalues.iter().zip(&exact.eigenvalues) {
+        assert!((e - e_ref).abs() < 1e-8, "{} != {}", e, e_ref);
+    }
+}
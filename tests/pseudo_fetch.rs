@@ -0,0 +1,24 @@
+#![cfg(feature = "network")]
+
+use bravie::io::pseudo_fetch::{FetchError, ManifestEntry, PseudoFetcher, PseudoManifest};
+
+fn manifest(name: &str, file: &str) -> PseudoManifest {
+    PseudoManifest {
+        name: name.to_string(),
+        base_url: "http://localhost:1".to_string(),
+        entries: vec![ManifestEntry { element: "Si".to_string(), file: file.to_string(), sha256: "00".to_string() }],
+    }
+}
+
+#[test]
+fn manifest_names_cannot_escape_the_cache() {
+    assert!(manifest("sssp-efficiency-1.3", "Si.pbe-n-rrkjus_psl.1.0.0.UPF").validate().is_ok());
+
+    let fetcher = PseudoFetcher::new(std::env::temp_dir().join("bravie-fetch-test"));
+    for (name, file) in [("..", "Si.UPF"), ("../x", "Si.UPF"), ("/tmp", "Si.UPF"), ("t", "../../Si.UPF"), ("t", "/etc/Si.UPF"), ("t", "a\\Si.UPF"), ("t", "")] {
+        let bad = manifest(name, file);
+        assert!(matches!(bad.validate(), Err(FetchError::UnsafeName(_))), "{} / {}", name, file);
+        // Recusado antes de qualquer download ou escrita
+        assert!(matches!(fetcher.fetch(&bad, "Si"), Err(FetchError::UnsafeName(_))), "{} / {}", name, file);
+    }
+}
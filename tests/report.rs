@@ -0,0 +1,46 @@
+use bravie::core::kpoints::KGrid;
+use bravie::core::structure::{Species, Structure};
+use bravie::dft::scf::ScfHistory;
+use bravie::io::report::RunReport;
+use bravie::{Pseudopotential, Simulation};
+
+fn hydrogen_box() -> Simulation {
+    let structure = Structure::builder()
+        .cubic(8.0)
+        .add_species(Species {
+            id: 0,
+            element: "H".to_string(),
+            atomic_number: 1,
+            mass: 1.008,
+            pseudo_path: "inexistente.UPF".to_string(),
+        })
+        .add_atom([4.0, 4.0, 4.0], 0)
+        .build()
+        .unwrap();
+    Simulation::builder()
+        .structure(structure)
+        .ecut(6.0)
+        .k_grid(KGrid::gamma())
+        .pseudo(0, Pseudopotential::mock("H", 1.0))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn scf_table_comes_from_the_history() {
+    let sim = hydrogen_box();
+    let mut history = ScfHistory::new("teste");
+    history.record(-1.0, 1e-2, None, 1e-4);
+    history.record(-1.5, 1e-5, Some(0.1), 1e-6);
+
+    let empty = RunReport::new(&sim).scf_history(&ScfHistory::new("vazio")).render();
+    assert!(!empty.contains("--- Ciclo SCF ---"));
+
+    let text = RunReport::new(&sim).scf_history(&history).render();
+    let table: Vec<&str> = text.lines().skip_while(|l| *l != "--- Ciclo SCF ---").skip(2).take(2).collect();
+    assert_eq!(table.len(), 2);
+    assert!(table[0].trim_start().starts_with("1 ") && table[0].contains("-1.0000000000"));
+    assert!(table[1].trim_start().starts_with("2 ") && table[1].contains("-5.0000e-1"));
+    // Sem tempos: o texto é o mesmo em outra execução
+    assert_eq!(text, RunReport::new(&sim).scf_history(&history).render());
+}
@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use ndarray::Array3;
+
+use bravie::core::fft::FftGrid;
+use bravie::dft::error::DftError;
+use bravie::dft::mixing::MixingAction;
+use bravie::dft::occupations::{Occupations, Smearing};
+use bravie::dft::scf::{run_scf_loop, AdaptiveTolerance, ScfIteration, ScfParameters, ScfStep};
+use bravie::io::checkpoint::Checkpoint;
+use bravie::testkit::empty_cubic_box;
+
+const SIZE: [usize; 3] = [4, 4, 4];
+
+fn pattern() -> Array3<f64> {
+    Array3::from_shape_fn((SIZE[0], SIZE[1], SIZE[2]), |(i, j, k)| ((i + 2 * j + 3 * k) as f64).cos())
+}
+
+#[test]
+fn oscillating_residual_walks_the_mixing_ladder() {
+    let structure = empty_cubic_box(4.0);
+    let mut fft = FftGrid::with_size(SIZE).unwrap();
+    let params = ScfParameters { max_iterations: 48, ..Default::default() };
+    let pattern = pattern();
+
+    // F = ρ_out - ρ_in alterna entre dois tamanhos, qualquer que seja o mixing
+    let mut calls = 0;
+    let step = |rho_in: &Array3<f64>, _tol: f64, _fft: &mut FftGrid| -> Result<ScfStep, DftError> {
+        calls += 1;
+        let amplitude = if calls % 2 == 0 { 0.8 } else { 1.0 };
+        Ok(ScfStep { rho_out: rho_in + &(&pattern * amplitude), energy: -1.0, fermi_energy: None, occupations: None, v_eff: None })
+    };
+    let outcome = run_scf_loop(&params, "oscilante", &structure.lattice, &mut fft, 1.0, Array3::zeros(pattern.dim()), step).unwrap();
+
+    assert!(!outcome.converged);
+    let iterations: Vec<usize> = outcome.mixing_actions.iter().map(|(it, _)| *it).collect();
+    assert_eq!(iterations, vec![6, 12, 18, 24, 30, 36, 42, 48]);
+
+    let actions: Vec<MixingAction> = outcome.mixing_actions.iter().map(|(_, a)| *a).collect();
+    let floor = params.min_mixing_beta;
+    assert_eq!(actions, vec![
+        MixingAction::ReduceBeta { from: 0.3, to: 0.15 },
+        MixingAction::EnableKerker { q0: params.kerker_q0 },
+        MixingAction::ResetHistory,
+        MixingAction::ReduceBeta { from: 0.15, to: 0.075 },
+        MixingAction::ReduceBeta { from: 0.075, to: 0.0375 },
+        MixingAction::ReduceBeta { from: 0.0375, to: floor },
+        MixingAction::ResetHistory,
+        MixingAction::ResetHistory,
+    ]);
+    for action in &actions {
+        if let MixingAction::ReduceBeta { to, .. } = action {
+            assert!(*to >= floor, "β = {} abaixo do piso {}", to, floor);
+        }
+    }
+}
+
+#[test]
+fn contracting_step_converges() {
+    let structure = empty_cubic_box(4.0);
+    let mut fft = FftGrid::with_size(SIZE).unwrap();
+    let params = ScfParameters::default();
+    let target = pattern() + 1.0;
+
+    // ρ_out = ρ* + (ρ_in - ρ*)/2: ponto fixo ρ*
+    let step = |rho_in: &Array3<f64>, _tol: f64, _fft: &mut FftGrid| -> Result<ScfStep, DftError> {
+        let rho_out = &target + &((rho_in - &target) * 0.5);
+        let energy = (rho_in - &target).iter().map(|d| d * d).sum::<f64>();
+        Ok(ScfStep { rho_out, energy, fermi_energy: None, occupations: None, v_eff: None })
+    };
+    let outcome = run_scf_loop(&params, "contrativo", &structure.lattice, &mut fft, 1.0, Array3::zeros(target.dim()), step).unwrap();
+
+    assert!(outcome.converged, "não convergiu em {} iterações", outcome.history.len());
+    assert!(outcome.mixing_actions.is_empty());
+    let error = (&outcome.rho - &target).iter().fold(0.0_f64, |m, d| m.max(d.abs()));
+    assert!(error < 1e-6, "|ρ - ρ*| = {:.3e}", error);
+}
+
+#[test]
+fn observer_sees_every_iteration() {
+    let structure = empty_cubic_box(4.0);
+    let mut fft = FftGrid::with_size(SIZE).unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let params = ScfParameters::default()
+        .on_iteration(Box::new(move |it: &ScfIteration| sink.lock().unwrap().push((it.iteration, it.density_residual))));
+    let target = pattern();
+
+    let step = |rho_in: &Array3<f64>, _tol: f64, _fft: &mut FftGrid| -> Result<ScfStep, DftError> {
+        Ok(ScfStep { rho_out: &target + &((rho_in - &target) * 0.5), energy: 0.0, fermi_energy: None, occupations: None, v_eff: None })
+    };
+    let outcome = run_scf_loop(&params, "observado", &structure.lattice, &mut fft, 1.0, Array3::zeros(target.dim()), step).unwrap();
+
+    let expected: Vec<(usize, f64)> = outcome.history.iterations.iter().map(|it| (it.iteration, it.density_residual)).collect();
+    assert_eq!(*seen.lock().unwrap(), expected);
+}
+
+#[test]
+fn cancelled_scf_writes_checkpoint() {
+    let structure = empty_cubic_box(4.0);
+    let mut fft = FftGrid::with_size(SIZE).unwrap();
+    let path = std::env::temp_dir().join(format!("bravie_scf_cancel_{}.ckpt", std::process::id()));
+    let token = Arc::new(AtomicBool::new(false));
+    let params = ScfParameters::default()
+        .cancel_token(Arc::clone(&token))
+        .checkpoint_on_cancel(&path, 10.0, 40.0);
+    let target = pattern() + 1.0;
+
+    // Cancela durante a terceira iteração: ela termina e o ciclo para em seguida
+    let mut calls = 0;
+    let step = |rho_in: &Array3<f64>, _tol: f64, _fft: &mut FftGrid| -> Result<ScfStep, DftError> {
+        calls += 1;
+        if calls == 3 {
+            token.store(true, Ordering::Relaxed);
+        }
+        let rho_out = &target + &((rho_in - &target) * 0.9);
+        Ok(ScfStep { rho_out, energy: calls as f64, fermi_energy: None, occupations: None, v_eff: Some(Array3::from_elem(target.dim(), -0.5)) })
+    };
+    let outcome = run_scf_loop(&params, "cancelado", &structure.lattice, &mut fft, 1.0, Array3::zeros(target.dim()), step).unwrap();
+
+    assert!(outcome.cancelled);
+    assert!(!outcome.converged);
+    assert_eq!(outcome.history.len(), 3);
+
+    let ckpt = Checkpoint::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(ckpt.rho.dims(), SIZE);
+    assert!(ckpt.v_eff.data.iter().all(|&v| v == -0.5));
+    assert_eq!((ckpt.ecut, ckpt.ecut_rho), (10.0, 40.0));
+}
+
+#[test]
+fn outcome_keeps_last_occupations_and_fermi_level() {
+    let structure = empty_cubic_box(4.0);
+    let mut fft = FftGrid::with_size(SIZE).unwrap();
+    let target = pattern();
+    // 4 elétrons, uma banda baixa e um par degenerado no nível de Fermi: 2, 1, 1
+    let eigenvalues = vec![vec![-0.5, 0.0, 0.0, 0.4]];
+    let smearing = Smearing::Gaussian { width: 0.01 };
+
+    let step = |rho_in: &Array3<f64>, _tol: f64, _fft: &mut FftGrid| -> Result<ScfStep, DftError> {
+        let occupations = Occupations::compute(&eigenvalues, &[1.0], 4.0, smearing);
+        Ok(ScfStep { rho_out: &target + &((rho_in - &target) * 0.5), energy: 0.0, fermi_energy: None, occupations: Some(occupations), v_eff: None })
+    };
+    let outcome = run_scf_loop(&ScfParameters::default(), "metal", &structure.lattice, &mut fft, 4.0, Array3::zeros(target.dim()), step).unwrap();
+
+    assert!(outcome.converged);
+    let occupations = outcome.occupations.unwrap();
+    assert_eq!(outcome.fermi_energy, Some(occupations.fermi_energy));
+    assert!(occupations.fermi_energy.abs() < 1e-6);
+    for (f, expected) in occupations.values[0].iter().zip([2.0, 1.0, 1.0, 0.0]) {
+        assert!((f - expected).abs() < 1e-6, "{:?}", occupations.values);
+    }
+    assert!(outcome.history.iterations.iter().all(|it| it.fermi_energy == outcome.fermi_energy));
+}
+
+#[test]
+fn adaptive_tolerance_survives_nan_and_swapped_bounds() {
+    let mut tolerance = AdaptiveTolerance::new(&ScfParameters::default(), 8.0);
+    let start = tolerance.current();
+    assert_eq!(tolerance.update(f64::NAN), start);
+    assert!(tolerance.update(8e-3) < start);
+
+    let swapped = ScfParameters { solver_tol_min: 1e-2, solver_tol_max: 1e-8, ..Default::default() };
+    let mut tolerance = AdaptiveTolerance::new(&swapped, 1.0);
+    assert_eq!(tolerance.current(), 1e-2);
+    assert_eq!(tolerance.update(1e-20), 1e-8);
+    assert_eq!(tolerance.update(1.0), 1e-8);
+
+    let nan = ScfParameters { solver_tol_min: f64::NAN, ..Default::default() };
+    let mut tolerance = AdaptiveTolerance::new(&nan, 1.0);
+    assert!(tolerance.update(1e-3).is_finite());
+}
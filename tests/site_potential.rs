@@ -0,0 +1,15 @@
+use ndarray::Array3;
+
+use bravie::core::fft::FftGrid;
+use bravie::dft::error::DftError;
+use bravie::postproc::site_potential::site_potentials;
+use bravie::testkit::empty_cubic_box;
+
+#[test]
+fn density_on_another_grid_is_an_error() {
+    let structure = empty_cubic_box(8.0);
+    let mut fft = FftGrid::with_size([12, 12, 12]).unwrap();
+    let rho = Array3::<f64>::zeros((10, 12, 12));
+    let err = site_potentials(&structure, &mut fft, &rho, None::<fn(usize, f64) -> f64>).unwrap_err();
+    assert!(matches!(err, DftError::GridMismatch([10, 12, 12], [12, 12, 12])));
+}